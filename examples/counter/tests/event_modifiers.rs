@@ -0,0 +1,52 @@
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+use leptos::*;
+use std::{cell::RefCell, rc::Rc};
+use web_sys::HtmlElement;
+
+#[wasm_bindgen_test]
+fn prevent_and_stop() {
+    // the handlers that ran, in order
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    mount_to_body({
+        let log = Rc::clone(&log);
+        move |cx| {
+            let (div_log, link_log, button_log) = (Rc::clone(&log), Rc::clone(&log), log);
+            view! { cx,
+                <div on:click=move |ev: web_sys::MouseEvent| {
+                    div_log.borrow_mut().push(if ev.default_prevented() { "div (prevented)" } else { "div" });
+                }>
+                    <a id="prevent" href="#navigated" on:click:prevent=move |_| link_log.borrow_mut().push("a")>
+                        "Prevent"
+                    </a>
+                    <button id="stop" on:click:stop=move |_| button_log.borrow_mut().push("button")>
+                        "Stop"
+                    </button>
+                </div>
+            }
+        }
+    });
+
+    let document = leptos::document();
+    let link = document
+        .get_element_by_id("prevent")
+        .unwrap()
+        .dyn_into::<HtmlElement>()
+        .unwrap();
+    let button = document
+        .get_element_by_id("stop")
+        .unwrap()
+        .dyn_into::<HtmlElement>()
+        .unwrap();
+
+    // the link's handler runs and the click bubbles, but the link isn't followed
+    link.click();
+    assert_eq!(*log.borrow(), ["a", "div (prevented)"]);
+    assert_ne!(window().location().hash().unwrap(), "#navigated");
+
+    // the button's handler runs, but the click doesn't reach the div
+    button.click();
+    assert_eq!(*log.borrow(), ["a", "div (prevented)", "button"]);
+}
@@ -10,7 +10,7 @@ if #[cfg(feature = "ssr")] {
         extract::Extension,
     };
     use leptos_axum::{generate_route_list, LeptosRoutes};
-    use std::sync::Arc;
+    use std::{net::SocketAddr, sync::Arc};
     use hackernews_axum::fallback::file_and_error_handler;
 
     #[tokio::main]
@@ -35,7 +35,7 @@ if #[cfg(feature = "ssr")] {
         // `axum::Server` is a re-export of `hyper::Server`
         log!("listening on {}", addr);
         axum::Server::bind(&addr)
-            .serve(app.into_make_service())
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
             .await
             .unwrap();
     }
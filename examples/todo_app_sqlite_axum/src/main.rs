@@ -16,7 +16,7 @@ if #[cfg(feature = "ssr")] {
     use todo_app_sqlite_axum::*;
     use crate::fallback::file_and_error_handler;
     use leptos_axum::{generate_route_list, LeptosRoutes};
-    use std::sync::Arc;
+    use std::{net::SocketAddr, sync::Arc};
     use leptos_reactive::run_scope;
 
     //Define a handler to test extractor with state
@@ -60,7 +60,7 @@ if #[cfg(feature = "ssr")] {
         // `axum::Server` is a re-export of `hyper::Server`
         log!("listening on {}", &addr);
         axum::Server::bind(&addr)
-            .serve(app.into_make_service())
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
             .await
             .unwrap();
     }
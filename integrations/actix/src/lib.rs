@@ -76,8 +76,33 @@ impl ResponseOptions {
 /// Provides an easy way to redirect the user from within a server function. Mimicing the Remix `redirect()`,
 /// it sets a [StatusCode] of 302 and a [LOCATION](header::LOCATION) header with the provided value.
 /// If looking to redirect from the client, `leptos_router::use_navigate()` should be used instead.
+///
+/// To stop a misconfigured flow (like a login page that redirects to itself) from sending the browser around
+/// in circles, this counts the redirects it makes in a row, and refuses to make more than the [RedirectLimit]
+/// provided as context, or [DEFAULT_REDIRECT_LIMIT] if there isn't one. The count is kept in a short-lived
+/// `leptos_redirects` cookie, so it only includes redirects that follow each other within a few seconds, like
+/// a browser following them. Once the limit is reached, the response gets a `508 Loop Detected` status and no
+/// `Location` header, and the count is reset.
 pub async fn redirect(cx: leptos::Scope, path: &str) {
     let response_options = use_context::<ResponseOptions>(cx).unwrap();
+    let RedirectLimit(limit) = use_context(cx).unwrap_or(RedirectLimit(DEFAULT_REDIRECT_LIMIT));
+    let redirects = use_context::<HttpRequest>(cx)
+        .map(|req| redirect_count(&req))
+        .unwrap_or(0)
+        + 1;
+
+    if redirects > limit {
+        response_options.set_status(StatusCode::LOOP_DETECTED).await;
+        response_options
+            .append_header(
+                header::SET_COOKIE,
+                header::HeaderValue::from_str(&format!("{REDIRECT_COOKIE}=0; Path=/; Max-Age=0"))
+                    .expect("Failed to create HeaderValue"),
+            )
+            .await;
+        return;
+    }
+
     response_options.set_status(StatusCode::FOUND).await;
     response_options
         .insert_header(
@@ -85,6 +110,40 @@ pub async fn redirect(cx: leptos::Scope, path: &str) {
             header::HeaderValue::from_str(path).expect("Failed to create HeaderValue"),
         )
         .await;
+    response_options
+        .append_header(
+            header::SET_COOKIE,
+            header::HeaderValue::from_str(&format!(
+                "{REDIRECT_COOKIE}={redirects}; Path=/; Max-Age={REDIRECT_COOKIE_MAX_AGE}; HttpOnly; SameSite=Lax"
+            ))
+            .expect("Failed to create HeaderValue"),
+        )
+        .await;
+}
+
+/// The number of redirects in a row that [redirect] makes before refusing, unless a [RedirectLimit] is
+/// provided as context.
+pub const DEFAULT_REDIRECT_LIMIT: u32 = 10;
+
+/// The number of redirects in a row that [redirect] makes before refusing to redirect again. Provide it as
+/// context, e.g. in the `additional_context` of [handle_server_fns_with_context], to change the
+/// [DEFAULT_REDIRECT_LIMIT].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedirectLimit(pub u32);
+
+// The cookie in which [redirect] counts its redirects, and how many seconds a redirect counts for
+const REDIRECT_COOKIE: &str = "leptos_redirects";
+const REDIRECT_COOKIE_MAX_AGE: u32 = 10;
+
+fn redirect_count(req: &HttpRequest) -> u32 {
+    req.headers()
+        .get_all(header::COOKIE)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == REDIRECT_COOKIE)
+        .and_then(|(_, count)| count.parse().ok())
+        .unwrap_or(0)
 }
 
 /// An Actix [Route](actix_web::Route) that listens for a `POST` request with
@@ -140,6 +199,9 @@ pub fn handle_server_fns() -> Route {
 /// This version allows you to pass in a closure that adds additional route data to the
 /// context, allowing you to pass in info about the route or user from Actix, or other info.
 ///
+/// Server functions are looked up in the global [ServerFnRegistry](leptos::ServerFnRegistry), unless
+/// the app has its own registry in its app data (added with `.app_data(registry)`).
+///
 /// ## Provided Context Types
 /// This function always provides context values including the following types:
 /// - [ResponseOptions]
@@ -159,7 +221,13 @@ pub fn handle_server_fns_with_context(
                     .get("Accept")
                     .and_then(|value| value.to_str().ok());
 
-                if let Some(server_fn) = server_fn_by_path(path.as_str()) {
+                // a registry added with `.app_data(registry)` takes the place of the global one
+                let registry = req
+                    .app_data::<ServerFnRegistry>()
+                    .cloned()
+                    .unwrap_or_else(ServerFnRegistry::global);
+
+                if let Some(server_fn) = registry.get(path.as_str()) {
                     let body: &[u8] = &body;
 
                     let runtime = create_runtime();
@@ -173,39 +241,79 @@ pub fn handle_server_fns_with_context(
                     provide_context(cx, req.clone());
                     provide_context(cx, res_options.clone());
 
-                    match server_fn(cx, body).await {
+                    // bodies with a registered content type are converted into the server fn's encoding
+                    let content_type = req
+                        .headers()
+                        .get(header::CONTENT_TYPE)
+                        .and_then(|value| value.to_str().ok());
+                    // a request without one of the headers the server fn requires is rejected before it runs
+                    let missing_header = registry
+                        .required_headers(&path)
+                        .iter()
+                        .find(|name| !req.headers().contains_key(**name));
+                    if let Some(name) = missing_header {
+                        disposer.dispose();
+                        runtime.dispose();
+                        return HttpResponse::BadRequest()
+                            .body(ServerFnError::MissingArg(name.to_string()).to_string());
+                    }
+                    let result = match registry.decode_body(&path, content_type, body) {
+                        Ok(body) => server_fn(cx, &body).await,
+                        Err(e) => Err(e),
+                    };
+
+                    match result {
                         Ok(serialized) => {
                             let res_options = use_context::<ResponseOptions>(cx).unwrap();
 
-                            // clean up the scope, which we only needed to run the server fn
-                            disposer.dispose();
-                            runtime.dispose();
+                            // clean up the scope, which we only needed to run the server fn,
+                            // unless it's still needed to produce a stream of results
+                            let mut scope = Some((disposer, runtime));
+                            if !matches!(serialized, Payload::Ndjson(_)) {
+                                if let Some((disposer, runtime)) = scope.take() {
+                                    disposer.dispose();
+                                    runtime.dispose();
+                                }
+                            }
 
                             let mut res: HttpResponseBuilder;
                             let mut res_parts = res_options.0.write().await;
 
+                            // a file is downloaded even by a <form> submit
                             if accept_header == Some("application/json")
                                 || accept_header == Some("application/x-www-form-urlencoded")
                                 || accept_header == Some("application/cbor")
+                                || accept_header == Some("application/x-ndjson")
+                                || matches!(serialized, Payload::File(_))
                             {
                                 res = HttpResponse::Ok();
                             }
-                            // otherwise, it's probably a <form> submit or something: redirect back to the referrer
+                            // otherwise, it's probably a <form> submit or something: redirect back to the referrer,
+                            // unless the server fn set a Location of its own
                             else {
-                                let referer = req
-                                    .headers()
-                                    .get("Referer")
-                                    .and_then(|value| value.to_str().ok())
-                                    .unwrap_or("/");
                                 res = HttpResponse::SeeOther();
-                                res.insert_header(("Location", referer))
-                                    .content_type("application/json");
+                                if !res_parts.headers.contains_key(header::LOCATION) {
+                                    let referer = req
+                                        .headers()
+                                        .get("Referer")
+                                        .and_then(|value| value.to_str().ok())
+                                        .unwrap_or("/");
+                                    res.insert_header(("Location", referer));
+                                }
+                                res.content_type("application/json");
                             };
                             // Override StatusCode if it was set in a Resource or Element
                             if let Some(status) = res_parts.status {
                                 res.status(status);
                             }
 
+                            // the headers declared by the server fn, unless it set them itself
+                            for (name, value) in registry.headers(&path) {
+                                if !res_parts.headers.contains_key(*name) {
+                                    res.insert_header((*name, *value));
+                                }
+                            }
+
                             // Use provided ResponseParts headers if they exist
                             let _count = res_parts
                                 .headers
@@ -230,6 +338,42 @@ pub fn handle_server_fns_with_context(
                                     res.content_type("application/json");
                                     res.body(data)
                                 }
+                                Payload::Ndjson(lines) => {
+                                    // dispose of the scope once the last line has been sent
+                                    let cleanup = futures::stream::once(async move {
+                                        if let Some((disposer, runtime)) = scope {
+                                            disposer.dispose();
+                                            runtime.dispose();
+                                        }
+                                    })
+                                    .filter_map(|_| async { None });
+                                    res.content_type("application/x-ndjson");
+                                    res.streaming(
+                                        lines
+                                            .chain(cleanup)
+                                            .map(|line| Ok::<_, Error>(Bytes::from(line))),
+                                    )
+                                }
+                                Payload::File(file) => {
+                                    let opened = match file.path() {
+                                        Some(path) => tokio::fs::File::open(path).await.ok(),
+                                        None => None,
+                                    };
+                                    match opened {
+                                        Some(opened) => {
+                                            res.content_type(
+                                                file.content_type()
+                                                    .unwrap_or("application/octet-stream"),
+                                            );
+                                            res.insert_header((
+                                                header::CONTENT_DISPOSITION,
+                                                file.content_disposition(),
+                                            ));
+                                            res.streaming(tokio_util::io::ReaderStream::new(opened))
+                                        }
+                                        None => HttpResponse::NotFound().finish(),
+                                    }
+                                }
                             }
                         }
                         Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
@@ -341,7 +485,7 @@ where
 
             let (head, tail) = html_parts(&options);
 
-            stream_app(app, head, tail, res_options, additional_context).await
+            stream_app(&options, app, head, tail, res_options, additional_context).await
         }
     })
 }
@@ -431,7 +575,7 @@ where
 
             let (head, tail) = html_parts(&options);
 
-            stream_app(app, head, tail, res_options, |_cx| {}).await
+            stream_app(&options, app, head, tail, res_options, |_cx| {}).await
         }
     })
 }
@@ -441,7 +585,7 @@ fn provide_contexts(cx: leptos::Scope, req: &HttpRequest, res_options: ResponseO
 
     let integration = ServerIntegration { path };
     provide_context(cx, RouterIntegrationContext::new(integration));
-    provide_context(cx, MetaContext::new());
+    provide_meta_context(cx);
     provide_context(cx, res_options);
     provide_context(cx, req.clone());
 }
@@ -457,6 +601,7 @@ fn leptos_corrected_path(req: &HttpRequest) -> String {
 }
 
 async fn stream_app(
+    options: &LeptosOptions,
     app: impl FnOnce(leptos::Scope) -> View + 'static,
     head: String,
     tail: String,
@@ -501,8 +646,14 @@ async fn stream_app(
     ])
     .chain(stream);
     let mut res = HttpResponse::Ok()
-        .content_type("text/html")
+        .content_type(format!("text/html; charset={}", options.charset))
         .streaming(complete_stream);
+    if let Some(version) = &options.app_version {
+        if let Ok(version) = header::HeaderValue::from_str(version) {
+            res.headers_mut()
+                .insert(header::HeaderName::from_static("x-app-version"), version);
+        }
+    }
     // Add headers manipulated in the response
     for (key, value) in headers.drain() {
         if let Some(key) = key {
@@ -520,21 +671,35 @@ fn html_parts(options: &LeptosOptions) -> (String, String) {
     // Because wasm-pack adds _bg to the end of the WASM filename, and we want to mantain compatibility with it's default options
     // we add _bg to the wasm files if cargo-leptos doesn't set the env var LEPTOS_OUTPUT_NAME
     // Otherwise we need to add _bg because wasm_pack always does. This is not the same as options.output_name, which is set regardless
+    // When LEPTOS_OUTPUT_NAME is set, get_configuration uses it as options.output_name even if the config file disagrees
     let output_name = &options.output_name;
     let mut wasm_output_name = output_name.clone();
     if std::env::var("LEPTOS_OUTPUT_NAME").is_err() {
         wasm_output_name.push_str("_bg");
     }
 
-    let site_ip = &options.site_address.ip().to_string();
-    let reload_port = options.reload_port;
+    let reload_origin = options.reload_ws_origin();
     let pkg_path = &options.site_pkg_dir;
+    let charset = &options.charset;
+    let preconnect = preconnect_links(options);
+    let version_meta = version_meta(options);
+    let hydration_imports = hydration_imports(options);
+    let init_fn_name = &options.init_fn_name;
+    let hydrate_fn_name = &options.hydrate_fn_name;
+    let preload_hints = match options.preload_hints {
+        true => format!(
+            r#"<link rel="modulepreload" href="/{pkg_path}/{output_name}.js">
+                <link rel="preload" href="/{pkg_path}/{wasm_output_name}.wasm" as="fetch" type="application/wasm" crossorigin="">
+                "#
+        ),
+        false => "".to_string(),
+    };
 
     let leptos_autoreload = match std::env::var("LEPTOS_WATCH").is_ok() {
         true => format!(
             r#"
             <script crossorigin="">(function () {{
-                var ws = new WebSocket('ws://{site_ip}:{reload_port}/live_reload');
+                var ws = new WebSocket('{reload_origin}/live_reload');
                 ws.onmessage = (ev) => {{
                     let msg = JSON.parse(ev.data);
                     if (msg.all) window.location.reload();
@@ -557,15 +722,15 @@ fn html_parts(options: &LeptosOptions) -> (String, String) {
         false => "".to_string(),
     };
 
+    let doctype = &options.doctype;
+    let lang = &options.lang;
     let head = format!(
-        r#"<!DOCTYPE html>
-        <html lang="en">
+        r#"{doctype}
+        <html lang="{lang}">
             <head>
-                <meta charset="utf-8"/>
+                <meta charset="{charset}"/>
                 <meta name="viewport" content="width=device-width, initial-scale=1"/>
-                <link rel="modulepreload" href="/{pkg_path}/{output_name}.js">
-                <link rel="preload" href="/{pkg_path}/{wasm_output_name}.wasm" as="fetch" type="application/wasm" crossorigin="">
-                <script type="module">import init, {{ hydrate }} from '/{pkg_path}/{output_name}.js'; init('/{pkg_path}/{wasm_output_name}.wasm').then(hydrate);</script>
+                {version_meta}{preconnect}{preload_hints}<script type="module">import {hydration_imports} from '/{pkg_path}/{output_name}.js'; {init_fn_name}('/{pkg_path}/{wasm_output_name}.wasm').then({hydrate_fn_name});</script>
                 {leptos_autoreload}
                 "#
     );
@@ -574,10 +739,70 @@ fn html_parts(options: &LeptosOptions) -> (String, String) {
     (head, tail)
 }
 
+// The names that the hydration script imports from the app's JS module. wasm-bindgen exports its
+// `init` function as the module's default export, so only a custom one is a named export.
+fn hydration_imports(options: &LeptosOptions) -> String {
+    let init = &options.init_fn_name;
+    let hydrate = &options.hydrate_fn_name;
+    if init == "init" {
+        format!("init, {{ {hydrate} }}")
+    } else {
+        format!("{{ {init}, {hydrate} }}")
+    }
+}
+
+// The tag for [LeptosOptions::app_version], if it's set
+fn version_meta(options: &LeptosOptions) -> String {
+    options
+        .app_version
+        .as_ref()
+        .map(|version| {
+            format!(
+                r#"<meta name="version" content="{}"/>"#,
+                escape_attr(version)
+            )
+        })
+        .unwrap_or_default()
+}
+
+// The resource hints for each of the origins in [LeptosOptions::preconnect_origins]
+fn preconnect_links(options: &LeptosOptions) -> String {
+    options
+        .preconnect_origins
+        .iter()
+        .map(|origin| {
+            let origin = origin.replace('"', "&quot;");
+            format!(
+                r#"<link rel="preconnect" href="{origin}" crossorigin=""><link rel="dns-prefetch" href="{origin}">"#
+            )
+        })
+        .collect()
+}
+
 /// Generates a list of all routes defined in Leptos's Router in your app. We can then use this to automatically
 /// create routes in Actix's App without having to use wildcard matching or fallbacks. Takes in your root app Element
 /// as an argument so it can walk you app tree. This version is tailored to generated Actix compatible paths.
+///
+/// If no routes are found, this logs a warning and falls back to a single `/` route. This usually means the app
+/// is missing a `<Router/>` or `<Routes/>`; use [try_generate_route_list] if you'd rather handle that case yourself.
 pub fn generate_route_list<IV>(app_fn: impl FnOnce(leptos::Scope) -> IV + 'static) -> Vec<String>
+where
+    IV: IntoView + 'static,
+{
+    try_generate_route_list(app_fn).unwrap_or_else(|| {
+        leptos::warn!(
+            "generate_route_list() did not find any routes in your app, so only `/` will be \
+             served. Did you forget to add a <Router/> with <Routes/> to your app?"
+        );
+        vec!["/".to_string()]
+    })
+}
+
+/// Generates a list of all routes defined in Leptos's Router in your app, like [generate_route_list],
+/// but returns `None` instead of falling back to `/` if no routes are found.
+pub fn try_generate_route_list<IV>(
+    app_fn: impl FnOnce(leptos::Scope) -> IV + 'static,
+) -> Option<Vec<String>>
 where
     IV: IntoView + 'static,
 {
@@ -607,9 +832,9 @@ where
         .collect();
 
     if routes.is_empty() {
-        vec!["/".to_string()]
+        None
     } else {
-        routes
+        Some(routes)
     }
 }
 
@@ -5,22 +5,65 @@
 //! For more details on how to use the integrations, see the
 //! [`examples`](https://github.com/leptos-rs/leptos/tree/main/examples)
 //! directory in the Leptos repository.
+//!
+//! ## Error overlay
+//! While developing, the pages rendered by the integration show errors in the browser, on a panel over the
+//! page: a server function that returned an error, a render that failed and was replaced by the fallback of
+//! [render_app_to_stream_with_fallback], and the `error` messages pushed by `cargo-leptos` over the
+//! live-reload WebSocket. The overlay is a script added to the head of the page, which can also be called
+//! as `window.__leptos_dev_overlay.show({ kind, error })`.
+//!
+//! Error messages can reveal details of the server, so the overlay is only added when
+//! [LeptosOptions::env] is [Env::DEV] (set `LEPTOS_ENV=PROD` when deploying), and even then only to pages
+//! rendered for [local](RequestParts::is_local) requests, like the live-reload script. Server function
+//! errors are only marked for it under the same conditions.
+//!
+//! A request is only local if Axum knows the address of the peer that sent it, so serve the app with
+//! `app.into_make_service_with_connect_info::<SocketAddr>()` while developing. Otherwise, a warning is
+//! logged the first time a page is rendered without the live-reload script or the overlay for that reason.
 
+use async_compression::tokio::write::{BrotliEncoder, GzipEncoder};
 use axum::{
     body::{Body, Bytes, Full, StreamBody},
-    extract::Path,
+    extract::{FromRequestParts, MatchedPath, Path, State},
     http::{header::HeaderName, header::HeaderValue, HeaderMap, Request, StatusCode},
     response::IntoResponse,
-    routing::get,
+    routing::{any, get, on, MethodFilter},
 };
+pub use cookie::{Cookie, Key, SameSite};
 use futures::{Future, SinkExt, Stream, StreamExt};
-use http::{header, method::Method, uri::Uri, version::Version, Response};
-use hyper::body;
+use http::{header, method::Method, uri::Uri, version::Version, Extensions, Response};
+use hyper::body::{self, HttpBody};
 use leptos::*;
-use leptos_meta::MetaContext;
+use leptos_meta::{provide_meta_context, MetaContext};
 use leptos_router::*;
-use std::{io, pin::Pin, sync::Arc};
-use tokio::{sync::RwLock, task::spawn_blocking, task::LocalSet};
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt},
+    sync::RwLock,
+    task::spawn_blocking,
+    task::LocalSet,
+};
+pub use tokio_util::sync::CancellationToken;
+use tokio_util::{io::ReaderStream, task::LocalPoolHandle};
+
+// The threads that render the app and run server functions. Each has its own current-thread runtime and
+// `LocalSet`, so the reactive runtime, which isn't `Send`, can be used there without building a new
+// Tokio runtime for every request.
+static LOCAL_POOL: Lazy<LocalPoolHandle> = Lazy::new(|| {
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+    LocalPoolHandle::new(threads)
+});
 
 /// A struct to hold the parts of the incoming Request. Since `http::Request` isn't cloneable, we're forced
 /// to construct this for Leptos to use in Axum
@@ -31,13 +74,307 @@ pub struct RequestParts {
     pub uri: Uri,
     pub headers: HeaderMap<HeaderValue>,
     pub body: Bytes,
+    /// The request's extensions, including any values that middleware inserted with
+    /// `req.extensions_mut().insert(...)`, like authentication claims or a trace id.
+    pub extensions: Arc<Extensions>,
+}
+
+// The extensions of a request that's rendered more than once, which can't be cloned into each copy
+struct SharedExtensions(Arc<Extensions>);
+
+/// The path params of the route that matched the request, as extracted by Axum. This is provided as
+/// context when rendering the app, so components and server code can read them without parsing the URL
+/// again.
+///
+/// The keys are the names of the params in the route's path, without the leading `:` or `*`, so for a
+/// route registered as `/posts/:id/*rest`, a request to `/posts/42/comments/7` has the params
+/// `id = "42"` and `rest = "comments/7"`. The values have already been percent-decoded. The map is
+/// empty if the request was handled by a route without params, or by a fallback.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathParams(pub HashMap<String, String>);
+
+impl PathParams {
+    /// Returns the value of the param with the given name, if the route has one.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+}
+
+/// The path of the route that a page was rendered for, as it was added to the router, like `/posts/:id`
+/// rather than `/posts/42`. It's the same for every request to the route, so it suits analytics, logs,
+/// and cache keys:
+/// ```ignore
+/// let route = use_context::<MatchedRoute>(cx).map(|route| route.0);
+/// ```
+/// It's provided as context when rendering pages for the routes added with [LeptosRoutes], and isn't
+/// provided for pages rendered by a fallback or a route you added yourself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedRoute(pub String);
+
+impl MatchedRoute {
+    /// The path of the route, like `/posts/:id`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The URL that a page was requested at, as the client sees it. This is provided as context when
+/// rendering the app, so components that need the origin, like to build a canonical link, a
+/// redirect, or any other absolute URL, don't each have to work it out from the [RequestParts].
+///
+/// The fields come from
+/// - `scheme`: the `proto` of the first `Forwarded` entry, or else the first `X-Forwarded-Proto`,
+///   if [LeptosOptions::trust_forwarded_headers] is set; otherwise the scheme of the request's URI,
+///   which is usually only there for HTTP/2, falling back to `http`.
+/// - `host`: the `host` of the first `Forwarded` entry, or else the first `X-Forwarded-Host`, if
+///   [LeptosOptions::trust_forwarded_headers] is set; otherwise the `Host` header, falling back to
+///   the authority of the request's URI. It includes the port, if there is one, and is empty if the
+///   request doesn't say which host it was sent to.
+/// - `path`: the path of the request's URI, which is never forwarded.
+/// - `query`: the query of the request's URI, without the `?`, if it has one.
+///
+/// Its [Display](std::fmt::Display) implementation writes the full URL, like
+/// `https://example.com/posts?page=2`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestUrl {
+    pub scheme: String,
+    pub host: String,
+    pub path: String,
+    pub query: Option<String>,
+}
+
+impl RequestUrl {
+    /// Works out the URL that the request was made to, using the forwarded headers only if
+    /// `trust_forwarded_headers` is `true`.
+    pub fn new(req: &RequestParts, trust_forwarded_headers: bool) -> Self {
+        let forwarded = trust_forwarded_headers.then(|| forwarded_pairs(&req.headers));
+        let forwarded = forwarded.as_deref().unwrap_or_default();
+        let forwarded_value = |key: &str, header: &str| {
+            forwarded
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value.to_string())
+                .or_else(|| {
+                    req.headers
+                        .get(header)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.split(',').next())
+                        .map(|value| value.trim().to_string())
+                })
+                .filter(|value| !value.is_empty())
+        };
+
+        let scheme = trust_forwarded_headers
+            .then(|| forwarded_value("proto", "x-forwarded-proto"))
+            .flatten()
+            .or_else(|| req.uri.scheme_str().map(String::from))
+            .unwrap_or_else(|| "http".to_string())
+            .to_ascii_lowercase();
+        let host = trust_forwarded_headers
+            .then(|| forwarded_value("host", "x-forwarded-host"))
+            .flatten()
+            .or_else(|| {
+                req.headers
+                    .get(header::HOST)
+                    .and_then(|host| host.to_str().ok())
+                    .map(String::from)
+            })
+            .or_else(|| req.uri.authority().map(|authority| authority.to_string()))
+            .unwrap_or_default();
+
+        Self {
+            scheme,
+            host,
+            path: req.uri.path().to_string(),
+            query: req.uri.query().map(String::from),
+        }
+    }
+
+    /// The scheme and host, like `https://example.com`.
+    pub fn origin(&self) -> String {
+        format!("{}://{}", self.scheme, self.host)
+    }
+}
+
+impl std::fmt::Display for RequestUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}{}", self.scheme, self.host, self.path)?;
+        if let Some(query) = &self.query {
+            write!(f, "?{query}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The ID of the current request, provided as context while rendering and in server functions, for
+/// example to include in logs and error reports.
+///
+/// It's the value of the request header named by [LeptosOptions::request_id_header], usually set by a
+/// load balancer, or a new ID if the request doesn't have one. Either way, it's sent back in the same
+/// header of the response, so a client can quote it. New IDs are 32 hex digits, or a `traceparent`
+/// value if that's the configured header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    fn from_headers(header_name: &str, headers: &HeaderMap) -> Self {
+        let id = headers
+            .get(header_name)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| !value.is_empty());
+        match id {
+            Some(id) => Self(id.to_string()),
+            None => Self::generate(header_name),
+        }
+    }
+
+    fn generate(header_name: &str) -> Self {
+        static COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        let count = COUNT.fetch_add(1, Ordering::Relaxed);
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or_default();
+        let id = format!("{nanos:016x}{count:016x}");
+        if header_name.eq_ignore_ascii_case("traceparent") {
+            // a new trace, with this request as its root span, whose ID can't be all zeros
+            let span = count + 1;
+            Self(format!("00-{id}-{span:016x}-01"))
+        } else {
+            Self(id)
+        }
+    }
+
+    // Sends the ID back in the response, unless the configured header name isn't valid
+    async fn send(&self, header_name: &str, res_options: &ResponseOptions) {
+        if let Ok((name, value)) = parse_header(header_name, &self.0) {
+            res_options.insert_header(name, value).await;
+        }
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// The `key=value` pairs of the first entry of the `Forwarded` header, which the proxy closest to the
+// client added
+fn forwarded_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .get(header::FORWARDED)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(|entry| {
+            entry
+                .split(';')
+                .filter_map(|pair| pair.trim().split_once('='))
+                .map(|(key, value)| {
+                    (
+                        key.trim().to_string(),
+                        value.trim().trim_matches('"').to_string(),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl RequestParts {
+    /// Returns the value of type `T` that was inserted into the request's extensions, for example
+    /// by a tower layer in front of the Leptos handlers:
+    /// ```ignore
+    /// let claims = use_context::<RequestParts>(cx)
+    ///     .and_then(|req| req.extension::<Claims>().cloned())
+    ///     .ok_or_else(|| ServerFnError::ServerError("not signed in".into()))?;
+    /// ```
+    pub fn extension<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions.get::<T>()
+    }
+
+    /// Returns the cookie with the given name if it was sent with the request and its signature
+    /// can be verified with `key`. Cookies that are missing, unsigned, or have been tampered with
+    /// all return `None`.
+    ///
+    /// The `key` must be the same one that was used to sign the cookie with
+    /// [ResponseOptions::set_signed_cookie]. See that method for notes on managing the key.
+    pub fn signed_cookie(&self, key: &Key, name: &str) -> Option<Cookie<'static>> {
+        let mut jar = cookie::CookieJar::new();
+        for cookie in request_cookies(&self.headers) {
+            jar.add_original(cookie);
+        }
+        jar.signed(key).get(name)
+    }
+
+    /// Whether the request was made to this machine from this machine. The integration only adds the
+    /// live-reload script and the error overlay to pages rendered for such requests, so the development
+    /// tooling isn't served to other clients that can reach a development server.
+    ///
+    /// A request is local if both
+    /// - the address of the peer that connected to the server is a loopback address like `127.0.0.1` or
+    ///   `::1`, and
+    /// - if it came through proxies that set `X-Forwarded-For` or `Forwarded`, every client address
+    ///   they list is a loopback address.
+    ///
+    /// The peer's address is read from the [ConnectInfo](axum::extract::ConnectInfo) that Axum adds to
+    /// requests when the app is served with `into_make_service_with_connect_info::<SocketAddr>()`. Without
+    /// it, no request is local. The `Host` header is set by the client, so it isn't taken into account.
+    pub fn is_local(&self) -> bool {
+        let peer = self
+            .extensions
+            .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+            .map(|axum::extract::ConnectInfo(addr)| addr.ip());
+        if !peer.map(|ip| ip.is_loopback()).unwrap_or(false) {
+            return false;
+        }
+
+        let forwarded_for = self
+            .headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim);
+        let forwarded = self
+            .headers
+            .get_all(header::FORWARDED)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split([',', ';']))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .filter(|(key, _)| key.eq_ignore_ascii_case("for"))
+            .map(|(_, value)| value.trim_matches('"'));
+        forwarded_for.chain(forwarded).all(is_loopback_ip)
+    }
+}
+
+// Whether a client address from a proxy header, which may have a port, is a loopback address
+fn is_loopback_ip(address: &str) -> bool {
+    let address = address.trim_start_matches('[');
+    let address = address.split(']').next().unwrap_or_default();
+    address
+        .parse::<std::net::IpAddr>()
+        .or_else(|_| {
+            address
+                .parse::<std::net::SocketAddr>()
+                .map(|addr| addr.ip())
+        })
+        .map(|ip| ip.is_loopback())
+        .unwrap_or(false)
 }
+
 /// This struct lets you define headers and override the status of the Response from an Element or a Server Function
 /// Typically contained inside of a ResponseOptions. Setting this is useful for cookies and custom responses.
 #[derive(Debug, Clone, Default)]
 pub struct ResponseParts {
     pub status: Option<StatusCode>,
     pub headers: HeaderMap,
+    /// A body that's sent in place of the rendered page or the server function's result. See
+    /// [ResponseOptions::set_body].
+    pub body: Option<Bytes>,
+    /// The `Content-Type` of [ResponseParts::body].
+    pub content_type: Option<HeaderValue>,
 }
 
 impl ResponseParts {
@@ -49,6 +386,50 @@ impl ResponseParts {
     pub fn append_header(&mut self, key: HeaderName, value: HeaderValue) {
         self.headers.append(key, value);
     }
+    /// Insert a header given as strings, overwriting any previous value with the same key. Fails without
+    /// changing the headers if the name or value isn't valid.
+    pub fn insert_header_str(&mut self, key: &str, value: &str) -> Result<(), InvalidHeader> {
+        let (key, value) = parse_header(key, value)?;
+        self.insert_header(key, value);
+        Ok(())
+    }
+    /// Append a header given as strings, leaving any header with the same key intact. Fails without
+    /// changing the headers if the name or value isn't valid.
+    pub fn append_header_str(&mut self, key: &str, value: &str) -> Result<(), InvalidHeader> {
+        let (key, value) = parse_header(key, value)?;
+        self.append_header(key, value);
+        Ok(())
+    }
+}
+
+/// The error returned when a header given as strings, like to [ResponseOptions::insert_header_str],
+/// isn't a valid HTTP header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidHeader {
+    /// The name isn't a valid header name.
+    Name(String),
+    /// The value given for the header with this name isn't a valid header value, for example
+    /// because it contains a newline.
+    Value(String),
+}
+
+impl std::fmt::Display for InvalidHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "invalid header name {name:?}"),
+            Self::Value(name) => write!(f, "invalid value for the {name} header"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidHeader {}
+
+fn parse_header(key: &str, value: &str) -> Result<(HeaderName, HeaderValue), InvalidHeader> {
+    let key =
+        HeaderName::from_bytes(key.as_bytes()).map_err(|_| InvalidHeader::Name(key.to_string()))?;
+    let value =
+        HeaderValue::from_str(value).map_err(|_| InvalidHeader::Value(key.as_str().to_string()))?;
+    Ok((key, value))
 }
 
 /// Adding this Struct to your Scope inside of a Server Fn or Element will allow you to override details of the Response
@@ -81,13 +462,214 @@ impl ResponseOptions {
         let res_parts = &mut *writeable;
         res_parts.headers.append(key, value);
     }
+    /// Insert a header given as strings, overwriting any previous value with the same key. Fails without
+    /// changing the response if the name or value isn't valid.
+    /// ```ignore
+    /// response_options.insert_header_str("Cache-Control", "max-age=60").await?;
+    /// ```
+    pub async fn insert_header_str(&self, key: &str, value: &str) -> Result<(), InvalidHeader> {
+        let (key, value) = parse_header(key, value)?;
+        self.insert_header(key, value).await;
+        Ok(())
+    }
+    /// Append a header given as strings, leaving any header with the same key intact. Fails without
+    /// changing the response if the name or value isn't valid.
+    pub async fn append_header_str(&self, key: &str, value: &str) -> Result<(), InvalidHeader> {
+        let (key, value) = parse_header(key, value)?;
+        self.append_header(key, value).await;
+        Ok(())
+    }
+    /// Set the `Content-Type` of the returned Response, like `application/pdf`
+    pub async fn set_content_type(&self, content_type: &str) -> Result<(), InvalidHeader> {
+        self.insert_header_str(header::CONTENT_TYPE.as_str(), content_type)
+            .await
+    }
+    /// Replace the body of the returned Response with `body`, sent with the given `Content-Type`, like a
+    /// generated PDF or a JSON body for a redirect. Fails without changing the response if the content
+    /// type isn't a valid header value.
+    /// ```ignore
+    /// response_options.set_body("application/pdf", report.into_bytes()).await?;
+    /// ```
+    /// The status and headers set through [ResponseOptions] are kept, and the status is `200 OK` if none
+    /// was set. In a server function, the body is sent in place of its serialized result, as long as it
+    /// returns `Ok`; an error is sent as usual. While rendering, the body is sent in place of the page, as
+    /// long as it's set before the app shell has rendered: a streamed page has already started by the time
+    /// anything under a `<Suspense/>` runs, so setting a body from there is ignored, and logged as a warning.
+    pub async fn set_body(
+        &self,
+        content_type: &str,
+        body: impl Into<Bytes>,
+    ) -> Result<(), InvalidHeader> {
+        let content_type = HeaderValue::from_str(content_type)
+            .map_err(|_| InvalidHeader::Value(header::CONTENT_TYPE.as_str().to_string()))?;
+        let mut writeable = self.0.write().await;
+        let res_parts = &mut *writeable;
+        res_parts.body = Some(body.into());
+        res_parts.content_type = Some(content_type);
+        Ok(())
+    }
+    /// Set the `Location` header of the returned Response. This doesn't change its status; see
+    /// [redirect] to redirect the user.
+    pub async fn set_location(&self, location: &str) -> Result<(), InvalidHeader> {
+        self.insert_header_str(header::LOCATION.as_str(), location)
+            .await
+    }
+    /// Signs `cookie` with `key` and appends it to the response as a `Set-Cookie` header. The value
+    /// stays readable by the client, but any change to it will be rejected by [RequestParts::signed_cookie].
+    ///
+    /// The [Key] should be created once at startup, e.g. with `Key::from(secret)` using a secret of
+    /// at least 64 random bytes loaded from your environment, and shared with every handler. Keep it
+    /// out of your source code: anyone who has it can forge cookies. Changing the key invalidates all
+    /// cookies signed with the old one.
+    pub async fn set_signed_cookie(&self, key: &Key, cookie: Cookie<'static>) {
+        let name = cookie.name().to_string();
+        let mut jar = cookie::CookieJar::new();
+        jar.signed_mut(key).add(cookie);
+        if let Some(signed) = jar.get(&name) {
+            self.append_header(
+                header::SET_COOKIE,
+                HeaderValue::from_str(&signed.to_string()).expect("Failed to create HeaderValue"),
+            )
+            .await;
+        }
+    }
+}
+
+/// The cookies sent with the current request, which server functions and the app can read and change
+/// without handling the `Cookie` and `Set-Cookie` headers themselves. It's provided as context along
+/// with [RequestParts]:
+/// ```ignore
+/// let cookies = use_context::<CookieJar>(cx).expect("no CookieJar");
+/// let theme = cookies.get("theme").map(|cookie| cookie.value().to_string());
+/// cookies.set(
+///     Cookie::build("theme", "dark")
+///         .path("/")
+///         .http_only(true)
+///         .secure(true)
+///         .same_site(SameSite::Lax)
+///         .max_age(time::Duration::days(365))
+///         .finish(),
+/// );
+/// cookies.remove(Cookie::build("session", "").path("/").finish());
+/// ```
+/// Each cookie that was set or removed is added to the [ResponseOptions] as a `Set-Cookie` header when
+/// a server function returns successfully or, when rendering the app, once its shell has rendered:
+/// changes made after the response's headers have been built aren't sent.
+#[derive(Clone, Default)]
+pub struct CookieJar(Arc<std::sync::Mutex<cookie::CookieJar>>);
+
+impl CookieJar {
+    /// Creates a jar with the cookies in the `Cookie` headers of a request.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let mut jar = cookie::CookieJar::new();
+        for cookie in request_cookies(headers) {
+            jar.add_original(cookie);
+        }
+        Self(Arc::new(std::sync::Mutex::new(jar)))
+    }
+
+    /// Returns the cookie with the given name, as it was sent with the request or last set.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.0.lock().unwrap().get(name).cloned()
+    }
+
+    /// Adds a cookie, or replaces the one with the same name, along with its attributes like `Path`,
+    /// `HttpOnly`, `Secure`, `SameSite` and `Max-Age`.
+    pub fn set(&self, cookie: Cookie<'static>) {
+        self.0.lock().unwrap().add(cookie);
+    }
+
+    /// Removes a cookie, telling the browser to delete it if it was sent with the request. The browser
+    /// only deletes a cookie with the same `Path` and `Domain` as the one it's given, so those should
+    /// match the attributes the cookie was set with.
+    pub fn remove(&self, cookie: Cookie<'static>) {
+        self.0.lock().unwrap().remove(cookie);
+    }
+
+    /// All of the cookies in the jar, including the ones that have been set since the request.
+    pub fn cookies(&self) -> Vec<Cookie<'static>> {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+
+    // Returns the cookies that were set or removed, and keeps the jar as it is now, so the same
+    // change is only sent once
+    fn take_changes(&self) -> Vec<Cookie<'static>> {
+        let mut jar = self.0.lock().unwrap();
+        let changes = jar.delta().cloned().collect();
+        let mut unchanged = cookie::CookieJar::new();
+        for cookie in jar.iter() {
+            unchanged.add_original(cookie.clone());
+        }
+        *jar = unchanged;
+        changes
+    }
+}
+
+impl std::fmt::Debug for CookieJar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CookieJar").field(&self.cookies()).finish()
+    }
+}
+
+// The cookies in the `Cookie` headers of a request
+fn request_cookies(headers: &HeaderMap) -> impl Iterator<Item = Cookie<'static>> + '_ {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| Cookie::parse(cookie.trim().to_string()).ok())
+}
+
+// Adds the changes to the CookieJar in context to the ResponseOptions, as `Set-Cookie` headers
+async fn flush_cookies(cx: leptos::Scope) {
+    if let (Some(jar), Some(res_options)) = (
+        use_context::<CookieJar>(cx),
+        use_context::<ResponseOptions>(cx),
+    ) {
+        for cookie in jar.take_changes() {
+            if let Ok(value) = HeaderValue::from_str(&cookie.to_string()) {
+                res_options.append_header(header::SET_COOKIE, value).await;
+            }
+        }
+    }
 }
 
 /// Provides an easy way to redirect the user from within a server function. Mimicing the Remix `redirect()`,
 /// it sets a StatusCode of 302 and a LOCATION header with the provided value.
 /// If looking to redirect from the client, `leptos_router::use_navigate()` should be used instead
+///
+/// To stop a misconfigured flow (like a login page that redirects to itself) from sending the browser around
+/// in circles, this counts the redirects it makes in a row, and refuses to make more than the [RedirectLimit]
+/// provided as context, or [DEFAULT_REDIRECT_LIMIT] if there isn't one. The count is kept in a short-lived
+/// `leptos_redirects` cookie, so it only includes redirects that follow each other within a few seconds, like
+/// a browser following them. Once the limit is reached, the response gets a `508 Loop Detected` status and no
+/// `Location` header, the count is reset, and the refused redirect is logged as a `tracing` warning with the
+/// `leptos_axum::redirect` target.
 pub async fn redirect(cx: leptos::Scope, path: &str) {
     let response_options = use_context::<ResponseOptions>(cx).unwrap();
+    let RedirectLimit(limit) = use_context(cx).unwrap_or(RedirectLimit(DEFAULT_REDIRECT_LIMIT));
+    let redirects = use_context::<RequestParts>(cx)
+        .map(|req| redirect_count(&req.headers))
+        .unwrap_or(0)
+        + 1;
+
+    if redirects > limit {
+        tracing::warn!(
+            target: "leptos_axum::redirect",
+            "refused to redirect to {path} after {limit} redirects in a row"
+        );
+        response_options.set_status(StatusCode::LOOP_DETECTED).await;
+        response_options
+            .append_header(
+                header::SET_COOKIE,
+                HeaderValue::from_str(&format!("{REDIRECT_COOKIE}=0; Path=/; Max-Age=0"))
+                    .expect("Failed to create HeaderValue"),
+            )
+            .await;
+        return;
+    }
+
     response_options.set_status(StatusCode::FOUND).await;
     response_options
         .insert_header(
@@ -95,20 +677,369 @@ pub async fn redirect(cx: leptos::Scope, path: &str) {
             header::HeaderValue::from_str(path).expect("Failed to create HeaderValue"),
         )
         .await;
+    response_options
+        .append_header(
+            header::SET_COOKIE,
+            HeaderValue::from_str(&format!(
+                "{REDIRECT_COOKIE}={redirects}; Path=/; Max-Age={REDIRECT_COOKIE_MAX_AGE}; HttpOnly; SameSite=Lax"
+            ))
+            .expect("Failed to create HeaderValue"),
+        )
+        .await;
+}
+
+/// The number of redirects in a row that [redirect] makes before refusing, unless a [RedirectLimit] is
+/// provided as context.
+pub const DEFAULT_REDIRECT_LIMIT: u32 = 10;
+
+/// The number of redirects in a row that [redirect] makes before refusing to redirect again. Provide it as
+/// context, e.g. in the `additional_context` of [handle_server_fns_with_context] and
+/// [render_app_to_stream_with_context], to change the [DEFAULT_REDIRECT_LIMIT].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedirectLimit(pub u32);
+
+// The cookie in which [redirect] counts its redirects, and how many seconds a redirect counts for
+const REDIRECT_COOKIE: &str = "leptos_redirects";
+const REDIRECT_COOKIE_MAX_AGE: u32 = 10;
+
+fn redirect_count(headers: &HeaderMap) -> u32 {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == REDIRECT_COOKIE)
+        .and_then(|(_, count)| count.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Runs blocking or CPU-heavy work, like image processing, from within a server function without
+/// stalling the executor the server function is running on. `f` is moved onto Tokio's blocking
+/// thread pool and its return value is handed back when it completes.
+///
+/// `f` runs on a different thread, where the reactive system isn't available, so it can't call
+/// [use_context] or read signals. Pull everything it needs out of the [Scope] before calling this;
+/// once the returned future resolves, the server function continues on its original thread, so
+/// its `cx` and any context (like [ResponseOptions]) can be used as usual with the result:
+/// ```ignore
+/// #[server(Thumbnail, "/api")]
+/// pub async fn thumbnail(cx: Scope, image: Vec<u8>) -> Result<Vec<u8>, ServerFnError> {
+///     let thumbnail = leptos_axum::run_blocking(move || resize(&image, 128, 128)).await?;
+///     let res = use_context::<leptos_axum::ResponseOptions>(cx).unwrap();
+///     res.insert_header(header::CONTENT_TYPE, HeaderValue::from_static("image/png")).await;
+///     Ok(thumbnail)
+/// }
+/// ```
+/// If `f` panics, this returns a [ServerFnError::ServerError] rather than taking down the request.
+pub async fn run_blocking<T, F>(f: F) -> Result<T, ServerFnError>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    spawn_blocking(f)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
+
+/// Stops a server function early and responds with `status`, for cases like a missing login where there's
+/// no useful error to send back. Return the error this gives you from the server function:
+/// ```ignore
+/// #[server(DeletePost, "/api")]
+/// pub async fn delete_post(cx: Scope, id: u32) -> Result<(), ServerFnError> {
+///     if current_user(cx).await.is_none() {
+///         return Err(leptos_axum::abort_with_status(cx, StatusCode::UNAUTHORIZED));
+///     }
+///     // ...
+/// }
+/// ```
+/// The response has the given status, any headers already set with [ResponseOptions] (like
+/// `WWW-Authenticate`), and a plain text body containing only the status's reason phrase, e.g. `Unauthorized`.
+/// Unlike other errors, it's never redirected back to the referrer. On the client, the server function
+/// returns a [ServerFnError] as it would for any other error status.
+pub fn abort_with_status(cx: leptos::Scope, status: StatusCode) -> ServerFnError {
+    provide_context(cx, AbortStatus(status));
+    ServerFnError::ServerError(status.to_string())
+}
+
+#[derive(Clone, Copy)]
+struct AbortStatus(StatusCode);
+
+/// Returns a `Content-Security-Policy` header value that allows the inline scripts emitted while
+/// rendering the app (the hydration script, the scripts that stream [Resource]s and `<Suspense/>`
+/// fragments, and the live-reload script if `autoreload` is enabled), and nothing else inline:
+/// - `default-src 'self'`: by default, everything must come from your own origin.
+/// - `script-src 'self' 'nonce-…' 'wasm-unsafe-eval'`: scripts from your origin and inline scripts
+///   with the nonce, which all of the scripts above carry, as well as compiling the app's WebAssembly.
+///   Without a nonce, `'unsafe-inline'` is needed instead.
+/// - `style-src 'self' 'unsafe-inline'`: stylesheets from your origin, and `style` attributes and
+///   `<style>` tags, which views often use.
+/// - `connect-src 'self' ws://…`: with `autoreload`, the live-reload WebSocket at
+///   [LeptosOptions::reload_ws_origin] is allowed as well.
+///
+/// Use [set_content_security_policy] to send it with a rendered page.
+pub fn content_security_policy(
+    options: &LeptosOptions,
+    nonce: Option<&CspNonce>,
+    autoreload: bool,
+) -> String {
+    let inline_scripts = match nonce {
+        Some(nonce) => format!("'nonce-{}'", nonce.0),
+        None => "'unsafe-inline'".to_string(),
+    };
+    let mut policy = format!(
+        "default-src 'self'; script-src 'self' {inline_scripts} 'wasm-unsafe-eval'; \
+         style-src 'self' 'unsafe-inline'"
+    );
+    if autoreload {
+        policy.push_str(&format!(
+            "; connect-src 'self' {}",
+            options.reload_ws_origin()
+        ));
+    }
+    policy
+}
+
+/// Sends the [content_security_policy] for `nonce` with the rendered page. Call this in the
+/// `additional_context` of a render function, like [render_app_to_stream_with_context], with a
+/// nonce generated randomly for each request:
+/// ```ignore
+/// let handler = leptos_axum::render_app_to_stream_with_context(
+///     options.clone(),
+///     move |cx| leptos_axum::set_content_security_policy(cx, &options, leptos_axum::generate_csp_nonce()),
+///     |cx| view! { cx, <TodoApp/> },
+/// );
+/// ```
+/// This provides the [CspNonce] as context, so every inline script in the page gets the nonce, and
+/// sets the `Content-Security-Policy` header in the [ResponseOptions] once the app has rendered,
+/// unless the app set one itself. Whether the live-reload script is allowed follows the
+/// `LEPTOS_WATCH` environment variable, just like whether that script is emitted.
+pub fn set_content_security_policy(cx: leptos::Scope, options: &LeptosOptions, nonce: CspNonce) {
+    let policy = content_security_policy(options, Some(&nonce), autoreload_enabled());
+    let policy = HeaderValue::from_str(&policy).expect("Failed to create HeaderValue");
+    provide_context(cx, nonce);
+    provide_context(cx, ContentSecurityPolicy(policy));
+}
+
+/// Generates a new [CspNonce] from 16 cryptographically random bytes, for one response. The integration
+/// generates one for every rendered page when [LeptosOptions::csp_nonce] is set.
+///
+/// ## Panics
+/// Panics if the operating system's random number generator can't be read.
+pub fn generate_csp_nonce() -> CspNonce {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("couldn't generate a random CSP nonce");
+    CspNonce(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+// The policy set by set_content_security_policy(), added to the response after rendering
+#[derive(Clone)]
+struct ContentSecurityPolicy(HeaderValue);
+
+// Whether the live-reload script can be added to pages, which is the case while cargo-leptos is watching;
+// it's only added to pages for local requests
+fn autoreload_enabled() -> bool {
+    std::env::var("LEPTOS_WATCH").is_ok()
+}
+
+// Logs, once, that the live-reload script and the error overlay are left out of the app's pages because it
+// isn't served with the address of each request's peer, which is how local requests are told apart
+fn warn_without_peer_address(req: &RequestParts) {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+    let has_peer = req
+        .extensions
+        .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+        .is_some();
+    if !has_peer && !WARNED.swap(true, Ordering::Relaxed) {
+        tracing::warn!(
+            target: "leptos_axum::autoreload",
+            "live reload and the error overlay are turned off, because requests don't have the \
+             address of their peer; serve the app with \
+             `into_make_service_with_connect_info::<SocketAddr>()` to turn them on"
+        );
+    }
+}
+
+// Whether the error overlay can be added to pages, which is only the case in [Env::DEV]; like the live-reload
+// script, it's only added to pages for local requests
+fn dev_overlay_enabled(options: &LeptosOptions) -> bool {
+    matches!(options.env, Env::DEV)
+}
+
+// The header that marks a server fn's error response for the overlay, with the kind of error as its value
+const DEV_ERROR_HEADER: &str = "x-leptos-error";
+
+// The error overlay shown in the browser during development. It shows the errors pushed with
+// `window.__leptos_dev_overlay.show()`: server fn responses marked with [DEV_ERROR_HEADER], a render that
+// failed before its shell was sent, and `error` messages from the live-reload WebSocket. The errors are only
+// ever inserted as text. Clicking outside of them or pressing Escape hides the overlay.
+fn dev_overlay_script(nonce: &str) -> String {
+    format!(
+        r#"
+            <script{nonce}>(function () {{
+                if (window.__leptos_dev_overlay) return;
+                var panel = null;
+                function hide() {{
+                    if (panel) panel.remove();
+                    panel = null;
+                }}
+                function show(err) {{
+                    if (!document.body) {{
+                        document.addEventListener('DOMContentLoaded', function () {{ show(err); }});
+                        return;
+                    }}
+                    if (!panel) {{
+                        panel = document.createElement('div');
+                        panel.id = 'leptos-dev-overlay';
+                        panel.style.cssText = 'position:fixed;inset:0;z-index:2147483647;overflow:auto;padding:2rem;'
+                            + 'background:rgba(20,20,24,.9);color:#f8f8f2;font:14px/1.5 ui-monospace,Menlo,monospace';
+                        panel.addEventListener('click', function (ev) {{ if (ev.target === panel) hide(); }});
+                        document.body.appendChild(panel);
+                    }}
+                    var entry = document.createElement('div');
+                    entry.style.cssText = 'max-width:60rem;margin:0 auto 1rem;padding:1rem 1.25rem;'
+                        + 'border-left:4px solid #ff5555;background:#282a36';
+                    var title = document.createElement('div');
+                    title.style.cssText = 'color:#ff5555;font-weight:bold';
+                    title.textContent = (err.kind || 'Error') + (err.source ? ' in ' + err.source : '');
+                    var message = document.createElement('pre');
+                    message.style.cssText = 'margin:.5rem 0 0;white-space:pre-wrap';
+                    message.textContent = err.error || String(err);
+                    entry.append(title, message);
+                    panel.appendChild(entry);
+                }}
+                document.addEventListener('keydown', function (ev) {{ if (ev.key === 'Escape') hide(); }});
+                var fetch = window.fetch;
+                window.fetch = function () {{
+                    return fetch.apply(this, arguments).then(function (res) {{
+                        var kind = res.headers.get('{DEV_ERROR_HEADER}');
+                        if (kind) {{
+                            res.clone().text().then(function (text) {{
+                                var error = text;
+                                try {{ error = JSON.parse(text).error || text; }} catch (e) {{}}
+                                show({{ kind: 'ServerFnError::' + kind, error: error, source: res.url }});
+                            }});
+                        }}
+                        return res;
+                    }});
+                }};
+                window.__leptos_dev_overlay = {{ show: show, hide: hide }};
+            }})()
+            </script>
+            "#
+    )
+}
+
+// The error that stopped the app from rendering, provided to the fallback so the overlay can show it
+#[derive(Clone)]
+struct RenderError(String);
+
+// Shows a render error in the overlay, as JSON that can't close the script early
+fn dev_render_error_script(nonce: &str, error: &str) -> String {
+    let error = serde_json::json!({ "kind": "Render error", "error": error })
+        .to_string()
+        .replace("</", "<\\/");
+    format!(
+        r#"<script{nonce}>window.__leptos_dev_overlay && window.__leptos_dev_overlay.show({error});</script>"#
+    )
 }
 
 /// Decomposes an HTTP request into its parts, allowing you to read its headers
 /// and other data without consuming the body.
-pub async fn generate_request_parts(req: Request<Body>) -> RequestParts {
-    // provide request headers as context in server scope
-    let (parts, body) = req.into_parts();
-    let body = body::to_bytes(body).await.unwrap_or_default();
-    RequestParts {
+///
+/// The whole body is read into memory. If the router has an `Extension(Arc<LeptosOptions>)` layer, a body
+/// larger than [LeptosOptions::max_body_size] is rejected with `413 Payload Too Large` instead, as soon as
+/// it turns out to be too large, so a client can't make the server buffer an arbitrarily large body. Use
+/// [generate_request_parts_with_limit] to pass the limit explicitly.
+pub async fn generate_request_parts(req: Request<Body>) -> Result<RequestParts, StatusCode> {
+    let max_body_size = req
+        .extensions()
+        .get::<Arc<LeptosOptions>>()
+        .and_then(|options| options.max_body_size);
+    generate_request_parts_with_limit(req, max_body_size).await
+}
+
+/// Decomposes an HTTP request into its parts, like [generate_request_parts], rejecting it with
+/// `413 Payload Too Large` if its body is larger than `max_body_size` bytes. With a `max_body_size` of `None`,
+/// bodies of any size are read.
+///
+/// A body that can't be read because the connection failed is rejected with `400 Bad Request` if there's a
+/// limit, and is otherwise treated as empty.
+pub async fn generate_request_parts_with_limit(
+    req: Request<Body>,
+    max_body_size: Option<usize>,
+) -> Result<RequestParts, StatusCode> {
+    let (mut parts, body) = req.into_parts();
+    let body = read_body(&parts.headers, body, max_body_size).await?;
+    let extensions = match parts.extensions.remove::<SharedExtensions>() {
+        Some(SharedExtensions(extensions)) => extensions,
+        None => Arc::new(parts.extensions),
+    };
+    Ok(RequestParts {
         method: parts.method,
         uri: parts.uri,
         headers: parts.headers,
         version: parts.version,
         body,
+        extensions,
+    })
+}
+
+// Reads the whole body, failing as soon as it turns out to be larger than `max_body_size`, which
+// a `Content-Length` header can tell before any of it has been read
+async fn read_body(
+    headers: &HeaderMap,
+    mut body: Body,
+    max_body_size: Option<usize>,
+) -> Result<Bytes, StatusCode> {
+    let max_body_size = match max_body_size {
+        Some(max_body_size) => max_body_size,
+        None => return Ok(body::to_bytes(body).await.unwrap_or_default()),
+    };
+
+    let content_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if content_length.unwrap_or_default() > max_body_size {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    let mut buffer = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| StatusCode::BAD_REQUEST)?;
+        if buffer.len() + chunk.len() > max_body_size {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(buffer.into())
+}
+
+// Buffers the request's body if [LeptosOptions::max_body_size] is set, rejecting it with
+// `413 Payload Too Large` as soon as it turns out to be larger than that
+async fn limit_body(
+    options: &LeptosOptions,
+    req: Request<Body>,
+) -> Result<Request<Body>, StatusCode> {
+    if options.max_body_size.is_none() {
+        return Ok(req);
+    }
+    let (parts, body) = req.into_parts();
+    let body = read_body(&parts.headers, body, options.max_body_size).await?;
+    Ok(Request::from_parts(parts, Body::from(body)))
+}
+
+// Waits for `fut`, failing with `408 Request Timeout` if [LeptosOptions::request_timeout] elapses first
+async fn limit_time<T>(
+    options: &LeptosOptions,
+    fut: impl Future<Output = T>,
+) -> Result<T, StatusCode> {
+    match options.request_timeout {
+        Some(timeout) => tokio::time::timeout(Duration::from_millis(timeout), fut)
+            .await
+            .map_err(|_| StatusCode::REQUEST_TIMEOUT),
+        None => Ok(fut.await),
     }
 }
 
@@ -134,7 +1065,7 @@ pub async fn generate_request_parts(req: Request<Body>) -> RequestParts {
 ///     // run our app with hyper
 ///     // `axum::Server` is a re-export of `hyper::Server`
 ///     axum::Server::bind(&addr)
-///         .serve(app.into_make_service())
+///         .serve(app.into_make_service_with_connect_info::<SocketAddr>())
 ///         .await
 ///         .unwrap();
 /// }
@@ -143,16 +1074,170 @@ pub async fn generate_request_parts(req: Request<Body>) -> RequestParts {
 /// Leptos provides a generic implementation of `handle_server_fns`. If access to more specific parts of the Request is desired,
 /// you can specify your own server fn handler based on this one and give it it's own route in the server macro.
 ///
+/// If the request's `Accept` header isn't one of the formats server functions respond with (`application/json`,
+/// `application/x-www-form-urlencoded`, `application/cbor`, or `application/x-ndjson`), the server function is
+/// assumed to have been called by a plain `<form>` submission, and a successful call responds with a
+/// `303 See Other` redirect back to the `Referer`. Use [handle_server_fns_api] if your server functions are
+/// only called programmatically.
+///
+/// Request bodies are parsed according to the server function's encoding, unless a deserializer has been
+/// registered for their `Content-Type` with [register_body_deserializer](leptos::register_body_deserializer).
+///
+/// Server functions are looked up in the global [ServerFnRegistry](leptos::ServerFnRegistry), which is where
+/// `register()` adds them. To keep the server functions of several apps apart, register each app's functions
+/// in its own registry with `register_in()` and add that registry to its router with
+/// `.layer(Extension(registry))`; requests to that router only see the functions in it.
+///
+/// ## Errors
+/// A server function that returns an error gets a `400 Bad Request` response if its arguments couldn't be
+/// read ([ServerFnError::Args], [ServerFnError::MissingArg], or [ServerFnError::Deserialization], which is
+/// what decoding the request body fails with), and a `500 Internal Server Error` otherwise. A status set with
+/// [ResponseOptions::set_status] before returning the error takes the place of either, like a
+/// `422 Unprocessable Entity` for a form that didn't validate, and the headers set through [ResponseOptions]
+/// are sent as well.
+/// The body is the error message as plain text, unless the request has an `Accept: application/json` header,
+/// in which case it's a JSON object with the message and the kind of error:
+/// ```json
+/// {"error": "error running server function: not signed in", "kind": "ServerError"}
+/// ```
+/// In [Env::DEV], if the router has an `Extension(Arc<LeptosOptions>)` layer, error responses to
+/// [local](RequestParts::is_local) requests also have an `X-Leptos-Error` header with the kind of error, so
+/// the error overlay (see the [crate] docs) can show them.
+///
+/// A server function that panics gets a `500 Internal Server Error` response with a generic body, so the
+/// panic's message isn't sent to the client; it's logged instead, with the `leptos_axum::server_fn` target.
+///
+/// To send errors in a format of your own, like an HTML error page for browsers, add an [ErrorRenderer]
+/// to the router.
+///
+/// A request that lacks one of the headers a server function requires with `require_header = "..."` is
+/// rejected before the function runs, as a [ServerFnError::MissingArg] with the name of the header: a
+/// `400 Bad Request` with the body `missing argument X-Api-Key`, or the JSON error described above.
+///
+/// Server functions run on a shared pool of threads, one per CPU, which also renders the app, so a server
+/// function that blocks holds up the other requests on its thread. Move blocking work off the pool with
+/// [run_blocking].
+///
+/// ## Limits
+/// If the router has an `Extension(Arc<LeptosOptions>)` layer, as in the examples, requests whose body is
+/// larger than [LeptosOptions::max_body_size] are rejected with `413 Payload Too Large`, and server functions
+/// that take longer than [LeptosOptions::request_timeout] to return get a `408 Request Timeout` response. This
+/// applies to all of the server function handlers. Without the layer, this handler can't see the options, and
+/// neither limit applies; use [handle_server_fns_with_options] to pass them in instead:
+/// ```ignore
+/// let app = Router::new()
+///     .route("/api/*fn_name", post(leptos_axum::handle_server_fns_with_options(leptos_options.clone())));
+/// ```
+///
+/// ## Cancellation
+/// Each call gets a [CancellationToken] in its context, which is cancelled if the client goes away before the
+/// response has been sent, or if the call takes longer than [LeptosOptions::request_timeout]. For a streamed
+/// NDJSON response, it's also cancelled when the client stops reading the stream. The server function keeps
+/// running either way, so long-running work should check the token, or race against it, and stop early:
+/// ```ignore
+/// #[server(Report, "/api")]
+/// pub async fn report(cx: Scope) -> Result<String, ServerFnError> {
+///     let cancelled = use_context::<leptos_axum::CancellationToken>(cx).unwrap();
+///     tokio::select! {
+///         report = build_report() => Ok(report),
+///         _ = cancelled.cancelled() => Err(ServerFnError::ServerError("the client went away".into())),
+///     }
+/// }
+/// ```
+///
+/// ## CORS
+/// CORS is off by default, so browsers only let pages from the app's own origin call its server functions.
+/// To let other origins call them too, like a separate frontend or a browser extension, list them in
+/// [LeptosOptions::server_fn_cors_origins] and add the `Extension(Arc<LeptosOptions>)` layer. Requests from
+/// those origins then get an `Access-Control-Allow-Origin` header, and the handler answers their `OPTIONS`
+/// preflight requests with `204 No Content` and the methods and headers in
+/// [LeptosOptions::server_fn_cors_methods] and [LeptosOptions::server_fn_cors_headers]. The route has to send
+/// `OPTIONS` requests to the handler as well:
+/// ```ignore
+/// let app = Router::new()
+///     .route("/api/*fn_name", post(leptos_axum::handle_server_fns).options(leptos_axum::handle_server_fns))
+///     .layer(Extension(Arc::new(leptos_options)));
+/// ```
+///
 /// ## Provided Context Types
 /// This function always provides context values including the following types:
 /// - [RequestParts]
+/// - [CookieJar]
 /// - [ResponseOptions]
+/// - [CancellationToken]
+/// - [RequestId]
 pub async fn handle_server_fns(
     Path(fn_name): Path<String>,
     headers: HeaderMap,
     req: Request<Body>,
 ) -> impl IntoResponse {
-    handle_server_fns_inner(fn_name, headers, |_| {}, req).await
+    handle_server_fns_inner(fn_name, headers, |_| {}, false, req).await
+}
+
+/// Returns an Axum handler that runs server functions like [handle_server_fns], with `options` in place of
+/// an `Extension(Arc<LeptosOptions>)` layer, so [LeptosOptions::max_body_size],
+/// [LeptosOptions::request_timeout], and the CORS and dev overlay settings apply without one:
+/// ```ignore
+/// let app = Router::new()
+///     .route("/api/*fn_name", post(leptos_axum::handle_server_fns_with_options(leptos_options.clone())))
+///     .leptos_routes(leptos_options.clone(), routes, |cx| view! { cx, <App/> });
+/// ```
+/// If the router does have the layer, the options passed here take precedence.
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [CancellationToken]
+/// - [RequestId]
+pub fn handle_server_fns_with_options(
+    options: LeptosOptions,
+) -> impl Fn(
+    Path<String>,
+    HeaderMap,
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<Body>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static {
+    let options = Arc::new(options);
+    move |Path(fn_name): Path<String>, headers: HeaderMap, mut req: Request<Body>| {
+        req.extensions_mut().insert(Arc::clone(&options));
+        Box::pin(handle_server_fns_inner(
+            fn_name,
+            headers,
+            |_| {},
+            false,
+            req,
+        ))
+    }
+}
+
+/// An Axum handler that runs server functions like [handle_server_fns], but in "API mode": the result
+/// is always sent in the server function's own format, with a `200 OK` status (unless the server
+/// function sets another one), and the handler never redirects, whatever the request's `Accept` header is.
+/// The result of a `"Url"` server function, which is JSON, is sent as `application/json`.
+///
+/// This suits APIs that are only called programmatically, where a redirect to the `Referer` would be
+/// surprising. `<form>`s that post to these server functions without JavaScript will show the raw result.
+/// ```ignore
+/// let app = Router::new().route("/api/*fn_name", post(leptos_axum::handle_server_fns_api));
+/// ```
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [CancellationToken]
+/// - [RequestId]
+pub async fn handle_server_fns_api(
+    Path(fn_name): Path<String>,
+    headers: HeaderMap,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    handle_server_fns_inner(fn_name, headers, |_| {}, true, req).await
 }
 
 /// An Axum handlers to listens for a request with Leptos server function arguments in the body,
@@ -168,134 +1253,650 @@ pub async fn handle_server_fns(
 /// ## Provided Context Types
 /// This function always provides context values including the following types:
 /// - [RequestParts]
+/// - [CookieJar]
 /// - [ResponseOptions]
+/// - [CancellationToken]
+/// - [RequestId]
 pub async fn handle_server_fns_with_context(
     Path(fn_name): Path<String>,
     headers: HeaderMap,
     additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
     req: Request<Body>,
 ) -> impl IntoResponse {
-    handle_server_fns_inner(fn_name, headers, additional_context, req).await
+    handle_server_fns_inner(fn_name, headers, additional_context, false, req).await
 }
 
-async fn handle_server_fns_inner(
-    fn_name: String,
+/// An Axum handler that runs server functions like [handle_server_fns], with the router's [State]
+/// provided as context, like [render_app_to_stream_with_state] does for rendering. Name the state type
+/// when adding it to the router:
+/// ```ignore
+/// let app = Router::new()
+///     .route("/api/*fn_name", post(leptos_axum::handle_server_fns_with_state::<AppState>))
+///     .with_state(app_state);
+///
+/// #[server(CountTodos, "/api")]
+/// pub async fn count_todos(cx: Scope) -> Result<i64, ServerFnError> {
+///     let AppState { pool } = use_context::<AppState>(cx).expect("the state to be provided");
+///     // ...
+/// }
+/// ```
+/// `S` has the same bounds as any Axum state, `Clone + Send + Sync + 'static`, and is cloned into the
+/// context of every call.
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - `S`, the router's state
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [CancellationToken]
+/// - [RequestId]
+pub async fn handle_server_fns_with_state<S>(
+    State(state): State<S>,
+    Path(fn_name): Path<String>,
     headers: HeaderMap,
-    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
     req: Request<Body>,
-) -> impl IntoResponse {
+) -> impl IntoResponse
+where
+    S: Clone + Send + Sync + 'static,
+{
+    handle_server_fns_inner(
+        fn_name,
+        headers,
+        move |cx| provide_context(cx, state.clone()),
+        false,
+        req,
+    )
+    .await
+}
+
+/// An Axum handler that runs server functions in "API mode", like [handle_server_fns_api], and
+/// lets you pass in a closure to add additional data to the context, like [handle_server_fns_with_context].
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [CancellationToken]
+/// - [RequestId]
+pub async fn handle_server_fns_api_with_context(
+    Path(fn_name): Path<String>,
+    headers: HeaderMap,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    handle_server_fns_inner(fn_name, headers, additional_context, true, req).await
+}
+
+async fn handle_server_fns_inner(
+    fn_name: String,
+    headers: HeaderMap,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    api_mode: bool,
+    req: Request<Body>,
+) -> Response<Body> {
+    let request_log = RequestLog::for_server_fn(&req);
+    let res = call_server_fn_with_cors(fn_name, headers, additional_context, api_mode, req).await;
+    match request_log {
+        Some(mut entry) => {
+            entry.status = res.status();
+            let (parts, body) = res.into_parts();
+            let body = body.inspect(move |chunk| {
+                if let Ok(chunk) = chunk {
+                    entry.sent(chunk.len());
+                }
+            });
+            Response::from_parts(parts, Body::wrap_stream(body))
+        }
+        None => res,
+    }
+}
+
+async fn call_server_fn_with_cors(
+    fn_name: String,
+    headers: HeaderMap,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    api_mode: bool,
+    req: Request<Body>,
+) -> Response<Body> {
+    // without any allowed origins, CORS is off, and OPTIONS requests are handled like any other
+    let options = req
+        .extensions()
+        .get::<Arc<LeptosOptions>>()
+        .filter(|options| !options.server_fn_cors_origins.is_empty())
+        .cloned();
+    let options = match options {
+        Some(options) => options,
+        None => return call_server_fn(fn_name, headers, additional_context, api_mode, req).await,
+    };
+
+    let preflight = req.method() == Method::OPTIONS;
+    let cors = cors_headers(&options, &headers, preflight);
+    let mut res = if preflight {
+        let mut res = Response::new(Body::empty());
+        *res.status_mut() = StatusCode::NO_CONTENT;
+        res
+    } else {
+        call_server_fn(fn_name, headers, additional_context, api_mode, req).await
+    };
+    res.headers_mut().extend(cors);
+    res
+}
+
+// The CORS headers for a request from another origin, which are empty unless it's one of
+// [LeptosOptions::server_fn_cors_origins]. Preflight responses also list the allowed methods and headers.
+fn cors_headers(options: &LeptosOptions, headers: &HeaderMap, preflight: bool) -> HeaderMap {
+    let mut cors = HeaderMap::new();
+    let origin = match headers.get(header::ORIGIN) {
+        Some(origin) => origin,
+        None => return cors,
+    };
+    let any_origin = options
+        .server_fn_cors_origins
+        .iter()
+        .any(|allowed| allowed == "*");
+    let allowed = any_origin
+        || options.server_fn_cors_origins.iter().any(|allowed| {
+            allowed
+                .trim_end_matches('/')
+                .as_bytes()
+                .eq_ignore_ascii_case(origin.as_bytes())
+        });
+    if !allowed {
+        return cors;
+    }
+
+    if any_origin {
+        cors.insert(
+            header::ACCESS_CONTROL_ALLOW_ORIGIN,
+            HeaderValue::from_static("*"),
+        );
+    } else {
+        // the response depends on the origin, so caches have to keep one per origin
+        cors.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+        cors.insert(header::VARY, HeaderValue::from_static("Origin"));
+    }
+    if preflight {
+        let list = |items: &[String]| HeaderValue::from_str(&items.join(", ")).ok();
+        if let Some(methods) = list(&options.server_fn_cors_methods) {
+            cors.insert(header::ACCESS_CONTROL_ALLOW_METHODS, methods);
+        }
+        if let Some(allowed_headers) = list(&options.server_fn_cors_headers) {
+            cors.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, allowed_headers);
+        }
+    }
+    cors
+}
+
+/// The media types that server functions are sent and answered with. A request that accepts one of them
+/// is answered with a status instead of being redirected back to the referrer, as a `<form>` submit is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentType {
+    /// `application/json`, for [Payload::Json].
+    Json,
+    /// `application/x-www-form-urlencoded`, for [Payload::Url].
+    UrlEncoded,
+    /// `application/cbor`, for [Payload::Binary].
+    Cbor,
+    /// `application/x-ndjson`, for [Payload::Ndjson].
+    Ndjson,
+}
+
+impl ContentType {
+    /// The media type, like `application/json`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::UrlEncoded => "application/x-www-form-urlencoded",
+            Self::Cbor => "application/cbor",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    /// The `Content-Type` a server function's response is sent with, or `None` for a [Payload::File],
+    /// which has a type of its own.
+    pub fn for_payload(payload: &Payload) -> Option<Self> {
+        match payload {
+            Payload::Json(_) => Some(Self::Json),
+            Payload::Url(_) => Some(Self::UrlEncoded),
+            Payload::Binary(_) => Some(Self::Cbor),
+            Payload::Ndjson(_) => Some(Self::Ndjson),
+            Payload::File(_) => None,
+        }
+    }
+
+    /// The media type that an `Accept` header asks for, if it's exactly one of these.
+    pub fn from_accept(headers: &HeaderMap) -> Option<Self> {
+        headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|accept| accept.parse().ok())
+    }
+
+    fn header_value(self) -> HeaderValue {
+        HeaderValue::from_static(self.as_str())
+    }
+}
+
+/// Parses exactly one of the media types, without any parameters like `charset`.
+impl std::str::FromStr for ContentType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "application/json" => Ok(Self::Json),
+            "application/x-www-form-urlencoded" => Ok(Self::UrlEncoded),
+            "application/cbor" => Ok(Self::Cbor),
+            "application/x-ndjson" => Ok(Self::Ndjson),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for ContentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+async fn call_server_fn(
+    fn_name: String,
+    headers: HeaderMap,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    api_mode: bool,
+    req: Request<Body>,
+) -> Response<Body> {
     // Axum Path extractor doesn't remove the first slash from the path, while Actix does
     let fn_name = fn_name
         .strip_prefix('/')
         .map(|fn_name| fn_name.to_string())
         .unwrap_or(fn_name);
 
+    let options = req.extensions().get::<Arc<LeptosOptions>>().cloned();
+    // a registry added with `.layer(Extension(registry))` takes the place of the global one
+    let registry = req
+        .extensions()
+        .get::<ServerFnRegistry>()
+        .cloned()
+        .unwrap_or_else(ServerFnRegistry::global);
+    // the header is the same as LeptosOptions' default if the options haven't been added to the router
+    let request_id_header = options
+        .as_ref()
+        .map(|options| options.request_id_header.clone())
+        .unwrap_or_else(|| "X-Request-ID".to_string());
+    let dev_overlay = options.as_deref().map(dev_overlay_enabled).unwrap_or(false);
+    let error_renderer = req.extensions().get::<ErrorRenderer>().cloned();
+    let req = match &options {
+        Some(options) => match limit_body(options, req).await {
+            Ok(req) => req,
+            Err(status) => return status_response(status),
+        },
+        None => req,
+    };
+
+    // cancelled if this future is dropped, because the client went away, before the response is ready
+    let cancel = CancellationToken::new();
+    let cancel_on_drop = cancel.clone().drop_guard();
+
     let (tx, rx) = futures::channel::oneshot::channel();
-    spawn_blocking({
-        move || {
-            tokio::runtime::Runtime::new()
-                .expect("couldn't spawn runtime")
-                .block_on({
-                    async move {
-                        let res = if let Some(server_fn) = server_fn_by_path(fn_name.as_str()) {
-                            let runtime = create_runtime();
-                            let (cx, disposer) = raw_scope_and_disposer(runtime);
-
-                            additional_context(cx);
-
-                            let req_parts = generate_request_parts(req).await;
-                            // Add this so we can get details about the Request
-                            provide_context(cx, req_parts.clone());
-                            // Add this so that we can set headers and status of the response
-                            provide_context(cx, ResponseOptions::default());
-
-                            match server_fn(cx, &req_parts.body).await {
-                                Ok(serialized) => {
-                                    // If ResponseOptions are set, add the headers and status to the request
-                                    let res_options = use_context::<ResponseOptions>(cx);
-
-                                    // clean up the scope, which we only needed to run the server fn
-                                    disposer.dispose();
-                                    runtime.dispose();
-
-                                    // if this is Accept: application/json then send a serialized JSON response
-                                    let accept_header =
-                                        headers.get("Accept").and_then(|value| value.to_str().ok());
-                                    let mut res = Response::builder();
-
-                                    // Add headers from ResponseParts if they exist. These should be added as long
-                                    // as the server function returns an OK response
-                                    let res_options_outer = res_options.unwrap().0;
-                                    let res_options_inner = res_options_outer.read().await;
-                                    let (status, mut res_headers) = (
-                                        res_options_inner.status,
-                                        res_options_inner.headers.clone(),
-                                    );
-
-                                    if let Some(header_ref) = res.headers_mut() {
-                                           header_ref.extend(res_headers.drain());
-                                    };
-
-                                    if accept_header == Some("application/json")
-                                        || accept_header
-                                            == Some("application/x-www-form-urlencoded")
-                                        || accept_header == Some("application/cbor")
-                                    {
-                                        res = res.status(StatusCode::OK);
-                                    }
-                                    // otherwise, it's probably a <form> submit or something: redirect back to the referrer
-                                    else {
-                                        let referer = headers
-                                            .get("Referer")
-                                            .and_then(|value| value.to_str().ok())
-                                            .unwrap_or("/");
-
-                                        res = res
-                                            .status(StatusCode::SEE_OTHER)
-                                            .header("Location", referer);
-                                    }
-                                    // Override StatusCode if it was set in a Resource or Element
-                                    res = match status {
-                                        Some(status) => res.status(status),
-                                        None => res,
-                                    };
-                                    match serialized {
-                                        Payload::Binary(data) => res
-                                            .header("Content-Type", "application/cbor")
-                                            .body(Full::from(data)),
-                                        Payload::Url(data) => res
+    let name = fn_name.clone();
+    let panic_renderer = error_renderer.clone();
+    let accept = headers.clone();
+    let task = LOCAL_POOL.spawn_pinned(move || async move {
+        // an NDJSON response is streamed after the response itself has been sent
+        let mut ndjson = None;
+        let res = if let Some(server_fn) = registry.get(fn_name.as_str()) {
+            let runtime = create_runtime();
+            let (cx, disposer) = raw_scope_and_disposer(runtime);
+
+            additional_context(cx);
+
+            // the body has already been checked against the limit, if there is one
+            let req_parts = generate_request_parts_with_limit(req, None)
+                .await
+                .expect("reading a body without a limit doesn't fail");
+            // errors are marked for the overlay in the developer's own browser
+            let dev_error = dev_overlay && req_parts.is_local();
+            // Add this so we can get details about the Request
+            provide_context(cx, req_parts.clone());
+            provide_context(cx, CookieJar::from_headers(&req_parts.headers));
+            // Add this so that we can set headers and status of the response
+            let res_options = ResponseOptions::default();
+            let request_id = RequestId::from_headers(&request_id_header, &req_parts.headers);
+            request_id.send(&request_id_header, &res_options).await;
+            provide_context(cx, request_id);
+            provide_context(cx, res_options);
+            provide_context(cx, cancel.clone());
+
+            // bodies with a registered content type are converted into the server fn's encoding
+            let content_type = req_parts
+                .headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok());
+            let body = registry.decode_body(&fn_name, content_type, &req_parts.body);
+            // a request without one of the headers the server fn requires is rejected before it runs
+            let missing_header = registry
+                .required_headers(&fn_name)
+                .iter()
+                .find(|name| !req_parts.headers.contains_key(**name));
+            let result = match (missing_header, body) {
+                (Some(name), _) => Err(ServerFnError::MissingArg(name.to_string())),
+                (None, Ok(body)) => server_fn(cx, &body).await,
+                (None, Err(e)) => Err(e),
+            };
+
+            match result {
+                Ok(serialized) => {
+                    flush_cookies(cx).await;
+                    // If ResponseOptions are set, add the headers and status to the request
+                    let res_options = use_context::<ResponseOptions>(cx);
+
+                    // clean up the scope, which we only needed to run the server fn,
+                    // unless it's still needed to produce a stream of results
+                    let mut scope = Some((disposer, runtime));
+                    if !matches!(serialized, Payload::Ndjson(_)) {
+                        if let Some((disposer, runtime)) = scope.take() {
+                            disposer.dispose();
+                            runtime.dispose();
+                        }
+                    }
+
+                    // if this is Accept: application/json then send a serialized JSON response
+                    let accepts = ContentType::from_accept(&headers);
+                    let mut res = Response::builder();
+
+                    // Add headers from ResponseParts if they exist. These should be added as long
+                    // as the server function returns an OK response
+                    let res_options_outer = res_options.unwrap().0;
+                    let res_options_inner = res_options_outer.read().await;
+                    let (status, mut res_headers) =
+                        (res_options_inner.status, res_options_inner.headers.clone());
+                    let custom_body = res_options_inner
+                        .body
+                        .clone()
+                        .map(|body| (body, res_options_inner.content_type.clone()));
+
+                    if let Some(header_ref) = res.headers_mut() {
+                        // the headers declared by the server fn, unless it set them itself
+                        for (name, value) in registry.headers(&fn_name) {
+                            if let Ok((name, value)) = parse_header(name, value) {
+                                if !res_headers.contains_key(&name) {
+                                    header_ref.insert(name, value);
+                                }
+                            }
+                        }
+                        header_ref.extend(res_headers.drain());
+                    };
+
+                    // a body set through ResponseOptions replaces the server fn's result
+                    if let Some((body, content_type)) = custom_body {
+                        if let Some((disposer, runtime)) = scope.take() {
+                            disposer.dispose();
+                            runtime.dispose();
+                        }
+                        res = res.status(status.unwrap_or(StatusCode::OK));
+                        if let (Some(header_ref), Some(content_type)) =
+                            (res.headers_mut(), content_type)
+                        {
+                            header_ref.insert(header::CONTENT_TYPE, content_type);
+                        }
+                        res.body(Body::from(body))
+                    } else {
+                        // a file is downloaded even by a <form> submit
+                        if api_mode || accepts.is_some() || matches!(serialized, Payload::File(_)) {
+                            res = res.status(StatusCode::OK);
+                        }
+                        // otherwise, it's probably a <form> submit or something: redirect back to the referrer,
+                        // unless the server fn set a Location of its own
+                        else {
+                            res = res.status(StatusCode::SEE_OTHER);
+                            let has_location = res
+                                .headers_ref()
+                                .map(|headers| headers.contains_key(header::LOCATION))
+                                .unwrap_or(false);
+                            if !has_location {
+                                let referer = headers
+                                    .get("Referer")
+                                    .and_then(|value| value.to_str().ok())
+                                    .unwrap_or("/");
+                                res = res.header("Location", referer);
+                            }
+                        }
+                        // Override StatusCode if it was set in a Resource or Element
+                        res = match status {
+                            Some(status) => res.status(status),
+                            None => res,
+                        };
+                        let content_type = match &serialized {
+                            // the result of a `"Url"` server fn is JSON, which API clients are told
+                            Payload::Url(_) if api_mode => Some(ContentType::Json),
+                            serialized => ContentType::for_payload(serialized),
+                        };
+                        if let Some(content_type) = content_type {
+                            res = res.header(header::CONTENT_TYPE, content_type.header_value());
+                        }
+                        match serialized {
+                            // buffered payloads are sent with their length, rather than leaving it to hyper
+                            Payload::Binary(data) => res
+                                .header(header::CONTENT_LENGTH, data.len())
+                                .body(Body::from(data)),
+                            Payload::Url(data) | Payload::Json(data) => res
+                                .header(header::CONTENT_LENGTH, data.len())
+                                .body(Body::from(data)),
+                            Payload::Ndjson(lines) => {
+                                let (line_tx, line_rx) =
+                                    futures::channel::mpsc::channel::<io::Result<String>>(8);
+                                ndjson = Some((lines, line_tx, scope));
+                                res.body(Body::wrap_stream(line_rx))
+                            }
+                            Payload::File(file) => {
+                                let opened = match file.path() {
+                                    Some(path) => tokio::fs::File::open(path).await,
+                                    None => Err(io::ErrorKind::NotFound.into()),
+                                };
+                                match opened {
+                                    Ok(opened) => {
+                                        let content_type = file
+                                            .content_type()
+                                            .or_else(|| {
+                                                file.path().and_then(content_type_from_extension)
+                                            })
+                                            .unwrap_or("application/octet-stream")
+                                            .to_string();
+                                        if let Ok(metadata) = opened.metadata().await {
+                                            res = res.header("Content-Length", metadata.len());
+                                        }
+                                        res.header("Content-Type", content_type)
                                             .header(
-                                                "Content-Type",
-                                                "application/x-www-form-urlencoded",
+                                                "Content-Disposition",
+                                                file.content_disposition(),
                                             )
-                                            .body(Full::from(data)),
-                                        Payload::Json(data) => res
-                                            .header("Content-Type", "application/json")
-                                            .body(Full::from(data)),
+                                            .body(Body::wrap_stream(ReaderStream::new(opened)))
                                     }
+                                    Err(e) if e.kind() == io::ErrorKind::NotFound => res
+                                        .status(StatusCode::NOT_FOUND)
+                                        .body(Body::from("Not Found")),
+                                    Err(e) => res
+                                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                                        .body(Body::from(e.to_string())),
                                 }
-                                Err(e) => Response::builder()
-                                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                                    .body(Full::from(e.to_string())),
                             }
-                        } else {
-                            Response::builder()
-                                .status(StatusCode::BAD_REQUEST)
-                                .body(Full::from(
-                                    format!("Could not find a server function at the route {fn_name}. \
-                                    \n\nIt's likely that you need to call ServerFn::register() on the \
-                                    server function type, somewhere in your `main` function." )
-                                ))
                         }
-                        .expect("could not build Response");
+                    }
+                }
+                Err(e) => match use_context::<AbortStatus>(cx) {
+                    // the server fn bailed out with abort_with_status()
+                    Some(AbortStatus(status)) => {
+                        let res_options = use_context::<ResponseOptions>(cx);
+                        disposer.dispose();
+                        runtime.dispose();
 
-                        _ = tx.send(res);
+                        let mut res = Response::builder().status(status);
+                        if let (Some(header_ref), Some(res_options)) =
+                            (res.headers_mut(), res_options)
+                        {
+                            header_ref.extend(res_options.0.read().await.headers.clone());
+                        }
+                        res.body(Body::from(status.canonical_reason().unwrap_or_default()))
                     }
-                })
+                    None => {
+                        let res_parts = match use_context::<ResponseOptions>(cx) {
+                            Some(res_options) => res_options.0.read().await.clone(),
+                            None => ResponseParts::default(),
+                        };
+                        disposer.dispose();
+                        runtime.dispose();
+                        server_fn_error_response(
+                            &e,
+                            &headers,
+                            res_parts,
+                            dev_error,
+                            error_renderer.as_ref(),
+                        )
+                    }
+                },
+            }
+        } else {
+            Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(format!(
+                    "Could not find a server function at the route {fn_name}. \
+                    \n\nIt's likely that you need to call ServerFn::register() on the \
+                    server function type, somewhere in your `main` function."
+                )))
+        }
+        .expect("could not build Response");
+
+        _ = tx.send(res);
+
+        if let Some((mut lines, mut line_tx, scope)) = ndjson {
+            while let Some(line) = lines.next().await {
+                if line_tx.send(Ok(line)).await.is_err() {
+                    // the client went away
+                    cancel.cancel();
+                    break;
+                }
+            }
+            drop(lines);
+            if let Some((disposer, runtime)) = scope {
+                disposer.dispose();
+                runtime.dispose();
+            }
         }
     });
 
-    rx.await.unwrap()
+    // a timeout drops the guard without disarming it, which cancels the call
+    let res = match &options {
+        Some(options) => match limit_time(options, rx).await {
+            Ok(res) => res,
+            Err(status) => return status_response(status),
+        },
+        None => rx.await,
+    };
+    cancel_on_drop.disarm();
+    match res {
+        Ok(res) => res,
+        // the task ended without responding, because the server fn panicked
+        Err(_) => {
+            if let Err(e) = task.await {
+                if e.is_panic() {
+                    tracing::error!(
+                        target: "leptos_axum::server_fn",
+                        "server function {name} panicked: {}",
+                        panic_message(e.into_panic())
+                    );
+                }
+            }
+            let status = StatusCode::INTERNAL_SERVER_ERROR;
+            let error = ErrorInfo {
+                status,
+                message: status.canonical_reason().unwrap_or_default().to_string(),
+                kind: "Panic",
+            };
+            match panic_renderer.and_then(|renderer| renderer.render(&accept, &error)) {
+                Some((content_type, body)) => {
+                    let mut res = Response::new(Body::from(body));
+                    *res.status_mut() = status;
+                    res.headers_mut()
+                        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+                    res
+                }
+                None => status_response(status),
+            }
+        }
+    }
+}
+
+// The response to a server fn that returned an error: the status it set through [ResponseOptions], or else
+// a 400 if the client sent arguments that couldn't be read and a 500 otherwise, with the error as JSON for
+// clients that accept it and as plain text otherwise. With `dev_error`, the kind of error is sent in
+// [DEV_ERROR_HEADER] for the overlay. An [ErrorRenderer] renders the body instead, if there is one.
+fn server_fn_error_response(
+    e: &ServerFnError,
+    headers: &HeaderMap,
+    mut res_parts: ResponseParts,
+    dev_error: bool,
+    error_renderer: Option<&ErrorRenderer>,
+) -> Result<Response<Body>, http::Error> {
+    let (status, kind) = match e {
+        ServerFnError::Args(_) => (StatusCode::BAD_REQUEST, "Args"),
+        ServerFnError::MissingArg(_) => (StatusCode::BAD_REQUEST, "MissingArg"),
+        ServerFnError::Registration(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Registration"),
+        ServerFnError::Request(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Request"),
+        ServerFnError::ServerError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ServerError"),
+        // on the server, this is the request body failing to decode into the server fn's arguments
+        ServerFnError::Deserialization(_) => (StatusCode::BAD_REQUEST, "Deserialization"),
+        ServerFnError::Serialization(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Serialization"),
+    };
+    let accepts_json = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .map(|accept| {
+            accept.split(',').any(|media_type| {
+                media_type
+                    .split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .parse()
+                    == Ok(ContentType::Json)
+            })
+        })
+        .unwrap_or(false);
+
+    let status = res_parts.status.unwrap_or(status);
+    let mut res = Response::builder().status(status);
+    if let Some(header_ref) = res.headers_mut() {
+        header_ref.extend(res_parts.headers.drain());
+        if dev_error {
+            header_ref.insert(DEV_ERROR_HEADER, HeaderValue::from_static(kind));
+        }
+    }
+    let error = ErrorInfo {
+        status,
+        message: e.to_string(),
+        kind,
+    };
+    if let Some((content_type, body)) =
+        error_renderer.and_then(|renderer| renderer.render(headers, &error))
+    {
+        res.header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+    } else if accepts_json {
+        let body = serde_json::json!({ "error": e.to_string(), "kind": kind });
+        res.header(header::CONTENT_TYPE, ContentType::Json.header_value())
+            .body(Body::from(body.to_string()))
+    } else {
+        res.body(Body::from(e.to_string()))
+    }
+}
+
+// A response with just a status, and its reason as the body
+fn status_response(status: StatusCode) -> Response<Body> {
+    let mut res = Response::new(Body::from(status.canonical_reason().unwrap_or_default()));
+    *res.status_mut() = status;
+    res
 }
 
 pub type PinnedHtmlStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send>>;
@@ -337,19 +1938,29 @@ pub type PinnedHtmlStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send>
 ///     // run our app with hyper
 ///     // `axum::Server` is a re-export of `hyper::Server`
 ///     axum::Server::bind(&addr)
-///         .serve(app.into_make_service())
+///         .serve(app.into_make_service_with_connect_info::<SocketAddr>())
 ///         .await
 ///         .unwrap();
 /// }
 /// # }
 /// ```
 ///
+/// `HEAD` requests, like those of health checks and crawlers, are rendered like `GET` requests, so they
+/// get the same status and headers, including a `404` from the fallback and any status, redirect or
+/// headers that the app sets while rendering with [ResponseOptions]. The body is dropped unsent, which
+/// stops the rendering of anything still streaming.
+///
 /// ## Provided Context Types
 /// This function always provides context values including the following types:
 /// - [RequestParts]
+/// - [CookieJar]
 /// - [ResponseOptions]
 /// - [MetaContext](leptos_meta::MetaContext)
 /// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
 pub fn render_app_to_stream<IV>(
     options: LeptosOptions,
     app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
@@ -365,6 +1976,54 @@ where
     render_app_to_stream_with_context(options, |_| {}, app_fn)
 }
 
+/// Several providers of additional context, combined into the single closure that the `_with_context`
+/// handlers take. This keeps cross-cutting providers, like one for the database and one for the signed-in
+/// user, apart without composing their closures by hand:
+/// ```ignore
+/// let context = ContextProviders::new()
+///     .with(move |cx| provide_context(cx, pool.clone()))
+///     .with(move |cx| provide_context(cx, feature_flags.clone()));
+/// let handler = leptos_axum::render_app_to_stream_with_context(options, context.into_fn(), |cx| {
+///     view! { cx, <TodoApp/> }
+/// });
+/// ```
+/// The providers run in the order they were added, so if two of them provide a value of the same type,
+/// the one added later overrides the earlier one. The contexts that the handlers provide themselves, like
+/// [RequestParts] and [ResponseOptions], are provided after all of them.
+#[derive(Clone, Default)]
+pub struct ContextProviders(Vec<Arc<dyn Fn(leptos::Scope) + Send + Sync>>);
+
+impl ContextProviders {
+    /// Creates an empty set of providers, which provides nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a provider, which runs after the ones that were added before it.
+    pub fn with(mut self, provider: impl Fn(leptos::Scope) + Send + Sync + 'static) -> Self {
+        self.0.push(Arc::new(provider));
+        self
+    }
+
+    /// Combines the providers into a closure that runs each of them in turn, to pass as the
+    /// `additional_context` of a handler.
+    pub fn into_fn(self) -> impl Fn(leptos::Scope) + Clone + Send + 'static {
+        move |cx| {
+            for provider in &self.0 {
+                provider(cx);
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for ContextProviders {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextProviders")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
 /// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
 /// to route it using [leptos_router], serving an HTML stream of your application.
 ///
@@ -382,14 +2041,30 @@ where
 ///     handler(req).await.into_response()
 /// }
 /// ```
+/// The additional context can also set defaults for the page's metadata, by providing a
+/// [MetaContext](leptos_meta::MetaContext) with them. The app's own `<Title/>` and `<Meta/>`
+/// components take precedence over these defaults:
+/// ```ignore
+/// let site_name = tenant_for_host(&host).site_name;
+/// let handler = leptos_axum::render_app_to_stream_with_context(
+///     options,
+///     move |cx| provide_context(cx, MetaContext::new().with_default_title(site_name.clone())),
+///     |cx| view! { cx, <TodoApp/> },
+/// );
+/// ```
 /// Otherwise, this function is identical to [render_app_to_stream].
 ///
 /// ## Provided Context Types
 /// This function always provides context values including the following types:
 /// - [RequestParts]
+/// - [CookieJar]
 /// - [ResponseOptions]
 /// - [MetaContext](leptos_meta::MetaContext)
 /// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
 pub fn render_app_to_stream_with_context<IV>(
     options: LeptosOptions,
     additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
@@ -403,229 +2078,3397 @@ pub fn render_app_to_stream_with_context<IV>(
 where
     IV: IntoView,
 {
-    move |req: Request<Body>| {
-        Box::pin({
-            let options = options.clone();
-            let app_fn = app_fn.clone();
-            let add_context = additional_context.clone();
-            let default_res_options = ResponseOptions::default();
-            let res_options2 = default_res_options.clone();
-            let res_options3 = default_res_options.clone();
-
-            async move {
-                // Need to get the path and query string of the Request
-                // For reasons that escape me, if the incoming URI protocol is https, it provides the absolute URI
-                // if http, it returns a relative path. Adding .path() seems to make it explicitly return the relative uri
-                let path = req.uri().path_and_query().unwrap().as_str();
-
-                let full_path = format!("http://leptos.dev{path}");
-
-                let pkg_path = &options.site_pkg_dir;
-                let output_name = &options.output_name;
-
-                // Because wasm-pack adds _bg to the end of the WASM filename, and we want to mantain compatibility with it's default options
-                // we add _bg to the wasm files if cargo-leptos doesn't set the env var LEPTOS_OUTPUT_NAME
-                // Otherwise we need to add _bg because wasm_pack always does. This is not the same as options.output_name, which is set regardless
-                let mut wasm_output_name = output_name.clone();
-                if std::env::var("LEPTOS_OUTPUT_NAME").is_err() {
-                    wasm_output_name.push_str("_bg");
-                }
-
-                let site_ip = &options.site_address.ip().to_string();
-                let reload_port = options.reload_port;
-
-                let leptos_autoreload = match std::env::var("LEPTOS_WATCH").is_ok() {
-                    true => format!(
-                        r#"
-                        <script crossorigin="">(function () {{
-                            var ws = new WebSocket('ws://{site_ip}:{reload_port}/live_reload');
-                            ws.onmessage = (ev) => {{
-                                let msg = JSON.parse(ev.data);
-                                if (msg.all) window.location.reload();
-                                if (msg.css) {{
-                                    const link = document.querySelector("link#leptos");
-                                    if (link) {{
-                                        let href = link.getAttribute('href').split('?')[0];
-                                        let newHref = href + '?version=' + new Date().getMilliseconds();
-                                        link.setAttribute('href', newHref);
-                                    }} else {{
-                                        console.warn("Could not find link#leptos");
-                                    }}
-                                }};
-                            }};
-                            ws.onclose = () => console.warn('Live-reload stopped. Manual reload necessary.');
-                        }})()
-                        </script>
-                        "#
-                    ),
-                    false => "".to_string(),
-                };
-
-                let head = format!(
-                    r#"<!DOCTYPE html>
-                    <html lang="en">
-                        <head>
-                            <meta charset="utf-8"/>
-                            <meta name="viewport" content="width=device-width, initial-scale=1"/>
-                            <link rel="modulepreload" href="/{pkg_path}/{output_name}.js">
-                            <link rel="preload" href="/{pkg_path}/{wasm_output_name}.wasm" as="fetch" type="application/wasm" crossorigin="">
-                            <script type="module">import init, {{ hydrate }} from '/{pkg_path}/{output_name}.js'; init('/{pkg_path}/{wasm_output_name}.wasm').then(hydrate);</script>
-                            {leptos_autoreload}
-                            "#
-                );
-                let tail = "</body></html>";
-
-                let (mut tx, rx) = futures::channel::mpsc::channel(8);
-
-                spawn_blocking({
-                    let app_fn = app_fn.clone();
-                    let add_context = add_context.clone();
-                    move || {
-                        tokio::runtime::Runtime::new()
-                            .expect("couldn't spawn runtime")
-                            .block_on({
-                                let app_fn = app_fn.clone();
-                                let add_context = add_context.clone();
-                                async move {
-                                    tokio::task::LocalSet::new()
-                                        .run_until(async {
-                                            let app = {
-                                                let full_path = full_path.clone();
-                                                let req_parts = generate_request_parts(req).await;
-                                                move |cx| {
-                                                    let integration = ServerIntegration {
-                                                        path: full_path.clone(),
-                                                    };
-                                                    provide_context(
-                                                        cx,
-                                                        RouterIntegrationContext::new(integration),
-                                                    );
-                                                    provide_context(cx, MetaContext::new());
-                                                    provide_context(cx, req_parts);
-                                                    provide_context(cx, default_res_options);
-                                                    app_fn(cx).into_view(cx)
-                                                }
-                                            };
-
-                                            let (bundle, runtime, scope) =
-                                                render_to_stream_with_prefix_undisposed_with_context(
-                                                    app,
-                                                    |cx| {
-                                                        let head = use_context::<MetaContext>(cx)
-                                                            .map(|meta| meta.dehydrate())
-                                                            .unwrap_or_default();
-                                                        format!("{head}</head><body>").into()
-                                                    },
-                                                    add_context,
-                                                );
-                                            let mut shell = Box::pin(bundle);
-                                            while let Some(fragment) = shell.next().await {
-                                                _ = tx.send(fragment).await;
-                                            }
-
-                                            // Extract the value of ResponseOptions from here
-                                            let cx = Scope { runtime, id: scope };
-                                            let res_options =
-                                                use_context::<ResponseOptions>(cx).unwrap();
-
-                                            let new_res_parts = res_options.0.read().await.clone();
-
-                                            let mut writable = res_options2.0.write().await;
-                                            *writable = new_res_parts;
-
-                                            runtime.dispose();
-
-                                            tx.close_channel();
-                                        })
-                                        .await;
-                                }
-                            });
-                    }
-                });
-
-                let mut stream = Box::pin(
-                    futures::stream::once(async move { head.clone() })
-                        .chain(rx)
-                        .chain(futures::stream::once(async { tail.to_string() }))
-                        .map(|html| Ok(Bytes::from(html))),
-                );
-
-                // Get the first, second, and third chunks in the stream, which renders the app shell, and thus allows Resources to run
-                let first_chunk = stream.next().await;
-                let second_chunk = stream.next().await;
-                let third_chunk = stream.next().await;
-
-                // Extract the resources now that they've been rendered
-                let res_options = res_options3.0.read().await;
-
-                let complete_stream = futures::stream::iter([
-                    first_chunk.unwrap(),
-                    second_chunk.unwrap(),
-                    third_chunk.unwrap(),
-                ])
-                .chain(stream);
-
-                let mut res = Response::new(StreamBody::new(
-                    Box::pin(complete_stream) as PinnedHtmlStream
-                ));
-
-                if let Some(status) = res_options.status {
-                    *res.status_mut() = status
-                }
-                let mut res_headers = res_options.headers.clone();
-                res.headers_mut().extend(res_headers.drain());
-
-                res
-            }
-        })
-    }
+    render_app_to_stream_with_head_transform(options, additional_context, |head| head, app_fn)
 }
 
-/// Generates a list of all routes defined in Leptos's Router in your app. We can then use this to automatically
-/// create routes in Axum's Router without having to use wildcard matching or fallbacks. Takes in your root app Element
-/// as an argument so it can walk you app tree. This version is tailored to generate Axum compatible paths.
-pub async fn generate_route_list<IV>(app_fn: impl FnOnce(Scope) -> IV + 'static) -> Vec<String>
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving an HTML stream of your application.
+///
+/// This version takes a `transform_head` closure that receives the `<head>` content generated by
+/// [leptos_meta] (the output of [MetaContext::dehydrate]) and returns the HTML that will actually be
+/// injected, right before `</head>`. You can use it to deduplicate tags, enforce a maximum title
+/// length, or append tags of your own:
+/// ```ignore
+/// let handler = leptos_axum::render_app_to_stream_with_head_transform(
+///     options,
+///     |_cx| {},
+///     |head| format!(r#"{head}<meta name="build" content="{}"/>"#, env!("CARGO_PKG_VERSION")),
+///     |cx| view! { cx, <TodoApp/> },
+/// );
+/// ```
+/// The closure runs once per request, after the app shell has been rendered (so every tag set
+/// while rendering the shell is included) and before the shell is sent to the client. It does not
+/// affect the static part of the head containing the `<meta charset>` and hydration script.
+///
+/// Otherwise, this function is identical to [render_app_to_stream_with_context].
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_stream_with_head_transform<IV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    transform_head: impl Fn(String) -> String + 'static + Clone + Send,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> impl Fn(
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<StreamBody<PinnedHtmlStream>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
 where
-    IV: IntoView + 'static,
+    IV: IntoView,
 {
-    #[derive(Default, Clone, Debug)]
-    pub struct Routes(pub Arc<RwLock<Vec<String>>>);
-
-    let routes = Routes::default();
-    let routes_inner = routes.clone();
+    render_app_to_stream_inner(
+        options,
+        additional_context,
+        transform_head,
+        default_tail,
+        false,
+        app_fn,
+    )
+}
 
-    let local = LocalSet::new();
-    // Run the local task set.
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving an HTML stream of your application.
+///
+/// This version takes a `tail` closure that produces the end of the page, which is
+/// `</body></html>` by default. You can use it to add scripts right before `</body>`, like
+/// analytics, or state that's only known once the app has rendered:
+/// ```ignore
+/// let handler = leptos_axum::render_app_to_stream_with_tail(
+///     options,
+///     |cx| provide_context(cx, PageEvents::default()),
+///     |cx| {
+///         let events = use_context::<PageEvents>(cx).map(|events| events.to_json()).unwrap_or_default();
+///         format!(r#"<script>analytics.track({events})</script></body></html>"#)
+///     },
+///     |cx| view! { cx, <TodoApp/> },
+/// );
+/// ```
+/// The closure runs once per request, in the app's root [Scope](leptos::Scope), after everything
+/// else has been streamed: the app shell, and then the serialized values of the resources read
+/// under `<Suspense/>`, which the client uses to hydrate. So the tail always comes after that
+/// hydration state, and every resource has resolved by the time it runs. It can read the context
+/// provided in the root scope, such as [RequestParts] or anything provided by `additional_context`,
+/// but not context provided inside your components.
+///
+/// Otherwise, this function is identical to [render_app_to_stream_with_context].
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_stream_with_tail<IV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    tail: impl Fn(leptos::Scope) -> String + 'static + Clone + Send,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> impl Fn(
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<StreamBody<PinnedHtmlStream>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    render_app_to_stream_inner(
+        options,
+        additional_context,
+        |head| head,
+        tail,
+        false,
+        app_fn,
+    )
+}
 
-    local
-        .run_until(async move {
-            tokio::task::spawn_local(async move {
-                let routes = leptos_router::generate_route_list_inner(app_fn);
-                let mut writable = routes_inner.0.write().await;
-                *writable = routes;
-            })
-            .await
-            .unwrap();
-        })
-        .await;
+/// What a custom app shell is built from, for [render_app_to_stream_with_shell].
+#[derive(Debug, Clone)]
+pub struct ShellContext {
+    /// The directory the app's JS and WASM are served from, relative to the site root, like `pkg`.
+    pub pkg_path: String,
+    /// The name of the app's JS file, without the `.js` extension.
+    pub output_name: String,
+    /// The name of the app's WASM file, without the `.wasm` extension. It's `output_name` with a `_bg`
+    /// suffix, unless the app was built by `cargo-leptos`.
+    pub wasm_output_name: String,
+    /// The language of the page, from [LeptosOptions::lang] or negotiated from the request.
+    pub lang: String,
+    /// The `nonce` attribute for the page's scripts, like ` nonce="..."` with a leading space, or an
+    /// empty string if the app hasn't set a [CspNonce].
+    pub nonce: String,
+    /// The script that reloads the page when `cargo-leptos` rebuilds the app, or an empty string if
+    /// live-reloading is off for this request.
+    pub autoreload: String,
+    /// The script for the error overlay, or an empty string outside of [Env::DEV] or for requests that
+    /// aren't [local](RequestParts::is_local). It should come before any of the page's own scripts.
+    pub dev_overlay: String,
+}
 
-    let routes = routes.0.read().await.to_owned();
-    // Axum's Router defines Root routes as "/" not ""
-    let routes: Vec<String> = routes
-        .into_iter()
-        .map(|s| if s.is_empty() { "/".to_string() } else { s })
-        .collect();
+// The closure that builds a custom app shell
+type ShellFn = Arc<dyn Fn(&ShellContext) -> (String, String) + Send + Sync>;
 
-    if routes.is_empty() {
-        vec!["/".to_string()]
-    } else {
-        routes
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving an HTML stream of your application.
+///
+/// This version lets you write the app shell yourself, for pages that need a different doctype, extra
+/// `<meta>` tags, or their own scripts. The `shell` closure returns the beginning of the page and its
+/// end: the head prefix, which is everything from the doctype up to the app's own `<head>` elements,
+/// and the tail, which replaces `</body></html>`. The head is followed by the tags added with
+/// [leptos_meta] and `</head><body>`, then the app, then the tail.
+///
+/// The head has to load and hydrate the app itself:
+/// ```ignore
+/// let handler = leptos_axum::render_app_to_stream_with_shell(
+///     options,
+///     |_| {},
+///     |shell| {
+///         let ShellContext { pkg_path, output_name, wasm_output_name, lang, nonce, autoreload, dev_overlay } = shell;
+///         let head = format!(
+///             r#"<!DOCTYPE html><html lang="{lang}"><head>
+///                 <meta name="theme-color" content="#663399"/>
+///                 {dev_overlay}<script type="module"{nonce}>import init, {{ hydrate }} from '/{pkg_path}/{output_name}.js'; init('/{pkg_path}/{wasm_output_name}.wasm').then(hydrate);</script>
+///                 {autoreload}"#
+///         );
+///         (head, "</body></html>".to_string())
+///     },
+///     |cx| view! { cx, <TodoApp/> },
+/// );
+/// ```
+/// The closure is called for every request, once the app shell has rendered, so the [CspNonce] of the
+/// page is known; the shell is never cached, even if [LeptosOptions::cache_shell] is set.
+///
+/// Otherwise, this function is identical to [render_app_to_stream_with_context].
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_stream_with_shell<IV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    shell: impl Fn(&ShellContext) -> (String, String) + Send + Sync + 'static,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> impl Fn(
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<StreamBody<PinnedHtmlStream>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    render_app_to_stream_with_cache(
+        options,
+        additional_context,
+        |head| head,
+        default_tail,
+        false,
+        app_fn,
+        Some(Arc::new(shell)),
+        None,
+    )
+}
+
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving an HTML stream of your application in document order.
+///
+/// [render_app_to_stream] sends the whole app shell as soon as it has rendered, with the fallback of
+/// each `<Suspense/>` whose resources are still loading, and then streams each one's HTML as soon as
+/// it's ready, in whatever order they resolve, along with a small inline script that swaps it in for
+/// the fallback. This sends the page in order instead: the shell up to the first `<Suspense/>` that's
+/// still loading, then its HTML once it has resolved, then the shell up to the next one, and so on.
+///
+/// Fallbacks are never shown and the page is complete without JavaScript, which suits crawlers and
+/// pages where a fallback would make the layout jump, but everything after a `<Suspense/>` waits for
+/// it, even parts that were ready sooner. The resources under every `<Suspense/>` still load at the
+/// same time, so the whole page takes no longer than with [render_app_to_stream].
+/// ```ignore
+/// let app = Router::new()
+///     .fallback(leptos_axum::render_app_to_stream_in_order(leptos_options, |cx| view! { cx, <MyApp/> }));
+/// ```
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_stream_in_order<IV>(
+    options: LeptosOptions,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> impl Fn(
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<StreamBody<PinnedHtmlStream>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    render_app_to_stream_in_order_with_context(options, |_| {}, app_fn)
+}
+
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving an HTML stream of your application in document order.
+///
+/// This version takes additional context, like [render_app_to_stream_with_context]. Otherwise, it's
+/// identical to [render_app_to_stream_in_order].
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_stream_in_order_with_context<IV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> impl Fn(
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<StreamBody<PinnedHtmlStream>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    render_app_to_stream_inner(
+        options,
+        additional_context,
+        |head| head,
+        default_tail,
+        true,
+        app_fn,
+    )
+}
+
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving an HTML stream of your application, with the router's
+/// [State] provided as context.
+///
+/// This saves writing your own handler just to pass the state to [render_app_to_stream_with_context].
+/// The handler takes the `State<S>` of the router it's added to, so `S` has to be the router's state
+/// type, or a part of it that implements [FromRef](axum::extract::FromRef). Like any Axum state, it has
+/// to be `Clone + Send + Sync + 'static`; it's cloned into the context of every request, so it's best
+/// for it to be cheap to clone, like a database pool or an `Arc`.
+/// ```ignore
+/// #[derive(Clone)]
+/// struct AppState {
+///     pool: PgPool,
+/// }
+///
+/// let app = Router::new()
+///     .route("/api/*fn_name", post(leptos_axum::handle_server_fns_with_state::<AppState>))
+///     .fallback(leptos_axum::render_app_to_stream_with_state::<AppState, _>(
+///         leptos_options,
+///         |cx| view! { cx, <TodoApp/> },
+///     ))
+///     .with_state(AppState { pool });
+///
+/// // in a component or server function
+/// let AppState { pool } = use_context::<AppState>(cx).expect("the state to be provided");
+/// ```
+/// Otherwise, this function is identical to [render_app_to_stream].
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - `S`, the router's state
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_stream_with_state<S, IV>(
+    options: LeptosOptions,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> impl Fn(
+    State<S>,
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<StreamBody<PinnedHtmlStream>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    S: Clone + Send + Sync + 'static,
+    IV: IntoView,
+{
+    // shared by every request to this handler, though each one gets a handler with its own state
+    let shell_cache = options.cache_shell.then(ShellCache::default);
+    move |State(state): State<S>, req: Request<Body>| {
+        let handler = render_app_to_stream_with_cache(
+            options.clone(),
+            move |cx| provide_context(cx, state.clone()),
+            |head| head,
+            default_tail,
+            false,
+            app_fn.clone(),
+            None,
+            shell_cache.clone(),
+        );
+        handler(req)
+    }
+}
+
+fn render_app_to_stream_inner<IV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    transform_head: impl Fn(String) -> String + 'static + Clone + Send,
+    tail: impl Fn(leptos::Scope) -> String + 'static + Clone + Send,
+    in_order: bool,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> impl Fn(
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<StreamBody<PinnedHtmlStream>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    // shared by every request to this handler
+    let shell_cache = options.cache_shell.then(ShellCache::default);
+    render_app_to_stream_with_cache(
+        options,
+        additional_context,
+        transform_head,
+        tail,
+        in_order,
+        app_fn,
+        None,
+        shell_cache,
+    )
+}
+
+fn render_app_to_stream_with_cache<IV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    transform_head: impl Fn(String) -> String + 'static + Clone + Send,
+    tail: impl Fn(leptos::Scope) -> String + 'static + Clone + Send,
+    in_order: bool,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    shell: Option<ShellFn>,
+    shell_cache: Option<ShellCache>,
+) -> impl Fn(
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<StreamBody<PinnedHtmlStream>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    move |req: Request<Body>| {
+        let head = req.method() == Method::HEAD;
+        Box::pin({
+            let options = options.clone();
+            let app_fn = app_fn.clone();
+            let add_context = additional_context.clone();
+            let transform_head = transform_head.clone();
+            let tail = tail.clone();
+            let shell = shell.clone();
+            let shell_cache = shell_cache.clone();
+
+            let render = async move {
+                let request_log = RequestLog::for_request(&options, &req);
+                let encoding = html_encoding(&options, req.headers());
+                let req = match limit_body(&options, req).await {
+                    Ok(req) => req,
+                    Err(status) => return html_status_response(status, request_log),
+                };
+                let error_renderer = req.extensions().get::<ErrorRenderer>().cloned();
+                let headers = req.headers().clone();
+                let rendered = limit_time(
+                    &options,
+                    stream_app(
+                        options.clone(),
+                        add_context,
+                        transform_head,
+                        tail,
+                        in_order,
+                        app_fn,
+                        req,
+                        shell,
+                        shell_cache,
+                    ),
+                )
+                .await;
+                match rendered {
+                    // with an error renderer, an app that didn't render gets an error instead of a broken page
+                    Ok((_, _, Some(message))) if error_renderer.is_some() => {
+                        let error = ErrorInfo {
+                            status: StatusCode::INTERNAL_SERVER_ERROR,
+                            message,
+                            kind: "Render",
+                        };
+                        let rendered =
+                            error_renderer.and_then(|renderer| renderer.render(&headers, &error));
+                        match rendered {
+                            Some((content_type, body)) => {
+                                html_error_response(error.status, content_type, body, request_log)
+                            }
+                            None => html_status_response(error.status, request_log),
+                        }
+                    }
+                    Ok((stream, res_options, _)) => {
+                        html_response(&options, stream, res_options, request_log, encoding).await
+                    }
+                    Err(status) => html_status_response(status, request_log),
+                }
+            };
+            html_head_response(head, render, empty_html_stream)
+        })
+    }
+}
+
+// The end of the page, unless a custom tail has been given
+fn default_tail(_cx: leptos::Scope) -> String {
+    "</body></html>".to_string()
+}
+
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving an HTML stream of your application.
+///
+/// If rendering `app_fn` fails before the app shell has been sent to the client, for example because
+/// a component panicked, this renders `fallback_fn` in its place, so the client gets a complete page
+/// instead of broken HTML. The fallback gets a fresh set of contexts (including [RequestParts] and
+/// [ResponseOptions]); if it doesn't set a status, the response has a `500 Internal Server Error` status.
+/// ```ignore
+/// let handler = leptos_axum::render_app_to_stream_with_fallback(
+///     options,
+///     |_cx| {},
+///     |cx| view! { cx, <TodoApp/> },
+///     |cx| view! { cx, <p>"Something went wrong. Please try again later."</p> },
+/// );
+/// ```
+/// This is a safety net for failures outside of your app's control, and complements rather than replaces
+/// [ErrorBoundary]: errors caught by an `<ErrorBoundary/>` inside the app are rendered by that boundary
+/// and never reach the fallback. Failures after the shell has been sent (for example, in a `<Suspense/>`
+/// that is streamed in later) can't be replaced, because part of the page has already been sent. In
+/// [Env::DEV], the fallback page shows the error that stopped the app from rendering, like the message of the
+/// panic, in the error overlay (see the [crate] docs).
+///
+/// Otherwise, this function is identical to [render_app_to_stream_with_context].
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_stream_with_fallback<IV, FV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    fallback_fn: impl Fn(leptos::Scope) -> FV + Clone + Send + 'static,
+) -> impl Fn(
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<StreamBody<PinnedHtmlStream>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+    FV: IntoView,
+{
+    // shared by every request to this handler
+    let shell_cache = options.cache_shell.then(ShellCache::default);
+    move |req: Request<Body>| {
+        let head = req.method() == Method::HEAD;
+        Box::pin({
+            let options = options.clone();
+            let app_fn = app_fn.clone();
+            let fallback_fn = fallback_fn.clone();
+            let add_context = additional_context.clone();
+            let shell_cache = shell_cache.clone();
+
+            let render = async move {
+                let request_log = RequestLog::for_request(&options, &req);
+                let encoding = html_encoding(&options, req.headers());
+                let req = match limit_body(&options, req).await {
+                    Ok(req) => req,
+                    Err(status) => return html_status_response(status, request_log),
+                };
+
+                // keep the parts of the request so that it can be rendered a second time
+                let rebuild_request = replayable_request(req).await;
+
+                let rendered = limit_time(
+                    &options,
+                    stream_app(
+                        options.clone(),
+                        add_context.clone(),
+                        |head| head,
+                        default_tail,
+                        false,
+                        app_fn,
+                        rebuild_request(),
+                        None,
+                        shell_cache.clone(),
+                    ),
+                )
+                .await;
+                let (stream, res_options, render_error) = match rendered {
+                    Ok(rendered) => rendered,
+                    Err(status) => return html_status_response(status, request_log),
+                };
+                let render_error = match render_error {
+                    Some(error) => error,
+                    None => {
+                        return html_response(&options, stream, res_options, request_log, encoding)
+                            .await
+                    }
+                };
+
+                // in development, the fallback page shows the error in the overlay
+                let add_context = move |cx: leptos::Scope| {
+                    add_context(cx);
+                    provide_context(cx, RenderError(render_error.clone()));
+                };
+                let (stream, res_options, _) = stream_app(
+                    options.clone(),
+                    add_context,
+                    |head| head,
+                    default_tail,
+                    false,
+                    fallback_fn,
+                    rebuild_request(),
+                    None,
+                    shell_cache,
+                )
+                .await;
+                {
+                    let mut res_parts = res_options.0.write().await;
+                    res_parts.status = res_parts.status.or(Some(StatusCode::INTERNAL_SERVER_ERROR));
+                }
+                html_response(&options, stream, res_options, request_log, encoding).await
+            };
+            html_head_response(head, render, empty_html_stream)
+        })
+    }
+}
+
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving an HTML stream of your application, or a page of your own
+/// with a `404 Not Found` status if none of the app's routes match the path.
+///
+/// When the `<Routes/>` in the app have no route for the path, the page rendered by `app_fn` is thrown away
+/// and `not_found_fn` is rendered in its place. It gets a fresh set of contexts, including [RequestParts]
+/// and [ResponseOptions], so it can set another status or headers; if it doesn't set a status, the
+/// response has a `404 Not Found` status.
+/// ```ignore
+/// let app = Router::new().fallback(leptos_axum::render_app_to_stream_with_context_and_fallback(
+///     options,
+///     |_cx| {},
+///     |cx| view! { cx, <TodoApp/> },
+///     |cx| view! { cx, <h1>"Page not found"</h1> },
+/// ));
+/// ```
+/// The app is rendered up to its shell before the router's match is known, so a page that isn't found
+/// takes as long as the shell of the app to render, as well as the page of its own.
+///
+/// Otherwise, this function is identical to [render_app_to_stream_with_context].
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_stream_with_context_and_fallback<IV, FV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    not_found_fn: impl Fn(leptos::Scope) -> FV + Clone + Send + 'static,
+) -> impl Fn(
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<StreamBody<PinnedHtmlStream>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+    FV: IntoView,
+{
+    // shared by every request to this handler
+    let shell_cache = options.cache_shell.then(ShellCache::default);
+    move |req: Request<Body>| {
+        let head = req.method() == Method::HEAD;
+        Box::pin({
+            let options = options.clone();
+            let app_fn = app_fn.clone();
+            let not_found_fn = not_found_fn.clone();
+            let add_context = additional_context.clone();
+            let shell_cache = shell_cache.clone();
+
+            let render = async move {
+                let request_log = RequestLog::for_request(&options, &req);
+                let encoding = html_encoding(&options, req.headers());
+                let req = match limit_body(&options, req).await {
+                    Ok(req) => req,
+                    Err(status) => return html_status_response(status, request_log),
+                };
+
+                // keep the parts of the request so that it can be rendered a second time
+                let rebuild_request = replayable_request(req).await;
+
+                // set by the app's <Routes/> if none of its routes match
+                let not_found = RouteNotFound::default();
+                let app_context = {
+                    let add_context = add_context.clone();
+                    let not_found = not_found.clone();
+                    move |cx: leptos::Scope| {
+                        add_context(cx);
+                        provide_context(cx, not_found.clone());
+                    }
+                };
+                let rendered = limit_time(
+                    &options,
+                    stream_app(
+                        options.clone(),
+                        app_context,
+                        |head| head,
+                        default_tail,
+                        false,
+                        app_fn,
+                        rebuild_request(),
+                        None,
+                        shell_cache.clone(),
+                    ),
+                )
+                .await;
+                let (stream, res_options, _) = match rendered {
+                    Ok(rendered) => rendered,
+                    Err(status) => return html_status_response(status, request_log),
+                };
+                if !not_found.get() {
+                    return html_response(&options, stream, res_options, request_log, encoding)
+                        .await;
+                }
+                drop(stream);
+
+                let (stream, res_options, _) = stream_app(
+                    options.clone(),
+                    add_context,
+                    |head| head,
+                    default_tail,
+                    false,
+                    not_found_fn,
+                    rebuild_request(),
+                    None,
+                    shell_cache,
+                )
+                .await;
+                {
+                    let mut res_parts = res_options.0.write().await;
+                    res_parts.status = res_parts.status.or(Some(StatusCode::NOT_FOUND));
+                }
+                html_response(&options, stream, res_options, request_log, encoding).await
+            };
+            html_head_response(head, render, empty_html_stream)
+        })
+    }
+}
+
+// Reads the body of `req` and returns a function that builds a copy of it, for handlers that render
+// the app a second time. The copies share the request's extensions.
+async fn replayable_request(req: Request<Body>) -> impl Fn() -> Request<Body> {
+    let (mut parts, body) = req.into_parts();
+    let body = body::to_bytes(body).await.unwrap_or_default();
+    let extensions = Arc::new(std::mem::take(&mut parts.extensions));
+    move || {
+        let mut req = Request::builder()
+            .method(parts.method.clone())
+            .uri(parts.uri.clone())
+            .version(parts.version)
+            .body(Body::from(body.clone()))
+            .expect("could not rebuild Request");
+        *req.headers_mut() = parts.headers.clone();
+        req.extensions_mut()
+            .insert(SharedExtensions(Arc::clone(&extensions)));
+        req
+    }
+}
+
+/// What the app function of [render_app_to_stream_with_outcome] decides to respond with. Anything but
+/// [Html](RenderOutcome::Html) skips rendering the page, so a route can answer with a redirect or a
+/// non-HTML response without going through [ResponseOptions].
+pub enum RenderOutcome<IV> {
+    /// Renders this view as the page, as the other handlers do.
+    Html(IV),
+    /// Redirects to this path, with a `302 Found` status and an empty body.
+    Redirect(String),
+    /// Responds with just this status, and its reason as the body.
+    Status(StatusCode),
+    /// Sends this response instead of a page, like a JSON error. Its status defaults to `200 OK`, and it
+    /// doesn't get a `Content-Type` unless the [ResponseParts] include one.
+    Response(ResponseParts, Bytes),
+}
+
+impl<IV> RenderOutcome<IV> {
+    // The view to render, or else the parts and body of the response to send instead
+    fn into_view_or_response(self) -> Result<IV, (ResponseParts, Bytes)> {
+        match self {
+            RenderOutcome::Html(view) => Ok(view),
+            RenderOutcome::Redirect(path) => {
+                let mut parts = ResponseParts {
+                    status: Some(StatusCode::FOUND),
+                    ..Default::default()
+                };
+                if let Ok(location) = HeaderValue::from_str(&path) {
+                    parts.headers.insert(header::LOCATION, location);
+                }
+                Err((parts, Bytes::new()))
+            }
+            RenderOutcome::Status(status) => {
+                let parts = ResponseParts {
+                    status: Some(status),
+                    ..Default::default()
+                };
+                Err((
+                    parts,
+                    Bytes::from(status.canonical_reason().unwrap_or_default()),
+                ))
+            }
+            RenderOutcome::Response(parts, body) => Err((parts, body)),
+        }
+    }
+}
+
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving an HTML stream of your application, unless the app
+/// function decides to respond with something else.
+///
+/// The app function returns a [RenderOutcome] instead of a view, so a route that only finds out at
+/// render time that it shouldn't be a page, like one for a post that has moved or been deleted, can
+/// say so directly:
+/// ```ignore
+/// let handler = leptos_axum::render_app_to_stream_with_outcome(options, |_cx| {}, |cx| {
+///     let req = use_context::<leptos_axum::RequestParts>(cx).unwrap();
+///     match moved_to(req.uri.path()) {
+///         Some(path) => RenderOutcome::Redirect(path),
+///         None => RenderOutcome::Html(view! { cx, <TodoApp/> }),
+///     }
+/// });
+/// ```
+/// The app function runs while the app shell is rendered, before anything has been sent, so any outcome
+/// but [Html](RenderOutcome::Html) replaces the whole response: the page isn't streamed at all, and
+/// resources the app function created are dropped without being awaited. That also means the outcome
+/// has to be decided synchronously. Once the app function has returned a view, it's streamed as usual,
+/// and a component under `<Suspense/>` can no longer change the response, other than through
+/// [ResponseOptions] before the shell is sent.
+///
+/// The headers set through [ResponseOptions] while the app function ran, like cookies, are added to
+/// every outcome, but the status is always the one the outcome gives. Responses other than pages are
+/// never compressed.
+///
+/// Otherwise, this function is identical to [render_app_to_stream_with_context].
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_stream_with_outcome<IV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    app_fn: impl Fn(leptos::Scope) -> RenderOutcome<IV> + Clone + Send + 'static,
+) -> impl Fn(
+    Request<Body>,
+) -> Pin<Box<dyn Future<Output = Response<StreamBody<PinnedHtmlStream>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    // shared by every request to this handler
+    let shell_cache = options.cache_shell.then(ShellCache::default);
+    move |req: Request<Body>| {
+        let head = req.method() == Method::HEAD;
+        Box::pin({
+            let options = options.clone();
+            let app_fn = app_fn.clone();
+            let add_context = additional_context.clone();
+            let shell_cache = shell_cache.clone();
+
+            let render = async move {
+                let request_log = RequestLog::for_request(&options, &req);
+                let encoding = html_encoding(&options, req.headers());
+                let req = match limit_body(&options, req).await {
+                    Ok(req) => req,
+                    Err(status) => return html_status_response(status, request_log),
+                };
+
+                // the response the app function returned instead of a view, if it did
+                let replaced = Arc::new(std::sync::Mutex::new(None));
+                let render_outcome = {
+                    let replaced = Arc::clone(&replaced);
+                    move |cx: leptos::Scope| match app_fn(cx).into_view_or_response() {
+                        Ok(view) => view.into_view(cx),
+                        Err(response) => {
+                            *replaced.lock().unwrap() = Some(response);
+                            ().into_view(cx)
+                        }
+                    }
+                };
+
+                let rendered = limit_time(
+                    &options,
+                    stream_app(
+                        options.clone(),
+                        add_context,
+                        |head| head,
+                        default_tail,
+                        false,
+                        render_outcome,
+                        req,
+                        None,
+                        shell_cache,
+                    ),
+                )
+                .await;
+                let (stream, res_options) = match rendered {
+                    Ok((stream, res_options, _)) => (stream, res_options),
+                    Err(status) => return html_status_response(status, request_log),
+                };
+                let replaced = replaced.lock().unwrap().take();
+                match replaced {
+                    Some((parts, body)) => {
+                        // the rest of the page is never rendered
+                        drop(stream);
+                        outcome_response(res_options, parts, body, request_log).await
+                    }
+                    None => {
+                        html_response(&options, stream, res_options, request_log, encoding).await
+                    }
+                }
+            };
+            html_head_response(head, render, empty_html_stream)
+        })
+    }
+}
+
+// The response that a [RenderOutcome] replaced the page with, with the headers set through
+// [ResponseOptions] while rendering and then the ones from the outcome
+async fn outcome_response(
+    res_options: ResponseOptions,
+    parts: ResponseParts,
+    body: Bytes,
+    request_log: Option<RequestLog>,
+) -> Response<StreamBody<PinnedHtmlStream>> {
+    let status = parts.status.unwrap_or(StatusCode::OK);
+    if let Some(mut entry) = request_log {
+        entry.status = status;
+        entry.sent(body.len());
+    }
+    let stream: PinnedHtmlStream = Box::pin(futures::stream::once(async move { Ok(body) }));
+    let mut res = Response::new(StreamBody::new(stream));
+    *res.status_mut() = status;
+    let mut res_headers = res_options.0.read().await.headers.clone();
+    res.headers_mut().extend(res_headers.drain());
+    for (name, value) in parts.headers.iter() {
+        res.headers_mut().insert(name, value.clone());
+    }
+    res
+}
+
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving the HTML of your application as a single response body.
+///
+/// This is for clients and deployment targets that can't handle a streamed response, like serverless
+/// functions behind gateways that buffer responses, or crawlers that don't support chunked transfer. The
+/// page is only sent once every resource read under `<Suspense/>` has resolved, so it takes as long as
+/// the slowest one; it includes their serialized values, so the client hydrates as usual. The status and
+/// headers set through [ResponseOptions] at any point while rendering are applied to the response.
+///
+/// It's a drop-in replacement for [render_app_to_stream]:
+/// ```ignore
+/// let app = Router::new()
+///     .fallback(leptos_axum::render_app_to_string(leptos_options, |cx| view! { cx, <MyApp/> }));
+/// ```
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_string<IV>(
+    options: LeptosOptions,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> impl Fn(Request<Body>) -> Pin<Box<dyn Future<Output = Response<Full<Bytes>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    render_app_to_string_with_context(options, |_| {}, app_fn)
+}
+
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving the HTML of your application as a single response body.
+///
+/// This version takes additional context, like [render_app_to_stream_with_context]. Otherwise, it's
+/// identical to [render_app_to_string].
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_string_with_context<IV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> impl Fn(Request<Body>) -> Pin<Box<dyn Future<Output = Response<Full<Bytes>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    render_app_to_string_inner(options, additional_context, app_fn, false, false)
+}
+
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving the HTML of your application as a single response body
+/// with an `ETag`, for pages whose content depends only on their URL.
+///
+/// The page is rendered in full, like with [render_app_to_string_with_context], and the response has a
+/// weak `ETag` computed from its body. A request whose `If-None-Match` header matches it gets a `304 Not
+/// Modified` without a body, so the client keeps using the copy it already has. The page is still
+/// rendered to compare it, but it isn't sent again. A streamed response can't have an `ETag`, since its
+/// headers are sent before its body is complete, so this is only available for a single response body.
+///
+/// Only pages with a `200 OK` status get an `ETag`. A page that differs between requests for the same
+/// URL, like one that shows the signed-in user, should opt out with [skip_etag] while rendering. A page
+/// that uses a [CspNonce] never matches, since its nonce is different every time.
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_to_string_with_etag<IV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> impl Fn(Request<Body>) -> Pin<Box<dyn Future<Output = Response<Full<Bytes>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    render_app_to_string_inner(options, additional_context, app_fn, false, true)
+}
+
+/// Opts the page being rendered out of the `ETag` added by [render_app_to_string_with_etag], so it's
+/// always sent in full. Call it from the component for any route whose content isn't the same for every
+/// request to its URL:
+/// ```ignore
+/// #[component]
+/// fn Account(cx: Scope) -> impl IntoView {
+///     leptos_axum::skip_etag(cx);
+///     // ...
+/// }
+/// ```
+/// It does nothing when the page is rendered by any other handler.
+pub fn skip_etag(cx: leptos::Scope) {
+    if let Some(SkipEtag(skip)) = use_context::<SkipEtag>(cx) {
+        skip.store(true, Ordering::Relaxed);
+    }
+}
+
+// Set by [skip_etag] while a page is rendered by [render_app_to_string_with_etag]
+#[derive(Clone, Default)]
+struct SkipEtag(Arc<AtomicBool>);
+
+/// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
+/// to route it using [leptos_router], serving either the HTML of your application or a fragment of
+/// it, for pages that update part of themselves without navigating, like after a form submit.
+///
+/// A request with a `Leptos-Fragment` header gets only the element whose `id` is the header's value:
+/// its outer HTML, from its start tag to its end tag. Any element with an `id` can be requested this
+/// way, so to make part of a component swappable, wrap it in an element with a fixed `id`:
+/// ```ignore
+/// #[component]
+/// fn Cart(cx: Scope) -> impl IntoView {
+///     view! { cx, <section id="cart">/* ... */</section> }
+/// }
+/// ```
+/// The page is rendered in full to find the fragment, and the fragment includes everything inside
+/// the element once its resources have resolved. If the page has no element with that `id`, the
+/// response is a `404 Not Found`. A request without the header gets the whole page, as with
+/// [render_app_to_string_with_context], so forms keep working without JavaScript. Every response
+/// has a `Vary: Leptos-Fragment` header, so caches keep pages and fragments apart.
+///
+/// On the client, the fragment replaces the element with the same `id`:
+/// ```js
+/// const res = await fetch(form.action, { method: "POST", body: new FormData(form), headers: { "Leptos-Fragment": "cart" } });
+/// if (res.ok) document.getElementById("cart").outerHTML = await res.text();
+/// ```
+/// The fragment is plain HTML: it isn't hydrated, so the swapped-in elements don't have the event
+/// listeners or reactivity of the app. It suits parts of the page that are rendered by the server and
+/// updated by replacing them, like the results of a search form.
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub fn render_app_fragments<IV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> impl Fn(Request<Body>) -> Pin<Box<dyn Future<Output = Response<Full<Bytes>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    render_app_to_string_inner(options, additional_context, app_fn, true, false)
+}
+
+// The request header that names the fragment of the page to send, for [render_app_fragments]
+const FRAGMENT_HEADER: &str = "leptos-fragment";
+
+fn render_app_to_string_inner<IV>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    fragments: bool,
+    etag: bool,
+) -> impl Fn(Request<Body>) -> Pin<Box<dyn Future<Output = Response<Full<Bytes>>> + Send + 'static>>
+       + Clone
+       + Send
+       + 'static
+where
+    IV: IntoView,
+{
+    // shared by every request to this handler
+    let shell_cache = options.cache_shell.then(ShellCache::default);
+    move |req: Request<Body>| {
+        let head = req.method() == Method::HEAD;
+        Box::pin({
+            let options = options.clone();
+            let app_fn = app_fn.clone();
+            let add_context = additional_context.clone();
+            let shell_cache = shell_cache.clone();
+
+            let render = async move {
+                let mut request_log = RequestLog::for_request(&options, &req);
+                let fragment = req
+                    .headers()
+                    .get(FRAGMENT_HEADER)
+                    .filter(|_| fragments)
+                    .and_then(|id| id.to_str().ok())
+                    .map(|id| id.to_string());
+                let if_none_match = req.headers().get(header::IF_NONE_MATCH).cloned();
+                let req = match limit_body(&options, req).await {
+                    Ok(req) => req,
+                    Err(status) => return full_status_response(status, request_log),
+                };
+                let skip_etag = SkipEtag::default();
+                let add_context = {
+                    let skip_etag = skip_etag.clone();
+                    move |cx| {
+                        if etag {
+                            provide_context(cx, skip_etag.clone());
+                        }
+                        add_context(cx)
+                    }
+                };
+                let rendered = limit_time(&options, async {
+                    let (mut stream, res_options, _) = stream_app(
+                        options.clone(),
+                        add_context,
+                        |head| head,
+                        default_tail,
+                        // a fragment is cut out of the page, so it needs the HTML of each
+                        // `<Suspense/>` in its place
+                        fragment.is_some(),
+                        app_fn,
+                        req,
+                        None,
+                        shell_cache,
+                    )
+                    .await;
+                    // the stream only ends once every resource has resolved
+                    let mut html = Vec::new();
+                    while let Some(chunk) = stream.next().await {
+                        html.extend_from_slice(&chunk?);
+                    }
+                    Ok::<_, io::Error>((html, res_options))
+                })
+                .await;
+                let (html, res_options) = match rendered {
+                    Ok(Ok(rendered)) => rendered,
+                    Ok(Err(_)) => {
+                        return full_status_response(StatusCode::INTERNAL_SERVER_ERROR, request_log)
+                    }
+                    Err(status) => return full_status_response(status, request_log),
+                };
+
+                let html = match &fragment {
+                    Some(id) => match page_fragment(&String::from_utf8_lossy(&html), id) {
+                        Some(fragment) => fragment.as_bytes().to_vec(),
+                        None => return full_status_response(StatusCode::NOT_FOUND, request_log),
+                    },
+                    None => html,
+                };
+
+                let res_parts = res_options.0.read().await;
+                // a body set through ResponseOptions is sent in place of the page
+                if let Some(body) = res_parts.body.clone() {
+                    return custom_body_response(&res_parts, body, request_log).map(Full::from);
+                }
+                let status = res_parts.status.unwrap_or(StatusCode::OK);
+                let etag =
+                    (etag && status == StatusCode::OK && !skip_etag.0.load(Ordering::Relaxed))
+                        .then(|| page_etag(&html));
+                let not_modified = match (&etag, &if_none_match) {
+                    (Some(etag), Some(if_none_match)) => etag_matches(if_none_match, etag),
+                    _ => false,
+                };
+                // the client already has the page, so only its headers are sent
+                let html = if not_modified { Vec::new() } else { html };
+                if let Some(entry) = &mut request_log {
+                    entry.status = if not_modified {
+                        StatusCode::NOT_MODIFIED
+                    } else {
+                        status
+                    };
+                    entry.sent(html.len());
+                }
+                let mut res = Response::new(Full::from(html));
+                apply_html_parts(&options, &res_parts, &mut res);
+                if let Some(etag) = etag {
+                    res.headers_mut().insert(
+                        header::ETAG,
+                        HeaderValue::from_str(&etag).expect("Failed to create HeaderValue"),
+                    );
+                }
+                if not_modified {
+                    *res.status_mut() = StatusCode::NOT_MODIFIED;
+                }
+                if fragments {
+                    res.headers_mut()
+                        .append(header::VARY, HeaderValue::from_static("Leptos-Fragment"));
+                }
+                res
+            };
+            html_head_response(head, render, Full::default)
+        })
+    }
+}
+
+// A weak `ETag` for a rendered page: the same HTML always gets the same tag from the same build of the server
+fn page_etag(html: &[u8]) -> String {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    html.hash(&mut hasher);
+    format!("W/\"{:016x}\"", hasher.finish())
+}
+
+// Whether an `If-None-Match` header matches an `ETag`, using the weak comparison that conditional `GET`s use:
+// any of its comma-separated tags matches, whether or not either is weak, and so does `*`
+fn etag_matches(if_none_match: &HeaderValue, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    match if_none_match.to_str() {
+        Ok(if_none_match) => if_none_match
+            .split(',')
+            .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag)),
+        Err(_) => false,
+    }
+}
+
+// The outer HTML of the first element in a rendered page whose `id` is `id`. Text and attribute values are
+// escaped in rendered HTML, so every `<` starts a tag or a comment, and void elements are rendered as `<tag/>`.
+fn page_fragment<'a>(html: &'a str, id: &str) -> Option<&'a str> {
+    let attribute = format!(" id=\"{}\"", leptos::leptos_dom::escape_attr(&id));
+    let (start, tag) = html.match_indices(&attribute).find_map(|(position, _)| {
+        // the attribute has to be in a start tag, rather than in text that happens to look like it
+        let start = html[..position].rfind('<')?;
+        if html[start..position].contains('>') {
+            return None;
+        }
+        let tag = html[start + 1..position].split_whitespace().next()?;
+        Some((start, tag))
+    })?;
+
+    let mut depth = 0;
+    let mut rest = start;
+    loop {
+        let next = rest + html[rest..].find('<')?;
+        let after = &html[next + 1..];
+        if let Some(close) = after.strip_prefix('/') {
+            if close.strip_prefix(tag).map(|close| close.starts_with('>')) == Some(true) {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&html[start..next + tag.len() + 3]);
+                }
+            }
+        } else if after
+            .strip_prefix(tag)
+            .map(|after| after.starts_with([' ', '>', '/']))
+            == Some(true)
+        {
+            let end = next + html[next..].find('>')?;
+            // a void element ends with its start tag
+            if html[..end].ends_with('/') {
+                if depth == 0 {
+                    return Some(&html[start..=end]);
+                }
+            } else {
+                depth += 1;
+            }
+        }
+        rest = next + 1;
+    }
+}
+
+// The response to a `HEAD` request for a page: the page is rendered as it would be for a `GET`, so the
+// response has the status and headers that the app sets while rendering, but its body is dropped, which
+// stops rendering whatever is still streaming
+async fn html_head_response<B>(
+    head: bool,
+    res: impl Future<Output = Response<B>>,
+    empty: impl FnOnce() -> B,
+) -> Response<B> {
+    let res = res.await;
+    if !head {
+        return res;
+    }
+    let (parts, _) = res.into_parts();
+    Response::from_parts(parts, empty())
+}
+
+// An empty body for the response to a `HEAD` request for a streamed page
+fn empty_html_stream() -> StreamBody<PinnedHtmlStream> {
+    let stream: PinnedHtmlStream = Box::pin(futures::stream::empty());
+    StreamBody::new(stream)
+}
+
+// Like [html_status_response], for the handlers that don't stream their response
+fn full_status_response(
+    status: StatusCode,
+    request_log: Option<RequestLog>,
+) -> Response<Full<Bytes>> {
+    if let Some(mut entry) = request_log {
+        entry.status = status;
+        entry.sent(status.canonical_reason().unwrap_or_default().len());
+    }
+    let mut res = Response::new(Full::from(status.canonical_reason().unwrap_or_default()));
+    *res.status_mut() = status;
+    res
+}
+
+// Builds the response for a rendered page, once the app shell has been rendered
+async fn html_response(
+    options: &LeptosOptions,
+    stream: PinnedHtmlStream,
+    res_options: ResponseOptions,
+    request_log: Option<RequestLog>,
+    encoding: Option<HtmlEncoding>,
+) -> Response<StreamBody<PinnedHtmlStream>> {
+    // a body set through ResponseOptions is sent in place of the page, if it was set before the shell rendered
+    let late_body = res_options.clone();
+    let res_options = res_options.0.read().await;
+    if let Some(body) = res_options.body.clone() {
+        return custom_body_response(&res_options, body, request_log).map(|body| {
+            let stream: PinnedHtmlStream = Box::pin(futures::stream::once(async move { Ok(body) }));
+            StreamBody::new(stream)
+        });
+    }
+    // once the page has started streaming, it's too late to replace it
+    let stream: PinnedHtmlStream = Box::pin(
+        stream.chain(
+            futures::stream::once(async move {
+                if late_body.0.read().await.body.is_some() {
+                    tracing::warn!(
+                        target: "leptos_axum::render",
+                        "a response body was set with ResponseOptions after the page started \
+                         streaming, so it was ignored"
+                    );
+                }
+            })
+            .filter_map(|()| async { None::<io::Result<Bytes>> }),
+        ),
+    );
+
+    // the chunks that were rendered before the response was built are compressed too
+    let stream = match encoding {
+        Some(HtmlEncoding::Brotli) => compress_html(stream, BrotliEncoder::new(Vec::new())),
+        Some(HtmlEncoding::Gzip) => compress_html(stream, GzipEncoder::new(Vec::new())),
+        None => stream,
+    };
+
+    let stream: PinnedHtmlStream = match request_log {
+        Some(mut entry) => {
+            entry.status = res_options.status.unwrap_or(StatusCode::OK);
+            Box::pin(stream.inspect(move |chunk| {
+                if let Ok(chunk) = chunk {
+                    entry.sent(chunk.len());
+                }
+            }))
+        }
+        None => stream,
+    };
+
+    let mut res = Response::new(StreamBody::new(stream));
+    apply_html_parts(options, &res_options, &mut res);
+    if let Some(encoding) = encoding {
+        res.headers_mut().insert(
+            header::CONTENT_ENCODING,
+            HeaderValue::from_static(encoding.as_str()),
+        );
+        res.headers_mut()
+            .append(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+    }
+    res
+}
+
+// The response for a body set with [ResponseOptions::set_body], which is sent with the status and headers from
+// the [ResponseOptions] in place of the page
+fn custom_body_response(
+    res_parts: &ResponseParts,
+    body: Bytes,
+    request_log: Option<RequestLog>,
+) -> Response<Bytes> {
+    let status = res_parts.status.unwrap_or(StatusCode::OK);
+    if let Some(mut entry) = request_log {
+        entry.status = status;
+        entry.sent(body.len());
+    }
+    let mut res = Response::new(body);
+    *res.status_mut() = status;
+    res.headers_mut().extend(res_parts.headers.clone());
+    if let Some(content_type) = &res_parts.content_type {
+        res.headers_mut()
+            .insert(header::CONTENT_TYPE, content_type.clone());
+    }
+    res
+}
+
+// The content codings that rendered pages can be compressed with, if [LeptosOptions::compress_html] is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HtmlEncoding {
+    Brotli,
+    Gzip,
+}
+
+impl HtmlEncoding {
+    fn as_str(self) -> &'static str {
+        match self {
+            HtmlEncoding::Brotli => "br",
+            HtmlEncoding::Gzip => "gzip",
+        }
+    }
+}
+
+// The coding to compress a rendered page with: Brotli, if the request's `Accept-Encoding` allows it, or
+// else gzip. Codings with `q=0` aren't acceptable, and neither is anything without the header.
+fn html_encoding(options: &LeptosOptions, headers: &HeaderMap) -> Option<HtmlEncoding> {
+    if !options.compress_html {
+        return None;
+    }
+    let accepted = headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|coding| {
+            let mut params = coding.split(';');
+            let name = params.next()?.trim().to_ascii_lowercase();
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some(name)
+        })
+        .collect::<Vec<_>>();
+    let accepts = |name: &str| {
+        accepted
+            .iter()
+            .any(|coding| coding == name || coding == "*")
+    };
+    if accepts("br") {
+        Some(HtmlEncoding::Brotli)
+    } else if accepts("gzip") {
+        Some(HtmlEncoding::Gzip)
+    } else {
+        None
+    }
+}
+
+// Compresses each chunk of the page as it comes, flushing the encoder after each one so the client
+// can start parsing the page before the stream is complete
+fn compress_html<E>(stream: PinnedHtmlStream, encoder: E) -> PinnedHtmlStream
+where
+    E: AsyncWrite + CompressedOutput + Unpin + Send + 'static,
+{
+    Box::pin(futures::stream::unfold(
+        Some((stream, encoder)),
+        |state| async move {
+            let (mut stream, mut encoder) = state?;
+            match stream.next().await {
+                Some(Ok(chunk)) => {
+                    let written = async {
+                        encoder.write_all(&chunk).await?;
+                        encoder.flush().await
+                    }
+                    .await;
+                    let compressed = Bytes::from(encoder.take_output());
+                    Some((written.map(|_| compressed), Some((stream, encoder))))
+                }
+                Some(Err(e)) => Some((Err(e), None)),
+                // the end of the compressed stream
+                None => {
+                    let finished = encoder.shutdown().await;
+                    let compressed = Bytes::from(encoder.take_output());
+                    Some((finished.map(|_| compressed), None))
+                }
+            }
+        },
+    ))
+}
+
+// An encoder that compresses into a buffer, whose contents can be taken once they've been flushed
+trait CompressedOutput {
+    fn take_output(&mut self) -> Vec<u8>;
+}
+
+impl CompressedOutput for BrotliEncoder<Vec<u8>> {
+    fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(self.get_mut())
+    }
+}
+
+impl CompressedOutput for GzipEncoder<Vec<u8>> {
+    fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(self.get_mut())
+    }
+}
+
+// Sets the content type and version headers of a rendered page, and the status and headers from its
+// [ResponseOptions]
+fn apply_html_parts<B>(options: &LeptosOptions, res_parts: &ResponseParts, res: &mut Response<B>) {
+    // a charset that can't be sent in a header is left out, as the app version is; loading the options
+    // with `get_configuration` rejects it, but options built in code may not have been validated
+    let content_type = HeaderValue::from_str(&format!("text/html; charset={}", options.charset))
+        .unwrap_or_else(|_| HeaderValue::from_static("text/html"));
+    res.headers_mut().insert(header::CONTENT_TYPE, content_type);
+    if let Some(version) = &options.app_version {
+        if let Ok(version) = HeaderValue::from_str(version) {
+            res.headers_mut()
+                .insert(HeaderName::from_static("x-app-version"), version);
+        }
+    }
+    if let Some(status) = res_parts.status {
+        *res.status_mut() = status
+    }
+    let mut res_headers = res_parts.headers.clone();
+    res.headers_mut().extend(res_headers.drain());
+}
+
+// An error response with a body from an [ErrorRenderer]
+fn html_error_response(
+    status: StatusCode,
+    content_type: &'static str,
+    body: String,
+    request_log: Option<RequestLog>,
+) -> Response<StreamBody<PinnedHtmlStream>> {
+    if let Some(mut entry) = request_log {
+        entry.status = status;
+        entry.sent(body.len());
+    }
+    let body = Bytes::from(body);
+    let stream: PinnedHtmlStream = Box::pin(futures::stream::once(async move { Ok(body) }));
+    let mut res = Response::new(StreamBody::new(stream));
+    *res.status_mut() = status;
+    res.headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    res
+}
+
+// The response for a page that couldn't be rendered because the request went over one of its limits
+fn html_status_response(
+    status: StatusCode,
+    request_log: Option<RequestLog>,
+) -> Response<StreamBody<PinnedHtmlStream>> {
+    if let Some(mut entry) = request_log {
+        entry.status = status;
+        entry.sent(status.canonical_reason().unwrap_or_default().len());
+    }
+    let body = Bytes::from(status.canonical_reason().unwrap_or_default());
+    let stream: PinnedHtmlStream = Box::pin(futures::stream::once(async move { Ok(body) }));
+    let mut res = Response::new(StreamBody::new(stream));
+    *res.status_mut() = status;
+    res
+}
+
+/// A callback that's told about every response sent by the render and server function handlers, once
+/// it's complete: the path of the request, the status of the response, the number of bytes of its body
+/// that were sent, and how long it took from the request coming in to the body being sent. It's a
+/// lighter-weight alternative to tracing for pushing metrics to your own system.
+///
+/// Add it to the router as an extension, and it applies to every handler in the router:
+/// ```ignore
+/// let app = Router::new()
+///     .route("/api/*fn_name", post(leptos_axum::handle_server_fns))
+///     .leptos_routes(leptos_options.clone(), routes, |cx| view! { cx, <App/> })
+///     .layer(Extension(ResponseObserver::new(|path, status, bytes, duration| {
+///         metrics::histogram!("response_time", duration, "status" => status.as_str().to_string());
+///     })));
+/// ```
+/// It's called for error responses too, like a `408 Request Timeout` or a server function that failed,
+/// and for responses the client stopped reading before they were complete, which count only the bytes
+/// that were sent.
+///
+/// The callback runs synchronously, on whichever of the server's threads drops the response body, which
+/// is usually one of Tokio's worker threads. It isn't given a reactive [Scope](leptos::Scope), so it can't
+/// read the app's context, and it shouldn't block: spawn a task with [tokio::spawn] for anything that has
+/// to `.await`.
+#[derive(Clone)]
+pub struct ResponseObserver(Arc<dyn Fn(&str, StatusCode, usize, Duration) + Send + Sync>);
+
+impl ResponseObserver {
+    /// Calls `f` with the path, status, number of bytes sent, and duration of every response.
+    pub fn new(f: impl Fn(&str, StatusCode, usize, Duration) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+impl std::fmt::Debug for ResponseObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ResponseObserver").finish()
+    }
+}
+
+/// Renders the body of error responses, as a JSON object for API clients or an HTML page for browsers,
+/// in place of the plain error message that's sent by default.
+///
+/// Add it to the router as an extension, like a [ResponseObserver]:
+/// ```ignore
+/// let app = Router::new()
+///     .route("/api/*fn_name", post(leptos_axum::handle_server_fns))
+///     .leptos_routes(leptos_options.clone(), routes, |cx| view! { cx, <App/> })
+///     .layer(Extension(ErrorRenderer::new(|format, error| match format {
+///         ErrorFormat::Json => serde_json::json!({ "status": error.status.as_u16(), "error": error.message }).to_string(),
+///         ErrorFormat::Html => format!("<!DOCTYPE html><h1>{}</h1><p>Please try again later.</p>", error.status),
+///     })));
+/// ```
+/// The closure is called with the [ErrorFormat] that the request's `Accept` header asks for, and the body
+/// it returns is sent with the matching `Content-Type`. It's invoked for
+/// - a server function that returned an error, whatever its status (but not one that called
+///   [abort_with_status]),
+/// - a server function that panicked, with a `500 Internal Server Error` status and a generic message, and
+/// - an app that failed to render before its shell was sent, which gets a `500 Internal Server Error`
+///   response instead of the incomplete page when there's a renderer. Handlers with a fallback, like
+///   [render_app_to_stream_with_fallback], render the fallback instead.
+///
+/// A request that accepts neither JSON nor HTML gets the plain message, as it does without a renderer.
+/// The message can include details of the server, like the message of a panic while rendering, so
+/// leave it out of the pages you show to users if that matters. The closure runs synchronously, without a
+/// reactive [Scope](leptos::Scope).
+#[derive(Clone)]
+pub struct ErrorRenderer(Arc<dyn Fn(ErrorFormat, &ErrorInfo) -> String + Send + Sync>);
+
+impl ErrorRenderer {
+    /// Renders error responses with `f`.
+    pub fn new(f: impl Fn(ErrorFormat, &ErrorInfo) -> String + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    // The body and its `Content-Type`, if the request accepts one of the formats
+    fn render(&self, headers: &HeaderMap, error: &ErrorInfo) -> Option<(&'static str, String)> {
+        let format = ErrorFormat::from_accept(headers)?;
+        let content_type = match format {
+            ErrorFormat::Json => ContentType::Json.as_str(),
+            ErrorFormat::Html => "text/html; charset=utf-8",
+        };
+        Some((content_type, (self.0)(format, error)))
+    }
+}
+
+impl std::fmt::Debug for ErrorRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ErrorRenderer").finish()
+    }
+}
+
+/// The format an [ErrorRenderer] renders an error in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// For requests that accept `application/json`, like API clients.
+    Json,
+    /// For requests that accept `text/html` but not JSON, like browsers.
+    Html,
+}
+
+impl ErrorFormat {
+    // JSON if the `Accept` header lists it, or else HTML if it lists that
+    fn from_accept(headers: &HeaderMap) -> Option<Self> {
+        let media_types: Vec<&str> = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|media_type| media_type.split(';').next().unwrap_or_default().trim())
+            .collect();
+        if media_types
+            .iter()
+            .any(|media_type| media_type.parse() == Ok(ContentType::Json))
+        {
+            Some(Self::Json)
+        } else if media_types.contains(&"text/html") {
+            Some(Self::Html)
+        } else {
+            None
+        }
+    }
+}
+
+/// An error that an [ErrorRenderer] renders the response for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorInfo {
+    /// The status the response is sent with.
+    pub status: StatusCode,
+    /// The error's message: the [ServerFnError] a server function returned, or why the app didn't render.
+    pub message: String,
+    /// The kind of error: the variant of a [ServerFnError], like `ServerError`, or `Panic` for a server
+    /// function that panicked, or `Render` for an app that failed to render.
+    pub kind: &'static str,
+}
+
+/// What's logged about a response: a line of the access log enabled by [LeptosOptions::access_log], a
+/// warning if rendering it took longer than [LeptosOptions::slow_render_threshold], and a call to the
+/// router's [ResponseObserver]. It's moved into the response body so it can count the bytes actually
+/// sent, and is emitted when the body is dropped, whether the stream finished or the client went away.
+struct RequestLog {
+    start: Instant,
+    path: String,
+    route: Option<String>,
+    status: StatusCode,
+    bytes: usize,
+    access_log: bool,
+    slow_render_threshold: Option<Duration>,
+    observer: Option<ResponseObserver>,
+}
+
+impl RequestLog {
+    fn for_request(options: &LeptosOptions, req: &Request<Body>) -> Option<Self> {
+        let slow_render_threshold = options.slow_render_threshold.map(Duration::from_millis);
+        let observer = req.extensions().get::<ResponseObserver>().cloned();
+        (options.access_log || slow_render_threshold.is_some() || observer.is_some()).then(|| {
+            RequestLog {
+                start: Instant::now(),
+                path: req.uri().path().to_string(),
+                route: req
+                    .extensions()
+                    .get::<MatchedPath>()
+                    .map(|path| path.as_str().to_string()),
+                status: StatusCode::OK,
+                bytes: 0,
+                access_log: options.access_log,
+                slow_render_threshold,
+                observer,
+            }
+        })
+    }
+
+    // server functions aren't in the access log, so they're only tracked for a [ResponseObserver]
+    fn for_server_fn(req: &Request<Body>) -> Option<Self> {
+        let observer = req.extensions().get::<ResponseObserver>().cloned()?;
+        Some(RequestLog {
+            start: Instant::now(),
+            path: req.uri().path().to_string(),
+            route: None,
+            status: StatusCode::OK,
+            bytes: 0,
+            access_log: false,
+            slow_render_threshold: None,
+            observer: Some(observer),
+        })
+    }
+
+    // takes `&mut self` so the closure wrapping the body captures the whole entry,
+    // not just its `bytes` field, and the line is only emitted when the body is dropped
+    fn sent(&mut self, bytes: usize) {
+        self.bytes += bytes;
+    }
+}
+
+impl Drop for RequestLog {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        if self.access_log {
+            let line = serde_json::json!({
+                "path": self.path,
+                "route": self.route,
+                "status": self.status.as_u16(),
+                "duration_ms": duration.as_millis() as u64,
+                "bytes": self.bytes,
+            });
+            tracing::info!(target: "leptos_axum::access_log", "{line}");
+        }
+        if let Some(threshold) = self.slow_render_threshold {
+            if duration > threshold {
+                tracing::warn!(
+                    target: "leptos_axum::slow_render",
+                    path = %self.path,
+                    route = self.route.as_deref(),
+                    status = self.status.as_u16(),
+                    duration_ms = duration.as_millis() as u64,
+                    threshold_ms = threshold.as_millis() as u64,
+                    "slow render"
+                );
+            }
+        }
+        if let Some(ResponseObserver(observer)) = &self.observer {
+            observer(&self.path, self.status, self.bytes, duration);
+        }
+    }
+}
+
+/// Renders `app_fn` to a string of HTML without a server, for snapshot tests of components'
+/// server-rendered markup:
+/// ```ignore
+/// #[test]
+/// fn greeting_renders_the_name() {
+///     let html = leptos_axum::render_to_string_for_test(|cx| view! { cx, <Greeting name="Alice"/> });
+///     assert!(html.contains("Hello, Alice!"));
+/// }
+/// ```
+/// The component gets the same contexts as when it's rendered by a request to `/`: the [RequestParts]
+/// of an empty `GET /` request, and [ResponseOptions] that it can write to, though nothing reads them.
+/// Only the view itself is rendered, without the `<head>` and hydration scripts that wrap it in a page,
+/// and any `<Suspense/>` renders its fallback, because nothing waits for resources to resolve. Use
+/// [render_app_to_writer] to render a whole page.
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar], which is empty
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), which is `en`
+/// - [PathParams], which are empty
+/// - [RequestUrl], with the `http` scheme, the path `/`, and an empty host, since the request has no `Host`
+/// - [RequestId], which is a new ID
+pub fn render_to_string_for_test<IV>(app_fn: impl FnOnce(leptos::Scope) -> IV + 'static) -> String
+where
+    IV: IntoView,
+{
+    render_to_string(move |cx| {
+        let integration = ServerIntegration {
+            path: "http://leptos.dev/".to_string(),
+        };
+        provide_context(cx, RouterIntegrationContext::new(integration));
+        provide_context(cx, MetaContext::new());
+        provide_context(cx, negotiate_locale(&HeaderMap::new(), "en"));
+        provide_context(cx, PathParams::default());
+        let req_parts = RequestParts {
+            version: Version::HTTP_11,
+            method: Method::GET,
+            uri: Uri::from_static("/"),
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+            extensions: Default::default(),
+        };
+        provide_context(cx, RequestUrl::new(&req_parts, false));
+        provide_context(cx, RequestId::generate("X-Request-ID"));
+        provide_context(cx, CookieJar::default());
+        provide_context(cx, req_parts);
+        provide_context(cx, ResponseOptions::default());
+        app_fn(cx).into_view(cx)
+    })
+}
+
+/// Renders your app for the given request and writes the resulting HTML into `writer` chunk by chunk,
+/// for servers that hand you a response writer or sink rather than letting you return a [Response].
+///
+/// The whole body is written before this returns, and the [ResponseParts] are read only after the
+/// last chunk has been written, so they include any status or headers set by resources that
+/// resolved while streaming. If your server needs the status and headers before the body, you'll
+/// need to write into a buffer and copy it into the real response once this has returned.
+///
+/// The `Content-Type` header is not included in the returned [ResponseParts] unless the app set it;
+/// it should be `text/html` with the charset from [LeptosOptions].
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [RequestParts]
+/// - [CookieJar]
+/// - [ResponseOptions]
+/// - [MetaContext](leptos_meta::MetaContext)
+/// - [RouterIntegrationContext](leptos_router::RouterIntegrationContext)
+/// - [Locale](leptos::Locale), negotiated from the `Accept-Language` header
+/// - [PathParams]
+/// - [RequestUrl]
+/// - [RequestId]
+pub async fn render_app_to_writer<IV, W>(
+    options: LeptosOptions,
+    additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    req: Request<Body>,
+    writer: &mut W,
+) -> io::Result<ResponseParts>
+where
+    IV: IntoView,
+    W: AsyncWrite + Unpin,
+{
+    let (mut stream, res_options, _) = stream_app(
+        options,
+        additional_context,
+        |head| head,
+        default_tail,
+        false,
+        app_fn,
+        req,
+        None,
+        None,
+    )
+    .await;
+
+    while let Some(chunk) = stream.next().await {
+        writer.write_all(&chunk?).await?;
+    }
+    writer.flush().await?;
+
+    let res_parts = res_options.0.read().await.clone();
+    Ok(res_parts)
+}
+
+/// The values of the [Resource]s of some routes, loaded ahead of time so that rendering those routes
+/// doesn't have to wait for their data. Clone it to share the same values between handlers.
+///
+/// The resources of a route are the ones created while rendering the app at its path, in the order
+/// they're created: [prewarm](PrewarmedResources::prewarm) renders the app for the path, waits for
+/// all of them to resolve, and stores their values. A render then starts with those values when the
+/// app calls [seed](PrewarmedResources::seed) before creating any resources, for example at the
+/// start of the function that renders it:
+/// ```rust,ignore
+/// let resources = PrewarmedResources::new();
+/// resources.prewarm(options.clone(), |_| {}, |cx| view! { cx, <App/> }, "/weather").await;
+///
+/// let app = Router::new().fallback(render_app_to_stream(options, {
+///     let resources = resources.clone();
+///     move |cx| {
+///         resources.seed(cx);
+///         view! { cx, <App/> }
+///     }
+/// }));
+/// ```
+/// Values are matched with requests by their path and query, so a request with a different query
+/// string loads its resources as usual.
+///
+/// A seeded resource only uses the stored value when it first loads, and refetches as usual when its
+/// source changes. The stored values are used for every render until they're replaced by prewarming
+/// the path again or removed with [invalidate](PrewarmedResources::invalidate): deciding when they're
+/// stale, for example by prewarming on a schedule, is up to you.
+#[derive(Clone, Default)]
+pub struct PrewarmedResources(Arc<std::sync::RwLock<HashMap<String, Vec<(ResourceId, String)>>>>);
+
+impl PrewarmedResources {
+    /// Creates an empty set of resource values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders the app at `path`, which may include a query string, and stores the values of all
+    /// the resources it created once they've resolved, replacing any that were stored for it before.
+    /// Returns the number of resources that were stored.
+    ///
+    /// The app is rendered with its `additional_context` like it would be for a `GET` request to the
+    /// path without any headers, and the values stored for the path aren't used to render it.
+    ///
+    /// ## Panics
+    /// Panics if `path` isn't a valid URI.
+    pub async fn prewarm<IV>(
+        &self,
+        mut options: LeptosOptions,
+        additional_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+        app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+        path: &str,
+    ) -> usize
+    where
+        IV: IntoView,
+    {
+        let req = Request::builder()
+            .uri(path)
+            .body(Body::empty())
+            .expect("couldn't prewarm the resources of an invalid path");
+        let key = prewarm_key(req.uri());
+
+        // the renderer sends all of the resources' values to the hydration state endpoint at once
+        options.hydration_state_endpoint = None;
+        let state = Arc::new(std::sync::Mutex::new(String::new()));
+        let add_context = {
+            let state = Arc::clone(&state);
+            move |cx| {
+                additional_context(cx);
+                provide_context(cx, Prewarming);
+                let state = Arc::clone(&state);
+                provide_context(
+                    cx,
+                    HydrationStateEndpoint(std::rc::Rc::new(move |json| {
+                        *state.lock().unwrap() = json;
+                        // the page itself isn't sent anywhere
+                        None
+                    })),
+                );
+            }
+        };
+
+        let (stream, _, _) = stream_app(
+            options,
+            add_context,
+            |head| head,
+            default_tail,
+            false,
+            app_fn,
+            req,
+            None,
+            None,
+        )
+        .await;
+        stream.for_each(|_| async {}).await;
+
+        let json = std::mem::take(&mut *state.lock().unwrap());
+        let resources: Vec<(ResourceId, String)> = serde_json::from_str(&json).unwrap_or_default();
+        let count = resources.len();
+        self.0.write().unwrap().insert(key, resources);
+        count
+    }
+
+    /// Seeds the resources of the current request with the values stored for its path, if there are
+    /// any, with [Scope::seed_resources]. It should be called before the app creates any resources.
+    pub fn seed(&self, cx: leptos::Scope) {
+        if use_context::<Prewarming>(cx).is_some() {
+            return;
+        }
+        if let Some(req) = use_context::<RequestParts>(cx) {
+            if let Some(resources) = self.0.read().unwrap().get(&prewarm_key(&req.uri)) {
+                cx.seed_resources(resources.iter().cloned());
+            }
+        }
+    }
+
+    /// Removes the values stored for `path`, so its resources are loaded again when it's rendered.
+    /// `path` is read the same way as by [prewarm](Self::prewarm), so a full URL invalidates the values
+    /// stored for its path and query. An invalid path has nothing stored for it.
+    pub fn invalidate(&self, path: &str) {
+        if let Ok(uri) = path.parse::<Uri>() {
+            self.0.write().unwrap().remove(&prewarm_key(&uri));
+        }
+    }
+
+    /// The paths that have resource values stored.
+    pub fn paths(&self) -> Vec<String> {
+        self.0.read().unwrap().keys().cloned().collect()
+    }
+}
+
+impl std::fmt::Debug for PrewarmedResources {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("PrewarmedResources")
+            .field(&self.paths())
+            .finish()
+    }
+}
+
+// Provided while prewarming, so the app's call to `seed` doesn't reuse the values being replaced
+#[derive(Clone, Copy)]
+struct Prewarming;
+
+fn prewarm_key(uri: &Uri) -> String {
+    uri.path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or_else(|| uri.path())
+        .to_string()
+}
+
+// The number of shells a handler keeps, so that requests for many different locales can't make the cache grow
+const SHELL_CACHE_SIZE: usize = 64;
+
+/// The static part of the head of the pages rendered by one handler, when [LeptosOptions::cache_shell] is
+/// set, keyed by the page's locale and whether it includes the live-reload script and the error overlay.
+#[derive(Clone, Default)]
+struct ShellCache(Arc<std::sync::Mutex<HashMap<ShellKey, Arc<str>>>>);
+
+// The page's locale, and whether it includes the live-reload script and the error overlay
+type ShellKey = (String, bool, bool);
+
+impl ShellCache {
+    fn get_or_insert(&self, key: ShellKey, build: impl FnOnce() -> String) -> Arc<str> {
+        let mut shells = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(shell) = shells.get(&key) {
+            return Arc::clone(shell);
+        }
+        let shell: Arc<str> = build().into();
+        if shells.len() < SHELL_CACHE_SIZE {
+            shells.insert(key, Arc::clone(&shell));
+        }
+        shell
+    }
+}
+
+// The number of hydration state blobs the server keeps at a time; pages rendered while the store is full
+// have their state inline instead
+const HYDRATION_STATES_SIZE: usize = 1024;
+
+// How long the server keeps a hydration state blob after the last page with it was rendered, which is also
+// how long clients may cache it
+const HYDRATION_STATE_TTL: Duration = Duration::from_secs(600);
+
+// The hydration state blobs served by [handle_hydration_state], by the hash of their contents, with the
+// time the last page with them was rendered
+static HYDRATION_STATES: Lazy<std::sync::Mutex<HashMap<String, (Arc<str>, Instant)>>> =
+    Lazy::new(Default::default);
+
+// Stores a page's hydration state, returning the key it's served under, or `None` if the store is full
+fn store_hydration_state(state: String) -> Option<String> {
+    let key = hydration_state_key(&state);
+    let mut states = HYDRATION_STATES.lock().unwrap_or_else(|e| e.into_inner());
+    states.retain(|_, (_, stored)| stored.elapsed() < HYDRATION_STATE_TTL);
+    if let Some((stored, rendered)) = states.get_mut(&key) {
+        // a different state with the same hash can't be served under it too
+        if **stored != *state {
+            return None;
+        }
+        // the same state as an earlier page, which is kept for longer
+        *rendered = Instant::now();
+        return Some(key);
+    }
+    if states.len() >= HYDRATION_STATES_SIZE {
+        return None;
+    }
+    states.insert(key.clone(), (state.into(), Instant::now()));
+    Some(key)
+}
+
+// The key of a blob of hydration state: the same state always gets the same key from the same build of the
+// server
+fn hydration_state_key(state: &str) -> String {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    state.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// An Axum handler that serves the data of the resources of a page rendered with
+/// [LeptosOptions::hydration_state_endpoint] set, which the page fetches while it hydrates. Add it to the
+/// router at that path, followed by a `:key` param:
+/// ```ignore
+/// let app = Router::new()
+///     .route("/_leptos/state/:key", get(leptos_axum::handle_hydration_state))
+///     .leptos_routes(leptos_options.clone(), routes, |cx| view! { cx, <App/> });
+/// ```
+/// The response is the JSON array of `[id, json]` pairs described in [HydrationStateEndpoint]. Each blob
+/// is served under a hash of its contents, so pages whose resources load the same data share a URL, and
+/// the HTML of a page can be cached for as long as that data doesn't change. A blob is kept for 10
+/// minutes after the last page with it was rendered, and sent with `Cache-Control: max-age=600`. Once
+/// it's gone, a page that still has its URL, like one served from a cache or restored from the browser's
+/// history, gets a `404 Not Found` and loads its resources in the browser instead, as if they hadn't been
+/// loaded on the server.
+///
+/// The URL isn't tied to the user the page was rendered for, so whoever has it can fetch the blob, and a
+/// shared cache may serve it to anyone. Don't use the endpoint for apps whose resources load data that's
+/// only meant for an authenticated user; leave [LeptosOptions::hydration_state_endpoint] unset, and the
+/// data is streamed into the page.
+///
+/// At most 1024 blobs are kept at a time, shared by all of the app's handlers. A page rendered while the
+/// store is full has its resources' data streamed into it instead.
+pub async fn handle_hydration_state(Path(key): Path<String>) -> Response<Full<Bytes>> {
+    let state = HYDRATION_STATES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&key)
+        .filter(|(_, stored)| stored.elapsed() < HYDRATION_STATE_TTL)
+        .map(|(state, _)| Arc::clone(state));
+    match state {
+        Some(state) => {
+            let mut res = Response::new(Full::from(Bytes::from(state.to_string())));
+            res.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+            res.headers_mut().insert(
+                header::CACHE_CONTROL,
+                HeaderValue::from_str(&format!("max-age={}", HYDRATION_STATE_TTL.as_secs()))
+                    .expect("the max age is a valid header value"),
+            );
+            res
+        }
+        None => {
+            let mut res = Response::new(Full::from(Bytes::from_static(b"Not Found")));
+            *res.status_mut() = StatusCode::NOT_FOUND;
+            // the blob may be stored again by the next page with it
+            res.headers_mut()
+                .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+            res
+        }
+    }
+}
+
+/// Renders the app into a [PinnedHtmlStream], returning it along with the [ResponseOptions] that
+/// the app can use to set the status and headers of the response.
+///
+/// The first chunks of the stream, which contain the app shell, have already been rendered by the
+/// time this returns, so the [ResponseOptions] reflect anything set while rendering the shell. If
+/// rendering failed before the shell was sent, the error is returned as well.
+async fn stream_app<IV>(
+    options: LeptosOptions,
+    add_context: impl Fn(leptos::Scope) + 'static + Clone + Send,
+    transform_head: impl Fn(String) -> String + 'static + Clone + Send,
+    tail: impl Fn(leptos::Scope) -> String + 'static + Clone + Send,
+    in_order: bool,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    req: Request<Body>,
+    shell: Option<ShellFn>,
+    shell_cache: Option<ShellCache>,
+) -> (PinnedHtmlStream, ResponseOptions, Option<String>)
+where
+    IV: IntoView,
+{
+    let default_res_options = ResponseOptions::default();
+    let res_options2 = default_res_options.clone();
+    let res_options3 = default_res_options.clone();
+
+    // Need to get the path and query string of the Request
+    // For reasons that escape me, if the incoming URI protocol is https, it provides the absolute URI
+    // if http, it returns a relative path. Adding .path() seems to make it explicitly return the relative uri
+    let path = req.uri().path_and_query().unwrap().as_str();
+
+    let full_path = format!("http://leptos.dev{path}");
+
+    // the path of the route, if the request was routed by [LeptosRoutes]
+    let matched_route = req.extensions().get::<MatchedRoute>().cloned();
+
+    // the params of the matched route, if the request was routed to one that has them
+    let (mut parts, body) = req.into_parts();
+    let path_params = Path::<HashMap<String, String>>::from_request_parts(&mut parts, &())
+        .await
+        .map(|Path(params)| PathParams(params))
+        .unwrap_or_default();
+    let req = Request::from_parts(parts, body);
+
+    // the negotiated locale is provided as context and embedded as <html lang>, where the client reads it back
+    let locale = match options.negotiate_lang {
+        true => negotiate_locale(req.headers(), &options.lang),
+        false => Locale(options.lang.clone()),
+    };
+    let lang = locale.as_str().to_string();
+    let lang_key = lang.clone();
+
+    let request_id = RequestId::from_headers(&options.request_id_header, req.headers());
+    request_id
+        .send(&options.request_id_header, &default_res_options)
+        .await;
+
+    let pkg_path = options.site_pkg_dir.clone();
+    let output_name = options.output_name.clone();
+
+    // Because wasm-pack adds _bg to the end of the WASM filename, and we want to mantain compatibility with it's default options
+    // we add _bg to the wasm files if cargo-leptos doesn't set the env var LEPTOS_OUTPUT_NAME
+    // Otherwise we need to add _bg because wasm_pack always does. This is not the same as options.output_name, which is set regardless
+    // When LEPTOS_OUTPUT_NAME is set, get_configuration uses it as options.output_name even if the config file disagrees
+    let mut wasm_output_name = output_name.clone();
+    if std::env::var("LEPTOS_OUTPUT_NAME").is_err() {
+        wasm_output_name.push_str("_bg");
+    }
+
+    let reload_origin = options.reload_ws_origin();
+    let charset = options.charset.clone();
+    let doctype = options.doctype.clone();
+    let preconnect = preconnect_links(&options);
+    let version_meta = version_meta(&options);
+    let hydration_imports = hydration_imports(&options);
+    let init_fn_name = options.init_fn_name.clone();
+    let hydrate_fn_name = options.hydrate_fn_name.clone();
+    let trust_forwarded_headers = options.trust_forwarded_headers;
+    let dev_overlay_enabled = dev_overlay_enabled(&options);
+    let preload_hints = match options.preload_hints {
+        true => format!(
+            r#"<link rel="modulepreload" href="/{pkg_path}/{output_name}.js">
+                <link rel="preload" href="/{pkg_path}/{wasm_output_name}.wasm" as="fetch" type="application/wasm" crossorigin="">
+                "#
+        ),
+        false => "".to_string(),
+    };
+
+    // with an endpoint for the hydration state, the resources' data is stored for the client to fetch, and
+    // with `csp_nonce`, every page gets a nonce of its own
+    let add_context = {
+        let state_endpoint = options
+            .hydration_state_endpoint
+            .as_ref()
+            .map(|endpoint| endpoint.trim_end_matches('/').to_string());
+        let csp_nonce = options.csp_nonce;
+        move |cx| {
+            add_context(cx);
+            // the app's own nonce, if it has one, is used for every script instead
+            if csp_nonce && use_context::<CspNonce>(cx).is_none() {
+                provide_context(cx, generate_csp_nonce());
+            }
+            if let Some(endpoint) = state_endpoint.clone() {
+                provide_context(
+                    cx,
+                    HydrationStateEndpoint(std::rc::Rc::new(move |state| {
+                        store_hydration_state(state).map(|key| format!("{endpoint}/{key}"))
+                    })),
+                );
+            }
+        }
+    };
+
+    // what the head of the shell is built from, whether it's the default or a custom one
+    let shell_context = {
+        let pkg_path = pkg_path.clone();
+        let output_name = output_name.clone();
+        let wasm_output_name = wasm_output_name.clone();
+        move |nonce: &str, autoreload: bool, dev_overlay: bool| ShellContext {
+            pkg_path: pkg_path.clone(),
+            output_name: output_name.clone(),
+            wasm_output_name: wasm_output_name.clone(),
+            lang: lang.clone(),
+            nonce: nonce.to_string(),
+            autoreload: match autoreload {
+                true => format!(
+                    r#"
+            <script crossorigin=""{nonce}>(function () {{
+                var ws = new WebSocket('{reload_origin}/live_reload');
+                ws.onmessage = (ev) => {{
+                    let msg = JSON.parse(ev.data);
+                    if (msg.all) window.location.reload();
+                    if (msg.error && window.__leptos_dev_overlay) window.__leptos_dev_overlay.show(msg.error);
+                    if (msg.css) {{
+                        const link = document.querySelector("link#leptos");
+                        if (link) {{
+                            let href = link.getAttribute('href').split('?')[0];
+                            let newHref = href + '?version=' + new Date().getMilliseconds();
+                            link.setAttribute('href', newHref);
+                        }} else {{
+                            console.warn("Could not find link#leptos");
+                        }}
+                    }};
+                }};
+                ws.onclose = () => console.warn('Live-reload stopped. Manual reload necessary.');
+            }})()
+            </script>
+            "#
+                ),
+                false => "".to_string(),
+            },
+            dev_overlay: match dev_overlay {
+                true => dev_overlay_script(nonce),
+                false => "".to_string(),
+            },
+        }
+    };
+
+    // the head is rendered along with the app, so its scripts can get the CspNonce from context
+    let build_head = move |shell: &ShellContext| {
+        tracing::trace!(target: "leptos_axum::shell", "building the head of the app shell");
+        let ShellContext {
+            pkg_path,
+            output_name,
+            wasm_output_name,
+            lang,
+            nonce,
+            autoreload,
+            dev_overlay,
+        } = shell;
+
+        format!(
+            r#"{doctype}
+        <html lang="{lang}">
+            <head>
+                <meta charset="{charset}"/>
+                <meta name="viewport" content="width=device-width, initial-scale=1"/>
+                {version_meta}{preconnect}{preload_hints}{dev_overlay}<script type="module"{nonce}>import {hydration_imports} from '/{pkg_path}/{output_name}.js'; {init_fn_name}('/{pkg_path}/{wasm_output_name}.wasm').then({hydrate_fn_name});</script>
+                {autoreload}
+                "#
+        )
+    };
+    // a custom shell gives the end of the page along with its head
+    let custom_tail = Arc::new(std::sync::Mutex::new(None::<String>));
+    let head = {
+        let custom_tail = Arc::clone(&custom_tail);
+        move |nonce: &str, autoreload: bool, dev_overlay: bool| {
+            let build = || {
+                let shell_cx = shell_context(nonce, autoreload, dev_overlay);
+                match &shell {
+                    Some(shell) => {
+                        let (head, tail) = shell(&shell_cx);
+                        *custom_tail.lock().unwrap() = Some(tail);
+                        head
+                    }
+                    None => build_head(&shell_cx),
+                }
+            };
+            // a head with a nonce is only valid for one response, and a custom shell is built for
+            // every response, so neither is cached
+            match &shell_cache {
+                Some(cache) if nonce.is_empty() && shell.is_none() => cache
+                    .get_or_insert((lang_key.clone(), autoreload, dev_overlay), build)
+                    .to_string(),
+                _ => build(),
+            }
+        }
+    };
+    let (mut tx, rx) = futures::channel::mpsc::channel(8);
+    // set once the first chunk of the app has been rendered, so we can tell whether rendering failed before that
+    let shell_rendered = Arc::new(AtomicBool::new(false));
+
+    let render_task = LOCAL_POOL.spawn_pinned({
+        let app_fn = app_fn.clone();
+        let add_context = add_context.clone();
+        let shell_rendered = Arc::clone(&shell_rendered);
+        move || async move {
+            let app = {
+                let full_path = full_path.clone();
+                // the handlers have already checked the body against the limit, if there is one
+                let req_parts = generate_request_parts_with_limit(req, None)
+                    .await
+                    .expect("reading a body without a limit doesn't fail");
+                let request_url = RequestUrl::new(&req_parts, trust_forwarded_headers);
+                move |cx| {
+                    let integration = ServerIntegration {
+                        path: full_path.clone(),
+                    };
+                    provide_context(cx, RouterIntegrationContext::new(integration));
+                    // keeps the MetaContext from the additional context, with its defaults
+                    provide_meta_context(cx);
+                    provide_context(cx, locale);
+                    provide_context(cx, path_params);
+                    if let Some(matched_route) = matched_route {
+                        provide_context(cx, matched_route);
+                    }
+                    provide_context(cx, request_url);
+                    provide_context(cx, request_id);
+                    provide_context(cx, CookieJar::from_headers(&req_parts.headers));
+                    provide_context(cx, req_parts);
+                    provide_context(cx, default_res_options);
+                    app_fn(cx).into_view(cx)
+                }
+            };
+
+            let prefix = move |cx: leptos::Scope| -> std::borrow::Cow<'static, str> {
+                let nonce = use_context::<CspNonce>(cx)
+                    .map(|nonce| nonce.to_attribute())
+                    .unwrap_or_default();
+                let meta = use_context::<MetaContext>(cx)
+                    .map(|meta| meta.dehydrate())
+                    .unwrap_or_default();
+                let meta = transform_head(meta);
+                // the live-reload script and the error overlay are only for the developer's own browser
+                let is_local = use_context::<RequestParts>(cx)
+                    .map(|req| {
+                        if autoreload_enabled() || dev_overlay_enabled {
+                            warn_without_peer_address(&req);
+                        }
+                        req.is_local()
+                    })
+                    .unwrap_or(false);
+                let autoreload = autoreload_enabled() && is_local;
+                let dev_overlay = dev_overlay_enabled && is_local;
+                let render_error = match use_context::<RenderError>(cx) {
+                    Some(RenderError(error)) if dev_overlay => {
+                        dev_render_error_script(&nonce, &error)
+                    }
+                    _ => String::new(),
+                };
+                format!(
+                    "{}{meta}{render_error}</head><body>",
+                    head(&nonce, autoreload, dev_overlay)
+                )
+                .into()
+            };
+            let (bundle, runtime, scope) = if in_order {
+                let (bundle, runtime, scope) =
+                    render_to_stream_in_order_with_prefix_undisposed_with_context(
+                        app,
+                        prefix,
+                        add_context,
+                    );
+                (bundle.boxed_local(), runtime, scope)
+            } else {
+                let (bundle, runtime, scope) =
+                    render_to_stream_with_prefix_undisposed_with_context(app, prefix, add_context);
+                (bundle.boxed_local(), runtime, scope)
+            };
+            let cx = Scope { runtime, id: scope };
+            flush_cookies(cx).await;
+
+            // the shell has rendered, so the app has had the chance to set its own policy
+            if let Some(ContentSecurityPolicy(policy)) = use_context(cx) {
+                if let Some(res_options) = use_context::<ResponseOptions>(cx) {
+                    res_options
+                        .0
+                        .write()
+                        .await
+                        .headers
+                        .entry(header::CONTENT_SECURITY_POLICY)
+                        .or_insert(policy);
+                }
+            }
+
+            let mut shell = Box::pin(bundle);
+            if let Some(fragment) = shell.next().await {
+                // the page starts with the head, which should start with the doctype
+                shell_rendered.store(true, Ordering::SeqCst);
+                _ = tx.send(fragment.trim_start().to_string()).await;
+            }
+            while let Some(fragment) = shell.next().await {
+                _ = tx.send(fragment).await;
+            }
+
+            // the tail comes after the app and its resolved resources
+            let tail = custom_tail
+                .lock()
+                .unwrap()
+                .take()
+                .unwrap_or_else(|| tail(cx));
+            _ = tx.send(tail).await;
+
+            // Extract the value of ResponseOptions from here
+            let res_options = use_context::<ResponseOptions>(cx).unwrap();
+
+            let new_res_parts = res_options.0.read().await.clone();
+
+            let mut writable = res_options2.0.write().await;
+            *writable = new_res_parts;
+
+            runtime.dispose();
+
+            tx.close_channel();
+        }
+    });
+
+    let mut collapser = options
+        .collapse_whitespace
+        .then(WhitespaceCollapser::default);
+    let mut stream = Box::pin(
+        rx.map(move |html| match &mut collapser {
+            Some(collapser) => collapser.collapse(&html),
+            None => html,
+        })
+        .map(|html| Ok(Bytes::from(html))),
+    );
+
+    // Get the first and second chunks in the stream, which renders the app shell, and thus allows Resources to run
+    let first_chunk = stream.next().await;
+    let second_chunk = stream.next().await;
+
+    // if rendering failed, the stream may end before the second chunk
+    let complete_stream =
+        futures::stream::iter(first_chunk.into_iter().chain(second_chunk)).chain(stream);
+
+    // without a shell, the render has ended, and a panic says why
+    let render_error = match shell_rendered.load(Ordering::SeqCst) {
+        true => None,
+        false => Some(match render_task.await {
+            Err(e) if e.is_panic() => panic_message(e.into_panic()),
+            _ => "the app didn't render anything".to_string(),
+        }),
+    };
+
+    (Box::pin(complete_stream), res_options3, render_error)
+}
+
+// The message a panic was started with, like `panic!("...")` or `.expect("...")`
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "the panic has no message".to_string(),
+        },
+    }
+}
+
+// The names that the hydration script imports from the app's JS module. wasm-bindgen exports its
+// `init` function as the module's default export, so only a custom one is a named export.
+fn hydration_imports(options: &LeptosOptions) -> String {
+    let init = &options.init_fn_name;
+    let hydrate = &options.hydrate_fn_name;
+    if init == "init" {
+        format!("init, {{ {hydrate} }}")
+    } else {
+        format!("{{ {init}, {hydrate} }}")
+    }
+}
+
+// The tag for [LeptosOptions::app_version], if it's set
+fn version_meta(options: &LeptosOptions) -> String {
+    options
+        .app_version
+        .as_ref()
+        .map(|version| {
+            format!(
+                r#"<meta name="version" content="{}"/>"#,
+                escape_attr(version)
+            )
+        })
+        .unwrap_or_default()
+}
+
+// The resource hints for each of the origins in [LeptosOptions::preconnect_origins]
+fn preconnect_links(options: &LeptosOptions) -> String {
+    options
+        .preconnect_origins
+        .iter()
+        .map(|origin| {
+            let origin = origin.replace('"', "&quot;");
+            format!(
+                r#"<link rel="preconnect" href="{origin}" crossorigin=""><link rel="dns-prefetch" href="{origin}">"#
+            )
+        })
+        .collect()
+}
+
+/// Serves the file at `uri`'s path from [LeptosOptions::site_root], for use in a fallback handler
+/// in front of your app. Returns `None` if there is no such file (or the path tries to escape the
+/// site root), so you can render your app or an error page instead:
+/// ```ignore
+/// async fn file_and_error_handler(
+///     uri: Uri,
+///     Extension(options): Extension<Arc<LeptosOptions>>,
+///     req: Request<Body>,
+/// ) -> Response {
+///     match leptos_axum::serve_static_file(&options, &uri).await {
+///         Some(res) => res.into_response(),
+///         None => {
+///             let handler = leptos_axum::render_app_to_stream((*options).clone(), |cx| view! { cx, <NotFound/> });
+///             handler(req).await.into_response()
+///         }
+///     }
+/// }
+/// ```
+/// The `Content-Type` is guessed from the file's extension. Files without a known extension are
+/// served as `text/plain` if they look like UTF-8 text, and with
+/// [LeptosOptions::static_default_content_type] otherwise.
+///
+/// This always serves the whole file. Use [serve_static_file_with_headers] to honor `Range` requests.
+pub async fn serve_static_file(options: &LeptosOptions, uri: &Uri) -> Option<Response<Body>> {
+    serve_static_file_with_headers(options, uri, &HeaderMap::new()).await
+}
+
+/// Serves the file at `uri`'s path from [LeptosOptions::site_root], like [serve_static_file], taking
+/// the request's `headers` into account. This lets media players and download managers fetch part of
+/// a file:
+/// - every response has an `Accept-Ranges: bytes` header
+/// - a request with a single `Range: bytes=...` range (like `bytes=0-1023`, `bytes=1024-`, or
+///   `bytes=-1024` for the last 1024 bytes) gets a `206 Partial Content` response with only those
+///   bytes and a `Content-Range` header
+/// - a range that starts past the end of the file gets a `416 Range Not Satisfiable` response
+///
+/// Requests for several ranges at once, with a malformed `Range` header, or with an `If-Range`
+/// header get the whole file, which is allowed for any `Range` request.
+/// ```ignore
+/// async fn file_and_error_handler(
+///     uri: Uri,
+///     headers: HeaderMap,
+///     Extension(options): Extension<Arc<LeptosOptions>>,
+///     req: Request<Body>,
+/// ) -> Response {
+///     match leptos_axum::serve_static_file_with_headers(&options, &uri, &headers).await {
+///         Some(res) => res.into_response(),
+///         None => { /* render your app or an error page */ }
+///     }
+/// }
+/// ```
+pub async fn serve_static_file_with_headers(
+    options: &LeptosOptions,
+    uri: &Uri,
+    headers: &HeaderMap,
+) -> Option<Response<Body>> {
+    serve_static_file_from_roots(options, &[&options.site_root], uri, headers).await
+}
+
+/// Serves the file at `uri`'s path from the first of `roots` that has it, like
+/// [serve_static_file_with_headers] does from [LeptosOptions::site_root]. This is for apps whose assets
+/// are spread over several directories, like the assets of a framework or design system next to the
+/// app's own:
+/// ```ignore
+/// async fn file_and_error_handler(
+///     uri: Uri,
+///     headers: HeaderMap,
+///     Extension(options): Extension<Arc<LeptosOptions>>,
+///     req: Request<Body>,
+/// ) -> Response {
+///     let roots = [options.site_root.as_str(), "vendor/ui-kit/assets"];
+///     match leptos_axum::serve_static_file_from_roots(&options, &roots, &uri, &headers).await {
+///         Some(res) => res.into_response(),
+///         None => { /* render your app or an error page */ }
+///     }
+/// }
+/// ```
+/// The roots are tried in order, so a file in an earlier root shadows a file at the same path in a later
+/// one. [LeptosOptions::site_root] isn't searched unless it's one of the roots. Returns `None` if none of
+/// them has the file (or the path tries to escape them).
+///
+/// The response is the same whichever root the file came from, and like the other helpers this sets no
+/// caching headers. To cache the files of one root differently, like fingerprinted framework assets that
+/// never change, call this once for each root with a single-element list, and add the headers for that
+/// root to its response.
+pub async fn serve_static_file_from_roots(
+    options: &LeptosOptions,
+    roots: &[impl AsRef<std::path::Path>],
+    uri: &Uri,
+    headers: &HeaderMap,
+) -> Option<Response<Body>> {
+    let relative = uri.path().trim_start_matches('/');
+    let relative = std::path::Path::new(relative);
+    if relative
+        .components()
+        .any(|component| !matches!(component, std::path::Component::Normal(_)))
+    {
+        return None;
+    }
+
+    for root in roots {
+        let path = root.as_ref().join(relative);
+        if let Some(res) = serve_file(options, &path, headers).await {
+            return Some(res);
+        }
+    }
+    None
+}
+
+// The response for the file at `path`, or `None` if there is no such file. The file is streamed from
+// disk, starting at the requested range, so large files are never read into memory.
+async fn serve_file(
+    options: &LeptosOptions,
+    path: &std::path::Path,
+    headers: &HeaderMap,
+) -> Option<Response<Body>> {
+    let metadata = tokio::fs::metadata(path).await.ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+    let len = metadata.len();
+    let mut file = tokio::fs::File::open(path).await.ok()?;
+
+    let content_type = match content_type_from_extension(path) {
+        Some(content_type) => content_type.to_string(),
+        None => {
+            // only the start of the file is sniffed
+            let mut sample = Vec::with_capacity(512);
+            (&mut file).take(512).read_to_end(&mut sample).await.ok()?;
+            file.seek(io::SeekFrom::Start(0)).await.ok()?;
+            match looks_like_text(&sample) {
+                true => "text/plain; charset=utf-8".to_string(),
+                false => options.static_default_content_type.clone(),
+            }
+        }
+    };
+
+    let range = match headers.get(header::RANGE) {
+        Some(_) if headers.contains_key(header::IF_RANGE) => None,
+        Some(range) => range
+            .to_str()
+            .ok()
+            .and_then(|range| parse_range(range, len)),
+        None => None,
+    };
+    let mut res = match range {
+        Some(Ok((start, end))) => {
+            file.seek(io::SeekFrom::Start(start)).await.ok()?;
+            let body = Body::wrap_stream(ReaderStream::new(file.take(end - start + 1)));
+            let mut res = Response::new(body);
+            *res.status_mut() = StatusCode::PARTIAL_CONTENT;
+            res.headers_mut().insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes {start}-{end}/{len}"))
+                    .expect("Failed to create HeaderValue"),
+            );
+            res.headers_mut()
+                .insert(header::CONTENT_LENGTH, HeaderValue::from(end - start + 1));
+            res
+        }
+        Some(Err(())) => {
+            let mut res = Response::new(Body::empty());
+            *res.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
+            res.headers_mut().insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{len}"))
+                    .expect("Failed to create HeaderValue"),
+            );
+            res
+        }
+        None => {
+            let mut res = Response::new(Body::wrap_stream(ReaderStream::new(file)));
+            res.headers_mut()
+                .insert(header::CONTENT_LENGTH, HeaderValue::from(len));
+            res
+        }
+    };
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(&content_type).expect("Failed to create HeaderValue"),
+    );
+    res.headers_mut()
+        .insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    Some(res)
+}
+
+// Parses a `Range` header with a single range into the inclusive bounds of the bytes to send, or
+// `Err` if the range is unsatisfiable. Anything else returns `None`, so the whole file is sent.
+fn parse_range(range: &str, len: u64) -> Option<Result<(u64, u64), ()>> {
+    let range = range.trim().strip_prefix("bytes=")?.trim();
+    if range.contains(',') {
+        return None;
+    }
+    let (start, end) = range.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    let bounds = if start.is_empty() {
+        // a suffix range, for the last `end` bytes
+        let suffix = end.parse::<u64>().ok()?;
+        if suffix == 0 || len == 0 {
+            return Some(Err(()));
+        }
+        (len.saturating_sub(suffix), len - 1)
+    } else {
+        let start = start.parse::<u64>().ok()?;
+        let end = match end {
+            "" => u64::MAX,
+            end => end.parse::<u64>().ok()?,
+        };
+        if end < start {
+            return None;
+        }
+        if start >= len {
+            return Some(Err(()));
+        }
+        (start, end.min(len - 1))
+    };
+    Some(Ok(bounds))
+}
+
+fn content_type_from_extension(path: &std::path::Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let content_type = match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json",
+        "wasm" => "application/wasm",
+        "txt" => "text/plain; charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => return None,
+    };
+    Some(content_type)
+}
+
+// Sniffs the start of the file: valid UTF-8 without any control characters other than
+// whitespace is treated as plain text, which covers files like `/.well-known/security.txt`
+// that are sometimes served without their extension.
+fn looks_like_text(contents: &[u8]) -> bool {
+    let sample = &contents[..contents.len().min(512)];
+    let text = match std::str::from_utf8(sample) {
+        Ok(text) => text,
+        // the sample may have cut a multi-byte character in half
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&sample[..e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return false,
+    };
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| !c.is_control() || c.is_ascii_whitespace())
+}
+
+/// Collapses runs of whitespace in a stream of HTML chunks into a single space, for
+/// [LeptosOptions::collapse_whitespace]. It keeps its state between chunks, so tags and
+/// runs of whitespace can be split across them.
+///
+/// Whitespace is only collapsed in text between tags, never removed, so every text node is kept.
+/// Attribute values and the contents of `<pre>`, `<textarea>`, `<script>`, and `<style>` are copied
+/// as they are.
+#[derive(Default)]
+struct WhitespaceCollapser {
+    in_tag: bool,
+    quote: Option<char>,
+    tag: String,
+    tag_done: bool,
+    self_closing: bool,
+    last_was_space: bool,
+    // the closing tag (like `</pre`) that ends the raw content we're in
+    raw_until: Option<&'static str>,
+    raw_tail: String,
+}
+
+impl WhitespaceCollapser {
+    fn collapse(&mut self, chunk: &str) -> String {
+        let mut out = String::with_capacity(chunk.len());
+        for c in chunk.chars() {
+            if let Some(closing) = self.raw_until {
+                out.push(c);
+                self.raw_tail.push(c.to_ascii_lowercase());
+                if self.raw_tail.len() > closing.len() {
+                    self.raw_tail.remove(0);
+                }
+                if self.raw_tail == closing {
+                    // we're now inside the closing tag
+                    self.raw_until = None;
+                    self.raw_tail.clear();
+                    self.in_tag = true;
+                    self.tag.clear();
+                    self.tag_done = true;
+                    self.self_closing = false;
+                }
+            } else if self.in_tag {
+                out.push(c);
+                match (self.quote, c) {
+                    (Some(quote), c) if c == quote => self.quote = None,
+                    (Some(_), _) => {}
+                    (None, '"' | '\'') => self.quote = Some(c),
+                    (None, '>') => {
+                        self.in_tag = false;
+                        self.last_was_space = false;
+                        if !self.self_closing {
+                            let name = self.tag.to_ascii_lowercase();
+                            self.raw_until = match name.as_str() {
+                                "pre" => Some("</pre"),
+                                "textarea" => Some("</textarea"),
+                                "script" => Some("</script"),
+                                "style" => Some("</style"),
+                                _ => None,
+                            };
+                        }
+                    }
+                    (None, c) => {
+                        if !self.tag_done
+                            && (c.is_ascii_alphanumeric() || (c == '/' && self.tag.is_empty()))
+                        {
+                            self.tag.push(c);
+                        } else {
+                            self.tag_done = true;
+                        }
+                        if !c.is_whitespace() {
+                            self.self_closing = c == '/';
+                        }
+                    }
+                }
+            } else if c == '<' {
+                out.push(c);
+                self.in_tag = true;
+                self.tag.clear();
+                self.tag_done = false;
+                self.self_closing = false;
+            } else if c.is_whitespace() {
+                if !self.last_was_space {
+                    out.push(' ');
+                    self.last_was_space = true;
+                }
+            } else {
+                out.push(c);
+                self.last_was_space = false;
+            }
+        }
+        out
+    }
+}
+
+// Picks the language tag with the highest quality value from the `Accept-Language` header,
+// falling back to `default`. Tags containing anything but letters, digits, and `-` are ignored,
+// since the result ends up in the page's `<html lang>` attribute.
+fn negotiate_locale(headers: &HeaderMap, default: &str) -> Locale {
+    let mut best: Option<(f32, &str)> = None;
+    let ranges = headers
+        .get_all(header::ACCEPT_LANGUAGE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','));
+    for range in ranges {
+        let mut parts = range.split(';');
+        let tag = parts.next().unwrap_or_default().trim();
+        let quality = parts
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        let valid = !tag.is_empty() && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if valid && quality > 0.0 && best.map(|(q, _)| quality > q).unwrap_or(true) {
+            best = Some((quality, tag));
+        }
+    }
+    Locale(best.map(|(_, tag)| tag).unwrap_or(default).to_string())
+}
+
+/// Generates a list of all routes defined in Leptos's Router in your app. We can then use this to automatically
+/// create routes in Axum's Router without having to use wildcard matching or fallbacks. Takes in your root app Element
+/// as an argument so it can walk you app tree. This version is tailored to generate Axum compatible paths.
+///
+/// `:param` segments are passed through as they are. A splat, like `<Route path="docs/*path"/>`, becomes an
+/// Axum wildcard with the same name (an unnamed `*` is named `any`), and since a wildcard doesn't match an
+/// empty splat, its parent path, like `/docs`, is listed as well unless the app has a route for it.
+///
+/// If no routes are found, this logs a warning and falls back to a single `/` route. This usually means the app
+/// is missing a `<Router/>` or `<Routes/>`; use [try_generate_route_list] if you'd rather handle that case yourself.
+pub async fn generate_route_list<IV>(app_fn: impl FnOnce(Scope) -> IV + 'static) -> Vec<String>
+where
+    IV: IntoView + 'static,
+{
+    generate_route_listing(app_fn)
+        .await
+        .into_iter()
+        .map(|listing| listing.path)
+        .collect()
+}
+
+/// Generates a list of all routes defined in Leptos's Router in your app, like [generate_route_list],
+/// along with the response headers each route declares with the `headers` prop of its `<Route/>`.
+/// Pass the result to [LeptosRoutes::leptos_routes_with_headers] to send those headers.
+pub async fn generate_route_listing<IV>(
+    app_fn: impl FnOnce(Scope) -> IV + 'static,
+) -> Vec<RouteListing>
+where
+    IV: IntoView + 'static,
+{
+    let routes = route_listing(app_fn).await;
+    if routes.is_empty() {
+        leptos::warn!(
+            "generate_route_list() did not find any routes in your app, so only `/` will be \
+             served. Did you forget to add a <Router/> with <Routes/> to your app?"
+        );
+        vec![RouteListing {
+            path: "/".to_string(),
+            methods: vec![leptos_router::Method::Get],
+            ..Default::default()
+        }]
+    } else {
+        routes
+    }
+}
+
+/// Generates a list of all routes defined in Leptos's Router in your app, like [generate_route_list],
+/// but returns `None` instead of falling back to `/` if no routes are found.
+pub async fn try_generate_route_list<IV>(
+    app_fn: impl FnOnce(Scope) -> IV + 'static,
+) -> Option<Vec<String>>
+where
+    IV: IntoView + 'static,
+{
+    let routes = route_listing(app_fn).await;
+    if routes.is_empty() {
+        None
+    } else {
+        Some(routes.into_iter().map(|listing| listing.path).collect())
+    }
+}
+
+/// How [generate_route_list_with_config] treats a trailing slash at the end of a route's path. The
+/// app's router matches `/posts/1` and `/posts/1/` alike, but Axum's doesn't, so only the one that's
+/// registered is served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingSlash {
+    /// Paths are registered as the app declares them, and the other variant gets a `404 Not Found`.
+    Strict,
+    /// Paths end with a slash, like `/posts/:id/`, and requests without it are redirected there.
+    RedirectToTrailing,
+    /// Paths don't end with a slash, like `/posts/:id`, and requests with one are redirected there.
+    RedirectToNonTrailing,
+}
+
+impl Default for TrailingSlash {
+    fn default() -> Self {
+        Self::Strict
+    }
+}
+
+/// Options for [generate_route_list_with_config].
+#[derive(Debug, Clone, Default)]
+pub struct RouteListConfig {
+    /// Whether routes are served with a trailing slash, without one, or as declared. Defaults to
+    /// [TrailingSlash::Strict].
+    pub trailing_slash: TrailingSlash,
+}
+
+/// Generates a list of all routes defined in Leptos's Router in your app, like [generate_route_list],
+/// with the trailing slash of each path added or removed as `config` says. Pass the paths to
+/// [LeptosRoutes::leptos_routes], and to [trailing_slash_redirects] for the redirects from the other
+/// variant of each path:
+/// ```ignore
+/// let config = RouteListConfig { trailing_slash: TrailingSlash::RedirectToNonTrailing };
+/// let routes = generate_route_list_with_config(|cx| view! { cx, <App/> }, config).await;
+/// let app = Router::new()
+///     .leptos_routes(leptos_options, routes.clone(), |cx| view! { cx, <App/> })
+///     .merge(leptos_axum::trailing_slash_redirects(&routes));
+/// ```
+/// The root `/` and paths that end with a wildcard, like `/files/*path`, are left as they are. Paths that
+/// only differ in their trailing slash are only listed once.
+pub async fn generate_route_list_with_config<IV>(
+    app_fn: impl FnOnce(Scope) -> IV + 'static,
+    config: RouteListConfig,
+) -> Vec<String>
+where
+    IV: IntoView + 'static,
+{
+    let mut paths: Vec<String> = Vec::new();
+    for path in generate_route_list(app_fn).await {
+        let path = match config.trailing_slash {
+            TrailingSlash::Strict => path,
+            _ if path == "/" || is_wildcard_path(&path) => path,
+            TrailingSlash::RedirectToTrailing if path.ends_with('/') => path,
+            TrailingSlash::RedirectToTrailing => format!("{path}/"),
+            TrailingSlash::RedirectToNonTrailing => path.trim_end_matches('/').to_string(),
+        };
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// Returns a router that redirects the other variant of each of `paths`, with or without a trailing
+/// slash, to the path as it's given, keeping the query string. Merge it into the router that serves
+/// `paths`; see [generate_route_list_with_config]. The redirects are `308 Permanent Redirect`s, so a
+/// form that posts to the other variant is posted again to the path.
+///
+/// The root `/` and paths that end with a wildcard aren't redirected, and neither is a variant that's
+/// one of `paths` itself.
+pub fn trailing_slash_redirects(paths: &[String]) -> axum::Router {
+    let mut router = axum::Router::new();
+    for path in paths {
+        if path == "/" || is_wildcard_path(path) {
+            continue;
+        }
+        let from = match path.strip_suffix('/') {
+            Some(path) => path.to_string(),
+            None => format!("{path}/"),
+        };
+        if paths.contains(&from) {
+            continue;
+        }
+        let add_slash = !from.ends_with('/');
+        router = router.route(
+            &from,
+            any(move |uri: Uri| async move {
+                let path = match add_slash {
+                    true => format!("{}/", uri.path()),
+                    false => uri.path().trim_end_matches('/').to_string(),
+                };
+                let location = match uri.query() {
+                    Some(query) => format!("{path}?{query}"),
+                    None => path,
+                };
+                let mut res = Response::new(Body::empty());
+                *res.status_mut() = StatusCode::PERMANENT_REDIRECT;
+                if let Ok(location) = HeaderValue::from_str(&location) {
+                    res.headers_mut().insert(header::LOCATION, location);
+                }
+                res
+            }),
+        );
+    }
+    router
+}
+
+// Whether the path ends with a wildcard segment like `*path`, which can't be followed by a slash
+fn is_wildcard_path(path: &str) -> bool {
+    path.rsplit('/')
+        .next()
+        .map_or(false, |segment| segment.starts_with('*'))
+}
+
+async fn route_listing<IV>(app_fn: impl FnOnce(Scope) -> IV + 'static) -> Vec<RouteListing>
+where
+    IV: IntoView + 'static,
+{
+    #[derive(Default, Clone, Debug)]
+    pub struct Routes(pub Arc<RwLock<Vec<RouteListing>>>);
+
+    let routes = Routes::default();
+    let routes_inner = routes.clone();
+
+    let local = LocalSet::new();
+    // Run the local task set.
+
+    local
+        .run_until(async move {
+            tokio::task::spawn_local(async move {
+                let routes = leptos_router::generate_route_listing_inner(app_fn);
+                let mut writable = routes_inner.0.write().await;
+                *writable = routes;
+            })
+            .await
+            .unwrap();
+        })
+        .await;
+
+    let routes = routes.0.read().await.to_owned();
+    let mut listings: Vec<RouteListing> = routes
+        .iter()
+        .map(|listing| RouteListing {
+            path: axum_path(&listing.path),
+            ..listing.clone()
+        })
+        .collect();
+    // a splat also matches its parent path, which an Axum wildcard doesn't, so the parent is added
+    // as well, unless the app has a route for it
+    for listing in routes {
+        if let Some(parent) = splat_parent(&listing.path) {
+            if !listings.iter().any(|existing| existing.path == parent) {
+                listings.push(RouteListing {
+                    path: parent,
+                    ..listing
+                });
+            }
+        }
+    }
+    listings
+}
+
+// A path from Leptos's router in Axum's syntax. `:param` segments are the same in both, but the root is
+// `""` in Leptos and `/` in Axum, and Axum needs a name for the wildcard that a splat (`*` or `*name`)
+// becomes, so an unnamed splat is named `any`.
+fn axum_path(path: &str) -> String {
+    match path.split_once("/*") {
+        Some((parent, "")) => format!("{parent}/*any"),
+        Some(_) => path.to_string(),
+        None if path.is_empty() => "/".to_string(),
+        None => path.to_string(),
+    }
+}
+
+// The path that a splat's route matches with an empty splat, like `/docs` for `/docs/*path`
+fn splat_parent(path: &str) -> Option<String> {
+    let (parent, _) = path.split_once("/*")?;
+    Some(match parent {
+        "" => "/".to_string(),
+        parent => parent.to_string(),
+    })
+}
+
+// Marks the responses of the routes added by [LeptosRoutes], so [validate_integration] can tell
+// them apart from the router's fallback
+#[derive(Clone, Copy)]
+struct MountedRoute;
+
+/// A problem with how an app is wired up to its Axum router, found by [validate_integration].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrationWarning {
+    /// A route in the app's `<Routes/>` isn't rendered by a handler added with [LeptosRoutes],
+    /// usually because its path wasn't passed to [LeptosRoutes::leptos_routes].
+    UnmountedRoute(String),
+    /// The app creates an action for a server function that hasn't been registered, so calling
+    /// it from the client fails. Call `register()` on it before starting the server.
+    UnregisteredServerFn(String),
+}
+
+impl std::fmt::Display for IntegrationWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrationWarning::UnmountedRoute(path) => write!(
+                f,
+                "the route {path} isn't rendered by Leptos; did you forget to pass it to \
+                 leptos_routes()?"
+            ),
+            IntegrationWarning::UnregisteredServerFn(path) => write!(
+                f,
+                "the server function at {path} is used by the app but isn't registered; did you \
+                 forget to call register() on it?"
+            ),
+        }
+    }
+}
+
+/// Checks that an app is fully wired up to its `router`, catching mistakes like a server function
+/// that was never registered when the server starts rather than when the client calls it. Each
+/// problem is logged as a warning, and they're all returned.
+///
+/// It checks that:
+/// - every route that [generate_route_list] finds in the app is rendered by a handler added with
+///   [LeptosRoutes::leptos_routes] or [LeptosRoutes::leptos_routes_with_headers]. Each route is
+///   requested from the router once, with `GET` and with a placeholder in place of each of its
+///   params, so the routes' components run as they would for a real request.
+/// - every server function the app creates an action for (with
+///   [create_server_action](leptos::create_server_action) or
+///   [create_server_multi_action](leptos::create_server_multi_action)) while rendering those routes
+///   is registered in the global [ServerFnRegistry](leptos::ServerFnRegistry).
+///
+/// Server functions that are only called directly, or only from components that aren't rendered for
+/// the placeholder params, aren't checked.
+///
+/// This is a development aid: it only runs in debug builds, and returns no warnings in release builds.
+/// ```rust,ignore
+/// let routes = generate_route_list(|cx| view! { cx, <App/> }).await;
+/// let app = Router::new()
+///     .route("/api/*fn_name", post(leptos_axum::handle_server_fns))
+///     .leptos_routes(leptos_options.clone(), routes, |cx| view! { cx, <App/> });
+///
+/// leptos_axum::validate_integration(|cx| view! { cx, <App/> }, &app).await;
+/// ```
+pub async fn validate_integration<IV>(
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + 'static,
+    router: &axum::Router,
+) -> Vec<IntegrationWarning>
+where
+    IV: IntoView + 'static,
+{
+    use tower::ServiceExt;
+
+    if !cfg!(debug_assertions) {
+        return Vec::new();
+    }
+
+    let mut warnings = Vec::new();
+    for path in try_generate_route_list(app_fn).await.unwrap_or_default() {
+        let uri = path
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with(':') || segment.starts_with('*') {
+                    "leptos-placeholder"
+                } else {
+                    segment
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let req = match Request::get(&uri).body(Body::empty()) {
+            Ok(req) => req,
+            Err(_) => {
+                warnings.push(IntegrationWarning::UnmountedRoute(path));
+                continue;
+            }
+        };
+        let res = match router.clone().oneshot(req).await {
+            Ok(res) => res,
+            Err(never) => match never {},
+        };
+        let mounted = res.extensions().get::<MountedRoute>().is_some();
+        // the whole app has to render for its actions to be created
+        _ = body::to_bytes(res.into_body()).await;
+        if !mounted {
+            warnings.push(IntegrationWarning::UnmountedRoute(path));
+        }
     }
+
+    let mut server_fns = leptos::referenced_server_fns();
+    server_fns.sort_unstable();
+    warnings.extend(
+        server_fns
+            .into_iter()
+            .filter(|path| server_fn_by_path(path).is_none())
+            .map(|path| IntegrationWarning::UnregisteredServerFn(path.to_string())),
+    );
+
+    for warning in &warnings {
+        leptos::warn!("{warning}");
+    }
+    warnings
 }
 
 /// This trait allows one to pass a list of routes and a render function to Axum's router, letting us avoid
 /// having to use wildcards or manually define all routes in multiple places.
 pub trait LeptosRoutes {
+    /// Adds a `GET` route for each of the `paths`, rendering the app with [render_app_to_stream].
+    /// The params in each route's path are provided to the app as [PathParams], and the path itself
+    /// as [MatchedRoute].
     fn leptos_routes<IV>(
         self,
         options: LeptosOptions,
@@ -634,6 +5477,65 @@ pub trait LeptosRoutes {
     ) -> Self
     where
         IV: IntoView + 'static;
+
+    /// Like [leptos_routes](LeptosRoutes::leptos_routes), but skips the paths in `excluded`, so you can
+    /// handle them yourself, like a path that upgrades to a WebSocket. Axum panics if two handlers are added
+    /// for the same method on the same path, so an excluded path needs a route of its own, which can be
+    /// added before or after this:
+    /// ```ignore
+    /// let app = Router::new()
+    ///     .route("/ws", get(ws_handler))
+    ///     .leptos_routes_with_exclusions(leptos_options, routes, &["/ws"], |cx| view! { cx, <App/> });
+    /// ```
+    /// The excluded paths are compared with the listed ones exactly, like `/posts/:id`.
+    fn leptos_routes_with_exclusions<IV>(
+        self,
+        options: LeptosOptions,
+        paths: Vec<String>,
+        excluded: &[&str],
+        app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    ) -> Self
+    where
+        IV: IntoView + 'static;
+
+    /// Like [leptos_routes](LeptosRoutes::leptos_routes), but takes a list of routes from
+    /// [generate_route_listing] and adds the headers declared for each route to its responses.
+    /// A header the app sets through [ResponseOptions] replaces the declared one.
+    ///
+    /// ## Panics
+    /// Panics if a declared header has an invalid name or value.
+    fn leptos_routes_with_headers<IV>(
+        self,
+        options: LeptosOptions,
+        routes: Vec<RouteListing>,
+        app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    ) -> Self
+    where
+        IV: IntoView + 'static;
+
+    /// Like [leptos_routes_with_headers](LeptosRoutes::leptos_routes_with_headers), but each route
+    /// responds to the methods declared with the `methods` prop of its `<Route/>`, rather than only
+    /// to `GET`. This lets a page with an `<ActionForm/>` that posts back to it render the response
+    /// to the `POST`, instead of getting a `405 Method Not Allowed`:
+    /// ```ignore
+    /// // in the app
+    /// <Route path="contact" view=|cx| view! { cx, <Contact/> } methods=vec![Method::Get, Method::Post]/>
+    ///
+    /// // in main
+    /// let routes = generate_route_listing(|cx| view! { cx, <App/> }).await;
+    /// let app = Router::new().leptos_routes_with_methods(leptos_options, routes, |cx| view! { cx, <App/> });
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if a declared header has an invalid name or value.
+    fn leptos_routes_with_methods<IV>(
+        self,
+        options: LeptosOptions,
+        routes: Vec<RouteListing>,
+        app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    ) -> Self
+    where
+        IV: IntoView + 'static;
 }
 /// The default implementation of `LeptosRoutes` which takes in a list of paths, and dispatches GET requests
 /// to those paths to Leptos's renderer.
@@ -649,11 +5551,138 @@ impl LeptosRoutes for axum::Router {
     {
         let mut router = self;
         for path in paths.iter() {
+            let handler = render_app_to_stream(options.clone(), app_fn.clone());
+            let route = MatchedRoute(path.clone());
             router = router.route(
                 path,
-                get(render_app_to_stream(options.clone(), app_fn.clone())),
+                get(move |mut req: Request<Body>| {
+                    req.extensions_mut().insert(route.clone());
+                    let res = handler(req);
+                    async move {
+                        let mut res = res.await;
+                        res.extensions_mut().insert(MountedRoute);
+                        res
+                    }
+                }),
+            );
+        }
+        router
+    }
+
+    fn leptos_routes_with_exclusions<IV>(
+        self,
+        options: LeptosOptions,
+        paths: Vec<String>,
+        excluded: &[&str],
+        app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    ) -> Self
+    where
+        IV: IntoView + 'static,
+    {
+        let paths = paths
+            .into_iter()
+            .filter(|path| !excluded.contains(&path.as_str()))
+            .collect();
+        self.leptos_routes(options, paths, app_fn)
+    }
+
+    fn leptos_routes_with_headers<IV>(
+        self,
+        options: LeptosOptions,
+        routes: Vec<RouteListing>,
+        app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    ) -> Self
+    where
+        IV: IntoView + 'static,
+    {
+        let mut router = self;
+        for listing in routes.iter() {
+            router = route_listing_with_methods(
+                router,
+                &options,
+                listing,
+                MethodFilter::GET,
+                app_fn.clone(),
             );
         }
         router
     }
+
+    fn leptos_routes_with_methods<IV>(
+        self,
+        options: LeptosOptions,
+        routes: Vec<RouteListing>,
+        app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+    ) -> Self
+    where
+        IV: IntoView + 'static,
+    {
+        let mut router = self;
+        for listing in routes.iter() {
+            let methods = listing
+                .methods
+                .iter()
+                .map(|method| match method {
+                    leptos_router::Method::Get => MethodFilter::GET,
+                    leptos_router::Method::Post => MethodFilter::POST,
+                    leptos_router::Method::Put => MethodFilter::PUT,
+                    leptos_router::Method::Patch => MethodFilter::PATCH,
+                    leptos_router::Method::Delete => MethodFilter::DELETE,
+                })
+                .reduce(|methods, method| methods | method)
+                .unwrap_or(MethodFilter::GET);
+            router = route_listing_with_methods(router, &options, listing, methods, app_fn.clone());
+        }
+        router
+    }
+}
+
+// Adds a route that renders the app for the listing's path, responding to `methods` and sending the
+// headers declared for it
+fn route_listing_with_methods<IV>(
+    router: axum::Router,
+    options: &LeptosOptions,
+    listing: &RouteListing,
+    methods: MethodFilter,
+    app_fn: impl Fn(leptos::Scope) -> IV + Clone + Send + 'static,
+) -> axum::Router
+where
+    IV: IntoView + 'static,
+{
+    let headers: Vec<(HeaderName, HeaderValue)> = listing
+        .headers
+        .iter()
+        .map(|(name, value)| {
+            let name = HeaderName::from_bytes(name.as_bytes()).unwrap_or_else(|_| {
+                panic!("invalid header name {name:?} declared for {}", listing.path)
+            });
+            let value = HeaderValue::from_str(value).unwrap_or_else(|_| {
+                panic!(
+                    "invalid header value {value:?} declared for {}",
+                    listing.path
+                )
+            });
+            (name, value)
+        })
+        .collect();
+    let handler = render_app_to_stream(options.clone(), app_fn);
+    let route = MatchedRoute(listing.path.clone());
+    router.route(
+        &listing.path,
+        on(methods, move |mut req: Request<Body>| {
+            req.extensions_mut().insert(route.clone());
+            let res = handler(req);
+            async move {
+                let mut res = res.await;
+                // headers set by the app through ResponseOptions take precedence
+                for (name, value) in headers {
+                    if !res.headers().contains_key(&name) {
+                        res.headers_mut().insert(name, value);
+                    }
+                }
+                res.extensions_mut().insert(MountedRoute);
+                res
+            }
+        }),
+    )
 }
@@ -0,0 +1,119 @@
+use axum::body::Body;
+use http::Request;
+use leptos::*;
+use leptos_config::LeptosOptions;
+use std::sync::{Arc, Mutex};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// Collects the messages of every `leptos_axum::access_log` event.
+#[derive(Clone, Default)]
+struct AccessLogCollector(Arc<Mutex<Vec<String>>>);
+
+struct MessageVisitor<'a>(&'a mut String);
+
+impl Visit for MessageVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            *self.0 = format!("{value:?}");
+        }
+    }
+}
+
+impl Subscriber for AccessLogCollector {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "leptos_axum::access_log"
+    }
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+    fn event(&self, event: &Event<'_>) {
+        let mut message = String::new();
+        event.record(&mut MessageVisitor(&mut message));
+        self.0.lock().unwrap().push(message);
+    }
+    fn enter(&self, _span: &span::Id) {}
+    fn exit(&self, _span: &span::Id) {}
+}
+
+#[component]
+fn App(cx: Scope) -> impl IntoView {
+    view! { cx, <p>"Hello, world!"</p> }
+}
+
+async fn render(access_log: bool) -> (usize, Vec<String>) {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .access_log(access_log)
+        .build();
+    let collector = AccessLogCollector::default();
+    let _guard = tracing::subscriber::set_default(collector.clone());
+
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <App/> });
+    let req = Request::builder()
+        .uri("/hello")
+        .body(Body::empty())
+        .unwrap();
+    let res = handler(req).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+
+    let lines = collector.0.lock().unwrap().clone();
+    (body.len(), lines)
+}
+
+#[tokio::test]
+async fn access_log_is_emitted_when_enabled() {
+    let (bytes, lines) = render(true).await;
+
+    assert_eq!(lines.len(), 1);
+    let line: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(line["path"], "/hello");
+    assert_eq!(line["status"], 200);
+    assert_eq!(line["bytes"], bytes);
+    assert!(line["duration_ms"].is_u64());
+    assert!(line.get("route").is_some());
+}
+
+#[tokio::test]
+async fn access_log_is_disabled_by_default() {
+    let (_, lines) = render(false).await;
+
+    assert!(lines.is_empty());
+}
+
+#[tokio::test]
+async fn response_observer_is_told_about_the_response() {
+    let observed = Arc::new(Mutex::new(Vec::new()));
+    let observer = leptos_axum::ResponseObserver::new({
+        let observed = Arc::clone(&observed);
+        move |path, status, bytes, duration| {
+            observed
+                .lock()
+                .unwrap()
+                .push((path.to_string(), status, bytes, duration));
+        }
+    });
+    let options = LeptosOptions::builder().output_name("test_app").build();
+
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <App/> });
+    let mut req = Request::builder()
+        .uri("/hello")
+        .body(Body::empty())
+        .unwrap();
+    req.extensions_mut().insert(observer);
+    let res = handler(req).await;
+    assert!(observed.lock().unwrap().is_empty());
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+
+    let observed = observed.lock().unwrap();
+    assert_eq!(observed.len(), 1);
+    let (path, status, bytes, duration) = &observed[0];
+    assert_eq!(path, "/hello");
+    assert_eq!(*status, http::StatusCode::OK);
+    assert_eq!(*bytes, body.len());
+    assert!(*duration > std::time::Duration::ZERO);
+}
@@ -0,0 +1,61 @@
+use axum::{body::Body, extract::ConnectInfo, http::Request};
+use leptos::*;
+use std::net::SocketAddr;
+
+#[component]
+fn App(cx: Scope) -> impl IntoView {
+    view! { cx, <p>"Hello, world!"</p> }
+}
+
+async fn render(req: Request<Body>) -> String {
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <App/> });
+    let res = handler(req).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+// A request with the given `Host` header from the given peer, if Axum knows it
+fn request(host: &str, peer: Option<SocketAddr>, forwarded_for: Option<&str>) -> Request<Body> {
+    let mut req = Request::builder().uri("/").header(http::header::HOST, host);
+    if let Some(forwarded_for) = forwarded_for {
+        req = req.header("X-Forwarded-For", forwarded_for);
+    }
+    let mut req = req.body(Body::empty()).unwrap();
+    if let Some(peer) = peer {
+        req.extensions_mut().insert(ConnectInfo(peer));
+    }
+    req
+}
+
+// This is the only test in this file, because it sets an env var for the whole process.
+#[tokio::test]
+async fn live_reload_script_is_only_served_to_local_requests() {
+    std::env::set_var("LEPTOS_WATCH", "ON");
+
+    let loopback_v4 = Some(SocketAddr::from(([127, 0, 0, 1], 50000)));
+    let loopback_v6 = Some(SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 1], 50000)));
+    let external_peer = Some(SocketAddr::from(([203, 0, 113, 7], 50000)));
+
+    let local = render(request("localhost:3000", loopback_v4, None)).await;
+    let local_v6 = render(request("[::1]:3000", loopback_v6, None)).await;
+    let external = render(request("example.com", external_peer, None)).await;
+    // the Host header is up to the client
+    let spoofed_host = render(request("localhost:3000", external_peer, None)).await;
+    let unknown_peer = render(request("localhost:3000", None, None)).await;
+    let proxied = render(request(
+        "localhost:3000",
+        loopback_v4,
+        Some("203.0.113.7, 127.0.0.1"),
+    ))
+    .await;
+
+    std::env::remove_var("LEPTOS_WATCH");
+
+    assert!(local.contains("/live_reload"));
+    assert!(local_v6.contains("/live_reload"));
+    assert!(!external.contains("/live_reload"));
+    assert!(!spoofed_host.contains("/live_reload"));
+    assert!(!unknown_peer.contains("/live_reload"));
+    assert!(!proxied.contains("/live_reload"));
+}
@@ -0,0 +1,64 @@
+use http::{header, HeaderMap};
+use leptos::*;
+use leptos_axum::ContentType;
+
+const ALL: [ContentType; 4] = [
+    ContentType::Json,
+    ContentType::UrlEncoded,
+    ContentType::Cbor,
+    ContentType::Ndjson,
+];
+
+#[test]
+fn media_types_round_trip() {
+    for content_type in ALL {
+        assert_eq!(content_type.to_string().parse(), Ok(content_type));
+        assert_eq!(content_type.as_str(), content_type.to_string());
+    }
+    assert_eq!("application/json".parse(), Ok(ContentType::Json));
+    assert_eq!(
+        "application/x-www-form-urlencoded".parse(),
+        Ok(ContentType::UrlEncoded)
+    );
+}
+
+#[test]
+fn other_media_types_are_not_parsed() {
+    for media_type in [
+        "text/html",
+        "application/octet-stream",
+        "application/json; charset=utf-8",
+        "application/json, text/plain",
+        "",
+    ] {
+        assert_eq!(media_type.parse::<ContentType>(), Err(()));
+    }
+}
+
+#[test]
+fn payloads_map_to_their_content_type() {
+    let cases = [
+        (Payload::Json("{}".to_string()), ContentType::Json),
+        (Payload::Url("a=1".to_string()), ContentType::UrlEncoded),
+        (Payload::Binary(vec![0xa0]), ContentType::Cbor),
+    ];
+    for (payload, content_type) in cases {
+        assert_eq!(ContentType::for_payload(&payload), Some(content_type));
+    }
+    let file = Payload::File(FileDownload::new("report.pdf"));
+    assert_eq!(ContentType::for_payload(&file), None);
+}
+
+#[test]
+fn accept_header_maps_to_content_type() {
+    for content_type in ALL {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, content_type.as_str().parse().unwrap());
+        assert_eq!(ContentType::from_accept(&headers), Some(content_type));
+    }
+
+    let mut headers = HeaderMap::new();
+    assert_eq!(ContentType::from_accept(&headers), None);
+    headers.insert(header::ACCEPT, "text/html".parse().unwrap());
+    assert_eq!(ContentType::from_accept(&headers), None);
+}
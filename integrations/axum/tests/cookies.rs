@@ -0,0 +1,74 @@
+use axum::http::{header, HeaderMap, HeaderValue};
+use leptos_axum::{Cookie, Key, RequestParts, ResponseOptions};
+
+fn request_with_cookie(cookie: &str) -> RequestParts {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::COOKIE, HeaderValue::from_str(cookie).unwrap());
+    RequestParts {
+        version: Default::default(),
+        method: Default::default(),
+        uri: Default::default(),
+        headers,
+        body: Default::default(),
+        extensions: Default::default(),
+    }
+}
+
+async fn signed_set_cookie(key: &Key, name: &'static str, value: &'static str) -> String {
+    let res_options = ResponseOptions::default();
+    res_options
+        .set_signed_cookie(key, Cookie::new(name, value))
+        .await;
+    let res_parts = res_options.0.read().await;
+    let set_cookie = res_parts.headers.get(header::SET_COOKIE).unwrap();
+    set_cookie.to_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn valid_signed_cookie_is_returned() {
+    let key = Key::generate();
+    let set_cookie = signed_set_cookie(&key, "session", "user-1").await;
+
+    let req = request_with_cookie(&set_cookie);
+    let cookie = req.signed_cookie(&key, "session").unwrap();
+    assert_eq!(cookie.value(), "user-1");
+}
+
+#[tokio::test]
+async fn tampered_signed_cookie_is_rejected() {
+    let key = Key::generate();
+    let set_cookie = signed_set_cookie(&key, "session", "user-1").await;
+    let tampered = set_cookie.replace("user-1", "user-2");
+
+    let req = request_with_cookie(&tampered);
+    assert!(req.signed_cookie(&key, "session").is_none());
+
+    // a cookie signed with another key is rejected too
+    let req = request_with_cookie(&set_cookie);
+    assert!(req.signed_cookie(&Key::generate(), "session").is_none());
+}
+
+#[tokio::test]
+async fn missing_signed_cookie_is_none() {
+    let key = Key::generate();
+    let req = request_with_cookie("other=value");
+    assert!(req.signed_cookie(&key, "session").is_none());
+}
+
+#[test]
+fn cookie_jar_reads_request_cookies() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::COOKIE,
+        HeaderValue::from_static("theme=light; lang=en"),
+    );
+    let jar = leptos_axum::CookieJar::from_headers(&headers);
+
+    assert_eq!(jar.get("theme").unwrap().value(), "light");
+    assert_eq!(jar.get("lang").unwrap().value(), "en");
+    assert!(jar.get("session").is_none());
+
+    jar.set(Cookie::new("theme", "dark"));
+    assert_eq!(jar.get("theme").unwrap().value(), "dark");
+    assert_eq!(jar.cookies().len(), 2);
+}
@@ -0,0 +1,149 @@
+use axum::{
+    body::Body,
+    extract::Path,
+    response::{IntoResponse, Response},
+};
+use http::{header, HeaderMap, Method, Request, StatusCode};
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use std::{future::Future, pin::Pin, sync::Arc};
+
+// The `#[server]` macro only generates the server half when the calling crate has an `ssr`
+// feature, so the test server function implements `ServerFn` by hand.
+#[derive(Clone, Serialize, Deserialize)]
+struct Greet {
+    name: String,
+}
+
+impl ServerFn for Greet {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "cors_greet"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { Ok(format!("Hello, {}!", self.name)) })
+    }
+}
+
+fn cors_options() -> LeptosOptions {
+    LeptosOptions::builder()
+        .output_name("test_app")
+        .server_fn_cors_origins(vec!["https://app.example.com".to_string()])
+        .build()
+}
+
+async fn call(options: LeptosOptions, method: Method, origin: &str) -> Response {
+    _ = Greet::register();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::ACCEPT, "application/json".parse().unwrap());
+    headers.insert(header::ORIGIN, origin.parse().unwrap());
+    if method == Method::OPTIONS {
+        headers.insert(
+            header::ACCESS_CONTROL_REQUEST_METHOD,
+            "POST".parse().unwrap(),
+        );
+    }
+    let mut req = Request::builder()
+        .method(method)
+        .uri("/api/cors_greet")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from("name=Ada"))
+        .unwrap();
+    req.headers_mut().extend(headers.clone());
+    req.extensions_mut().insert(Arc::new(options));
+
+    leptos_axum::handle_server_fns(Path("cors_greet".to_string()), headers, req)
+        .await
+        .into_response()
+}
+
+async fn body_string(res: Response) -> String {
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn preflight_from_allowed_origin_is_answered() {
+    let res = call(cors_options(), Method::OPTIONS, "https://app.example.com").await;
+
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    let headers = res.headers();
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://app.example.com"
+    );
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "POST");
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+        "Content-Type, Accept"
+    );
+    assert_eq!(headers[header::VARY], "Origin");
+}
+
+#[tokio::test]
+async fn cross_origin_call_gets_cors_headers() {
+    let res = call(cors_options(), Method::POST, "https://app.example.com").await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://app.example.com"
+    );
+    assert!(res
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_METHODS)
+        .is_none());
+    assert_eq!(body_string(res).await, "\"Hello, Ada!\"");
+}
+
+#[tokio::test]
+async fn other_origins_get_no_cors_headers() {
+    let res = call(cors_options(), Method::OPTIONS, "https://evil.example.com").await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    assert!(res
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+
+    let res = call(cors_options(), Method::POST, "https://evil.example.com").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+}
+
+#[tokio::test]
+async fn wildcard_allows_every_origin() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .server_fn_cors_origins(vec!["*".to_string()])
+        .build();
+    let res = call(options, Method::POST, "https://anywhere.example").await;
+
+    assert_eq!(res.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    assert!(res.headers().get(header::VARY).is_none());
+}
+
+#[tokio::test]
+async fn cors_is_off_by_default() {
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let res = call(options, Method::POST, "https://app.example.com").await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+}
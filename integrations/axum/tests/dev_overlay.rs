@@ -0,0 +1,147 @@
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Path},
+    response::{IntoResponse, Response},
+};
+use http::{header, HeaderMap, Request, StatusCode};
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use std::{future::Future, net::SocketAddr, pin::Pin, sync::Arc};
+
+#[component]
+fn App(cx: Scope) -> impl IntoView {
+    view! { cx, <p>"Hello, world!"</p> }
+}
+
+fn options(env: Env) -> LeptosOptions {
+    LeptosOptions::builder()
+        .output_name("test_app")
+        .env(env)
+        .build()
+}
+
+// a request from this machine, as Axum sees it when the app is served with connect info
+fn request(host: &str) -> Request<Body> {
+    let mut req = Request::builder()
+        .uri("/")
+        .header(header::HOST, host)
+        .body(Body::empty())
+        .unwrap();
+    req.extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 50000))));
+    req
+}
+
+async fn body_string(res: Response) -> String {
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+async fn render(env: Env, host: &str) -> String {
+    let handler = leptos_axum::render_app_to_stream(options(env), |cx| view! { cx, <App/> });
+    body_string(handler(request(host)).await.into_response()).await
+}
+
+#[tokio::test]
+async fn overlay_script_is_only_served_in_dev() {
+    let dev = render(Env::DEV, "localhost:3000").await;
+    let prod = render(Env::PROD, "localhost:3000").await;
+    let external = render(Env::DEV, "example.com").await;
+
+    assert!(dev.contains("window.__leptos_dev_overlay ="));
+    assert!(!prod.contains("__leptos_dev_overlay"));
+    assert!(!external.contains("__leptos_dev_overlay"));
+}
+
+#[component]
+fn BrokenApp(cx: Scope) -> impl IntoView {
+    let count: i32 = "not a number".parse().expect("</script> failed to render");
+    view! { cx, <p>{count}</p> }
+}
+
+#[tokio::test]
+async fn fallback_page_shows_the_render_error_in_dev() {
+    let handler = |env| {
+        leptos_axum::render_app_to_stream_with_fallback(
+            options(env),
+            |_cx| {},
+            |cx| view! { cx, <BrokenApp/> },
+            |cx| view! { cx, <p>"Something went wrong."</p> },
+        )
+    };
+
+    let dev = body_string(
+        handler(Env::DEV)(request("localhost:3000"))
+            .await
+            .into_response(),
+    )
+    .await;
+    assert!(dev.contains("Something went wrong."));
+    // the message can't end the script it's in
+    assert!(dev.contains(r#""error":"<\/script> failed to render"#));
+
+    let prod = body_string(
+        handler(Env::PROD)(request("localhost:3000"))
+            .await
+            .into_response(),
+    )
+    .await;
+    assert!(prod.contains("Something went wrong."));
+    assert!(!prod.contains("failed to render"));
+}
+
+// The `#[server]` macro only generates the server half when the calling crate has an `ssr`
+// feature, so the test server function implements `ServerFn` by hand.
+#[derive(Clone, Serialize, Deserialize)]
+struct Explode {}
+
+impl ServerFn for Explode {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "dev_overlay_explode"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { Err(ServerFnError::ServerError("boom".to_string())) })
+    }
+}
+
+async fn call(env: Env) -> Response {
+    _ = Explode::register();
+
+    let mut req = Request::builder()
+        .method("POST")
+        .uri("/api/dev_overlay_explode")
+        .header(header::HOST, "localhost:3000")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::empty())
+        .unwrap();
+    req.extensions_mut().insert(Arc::new(options(env)));
+    req.extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 50000))));
+    let headers = req.headers().clone();
+
+    leptos_axum::handle_server_fns(Path("dev_overlay_explode".to_string()), headers, req)
+        .await
+        .into_response()
+}
+
+#[tokio::test]
+async fn server_fn_errors_are_only_marked_in_dev() {
+    let dev = call(Env::DEV).await;
+    assert_eq!(dev.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(dev.headers().get("x-leptos-error").unwrap(), "ServerError");
+
+    let prod = call(Env::PROD).await;
+    assert_eq!(prod.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(prod.headers().get("x-leptos-error").is_none());
+}
@@ -0,0 +1,152 @@
+use axum::{
+    body::Body,
+    extract::Path,
+    response::{IntoResponse, Response},
+};
+use http::{header, HeaderMap, Request, StatusCode};
+use leptos::*;
+use leptos_axum::{ErrorFormat, ErrorInfo, ErrorRenderer};
+use serde::{Deserialize, Serialize};
+use std::{future::Future, pin::Pin};
+
+// The `#[server]` macro only generates the server half when the calling crate has an `ssr`
+// feature, so the test server function implements `ServerFn` by hand.
+#[derive(Clone, Serialize, Deserialize)]
+struct LoadReport {}
+
+impl ServerFn for LoadReport {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "error_renderer_load_report"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { Err(ServerFnError::ServerError("the report is missing".into())) })
+    }
+}
+
+fn renderer() -> ErrorRenderer {
+    ErrorRenderer::new(|format, error: &ErrorInfo| match format {
+        ErrorFormat::Json => serde_json::json!({
+            "status": error.status.as_u16(),
+            "kind": error.kind,
+            "message": error.message,
+        })
+        .to_string(),
+        ErrorFormat::Html => format!("<h1>{}</h1><p>{}</p>", error.status, error.kind),
+    })
+}
+
+async fn call(accept: &str, renderer: Option<ErrorRenderer>) -> Response {
+    _ = LoadReport::register();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::ACCEPT, accept.parse().unwrap());
+    let mut req = Request::builder()
+        .method("POST")
+        .uri("/api/error_renderer_load_report")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::empty())
+        .unwrap();
+    if let Some(renderer) = renderer {
+        req.extensions_mut().insert(renderer);
+    }
+
+    leptos_axum::handle_server_fns(Path(LoadReport::url().to_string()), headers, req)
+        .await
+        .into_response()
+}
+
+async fn body_string(res: Response) -> String {
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn server_fn_errors_are_rendered_as_json_for_api_clients() {
+    let res = call("application/json", Some(renderer())).await;
+
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "application/json");
+    let body: serde_json::Value = serde_json::from_str(&body_string(res).await).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "status": 500,
+            "kind": "ServerError",
+            "message": "error running server function: the report is missing",
+        })
+    );
+}
+
+#[tokio::test]
+async fn server_fn_errors_are_rendered_as_html_for_browsers() {
+    let res = call(
+        "text/html,application/xhtml+xml,*/*;q=0.8",
+        Some(renderer()),
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        res.headers()[header::CONTENT_TYPE],
+        "text/html; charset=utf-8"
+    );
+    assert_eq!(
+        body_string(res).await,
+        "<h1>500 Internal Server Error</h1><p>ServerError</p>"
+    );
+}
+
+#[tokio::test]
+async fn plain_message_is_kept_without_a_renderer_or_format() {
+    let res = call("text/html", None).await;
+    assert_eq!(
+        body_string(res).await,
+        "error running server function: the report is missing"
+    );
+
+    let res = call("text/plain", Some(renderer())).await;
+    assert_eq!(
+        body_string(res).await,
+        "error running server function: the report is missing"
+    );
+}
+
+#[component]
+fn BrokenApp(cx: Scope) -> impl IntoView {
+    let count: i32 = "not a number".parse().expect("failed to render");
+    view! { cx, <p>{count}</p> }
+}
+
+#[tokio::test]
+async fn render_failures_are_rendered_as_html() {
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <BrokenApp/> });
+    let mut req = Request::builder()
+        .uri("/")
+        .header(header::ACCEPT, "text/html")
+        .body(Body::empty())
+        .unwrap();
+    req.extensions_mut().insert(renderer());
+
+    let res = handler(req).await.into_response();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        res.headers()[header::CONTENT_TYPE],
+        "text/html; charset=utf-8"
+    );
+    assert_eq!(
+        body_string(res).await,
+        "<h1>500 Internal Server Error</h1><p>Render</p>"
+    );
+}
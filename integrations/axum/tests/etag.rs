@@ -0,0 +1,73 @@
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use leptos::*;
+
+fn options() -> LeptosOptions {
+    LeptosOptions::builder().output_name("test_app").build()
+}
+
+fn request(uri: &str, if_none_match: Option<&str>) -> Request<Body> {
+    let mut req = Request::builder().uri(uri);
+    if let Some(etag) = if_none_match {
+        req = req.header(header::IF_NONE_MATCH, etag);
+    }
+    req.body(Body::empty()).unwrap()
+}
+
+// `/account` differs between users, so it opts out of the `ETag`
+fn app(cx: Scope) -> impl IntoView {
+    let req = use_context::<leptos_axum::RequestParts>(cx).unwrap();
+    if req.uri.path() == "/account" {
+        leptos_axum::skip_etag(cx);
+    }
+    view! { cx, <p>"Hello, world!"</p> }
+}
+
+#[tokio::test]
+async fn matching_etag_gets_not_modified() {
+    let handler = leptos_axum::render_app_to_string_with_etag(options(), |_| {}, app);
+
+    let res = handler(request("/", None)).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let etag = res
+        .headers()
+        .get(header::ETAG)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(etag.starts_with("W/\""));
+    let html = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(String::from_utf8(html.to_vec())
+        .unwrap()
+        .contains("Hello, world!"));
+
+    let res = handler(request("/", Some(&etag))).await;
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(res.headers().get(header::ETAG).unwrap(), etag.as_str());
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn other_etags_get_the_page() {
+    let handler = leptos_axum::render_app_to_string_with_etag(options(), |_| {}, app);
+    let res = handler(request("/", Some("W/\"0000000000000000\""))).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let html = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(String::from_utf8(html.to_vec())
+        .unwrap()
+        .contains("Hello, world!"));
+}
+
+#[tokio::test]
+async fn skipped_routes_have_no_etag() {
+    let handler = leptos_axum::render_app_to_string_with_etag(options(), |_| {}, app);
+    let res = handler(request("/account", Some("*"))).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers().get(header::ETAG).is_none());
+}
@@ -0,0 +1,68 @@
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use leptos::*;
+
+fn options() -> LeptosOptions {
+    LeptosOptions::builder().output_name("test_app").build()
+}
+
+#[component]
+fn Shop(cx: Scope) -> impl IntoView {
+    view! { cx,
+        <main>
+            <h1>"Shop"</h1>
+            <div id="cart" class="cart">
+                <p>"2 items"</p>
+                <div>"Total: 5 < 6"<input type="submit"/></div>
+            </div>
+            <footer>"Footer"</footer>
+        </main>
+    }
+}
+
+fn request(fragment: Option<&str>) -> Request<Body> {
+    let mut req = Request::builder().uri("/");
+    if let Some(fragment) = fragment {
+        req = req.header("Leptos-Fragment", fragment);
+    }
+    req.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn fragment_is_the_element_with_the_requested_id() {
+    let handler = leptos_axum::render_app_fragments(options(), |_| {}, |cx| view! { cx, <Shop/> });
+    let res = handler(request(Some("cart"))).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get(header::VARY).unwrap(), "Leptos-Fragment");
+    let html = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(html.to_vec()).unwrap();
+    assert!(html.starts_with(r#"<div id="cart""#));
+    assert!(html.ends_with("</div></div>"));
+    assert!(html.contains("2 items"));
+    assert!(html.contains("Total: 5 &lt; 6"));
+    assert!(!html.contains("Shop"));
+    assert!(!html.contains("Footer"));
+}
+
+#[tokio::test]
+async fn missing_fragment_is_not_found() {
+    let handler = leptos_axum::render_app_fragments(options(), |_| {}, |cx| view! { cx, <Shop/> });
+    let res = handler(request(Some("wishlist"))).await;
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn requests_without_a_fragment_get_the_whole_page() {
+    let handler = leptos_axum::render_app_fragments(options(), |_| {}, |cx| view! { cx, <Shop/> });
+    let res = handler(request(None)).await;
+
+    let html = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(html.to_vec()).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Shop"));
+    assert!(html.contains("2 items"));
+}
@@ -0,0 +1,92 @@
+use axum::{body::Body, extract::Path, http::Request};
+use http::{header, StatusCode};
+use leptos::*;
+
+// Stores a blob of hydration state the way rendering does once the resources have resolved, and
+// renders the URL it's served at. (Resources themselves can't be loaded on the server when Leptos
+// is also built for the browser, as it is when the whole workspace is tested together.)
+#[component]
+fn StateUrl(cx: Scope) -> impl IntoView {
+    let url = use_context::<HydrationStateEndpoint>(cx).map(|HydrationStateEndpoint(store)| {
+        let state = vec![(0, serde_json::to_string("Hello from the server").unwrap())];
+        store(serde_json::to_string(&state).unwrap())
+    });
+    view! { cx, <p>"state at " {url.flatten().unwrap_or_default()} ";"</p> }
+}
+
+async fn render(options: LeptosOptions) -> String {
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <StateUrl/> });
+    let res = handler(Request::builder().uri("/").body(Body::empty()).unwrap()).await;
+    let html = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    String::from_utf8(html.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn hydration_state_is_served_from_its_endpoint() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .hydration_state_endpoint("/_leptos/state/")
+        .build();
+    let html = render(options).await;
+
+    let (_, url) = html.split_once("state at ").unwrap();
+    let (url, _) = url.split_once(';').unwrap();
+    let key = url.strip_prefix("/_leptos/state/").unwrap();
+    assert_eq!(key.len(), 16);
+
+    let res = leptos_axum::handle_hydration_state(Path(key.to_string())).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+    assert_eq!(
+        res.headers().get(header::CACHE_CONTROL).unwrap(),
+        "max-age=600"
+    );
+
+    let state = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let state: Vec<(serde_json::Value, String)> = serde_json::from_slice(&state).unwrap();
+    assert_eq!(state.len(), 1);
+    let greeting: String = serde_json::from_str(&state[0].1).unwrap();
+    assert_eq!(greeting, "Hello from the server");
+
+    // the blob can be fetched again, like by a second load of a cached page
+    let res = leptos_axum::handle_hydration_state(Path(key.to_string())).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = leptos_axum::handle_hydration_state(Path("0000000000000000".to_string())).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        res.headers().get(header::CACHE_CONTROL).unwrap(),
+        "no-store"
+    );
+}
+
+#[tokio::test]
+async fn the_same_state_gets_the_same_url_on_every_page() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .hydration_state_endpoint("/_leptos/state")
+        .build();
+    let first = render(options.clone()).await;
+    let second = render(options).await;
+
+    let url = |html: &str| {
+        html.split_once("state at ")
+            .unwrap()
+            .1
+            .split_once(';')
+            .unwrap()
+            .0
+            .to_string()
+    };
+    assert!(url(&first).starts_with("/_leptos/state/"));
+    assert_eq!(url(&first), url(&second));
+}
+
+#[tokio::test]
+async fn hydration_state_is_inline_by_default() {
+    let html = render(LeptosOptions::builder().output_name("test_app").build()).await;
+    assert!(html.contains("state at ;"));
+}
@@ -0,0 +1,64 @@
+use axum::{body::Body, http::Request};
+use leptos::*;
+use std::time::Duration;
+
+#[component]
+fn Delayed(cx: Scope, text: &'static str, delay_ms: u64) -> impl IntoView {
+    let data = create_resource(
+        cx,
+        || (),
+        move |_| async move {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            text.to_string()
+        },
+    );
+    view! { cx,
+        <Suspense fallback=|| view! { cx, <p>"Loading..."</p> }>
+            {move || data.read().map(|text| view! { cx, <p>{text}</p> })}
+        </Suspense>
+    }
+}
+
+#[component]
+fn App(cx: Scope) -> impl IntoView {
+    view! { cx,
+        <main>
+            <Delayed text="Slow" delay_ms=100/>
+            <p>"Between"</p>
+            <Delayed text="Fast" delay_ms=10/>
+        </main>
+    }
+}
+
+async fn render(in_order: bool) -> String {
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let req = Request::builder().uri("/").body(Body::empty()).unwrap();
+    let res = if in_order {
+        leptos_axum::render_app_to_stream_in_order(options, |cx| view! { cx, <App/> })(req).await
+    } else {
+        leptos_axum::render_app_to_stream(options, |cx| view! { cx, <App/> })(req).await
+    };
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn in_order_streaming_sends_suspense_in_place() {
+    let html = render(true).await;
+
+    assert!(!html.contains("Loading..."));
+    assert!(!html.contains("<template"));
+    let slow = html.find("Slow").unwrap();
+    let between = html.find("Between").unwrap();
+    let fast = html.find("Fast").unwrap();
+    assert!(slow < between && between < fast);
+}
+
+#[tokio::test]
+async fn out_of_order_streaming_sends_fallbacks_first() {
+    let html = render(false).await;
+
+    assert!(html.contains("Loading..."));
+    // the faster <Suspense/> is swapped in first, even though it comes later in the page
+    assert!(html.find("Fast").unwrap() < html.find("Slow").unwrap());
+}
@@ -0,0 +1,50 @@
+use axum::{body::Body, http::Request};
+use leptos::*;
+use leptos_meta::*;
+
+async fn render<IV: IntoView>(app: impl Fn(Scope) -> IV + Clone + Send + 'static) -> String {
+    let handler = leptos_axum::render_app_to_stream_with_context(
+        LeptosOptions::builder().output_name("test_app").build(),
+        |cx| {
+            provide_context(
+                cx,
+                MetaContext::new()
+                    .with_default_title("Acme")
+                    .with_default_meta("application-name", "Acme")
+                    .with_default_meta("description", "Widgets by Acme"),
+            )
+        },
+        app,
+    );
+    let res = handler(Request::builder().uri("/").body(Body::empty()).unwrap()).await;
+    let html = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(html.to_vec()).unwrap();
+    html[..html.find("</head>").unwrap()].to_string()
+}
+
+#[tokio::test]
+async fn handler_defaults_are_used_when_the_app_sets_nothing() {
+    let head = render(|cx| view! { cx, <p>"Hello"</p> }).await;
+
+    assert!(head.contains("<title>Acme</title>"));
+    assert!(head.contains(r#"<meta name="application-name" content="Acme"/>"#));
+    assert!(head.contains(r#"<meta name="description" content="Widgets by Acme"/>"#));
+}
+
+#[tokio::test]
+async fn app_overrides_handler_defaults() {
+    let head = render(|cx| {
+        view! { cx,
+            <Title text="Dashboard"/>
+            <Meta name="description" content="Your widgets"/>
+        }
+    })
+    .await;
+
+    assert!(head.contains("<title>Dashboard</title>"));
+    assert!(!head.contains("<title>Acme</title>"));
+    assert!(head.contains(r#"content="Your widgets""#));
+    assert!(!head.contains("Widgets by Acme"));
+    // defaults the app doesn't override are kept
+    assert!(head.contains(r#"<meta name="application-name" content="Acme"/>"#));
+}
@@ -0,0 +1,103 @@
+use axum::{
+    body::{Body, Bytes},
+    http::Request,
+};
+use http::{header, HeaderValue, StatusCode};
+use leptos::*;
+use leptos_axum::{RenderOutcome, ResponseParts};
+
+fn options() -> LeptosOptions {
+    LeptosOptions::builder().output_name("test_app").build()
+}
+
+fn request(uri: &str) -> Request<Body> {
+    Request::builder().uri(uri).body(Body::empty()).unwrap()
+}
+
+// The app for every test: `/moved` redirects, `/gone` is a 410, `/api` is JSON, and the rest are pages
+fn app(cx: Scope) -> RenderOutcome<impl IntoView> {
+    let req = use_context::<leptos_axum::RequestParts>(cx).unwrap();
+    match req.uri.path() {
+        "/moved" => RenderOutcome::Redirect("/new-home".to_string()),
+        "/gone" => RenderOutcome::Status(StatusCode::GONE),
+        "/api" => {
+            let mut parts = ResponseParts {
+                status: Some(StatusCode::NOT_FOUND),
+                ..Default::default()
+            };
+            parts.headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+            RenderOutcome::Response(parts, Bytes::from(r#"{"error":"not found"}"#))
+        }
+        _ => RenderOutcome::Html(view! { cx, <p>"Hello, world!"</p> }),
+    }
+}
+
+#[tokio::test]
+async fn html_outcome_renders_the_page() {
+    let handler = leptos_axum::render_app_to_stream_with_outcome(options(), |_cx| {}, app);
+    let res = handler(request("/")).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/html; charset=utf-8"
+    );
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Hello, world!"));
+}
+
+#[tokio::test]
+async fn redirect_outcome_skips_the_page() {
+    let handler = leptos_axum::render_app_to_stream_with_outcome(options(), |_cx| {}, app);
+    let res = handler(request("/moved")).await;
+
+    assert_eq!(res.status(), StatusCode::FOUND);
+    assert_eq!(res.headers().get(header::LOCATION).unwrap(), "/new-home");
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn head_requests_get_the_redirect() {
+    let handler = leptos_axum::render_app_to_stream_with_outcome(options(), |_cx| {}, app);
+    let req = Request::builder()
+        .method(http::Method::HEAD)
+        .uri("/moved")
+        .body(Body::empty())
+        .unwrap();
+    let res = handler(req).await;
+
+    assert_eq!(res.status(), StatusCode::FOUND);
+    assert_eq!(res.headers().get(header::LOCATION).unwrap(), "/new-home");
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn status_outcome_sends_only_the_status() {
+    let handler = leptos_axum::render_app_to_stream_with_outcome(options(), |_cx| {}, app);
+    let res = handler(request("/gone")).await;
+
+    assert_eq!(res.status(), StatusCode::GONE);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "Gone");
+}
+
+#[tokio::test]
+async fn response_outcome_replaces_the_page() {
+    let handler = leptos_axum::render_app_to_stream_with_outcome(options(), |_cx| {}, app);
+    let res = handler(request("/api")).await;
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, r#"{"error":"not found"}"#);
+}
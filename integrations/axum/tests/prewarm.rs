@@ -0,0 +1,92 @@
+use axum::{
+    body::{Body, StreamBody},
+    http::{Request, Response},
+};
+use leptos::*;
+use leptos_axum::{PinnedHtmlStream, PrewarmedResources};
+use std::{
+    future::Future,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static FETCHES: AtomicUsize = AtomicUsize::new(0);
+
+#[component]
+fn Forecast(cx: Scope) -> impl IntoView {
+    let forecast = create_resource(
+        cx,
+        || (),
+        |_| async {
+            let fetch = FETCHES.fetch_add(1, Ordering::SeqCst) + 1;
+            format!("Forecast #{fetch}")
+        },
+    );
+    view! { cx,
+        <Suspense fallback=|| ()>
+            {move || forecast.read().map(|forecast| view! { cx, <p>{forecast}</p> })}
+        </Suspense>
+    }
+}
+
+async fn render<F>(handler: &impl Fn(Request<Body>) -> F, path: &str) -> String
+where
+    F: Future<Output = Response<StreamBody<PinnedHtmlStream>>>,
+{
+    let res = handler(Request::builder().uri(path).body(Body::empty()).unwrap()).await;
+    let html = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    String::from_utf8(html.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn prewarmed_routes_render_without_fetching() {
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let resources = PrewarmedResources::new();
+
+    let stored = resources
+        .prewarm(
+            options.clone(),
+            |_| {},
+            |cx| view! { cx, <Forecast/> },
+            "/weather",
+        )
+        .await;
+    assert_eq!(stored, 1);
+    assert_eq!(FETCHES.load(Ordering::SeqCst), 1);
+    assert_eq!(resources.paths(), vec!["/weather".to_string()]);
+
+    let handler = leptos_axum::render_app_to_stream(options.clone(), {
+        let resources = resources.clone();
+        move |cx| {
+            resources.seed(cx);
+            view! { cx, <Forecast/> }
+        }
+    });
+
+    // the values from prewarming are used for every render of the path
+    for _ in 0..2 {
+        let html = render(&handler, "/weather").await;
+        assert!(html.contains("Forecast #1"));
+        assert_eq!(FETCHES.load(Ordering::SeqCst), 1);
+    }
+
+    // other paths load their resources as usual
+    let html = render(&handler, "/weather?day=monday").await;
+    assert!(html.contains("Forecast #2"));
+
+    resources.invalidate("/weather");
+    let html = render(&handler, "/weather").await;
+    assert!(html.contains("Forecast #3"));
+
+    // a full URL invalidates the values stored for its path and query
+    resources
+        .prewarm(
+            options.clone(),
+            |_| {},
+            |cx| view! { cx, <Forecast/> },
+            "/weather?day=monday",
+        )
+        .await;
+    assert_eq!(resources.paths(), vec!["/weather?day=monday".to_string()]);
+    resources.invalidate("http://localhost:3000/weather?day=monday");
+    assert!(resources.paths().is_empty());
+}
@@ -0,0 +1,98 @@
+use axum::{body::Body, extract::Path, response::IntoResponse};
+use http::{header, HeaderMap, Request, Response, StatusCode};
+use leptos::*;
+use leptos_axum::RedirectLimit;
+use serde::{Deserialize, Serialize};
+use std::{future::Future, pin::Pin};
+
+// A misconfigured login flow: the login page always redirects back to itself
+#[derive(Clone, Serialize, Deserialize)]
+struct Login {}
+
+impl ServerFn for Login {
+    type Output = ();
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "login"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, cx: Scope) -> Pin<Box<dyn Future<Output = Result<(), ServerFnError>>>> {
+        Box::pin(async move {
+            leptos_axum::redirect(cx, "/api/login").await;
+            Ok(())
+        })
+    }
+}
+
+// Follows redirects like a browser, sending back the cookies it's given, until it gets a response
+// that isn't a redirect. Returns the number of redirects, and the final response.
+async fn follow_redirects(limit: Option<u32>) -> (u32, Response<axum::body::BoxBody>) {
+    _ = Login::register();
+    let mut cookie: Option<String> = None;
+    let mut redirects = 0;
+    loop {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, "application/json".parse().unwrap());
+        let mut req = Request::builder()
+            .method("POST")
+            .uri("/api/login")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+        if let Some(cookie) = &cookie {
+            req = req.header(header::COOKIE, cookie);
+        }
+        let req = req.body(Body::empty()).unwrap();
+
+        let res = leptos_axum::handle_server_fns_with_context(
+            Path(Login::url().to_string()),
+            headers,
+            move |cx| {
+                if let Some(limit) = limit {
+                    provide_context(cx, RedirectLimit(limit));
+                }
+            },
+            req,
+        )
+        .await
+        .into_response();
+
+        if res.status() != StatusCode::FOUND {
+            return (redirects, res);
+        }
+        redirects += 1;
+        assert!(redirects <= 100, "the redirect loop was never stopped");
+        cookie = res
+            .headers()
+            .get(header::SET_COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(String::from);
+    }
+}
+
+#[tokio::test]
+async fn redirect_loops_are_stopped_at_the_default_limit() {
+    let (redirects, res) = follow_redirects(None).await;
+
+    assert_eq!(redirects, leptos_axum::DEFAULT_REDIRECT_LIMIT);
+    assert_eq!(res.status(), StatusCode::LOOP_DETECTED);
+    assert!(res.headers().get(header::LOCATION).is_none());
+    // the count starts over on the next request
+    let cookie = res.headers().get(header::SET_COOKIE).unwrap();
+    assert!(cookie.to_str().unwrap().contains("Max-Age=0"));
+}
+
+#[tokio::test]
+async fn redirect_limit_can_be_provided_as_context() {
+    let (redirects, res) = follow_redirects(Some(2)).await;
+
+    assert_eq!(redirects, 2);
+    assert_eq!(res.status(), StatusCode::LOOP_DETECTED);
+}
@@ -0,0 +1,760 @@
+use axum::{body::Body, http::Request};
+use http::header;
+use leptos::*;
+
+fn options() -> LeptosOptions {
+    LeptosOptions::builder().output_name("test_app").build()
+}
+
+#[component]
+fn App(cx: Scope) -> impl IntoView {
+    view! { cx, <p>"Hello, world!"</p> }
+}
+
+fn request(uri: &str) -> Request<Body> {
+    Request::builder().uri(uri).body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn render_sets_html_content_type() {
+    let handler = leptos_axum::render_app_to_stream(options(), |cx| view! { cx, <App/> });
+    let res = handler(request("/")).await;
+
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/html; charset=utf-8"
+    );
+}
+
+#[tokio::test]
+async fn render_uses_configured_charset() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .charset("iso-8859-1")
+        .build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <App/> });
+    let res = handler(request("/")).await;
+
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/html; charset=iso-8859-1"
+    );
+}
+
+#[tokio::test]
+async fn render_app_to_writer_writes_the_full_page() {
+    let mut buffer = Vec::new();
+    let res_parts = leptos_axum::render_app_to_writer(
+        options(),
+        |_cx| {},
+        |cx| view! { cx, <App/> },
+        request("/"),
+        &mut buffer,
+    )
+    .await
+    .unwrap();
+
+    let html = String::from_utf8(buffer).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Hello, world!"));
+    assert!(html.ends_with("</body></html>"));
+    assert_eq!(res_parts.status, None);
+}
+
+#[tokio::test]
+async fn head_transform_can_append_tags() {
+    let handler = leptos_axum::render_app_to_stream_with_head_transform(
+        options(),
+        |_cx| {},
+        |head| format!(r#"{head}<meta name="build" content="abc123"/>"#),
+        |cx| view! { cx, <App/> },
+    );
+    let res = handler(request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(html.contains(r#"<meta name="build" content="abc123"/></head><body>"#));
+}
+
+#[component]
+fn LocalizedApp(cx: Scope) -> impl IntoView {
+    let locale = use_locale(cx).map(|locale| locale.0).unwrap_or_default();
+    view! { cx, <p>"Locale: " {locale}</p> }
+}
+
+#[tokio::test]
+async fn negotiated_locale_is_provided_and_embedded() {
+    let handler = leptos_axum::render_app_to_stream(options(), |cx| view! { cx, <LocalizedApp/> });
+    let req = Request::builder()
+        .uri("/")
+        .header("Accept-Language", "de;q=0.7, fr-CH, fr;q=0.9, *;q=0.5")
+        .body(Body::empty())
+        .unwrap();
+    let res = handler(req).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(html.contains(r#"<html lang="fr-CH">"#));
+    assert!(html.contains("Locale: fr-CH"));
+}
+
+#[tokio::test]
+async fn locale_defaults_to_en() {
+    let handler = leptos_axum::render_app_to_stream(options(), |cx| view! { cx, <LocalizedApp/> });
+    let res = handler(request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(html.contains(r#"<html lang="en">"#));
+}
+
+#[tokio::test]
+async fn configured_lang_is_used_without_accept_language() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .lang("de")
+        .build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <LocalizedApp/> });
+    let res = handler(request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(html.contains(r#"<html lang="de">"#));
+    assert!(html.contains("Locale: de"));
+}
+
+#[tokio::test]
+async fn configured_lang_wins_when_negotiation_is_off() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .lang("de")
+        .negotiate_lang(false)
+        .doctype("<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Strict//EN\">")
+        .build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <LocalizedApp/> });
+    let req = Request::builder()
+        .uri("/")
+        .header("Accept-Language", "fr-CH, fr;q=0.9")
+        .body(Body::empty())
+        .unwrap();
+    let res = handler(req).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(html.starts_with(r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Strict//EN">"#));
+    assert!(html.contains(r#"<html lang="de">"#));
+    assert!(html.contains("Locale: de"));
+}
+
+#[component]
+fn SpaciousApp(cx: Scope) -> impl IntoView {
+    view! { cx,
+        <div title="keep   these">
+            <p>"lots     of\n\n   space"</p>
+            <pre>"  indented\n    code  "</pre>
+            <textarea>"  a\n  b"</textarea>
+        </div>
+    }
+}
+
+async fn render_spacious_app(collapse_whitespace: bool) -> String {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .collapse_whitespace(collapse_whitespace)
+        .build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <SpaciousApp/> });
+    let res = handler(request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn collapse_whitespace_collapses_text_in_normal_elements() {
+    let html = render_spacious_app(true).await;
+
+    assert!(html.contains(">lots of space</p>"));
+    assert!(html.contains(r#"title="keep   these""#));
+    assert!(html.starts_with("<!DOCTYPE html> <html lang=\"en\"> <head> <meta"));
+}
+
+#[tokio::test]
+async fn collapse_whitespace_preserves_pre_and_textarea() {
+    let html = render_spacious_app(true).await;
+
+    assert!(html.contains(">  indented\n    code  </pre>"));
+    assert!(html.contains(">  a\n  b</textarea>"));
+}
+
+#[tokio::test]
+async fn whitespace_is_kept_by_default() {
+    let html = render_spacious_app(false).await;
+
+    assert!(html.contains(">lots     of\n\n   space</p>"));
+}
+
+#[component]
+fn BrokenApp(cx: Scope) -> impl IntoView {
+    let count: i32 = "not a number".parse().expect("failed to render");
+    view! { cx, <p>{count}</p> }
+}
+
+#[tokio::test]
+async fn fallback_is_rendered_when_app_fails_before_shell() {
+    let handler = leptos_axum::render_app_to_stream_with_fallback(
+        options(),
+        |_cx| {},
+        |cx| view! { cx, <BrokenApp/> },
+        |cx| view! { cx, <p>"Something went wrong."</p> },
+    );
+    let res = handler(request("/")).await;
+    assert_eq!(res.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Something went wrong."));
+    assert!(html.ends_with("</body></html>"));
+}
+
+#[tokio::test]
+async fn fallback_is_unused_when_app_renders() {
+    let handler = leptos_axum::render_app_to_stream_with_fallback(
+        options(),
+        |_cx| {},
+        |cx| view! { cx, <App/> },
+        |cx| view! { cx, <p>"Something went wrong."</p> },
+    );
+    let res = handler(request("/")).await;
+    assert_eq!(res.status(), http::StatusCode::OK);
+
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Hello, world!"));
+    assert!(!html.contains("Something went wrong."));
+}
+
+#[component]
+fn RoutedApp(cx: Scope) -> impl IntoView {
+    use leptos_router::*;
+
+    view! { cx,
+        <Router>
+            <Routes>
+                <Route path="" view=|cx| view! { cx, <p>"Home"</p> }/>
+            </Routes>
+        </Router>
+    }
+}
+
+#[tokio::test]
+async fn not_found_page_is_rendered_when_no_route_matches() {
+    let handler = leptos_axum::render_app_to_stream_with_context_and_fallback(
+        options(),
+        |_cx| {},
+        |cx| view! { cx, <RoutedApp/> },
+        |cx| view! { cx, <p>"Page not found."</p> },
+    );
+    let res = handler(request("/missing")).await;
+    assert_eq!(res.status(), http::StatusCode::NOT_FOUND);
+
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Page not found."));
+    assert!(!html.contains("Home"));
+
+    let res = handler(request("/")).await;
+    assert_eq!(res.status(), http::StatusCode::OK);
+
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Home"));
+    assert!(!html.contains("Page not found."));
+}
+
+#[tokio::test]
+async fn tail_can_add_scripts_before_closing_body() {
+    let handler = leptos_axum::render_app_to_stream_with_tail(
+        options(),
+        |_cx| {},
+        |cx| {
+            let path = use_context::<leptos_axum::RequestParts>(cx)
+                .map(|req| req.uri.path().to_string())
+                .unwrap_or_default();
+            format!(r#"<script>track("{path}")</script></body></html>"#)
+        },
+        |cx| view! { cx, <App/> },
+    );
+    let res = handler(request("/home")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(html.ends_with(r#"<script>track("/home")</script></body></html>"#));
+    assert!(html.find("Hello, world!").unwrap() < html.find("<script>track").unwrap());
+}
+
+#[tokio::test]
+async fn csp_nonce_covers_every_inline_script() {
+    let options = options();
+    let handler = leptos_axum::render_app_to_stream_with_context(
+        options.clone(),
+        {
+            let options = options.clone();
+            move |cx| {
+                leptos_axum::set_content_security_policy(
+                    cx,
+                    &options,
+                    CspNonce("abc123".to_string()),
+                )
+            }
+        },
+        |cx| view! { cx, <App/> },
+    );
+    let res = handler(request("/")).await;
+
+    let policy = res
+        .headers()
+        .get(header::CONTENT_SECURITY_POLICY)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(
+        policy,
+        leptos_axum::content_security_policy(
+            &options,
+            Some(&CspNonce("abc123".to_string())),
+            false
+        )
+    );
+    assert!(policy.contains("script-src 'self' 'nonce-abc123'"));
+
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    let scripts = html.matches("<script").count();
+    assert!(scripts >= 2);
+    assert_eq!(
+        html.matches(r#"nonce="abc123""#).count(),
+        scripts,
+        "every script should carry the nonce: {html}"
+    );
+}
+
+#[test]
+fn csp_allows_live_reload_when_enabled() {
+    let options = options();
+    let nonce = CspNonce("abc123".to_string());
+
+    let policy = leptos_axum::content_security_policy(&options, Some(&nonce), true);
+    assert!(policy.contains(&format!(
+        "connect-src 'self' ws://{}:{}",
+        options.site_address.ip(),
+        options.reload_port
+    )));
+
+    let policy = leptos_axum::content_security_policy(&options, None, false);
+    assert!(policy.contains("'unsafe-inline' 'wasm-unsafe-eval'"));
+    assert!(!policy.contains("connect-src"));
+}
+
+#[test]
+fn csp_allows_the_external_live_reload_address() {
+    let mut options = options();
+    options.reload_ws_host = Some("localhost".to_string());
+    options.reload_external_port = Some(8001);
+
+    let policy = leptos_axum::content_security_policy(&options, None, true);
+    assert!(policy.contains("connect-src 'self' ws://localhost:8001"));
+}
+
+#[tokio::test]
+async fn csp_nonce_is_generated_for_every_page() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .csp_nonce(true)
+        .build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <App/> });
+
+    let mut nonces = Vec::new();
+    for _ in 0..2 {
+        let res = handler(request("/")).await;
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+
+        let nonce = html
+            .split(r#"nonce=""#)
+            .nth(1)
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap();
+        assert_eq!(nonce.len(), 32);
+        assert!(nonce.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(
+            html.matches(&format!(r#"nonce="{nonce}""#)).count(),
+            html.matches("<script").count(),
+            "every script should carry the nonce: {html}"
+        );
+        nonces.push(nonce.to_string());
+    }
+    assert_ne!(nonces[0], nonces[1]);
+}
+
+#[tokio::test]
+async fn render_rejects_bodies_over_max_body_size() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .max_body_size(16)
+        .build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <App/> });
+
+    let req = Request::builder()
+        .method("POST")
+        .uri("/")
+        .body(Body::from("a".repeat(64)))
+        .unwrap();
+    let res = handler(req).await;
+    assert_eq!(res.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+
+    let res = handler(request("/")).await;
+    assert_eq!(res.status(), http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn render_app_to_string_sends_the_full_page_at_once() {
+    let handler = leptos_axum::render_app_to_string(options(), |cx| view! { cx, <App/> });
+    let res = handler(request("/")).await;
+
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/html; charset=utf-8"
+    );
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Hello, world!"));
+    assert!(html.ends_with("</body></html>"));
+}
+
+#[component]
+fn SlowApp(cx: Scope) -> impl IntoView {
+    // rendering the shell takes longer than the request timeout
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    view! { cx, <p>"Finally!"</p> }
+}
+
+#[tokio::test]
+async fn render_times_out_when_shell_is_too_slow() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .request_timeout(50)
+        .build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <SlowApp/> });
+
+    let res = handler(request("/")).await;
+    assert_eq!(res.status(), http::StatusCode::REQUEST_TIMEOUT);
+}
+
+#[tokio::test]
+async fn preconnect_links_are_emitted_for_configured_origins() {
+    let preconnect_options = LeptosOptions::builder()
+        .output_name("test_app")
+        .preconnect_origins(vec![
+            "https://fonts.gstatic.com".to_string(),
+            "https://api.example.com".to_string(),
+        ])
+        .build();
+    let handler = leptos_axum::render_app_to_stream(preconnect_options, |cx| view! { cx, <App/> });
+    let res = handler(request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    let head = &html[..html.find("</head>").unwrap()];
+    for origin in ["https://fonts.gstatic.com", "https://api.example.com"] {
+        assert!(head.contains(&format!(
+            r#"<link rel="preconnect" href="{origin}" crossorigin=""><link rel="dns-prefetch" href="{origin}">"#
+        )));
+    }
+
+    // no hints unless they're configured
+    let handler = leptos_axum::render_app_to_stream(options(), |cx| view! { cx, <App/> });
+    let res = handler(request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(!String::from_utf8(body.to_vec())
+        .unwrap()
+        .contains("preconnect"));
+}
+
+#[tokio::test]
+async fn app_version_is_sent_in_meta_tag_and_header() {
+    let versioned_options = LeptosOptions::builder()
+        .output_name("test_app")
+        .app_version("1.2.3+abc")
+        .build();
+    let handler = leptos_axum::render_app_to_stream(versioned_options, |cx| view! { cx, <App/> });
+    let res = handler(request("/")).await;
+
+    assert_eq!(res.headers().get("x-app-version").unwrap(), "1.2.3+abc");
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    let head = &html[..html.find("</head>").unwrap()];
+    assert!(head.contains(r#"<meta name="version" content="1.2.3+abc"/>"#));
+
+    // both are left out unless a version is configured
+    let handler = leptos_axum::render_app_to_stream(options(), |cx| view! { cx, <App/> });
+    let res = handler(request("/")).await;
+    assert!(res.headers().get("x-app-version").is_none());
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(!String::from_utf8(body.to_vec())
+        .unwrap()
+        .contains(r#"name="version""#));
+}
+
+#[tokio::test]
+async fn hydration_script_uses_configured_entry_points() {
+    let custom_options = LeptosOptions::builder()
+        .output_name("test_app")
+        .init_fn_name("start")
+        .hydrate_fn_name("hydrate_app")
+        .build();
+    let handler = leptos_axum::render_app_to_stream(custom_options, |cx| view! { cx, <App/> });
+    let res = handler(request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("import { start, hydrate_app } from '/pkg/test_app.js';"));
+    assert!(html.contains(".wasm').then(hydrate_app);"));
+    assert!(html.contains("start('/pkg/test_app"));
+
+    // wasm-bindgen's default export and `hydrate` unless they're configured
+    let handler = leptos_axum::render_app_to_stream(options(), |cx| view! { cx, <App/> });
+    let res = handler(request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("import init, { hydrate } from '/pkg/test_app.js';"));
+    assert!(html.contains(".wasm').then(hydrate);"));
+}
+
+#[tokio::test]
+async fn preload_hints_can_be_disabled() {
+    let handler = leptos_axum::render_app_to_stream(options(), |cx| view! { cx, <App/> });
+    let res = handler(request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains(r#"<link rel="modulepreload" href="/pkg/test_app.js">"#));
+    assert!(html.contains(r#"<link rel="preload" href="/pkg/test_app"#));
+
+    let custom_options = LeptosOptions::builder()
+        .output_name("test_app")
+        .preload_hints(false)
+        .build();
+    let handler = leptos_axum::render_app_to_stream(custom_options, |cx| view! { cx, <App/> });
+    let res = handler(request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(!html.contains("modulepreload"));
+    assert!(!html.contains(r#"rel="preload""#));
+    // the app is still hydrated
+    assert!(html.contains("import init, { hydrate } from '/pkg/test_app.js';"));
+}
+
+#[derive(Clone)]
+struct Greeting(&'static str);
+
+#[derive(Clone)]
+struct Name(&'static str);
+
+#[component]
+fn GreetingApp(cx: Scope) -> impl IntoView {
+    let Greeting(greeting) = use_context(cx).unwrap();
+    let Name(name) = use_context(cx).unwrap();
+    view! { cx, <p>{format!("{greeting}, {name}!")}</p> }
+}
+
+#[tokio::test]
+async fn context_providers_are_combined_in_order() {
+    let context = leptos_axum::ContextProviders::new()
+        .with(|cx| provide_context(cx, Greeting("Hello")))
+        .with(|cx| provide_context(cx, Name("world")))
+        .with(|cx| provide_context(cx, Name("Leptos")));
+    let handler = leptos_axum::render_app_to_stream_with_context(
+        options(),
+        context.into_fn(),
+        |cx| view! { cx, <GreetingApp/> },
+    );
+    let res = handler(request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(html.contains("Hello, Leptos!"));
+}
+
+async fn render_compressed(
+    accept_encoding: &str,
+) -> http::Response<axum::body::StreamBody<leptos_axum::PinnedHtmlStream>> {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .compress_html(true)
+        .build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <App/> });
+    let req = Request::builder()
+        .uri("/")
+        .header(header::ACCEPT_ENCODING, accept_encoding)
+        .body(Body::empty())
+        .unwrap();
+    handler(req).await
+}
+
+#[tokio::test]
+async fn compress_html_gzips_the_stream() {
+    use tokio::io::AsyncReadExt;
+
+    let res = render_compressed("gzip, br;q=0").await;
+    assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+    assert_eq!(res.headers().get(header::VARY).unwrap(), "Accept-Encoding");
+
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let mut html = String::new();
+    async_compression::tokio::bufread::GzipDecoder::new(&body[..])
+        .read_to_string(&mut html)
+        .await
+        .unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Hello, world!"));
+    assert!(html.ends_with("</body></html>"));
+}
+
+#[tokio::test]
+async fn compress_html_prefers_brotli() {
+    let res = render_compressed("gzip, deflate, br").await;
+
+    assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "br");
+}
+
+#[tokio::test]
+async fn compress_html_leaves_other_encodings_uncompressed() {
+    let res = render_compressed("identity").await;
+
+    assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(String::from_utf8(body.to_vec())
+        .unwrap()
+        .contains("Hello, world!"));
+}
+
+#[derive(Clone)]
+struct SiteName(&'static str);
+
+#[tokio::test]
+async fn render_app_to_stream_with_state_provides_the_state() {
+    let handler = leptos_axum::render_app_to_stream_with_state(options(), |cx| {
+        let SiteName(name) = use_context(cx).unwrap();
+        view! { cx, <h1>{name}</h1> }
+    });
+    let res = handler(axum::extract::State(SiteName("My Site")), request("/")).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(html.contains("My Site"));
+}
+
+fn head_request(uri: &str) -> Request<Body> {
+    Request::builder()
+        .method(http::Method::HEAD)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap()
+}
+
+// Sets the status and a header while rendering, the way a page does when it can't find what it shows
+#[component]
+fn MissingPostApp(cx: Scope) -> impl IntoView {
+    let res_options = use_context::<leptos_axum::ResponseOptions>(cx).unwrap();
+    let mut parts = res_options.0.try_write().unwrap();
+    parts.status = Some(http::StatusCode::GONE);
+    parts.headers.insert(
+        header::CACHE_CONTROL,
+        http::HeaderValue::from_static("no-store"),
+    );
+    view! { cx, <p>"This post was deleted."</p> }
+}
+
+#[tokio::test]
+async fn head_requests_get_the_headers_of_the_rendered_page() {
+    let handler = leptos_axum::render_app_to_stream(options(), |cx| view! { cx, <App/> });
+    let res = handler(head_request("/")).await;
+
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/html; charset=utf-8"
+    );
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn head_requests_get_the_status_and_headers_set_while_rendering() {
+    let handler =
+        leptos_axum::render_app_to_stream(options(), |cx| view! { cx, <MissingPostApp/> });
+    let res = handler(head_request("/posts/1")).await;
+    assert_eq!(res.status(), http::StatusCode::GONE);
+    assert_eq!(
+        res.headers().get(header::CACHE_CONTROL).unwrap(),
+        "no-store"
+    );
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(body.is_empty());
+
+    let handler =
+        leptos_axum::render_app_to_string(options(), |cx| view! { cx, <MissingPostApp/> });
+    let res = handler(head_request("/posts/1")).await;
+    assert_eq!(res.status(), http::StatusCode::GONE);
+    assert_eq!(
+        res.headers().get(header::CACHE_CONTROL).unwrap(),
+        "no-store"
+    );
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn head_requests_for_missing_pages_are_not_found() {
+    let handler = leptos_axum::render_app_to_stream_with_context_and_fallback(
+        options(),
+        |_cx| {},
+        |cx| view! { cx, <RoutedApp/> },
+        |cx| view! { cx, <p>"Page not found."</p> },
+    );
+    let res = handler(head_request("/missing")).await;
+    assert_eq!(res.status(), http::StatusCode::NOT_FOUND);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(body.is_empty());
+
+    let res = handler(head_request("/")).await;
+    assert_eq!(res.status(), http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn custom_shell_replaces_head_and_tail() {
+    let handler = leptos_axum::render_app_to_stream_with_shell(
+        options(),
+        |_| {},
+        |shell| {
+            let head = format!(
+                r#"<!DOCTYPE html><!-- custom --><html lang="{}"><head><script type="module"{}>import '/{}/{}.js';</script>{}"#,
+                shell.lang, shell.nonce, shell.pkg_path, shell.output_name, shell.autoreload
+            );
+            (head, "<script>done()</script></body></html>".to_string())
+        },
+        |cx| view! { cx, <App/> },
+    );
+    let res = handler(request("/")).await;
+    let html = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(html.to_vec()).unwrap();
+
+    assert!(html.starts_with(r#"<!DOCTYPE html><!-- custom --><html lang="en"><head>"#));
+    assert!(html.contains("import '/pkg/test_app.js';"));
+    assert!(!html.contains("viewport"));
+    assert!(html.contains("</head><body>"));
+    assert!(html.contains("Hello, world!"));
+    assert!(html.ends_with("<script>done()</script></body></html>"));
+}
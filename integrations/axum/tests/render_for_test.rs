@@ -0,0 +1,24 @@
+use leptos::*;
+use leptos_meta::*;
+
+#[component]
+fn Greeting(cx: Scope, name: &'static str) -> impl IntoView {
+    // components can rely on the contexts an integration provides
+    provide_meta_context(cx);
+    let path = use_context::<leptos_axum::RequestParts>(cx)
+        .map(|req| req.uri.path().to_string())
+        .unwrap_or_default();
+    view! { cx,
+        <Title text="Greeting"/>
+        <p class="greeting" data-path=path>"Hello, " {name} "!"</p>
+    }
+}
+
+#[test]
+fn component_is_rendered_without_a_server() {
+    let html = leptos_axum::render_to_string_for_test(|cx| view! { cx, <Greeting name="Alice"/> });
+
+    assert!(html.contains(r#"class="greeting""#), "{html}");
+    assert!(html.contains(r#"data-path="/""#), "{html}");
+    assert!(html.contains(">Hello, Alice!</p>"), "{html}");
+}
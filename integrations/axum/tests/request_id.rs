@@ -0,0 +1,64 @@
+use axum::{body::Body, http::Request};
+use leptos::*;
+use leptos_axum::RequestId;
+
+fn options() -> LeptosOptions {
+    LeptosOptions::builder()
+        .output_name("test_app")
+        .request_id_header("X-Correlation-ID")
+        .build()
+}
+
+#[component]
+fn ShowRequestId(cx: Scope) -> impl IntoView {
+    let id = use_context::<RequestId>(cx).unwrap();
+    view! { cx, <p>"Request " {id.to_string()}</p> }
+}
+
+#[tokio::test]
+async fn request_id_is_read_from_the_configured_header() {
+    let handler = leptos_axum::render_app_to_stream(options(), |cx| view! { cx, <ShowRequestId/> });
+    let req = Request::builder()
+        .uri("/")
+        .header("x-correlation-id", "abc-123")
+        .header("x-request-id", "ignored")
+        .body(Body::empty())
+        .unwrap();
+    let res = handler(req).await;
+
+    assert_eq!(res.headers().get("x-correlation-id").unwrap(), "abc-123");
+    assert!(res.headers().get("x-request-id").is_none());
+    let html = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(html.to_vec()).unwrap();
+    assert!(html.contains("abc-123"));
+}
+
+#[tokio::test]
+async fn requests_without_an_id_get_a_new_one() {
+    let handler = leptos_axum::render_app_to_stream(options(), |cx| view! { cx, <ShowRequestId/> });
+    let first = handler(Request::builder().uri("/").body(Body::empty()).unwrap()).await;
+    let second = handler(Request::builder().uri("/").body(Body::empty()).unwrap()).await;
+
+    let first = first.headers().get("x-correlation-id").unwrap();
+    let second = second.headers().get("x-correlation-id").unwrap();
+    assert_eq!(first.len(), 32);
+    assert_ne!(first, second);
+}
+
+#[tokio::test]
+async fn new_traceparent_ids_are_valid_traceparent_values() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .request_id_header("traceparent")
+        .build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <ShowRequestId/> });
+    let res = handler(Request::builder().uri("/").body(Body::empty()).unwrap()).await;
+
+    let traceparent = res.headers().get("traceparent").unwrap().to_str().unwrap();
+    let parts = traceparent.split('-').collect::<Vec<_>>();
+    assert_eq!(parts.len(), 4);
+    assert_eq!(parts[0], "00");
+    assert_eq!(parts[1].len(), 32);
+    assert_eq!(parts[2].len(), 16);
+    assert_eq!(parts[3], "01");
+}
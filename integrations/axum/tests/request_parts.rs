@@ -0,0 +1,59 @@
+use axum::body::{Body, Bytes};
+use http::{header, Request, StatusCode};
+use leptos::LeptosOptions;
+use std::sync::Arc;
+
+const MB: usize = 1024 * 1024;
+
+fn options() -> Arc<LeptosOptions> {
+    Arc::new(
+        LeptosOptions::builder()
+            .output_name("test_app")
+            .max_body_size(MB)
+            .build(),
+    )
+}
+
+#[tokio::test]
+async fn bodies_over_the_limit_are_rejected() {
+    // streamed in chunks, without a Content-Length to give the size away up front
+    let chunks = (0..10).map(|_| Ok::<_, std::io::Error>(Bytes::from(vec![b'a'; MB])));
+    let mut req = Request::builder()
+        .method("POST")
+        .uri("/api/upload")
+        .body(Body::wrap_stream(futures::stream::iter(chunks)))
+        .unwrap();
+    req.extensions_mut().insert(options());
+
+    let parts = leptos_axum::generate_request_parts(req).await;
+
+    assert_eq!(parts.unwrap_err(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn content_length_over_the_limit_is_rejected() {
+    let req = Request::builder()
+        .method("POST")
+        .uri("/api/upload")
+        .header(header::CONTENT_LENGTH, 10 * MB)
+        .body(Body::from(vec![b'a'; 10 * MB]))
+        .unwrap();
+
+    let parts = leptos_axum::generate_request_parts_with_limit(req, Some(MB)).await;
+
+    assert_eq!(parts.unwrap_err(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn bodies_within_the_limit_are_read() {
+    let mut req = Request::builder()
+        .method("POST")
+        .uri("/api/upload")
+        .body(Body::from("name=leptos"))
+        .unwrap();
+    req.extensions_mut().insert(options());
+
+    let parts = leptos_axum::generate_request_parts(req).await.unwrap();
+
+    assert_eq!(parts.body, "name=leptos");
+}
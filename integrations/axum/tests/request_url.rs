@@ -0,0 +1,80 @@
+use axum::{body::Body, http::Request};
+use leptos::*;
+use leptos_axum::RequestUrl;
+
+#[component]
+fn App(cx: Scope) -> impl IntoView {
+    let url = use_context::<RequestUrl>(cx).expect("RequestUrl should be provided");
+    view! { cx, <p>"url=" {url.to_string()} ";origin=" {url.origin()}</p> }
+}
+
+async fn render(options: LeptosOptions, req: Request<Body>) -> String {
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <App/> });
+    let res = handler(req).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+fn forwarded_request() -> Request<Body> {
+    Request::builder()
+        .uri("/posts/1?page=2")
+        .header("host", "internal:3000")
+        .header("x-forwarded-proto", "https")
+        .header("x-forwarded-host", "example.com")
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn request_url_uses_host_header_for_direct_requests() {
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let req = Request::builder()
+        .uri("/posts/1?page=2")
+        .header("host", "localhost:3000")
+        .body(Body::empty())
+        .unwrap();
+    let html = render(options, req).await;
+
+    assert!(html.contains("url=http://localhost:3000/posts/1?page=2;origin=http://localhost:3000"));
+}
+
+#[tokio::test]
+async fn forwarded_headers_are_ignored_unless_trusted() {
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let html = render(options, forwarded_request()).await;
+
+    assert!(html.contains("url=http://internal:3000/posts/1?page=2"));
+    assert!(!html.contains("example.com"));
+}
+
+#[tokio::test]
+async fn trusted_x_forwarded_headers_set_scheme_and_host() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .trust_forwarded_headers(true)
+        .build();
+    let html = render(options, forwarded_request()).await;
+
+    assert!(html.contains("url=https://example.com/posts/1?page=2;origin=https://example.com"));
+}
+
+#[tokio::test]
+async fn trusted_forwarded_header_takes_precedence() {
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .trust_forwarded_headers(true)
+        .build();
+    let req = Request::builder()
+        .uri("/")
+        .header("host", "internal:3000")
+        .header(
+            "forwarded",
+            r#"for=192.0.2.60;proto=https;host="public.example", for=10.0.0.1"#,
+        )
+        .header("x-forwarded-host", "other.example")
+        .body(Body::empty())
+        .unwrap();
+    let html = render(options, req).await;
+
+    assert!(html.contains("url=https://public.example/;origin=https://public.example"));
+}
@@ -0,0 +1,104 @@
+use axum::http::header;
+use leptos_axum::{InvalidHeader, ResponseOptions, ResponseParts};
+
+#[tokio::test]
+async fn headers_can_be_set_from_strings() {
+    let res_options = ResponseOptions::default();
+    res_options
+        .insert_header_str("Cache-Control", "max-age=60")
+        .await
+        .unwrap();
+    res_options
+        .append_header_str("Vary", "Accept")
+        .await
+        .unwrap();
+    res_options
+        .append_header_str("Vary", "Accept-Language")
+        .await
+        .unwrap();
+    res_options
+        .set_content_type("application/pdf")
+        .await
+        .unwrap();
+    res_options.set_location("/reports/1").await.unwrap();
+
+    let res_parts = res_options.0.read().await;
+    assert_eq!(res_parts.headers[header::CACHE_CONTROL], "max-age=60");
+    assert_eq!(res_parts.headers.get_all(header::VARY).iter().count(), 2);
+    assert_eq!(res_parts.headers[header::CONTENT_TYPE], "application/pdf");
+    assert_eq!(res_parts.headers[header::LOCATION], "/reports/1");
+    assert_eq!(res_parts.status, None);
+}
+
+#[tokio::test]
+async fn invalid_header_strings_are_rejected() {
+    let res_options = ResponseOptions::default();
+
+    assert_eq!(
+        res_options.insert_header_str("Bad Name", "value").await,
+        Err(InvalidHeader::Name("Bad Name".to_string()))
+    );
+    assert_eq!(
+        res_options.set_location("/a\r\nSet-Cookie: x=1").await,
+        Err(InvalidHeader::Value("location".to_string()))
+    );
+    assert!(res_options.0.read().await.headers.is_empty());
+
+    let mut res_parts = ResponseParts::default();
+    let err = res_parts
+        .insert_header_str("X-Note", "line\nbreak")
+        .unwrap_err();
+    assert_eq!(err.to_string(), "invalid value for the x-note header");
+    assert!(res_parts.headers.is_empty());
+}
+
+#[tokio::test]
+async fn body_replaces_the_rendered_page() {
+    use axum::{body::Body, http::Request};
+    use leptos::*;
+
+    #[component]
+    fn Report(cx: Scope) -> impl IntoView {
+        let res_options = use_context::<ResponseOptions>(cx).unwrap();
+        let report = create_resource(
+            cx,
+            || (),
+            move |_| {
+                let res_options = res_options.clone();
+                async move {
+                    res_options
+                        .set_body("application/pdf", &b"%PDF-1.7"[..])
+                        .await
+                        .unwrap();
+                }
+            },
+        );
+        view! { cx,
+            <Suspense fallback=|| ()>
+                {move || report.read().map(|_| view! { cx, <p>"Report"</p> })}
+            </Suspense>
+        }
+    }
+
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let req = Request::builder().uri("/").body(Body::empty()).unwrap();
+    let res = leptos_axum::render_app_to_string(options, |cx| view! { cx, <Report/> })(req).await;
+
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "application/pdf");
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "%PDF-1.7");
+}
+
+#[tokio::test]
+async fn invalid_body_content_type_is_rejected() {
+    let res_options = ResponseOptions::default();
+
+    assert_eq!(
+        res_options.set_body("text/plain\n", "hello").await,
+        Err(InvalidHeader::Value("content-type".to_string()))
+    );
+    let res_parts = res_options.0.read().await;
+    assert!(res_parts.body.is_none());
+    assert!(res_parts.content_type.is_none());
+}
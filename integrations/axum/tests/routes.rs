@@ -0,0 +1,395 @@
+use leptos::*;
+use leptos_router::*;
+
+#[component]
+fn RoutedApp(cx: Scope) -> impl IntoView {
+    view! { cx,
+        <Router>
+            <Routes>
+                <Route path="" view=|cx| view! { cx, <p>"Home"</p> }/>
+                <Route path="about" view=|cx| view! { cx, <p>"About"</p> }/>
+            </Routes>
+        </Router>
+    }
+}
+
+#[component]
+fn UnroutedApp(cx: Scope) -> impl IntoView {
+    view! { cx, <p>"No router here"</p> }
+}
+
+#[tokio::test]
+async fn generates_routes_from_router() {
+    let routes = leptos_axum::generate_route_list(|cx| view! { cx, <RoutedApp/> }).await;
+    assert_eq!(routes, vec!["/about".to_string(), "/".to_string()]);
+}
+
+#[tokio::test]
+async fn missing_router_is_reported() {
+    let routes = leptos_axum::try_generate_route_list(|cx| view! { cx, <UnroutedApp/> }).await;
+    assert_eq!(routes, None);
+
+    // the infallible version keeps serving the root
+    let routes = leptos_axum::generate_route_list(|cx| view! { cx, <UnroutedApp/> }).await;
+    assert_eq!(routes, vec!["/".to_string()]);
+}
+
+#[component]
+fn HeaderApp(cx: Scope) -> impl IntoView {
+    view! { cx,
+        <Router>
+            <Routes>
+                <Route path="" view=|cx| view! { cx, <p>"Home"</p> }/>
+                <Route path="about" view=|cx| view! { cx, <p>"About"</p> } headers=vec![("X-Route", "about")]/>
+                <Route path="users" view=|cx| view! { cx, <Outlet/> } headers=vec![("X-Route", "users"), ("X-Section", "users")]>
+                    <Route path=":id" view=|cx| view! { cx, <p>"User"</p> } headers=vec![("x-route", "user")]/>
+                </Route>
+            </Routes>
+        </Router>
+    }
+}
+
+#[tokio::test]
+async fn route_listing_includes_declared_headers() {
+    let mut routes = leptos_axum::generate_route_listing(|cx| view! { cx, <HeaderApp/> }).await;
+    routes.sort_by(|a, b| a.path.cmp(&b.path));
+
+    assert_eq!(
+        routes,
+        vec![
+            RouteListing {
+                path: "/".to_string(),
+                headers: vec![],
+                methods: vec![Method::Get]
+            },
+            RouteListing {
+                path: "/about".to_string(),
+                headers: vec![("X-Route", "about")],
+                methods: vec![Method::Get]
+            },
+            RouteListing {
+                path: "/users/:id".to_string(),
+                headers: vec![("X-Section", "users"), ("x-route", "user")],
+                methods: vec![Method::Get]
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn declared_headers_are_sent_for_their_route_only() {
+    use axum::{body::Body, http::Request};
+    use leptos_axum::LeptosRoutes;
+    use tower::ServiceExt;
+
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let routes = leptos_axum::generate_route_listing(|cx| view! { cx, <HeaderApp/> }).await;
+    let app = axum::Router::new().leptos_routes_with_headers(
+        options,
+        routes,
+        |cx| view! { cx, <HeaderApp/> },
+    );
+
+    let request = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let res = app.clone().oneshot(request("/about")).await.unwrap();
+    assert_eq!(res.headers().get("x-route").unwrap(), "about");
+
+    let res = app.oneshot(request("/")).await.unwrap();
+    assert!(res.headers().get("x-route").is_none());
+}
+
+#[component]
+fn ActionApp(cx: Scope) -> impl IntoView {
+    view! { cx,
+        <Router>
+            <Routes>
+                <Route path="" view=|cx| view! { cx, <p>"Home"</p> }/>
+                <Route path="contact" view=|cx| view! { cx, <Outlet/> } methods=vec![Method::Get, Method::Post]>
+                    <Route path="" view=|cx| view! { cx, <p>"Contact"</p> }/>
+                    <Route path="delete" view=|cx| view! { cx, <p>"Deleted"</p> } methods=vec![Method::Delete]/>
+                </Route>
+            </Routes>
+        </Router>
+    }
+}
+
+#[tokio::test]
+async fn declared_methods_are_routed_to_the_app() {
+    use axum::{body::Body, http::Request};
+    use http::StatusCode;
+    use leptos_axum::LeptosRoutes;
+    use tower::ServiceExt;
+
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let mut routes = leptos_axum::generate_route_listing(|cx| view! { cx, <ActionApp/> }).await;
+    routes.sort_by(|a, b| a.path.cmp(&b.path));
+    let methods: Vec<_> = routes
+        .iter()
+        .map(|listing| listing.methods.clone())
+        .collect();
+    assert_eq!(
+        methods,
+        vec![
+            vec![Method::Get],
+            vec![Method::Get, Method::Post],
+            vec![Method::Delete],
+        ]
+    );
+
+    let app = axum::Router::new().leptos_routes_with_methods(
+        options,
+        routes,
+        |cx| view! { cx, <ActionApp/> },
+    );
+    let request = |method: &str, uri: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let res = app
+        .clone()
+        .oneshot(request("POST", "/contact"))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = app
+        .clone()
+        .oneshot(request("GET", "/contact"))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = app
+        .clone()
+        .oneshot(request("DELETE", "/contact/delete"))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = app
+        .clone()
+        .oneshot(request("GET", "/contact/delete"))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    let res = app.oneshot(request("POST", "/")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[component]
+fn PostApp(cx: Scope) -> impl IntoView {
+    let id = use_context::<leptos_axum::PathParams>(cx)
+        .and_then(|params| params.get("id").map(String::from))
+        .unwrap_or_default();
+    view! { cx,
+        <Router>
+            <Routes>
+                <Route path="posts/:id" view=move |cx| view! { cx, <p data-post=id.clone()>"Post"</p> }/>
+            </Routes>
+        </Router>
+    }
+}
+
+#[tokio::test]
+async fn route_params_are_provided_as_context() {
+    use axum::{body::Body, http::Request};
+    use leptos_axum::LeptosRoutes;
+    use tower::ServiceExt;
+
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let routes = leptos_axum::generate_route_list(|cx| view! { cx, <PostApp/> }).await;
+    assert_eq!(routes, vec!["/posts/:id".to_string()]);
+    let app = axum::Router::new().leptos_routes(options, routes, |cx| view! { cx, <PostApp/> });
+
+    let req = Request::builder()
+        .uri("/posts/42")
+        .body(Body::empty())
+        .unwrap();
+    let res = app.oneshot(req).await.unwrap();
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(html.contains(r#"data-post="42""#), "{html}");
+}
+
+#[component]
+fn CommentApp(cx: Scope) -> impl IntoView {
+    let route = use_context::<leptos_axum::MatchedRoute>(cx)
+        .map(|route| route.0)
+        .unwrap_or_default();
+    view! { cx,
+        <Router>
+            <Routes>
+                <Route path="posts/:id/comments" view=move |cx| view! { cx, <p data-route=route.clone()>"Comments"</p> }/>
+            </Routes>
+        </Router>
+    }
+}
+
+#[tokio::test]
+async fn matched_route_is_provided_as_context() {
+    use axum::{body::Body, http::Request};
+    use leptos_axum::LeptosRoutes;
+    use tower::ServiceExt;
+
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let routes = leptos_axum::generate_route_list(|cx| view! { cx, <CommentApp/> }).await;
+    let app = axum::Router::new().leptos_routes(options, routes, |cx| view! { cx, <CommentApp/> });
+
+    let req = Request::builder()
+        .uri("/posts/42/comments")
+        .body(Body::empty())
+        .unwrap();
+    let res = app.oneshot(req).await.unwrap();
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(
+        html.contains(r#"data-route="/posts/:id/comments""#),
+        "{html}"
+    );
+}
+
+#[tokio::test]
+async fn trailing_slashes_are_normalized_as_configured() {
+    use leptos_axum::{RouteListConfig, TrailingSlash};
+
+    let list = |trailing_slash| {
+        leptos_axum::generate_route_list_with_config(
+            |cx| view! { cx, <HeaderApp/> },
+            RouteListConfig { trailing_slash },
+        )
+    };
+    let mut strict = list(TrailingSlash::Strict).await;
+    strict.sort();
+    assert_eq!(strict, vec!["/", "/about", "/users/:id"]);
+
+    // the root is never duplicated
+    let mut trailing = list(TrailingSlash::RedirectToTrailing).await;
+    trailing.sort();
+    assert_eq!(trailing, vec!["/", "/about/", "/users/:id/"]);
+
+    let mut non_trailing = list(TrailingSlash::RedirectToNonTrailing).await;
+    non_trailing.sort();
+    assert_eq!(non_trailing, vec!["/", "/about", "/users/:id"]);
+}
+
+#[tokio::test]
+async fn other_slash_variant_is_redirected() {
+    use axum::{body::Body, http::Request};
+    use http::{header, StatusCode};
+    use leptos_axum::{LeptosRoutes, RouteListConfig, TrailingSlash};
+    use tower::ServiceExt;
+
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let routes = leptos_axum::generate_route_list_with_config(
+        |cx| view! { cx, <HeaderApp/> },
+        RouteListConfig {
+            trailing_slash: TrailingSlash::RedirectToTrailing,
+        },
+    )
+    .await;
+    let app = axum::Router::new()
+        .leptos_routes(options, routes.clone(), |cx| view! { cx, <HeaderApp/> })
+        .merge(leptos_axum::trailing_slash_redirects(&routes));
+
+    let request = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let res = app
+        .clone()
+        .oneshot(request("/users/7?tab=posts"))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(res.headers()[header::LOCATION], "/users/7/?tab=posts");
+
+    let res = app.clone().oneshot(request("/users/7/")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = app.oneshot(request("/")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[component]
+fn SplatApp(cx: Scope) -> impl IntoView {
+    view! { cx,
+        <Router>
+            <Routes>
+                <Route path="" view=|cx| view! { cx, <p>"Home"</p> }/>
+                <Route path="users/:id" view=|cx| view! { cx, <Outlet/> }>
+                    <Route path="files/*path" view=|cx| view! { cx, <p>"Files"</p> }/>
+                </Route>
+                <Route path="*" view=|cx| view! { cx, <p>"Not Found"</p> }/>
+            </Routes>
+        </Router>
+    }
+}
+
+#[tokio::test]
+async fn splats_become_axum_wildcards() {
+    use axum::{body::Body, http::Request};
+    use http::StatusCode;
+    use leptos_axum::LeptosRoutes;
+    use tower::ServiceExt;
+
+    let mut routes = leptos_axum::generate_route_list(|cx| view! { cx, <SplatApp/> }).await;
+    routes.sort();
+    assert_eq!(
+        routes,
+        vec!["/", "/*any", "/users/:id/files", "/users/:id/files/*path"]
+    );
+
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let app = axum::Router::new().leptos_routes(options, routes, |cx| view! { cx, <SplatApp/> });
+    let get = |uri: &str| {
+        let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let app = app.clone();
+        async move {
+            let res = app.oneshot(req).await.unwrap();
+            let status = res.status();
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+
+    for uri in ["/users/7/files/a/b.txt", "/users/7/files"] {
+        let (status, body) = get(uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(
+            body.contains("Files"),
+            "{uri} should render the files route"
+        );
+    }
+    let (status, body) = get("/somewhere/else").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("Not Found"));
+}
+
+#[tokio::test]
+async fn excluded_paths_are_left_to_their_own_handlers() {
+    use axum::{body::Body, http::Request, routing::get};
+    use leptos_axum::LeptosRoutes;
+    use tower::ServiceExt;
+
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let routes = leptos_axum::generate_route_list(|cx| view! { cx, <RoutedApp/> }).await;
+    // adding `/about` twice would panic
+    let app = axum::Router::new()
+        .route("/about", get(|| async { "handled elsewhere" }))
+        .leptos_routes_with_exclusions(options, routes, &["/about"], |cx| {
+            view! { cx, <RoutedApp/> }
+        });
+
+    let body = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            let res = app.oneshot(req).await.unwrap();
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        }
+    };
+
+    assert_eq!(body("/about").await, "handled elsewhere");
+    assert!(body("/").await.contains("Home"));
+}
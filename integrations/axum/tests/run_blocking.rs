@@ -0,0 +1,17 @@
+use leptos::*;
+
+#[tokio::test]
+async fn run_blocking_returns_the_result() {
+    let factorial = leptos_axum::run_blocking(|| (1..=20u64).product::<u64>())
+        .await
+        .unwrap();
+
+    assert_eq!(factorial, 2_432_902_008_176_640_000);
+}
+
+#[tokio::test]
+async fn run_blocking_reports_panics_as_server_errors() {
+    let result = leptos_axum::run_blocking(|| -> u64 { panic!("out of memory") }).await;
+
+    assert!(matches!(result, Err(ServerFnError::ServerError(_))));
+}
@@ -0,0 +1,42 @@
+use axum::{body::Body, http::Request};
+use leptos::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static FETCHES: AtomicUsize = AtomicUsize::new(0);
+
+#[component]
+fn Forecast(cx: Scope) -> impl IntoView {
+    let forecast = create_resource(
+        cx,
+        || (),
+        |_| {
+            FETCHES.fetch_add(1, Ordering::SeqCst);
+            async { "Sunny".to_string() }
+        },
+    );
+    view! { cx,
+        <Suspense fallback=|| ()>
+            {move || forecast.read().map(|forecast| view! { cx, <p>{forecast}</p> })}
+        </Suspense>
+    }
+}
+
+#[tokio::test]
+async fn seeded_resources_are_not_fetched() {
+    // the hydration state of an earlier render of the page, saved by the caller
+    let seed: Vec<(ResourceId, String)> =
+        serde_json::from_str(r#"[[{"idx":1,"version":1},"\"Cloudy\""]]"#).unwrap();
+
+    let handler = leptos_axum::render_app_to_stream_with_context(
+        LeptosOptions::builder().output_name("test_app").build(),
+        move |cx| cx.seed_resources(seed.clone()),
+        |cx| view! { cx, <Forecast/> },
+    );
+    let res = handler(Request::builder().uri("/").body(Body::empty()).unwrap()).await;
+    let html = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let html = String::from_utf8(html.to_vec()).unwrap();
+
+    assert!(html.contains("Cloudy"));
+    assert!(!html.contains("Sunny"));
+    assert_eq!(FETCHES.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,1365 @@
+use axum::{body::Body, extract::Path, response::IntoResponse};
+use futures::{
+    channel::{mpsc, oneshot},
+    StreamExt,
+};
+use http::{header, HeaderMap, Request, StatusCode};
+use hyper::body::HttpBody;
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+// The `#[server]` macro only generates the server half when the calling crate has an `ssr`
+// feature, so the test server functions implement `ServerFn` by hand.
+#[derive(Clone, Serialize, Deserialize)]
+struct Whoami {}
+
+impl ServerFn for Whoami {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "whoami"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { Err(leptos_axum::abort_with_status(cx, StatusCode::UNAUTHORIZED)) })
+    }
+}
+
+// the items streamed by `Numbers`, which the test sends one by one
+static NUMBERS: Mutex<Option<mpsc::UnboundedReceiver<u32>>> = Mutex::new(None);
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Numbers {}
+
+impl ServerFn for Numbers {
+    type Output = NdjsonStream<u32>;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "numbers"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Ndjson
+    }
+
+    fn into_ndjson(output: Self::Output) -> Result<NdjsonLines, ServerFnError> {
+        Ok(output.into_lines())
+    }
+
+    fn call_fn(
+        self,
+        _cx: Scope,
+    ) -> Pin<Box<dyn Future<Output = Result<NdjsonStream<u32>, ServerFnError>>>> {
+        Box::pin(async move {
+            let numbers = NUMBERS.lock().unwrap().take().unwrap();
+            Ok(NdjsonStream::new(numbers.map(Ok)))
+        })
+    }
+}
+
+fn server_fn_request(name: &str) -> (Path<String>, HeaderMap, Request<Body>) {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::ACCEPT, "application/json".parse().unwrap());
+    let req = Request::builder()
+        .method("POST")
+        .uri(format!("/api/{name}"))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::empty())
+        .unwrap();
+    (Path(name.to_string()), headers, req)
+}
+
+#[tokio::test]
+async fn abort_with_status_sets_status_and_minimal_body() {
+    _ = Whoami::register();
+    let (path, headers, req) = server_fn_request(Whoami::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "Unauthorized");
+}
+
+#[tokio::test]
+async fn ndjson_results_are_received_incrementally() {
+    _ = Numbers::register();
+    let (numbers, rx) = mpsc::unbounded();
+    *NUMBERS.lock().unwrap() = Some(rx);
+
+    let (path, mut headers, req) = server_fn_request(Numbers::url());
+    headers.insert(header::ACCEPT, "application/x-ndjson".parse().unwrap());
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/x-ndjson"
+    );
+
+    let chunks = futures::stream::unfold(res.into_body(), |mut body| async move {
+        let chunk = body
+            .data()
+            .await?
+            .map(|chunk| chunk.to_vec())
+            .map_err(|e| ServerFnError::Request(e.to_string()));
+        Some((chunk, body))
+    });
+    let mut items = NdjsonStream::<u32>::decode(chunks);
+    // each item arrives before the next one has even been produced
+    for n in [1, 2, 3] {
+        numbers.unbounded_send(n).unwrap();
+        assert_eq!(
+            tokio::time::timeout(Duration::from_secs(5), items.next())
+                .await
+                .expect("timed out waiting for an item")
+                .unwrap()
+                .unwrap(),
+            n
+        );
+    }
+    drop(numbers);
+    assert!(tokio::time::timeout(Duration::from_secs(5), items.next())
+        .await
+        .expect("timed out waiting for an item")
+        .is_none());
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Download {
+    path: String,
+}
+
+impl ServerFn for Download {
+    type Output = FileDownload;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "download"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::File
+    }
+
+    fn into_file(output: Self::Output) -> Result<FileDownload, ServerFnError> {
+        Ok(output)
+    }
+
+    fn call_fn(
+        self,
+        _cx: Scope,
+    ) -> Pin<Box<dyn Future<Output = Result<FileDownload, ServerFnError>>>> {
+        Box::pin(async move { Ok(FileDownload::new(self.path).with_filename("report.csv")) })
+    }
+}
+
+#[tokio::test]
+async fn files_are_streamed_as_downloads() {
+    _ = Download::register();
+    let contents = "id,name\n".to_string() + &"1,Alice\n".repeat(10_000);
+    let path =
+        std::env::temp_dir().join(format!("leptos_axum_download_{}.csv", std::process::id()));
+    std::fs::write(&path, &contents).unwrap();
+
+    // a <form> submit gets the file too, rather than a redirect
+    let (path_param, mut headers, req) = server_fn_request(Download::url());
+    headers.insert(header::ACCEPT, "text/html".parse().unwrap());
+    let (parts, _) = req.into_parts();
+    let body = form_urlencoded::Serializer::new(String::new())
+        .append_pair("path", path.to_str().unwrap())
+        .finish();
+    let req = Request::from_parts(parts, Body::from(body));
+    let res = leptos_axum::handle_server_fns(path_param, headers, req)
+        .await
+        .into_response();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/octet-stream"
+    );
+    assert_eq!(
+        res.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+        r#"attachment; filename="report.csv""#
+    );
+    assert_eq!(
+        res.headers().get(header::CONTENT_LENGTH).unwrap(),
+        &contents.len().to_string()
+    );
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, contents);
+}
+
+#[tokio::test]
+async fn missing_files_are_not_found() {
+    _ = Download::register();
+    let (path_param, headers, req) = server_fn_request(Download::url());
+    let (parts, _) = req.into_parts();
+    let req = Request::from_parts(parts, Body::from("path=%2Fdoes%2Fnot%2Fexist.csv"));
+
+    let res = leptos_axum::handle_server_fns(path_param, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Greet {}
+
+impl ServerFn for Greet {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "greet"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { Ok("hello".to_string()) })
+    }
+}
+
+fn form_request(name: &str) -> (Path<String>, HeaderMap, Request<Body>) {
+    let (path, mut headers, req) = server_fn_request(name);
+    headers.insert(header::ACCEPT, "text/html".parse().unwrap());
+    headers.insert(header::REFERER, "/contact".parse().unwrap());
+    (path, headers, req)
+}
+
+#[tokio::test]
+async fn form_submissions_are_redirected_by_default() {
+    _ = Greet::register();
+    let (path, headers, req) = form_request(Greet::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::SEE_OTHER);
+    assert_eq!(res.headers().get(header::LOCATION).unwrap(), "/contact");
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CreateOrder {}
+
+impl ServerFn for CreateOrder {
+    type Output = ();
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "create_order"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, cx: Scope) -> Pin<Box<dyn Future<Output = Result<(), ServerFnError>>>> {
+        Box::pin(async move {
+            let res_options = use_context::<leptos_axum::ResponseOptions>(cx).unwrap();
+            res_options.set_location("/orders/7").await.unwrap();
+            Ok(())
+        })
+    }
+}
+
+#[tokio::test]
+async fn form_submissions_follow_a_location_set_by_the_server_fn() {
+    _ = CreateOrder::register();
+    let (path, headers, req) = form_request(CreateOrder::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::SEE_OTHER);
+    let locations: Vec<_> = res.headers().get_all(header::LOCATION).iter().collect();
+    assert_eq!(locations, vec!["/orders/7"]);
+}
+
+#[tokio::test]
+async fn api_mode_never_redirects() {
+    _ = Greet::register();
+    let (path, headers, req) = form_request(Greet::url());
+
+    let res = leptos_axum::handle_server_fns_api(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers().get(header::LOCATION).is_none());
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "\"hello\"");
+}
+
+#[tokio::test]
+async fn concurrent_calls_all_complete() {
+    _ = Greet::register();
+
+    let calls = (0..100).map(|_| {
+        let (path, headers, req) = server_fn_request(Greet::url());
+        leptos_axum::handle_server_fns_api(path, headers, req)
+    });
+    let responses = tokio::time::timeout(Duration::from_secs(10), futures::future::join_all(calls))
+        .await
+        .expect("timed out waiting for the calls to complete");
+
+    assert_eq!(responses.len(), 100);
+    for res in responses {
+        let res = res.into_response();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "\"hello\"");
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Sleepy {}
+
+impl ServerFn for Sleepy {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "sleepy"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok("done".to_string())
+        })
+    }
+}
+
+// Adds the options to the request like an `Extension` layer would
+fn with_options(mut req: Request<Body>, options: LeptosOptions) -> Request<Body> {
+    req.extensions_mut().insert(Arc::new(options));
+    req
+}
+
+#[tokio::test]
+async fn bodies_over_max_body_size_are_rejected() {
+    _ = Greet::register();
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .max_body_size(16)
+        .build();
+
+    let (path, headers, req) = server_fn_request(Greet::url());
+    let (parts, _) = req.into_parts();
+    let req = Request::from_parts(parts, Body::from("name=".to_string() + &"a".repeat(64)));
+    let res = leptos_axum::handle_server_fns(path, headers, with_options(req, options.clone()))
+        .await
+        .into_response();
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let (path, headers, req) = server_fn_request(Greet::url());
+    let res = leptos_axum::handle_server_fns(path, headers, with_options(req, options))
+        .await
+        .into_response();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn limits_apply_to_the_handler_with_options_without_a_layer() {
+    _ = Greet::register();
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .max_body_size(16)
+        .build();
+    let handler = leptos_axum::handle_server_fns_with_options(options);
+
+    let (path, headers, req) = server_fn_request(Greet::url());
+    let (parts, _) = req.into_parts();
+    let req = Request::from_parts(parts, Body::from("name=".to_string() + &"a".repeat(64)));
+    let res = handler(path, headers, req).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let (path, headers, req) = server_fn_request(Greet::url());
+    let res = handler(path, headers, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn server_fns_over_request_timeout_time_out() {
+    _ = Sleepy::register();
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .request_timeout(50)
+        .build();
+    let (path, headers, req) = server_fn_request(Sleepy::url());
+
+    let res = tokio::time::timeout(
+        Duration::from_secs(2),
+        leptos_axum::handle_server_fns(path, headers, with_options(req, options)),
+    )
+    .await
+    .expect("the handler should give up before the server function returns")
+    .into_response();
+
+    assert_eq!(res.status(), StatusCode::REQUEST_TIMEOUT);
+}
+
+// receives the cancellation token of the running `Stalled` call
+static STALLED: Mutex<Option<oneshot::Sender<leptos_axum::CancellationToken>>> = Mutex::new(None);
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Stalled {}
+
+impl ServerFn for Stalled {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "stalled"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move {
+            let cancel = use_context::<leptos_axum::CancellationToken>(cx).unwrap();
+            _ = STALLED.lock().unwrap().take().unwrap().send(cancel.clone());
+            cancel.cancelled().await;
+            Err(ServerFnError::ServerError("cancelled".into()))
+        })
+    }
+}
+
+#[tokio::test]
+async fn dropping_the_client_cancels_the_token() {
+    _ = Stalled::register();
+    let (tx, rx) = oneshot::channel();
+    *STALLED.lock().unwrap() = Some(tx);
+    let (path, headers, req) = server_fn_request(Stalled::url());
+
+    // the handler future is dropped, as if the client went away, once the call has started
+    let cancel = tokio::select! {
+        _ = leptos_axum::handle_server_fns(path, headers, req) => {
+            panic!("the server function should still be running")
+        }
+        cancel = rx => cancel.unwrap(),
+    };
+
+    assert!(cancel.is_cancelled());
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct AddUser {
+    name: String,
+    age: u32,
+}
+
+impl ServerFn for AddUser {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "add_user"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { Ok(format!("{} is {}", self.name, self.age)) })
+    }
+}
+
+#[tokio::test]
+async fn registered_body_deserializers_parse_their_content_type() {
+    _ = AddUser::register();
+    // accepts bodies like `name=Alice;age=42`
+    leptos::register_body_deserializer("text/x-semicolon-pairs", |body| {
+        let body = std::str::from_utf8(body).map_err(|e| ServerFnError::Args(e.to_string()))?;
+        let fields = body
+            .split(';')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.to_string(), serde_json::Value::from(value)))
+            .collect();
+        Ok(serde_json::Value::Object(fields))
+    })
+    .unwrap();
+
+    let (path, headers, req) = server_fn_request(AddUser::url());
+    let (mut parts, _) = req.into_parts();
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        "text/x-semicolon-pairs; charset=utf-8".parse().unwrap(),
+    );
+    let req = Request::from_parts(parts, Body::from("name=Alice;age=42"));
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "\"Alice is 42\"");
+
+    // other content types are still parsed according to the server function's encoding
+    let (path, headers, req) = server_fn_request(AddUser::url());
+    let (parts, _) = req.into_parts();
+    let req = Request::from_parts(parts, Body::from("name=Bob&age=7"));
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "\"Bob is 7\"");
+}
+
+// Two apps' versions of the same server function, which return which app they belong to
+#[derive(Clone, Serialize, Deserialize)]
+struct AppNameA {}
+
+impl ServerFn for AppNameA {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "app_name"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { Ok("a".to_string()) })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct AppNameB {}
+
+impl ServerFn for AppNameB {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "app_name"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { Ok("b".to_string()) })
+    }
+}
+
+#[tokio::test]
+async fn separate_registries_keep_same_named_server_fns_apart() {
+    let registry_a = ServerFnRegistry::new();
+    let registry_b = ServerFnRegistry::new();
+    AppNameA::register_in(&registry_a).unwrap();
+    AppNameB::register_in(&registry_b).unwrap();
+    assert!(server_fn_by_path(AppNameA::url()).is_none());
+
+    for (registry, expected) in [(registry_a, "\"a\""), (registry_b, "\"b\"")] {
+        let (path, headers, mut req) = server_fn_request(AppNameA::url());
+        req.extensions_mut().insert(registry);
+        let res = leptos_axum::handle_server_fns(path, headers, req)
+            .await
+            .into_response();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, expected);
+    }
+
+    // without a registry, the handler only sees the global one
+    let (path, headers, req) = server_fn_request(AppNameA::url());
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+// a value that middleware inserts into the request's extensions
+#[derive(Clone)]
+struct SignedInUser(String);
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CurrentUser {}
+
+impl ServerFn for CurrentUser {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "current_user"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move {
+            let req_parts = use_context::<leptos_axum::RequestParts>(cx).unwrap();
+            req_parts
+                .extension::<SignedInUser>()
+                .map(|SignedInUser(name)| name.clone())
+                .ok_or_else(|| ServerFnError::ServerError("not signed in".into()))
+        })
+    }
+}
+
+#[tokio::test]
+async fn request_extensions_are_available_to_server_fns() {
+    _ = CurrentUser::register();
+    let (path, headers, mut req) = server_fn_request(CurrentUser::url());
+    req.extensions_mut().insert(SignedInUser("alice".into()));
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "\"alice\"");
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Uncached {}
+
+impl ServerFn for Uncached {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "uncached"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    // what `#[server(Uncached, "/api", headers(cache_control = "no-store"))]` declares
+    fn headers() -> &'static [(&'static str, &'static str)] {
+        &[("cache-control", "no-store")]
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { Ok("fresh".to_string()) })
+    }
+}
+
+#[tokio::test]
+async fn declared_headers_are_added_to_the_response() {
+    _ = Uncached::register();
+    let (path, headers, req) = server_fn_request(Uncached::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers().get(header::CACHE_CONTROL).unwrap(),
+        "no-store"
+    );
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SwitchTheme {}
+
+impl ServerFn for SwitchTheme {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "switch_theme"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move {
+            let cookies = use_context::<leptos_axum::CookieJar>(cx).unwrap();
+            let previous = cookies.get("theme").unwrap().value().to_string();
+            cookies.set(
+                leptos_axum::Cookie::parse(
+                    "theme=dark; Path=/; HttpOnly; Secure; SameSite=Lax; Max-Age=3600",
+                )
+                .unwrap(),
+            );
+            cookies.remove(leptos_axum::Cookie::build("session", "").path("/").finish());
+            Ok(previous)
+        })
+    }
+}
+
+#[tokio::test]
+async fn cookie_jar_changes_are_sent_as_set_cookie_headers() {
+    _ = SwitchTheme::register();
+    let (path, headers, mut req) = server_fn_request(SwitchTheme::url());
+    req.headers_mut()
+        .insert(header::COOKIE, "theme=light; session=abc".parse().unwrap());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let set_cookies = res
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .map(|value| value.to_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(set_cookies.len(), 2);
+
+    let theme = set_cookies
+        .iter()
+        .find(|c| c.starts_with("theme="))
+        .unwrap();
+    assert!(theme.starts_with("theme=dark;"));
+    for attribute in [
+        "Path=/",
+        "HttpOnly",
+        "Secure",
+        "SameSite=Lax",
+        "Max-Age=3600",
+    ] {
+        assert!(theme.contains(attribute), "{theme} is missing {attribute}");
+    }
+    let session = set_cookies
+        .iter()
+        .find(|c| c.starts_with("session="))
+        .unwrap();
+    assert!(session.contains("Max-Age=0"));
+
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "\"light\"");
+}
+
+#[tokio::test]
+async fn errors_are_sent_as_json_when_it_is_accepted() {
+    _ = CurrentUser::register();
+    let (path, headers, req) = server_fn_request(CurrentUser::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(
+        body,
+        r#"{"error":"error running server function: not signed in","kind":"ServerError"}"#
+    );
+}
+
+#[tokio::test]
+async fn errors_are_sent_as_plain_text_to_forms() {
+    _ = CurrentUser::register();
+    let (path, mut headers, req) = server_fn_request(CurrentUser::url());
+    headers.insert(header::ACCEPT, "text/html".parse().unwrap());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(res.headers().get(header::CONTENT_TYPE).is_none());
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "error running server function: not signed in");
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct GetPost {
+    id: u32,
+}
+
+impl ServerFn for GetPost {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "get_post"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { Ok(format!("post {}", self.id)) })
+    }
+}
+
+#[tokio::test]
+async fn arguments_that_cannot_be_read_are_a_bad_request() {
+    _ = GetPost::register();
+    // the body is empty, so it's missing `id`
+    let (path, headers, req) = server_fn_request(GetPost::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["kind"], "Deserialization");
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SubmitPost {}
+
+impl ServerFn for SubmitPost {
+    type Output = ();
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "submit_post"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, cx: Scope) -> Pin<Box<dyn Future<Output = Result<(), ServerFnError>>>> {
+        Box::pin(async move {
+            let res_options = use_context::<leptos_axum::ResponseOptions>(cx).unwrap();
+            res_options
+                .set_status(StatusCode::UNPROCESSABLE_ENTITY)
+                .await;
+            res_options
+                .insert_header_str("x-invalid-field", "title")
+                .await
+                .unwrap();
+            Err(ServerFnError::ServerError("a title is required".into()))
+        })
+    }
+}
+
+#[tokio::test]
+async fn errors_keep_the_status_and_headers_set_by_the_server_fn() {
+    _ = SubmitPost::register();
+    let (path, headers, req) = server_fn_request(SubmitPost::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(res.headers().get("x-invalid-field").unwrap(), "title");
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        error["error"],
+        "error running server function: a title is required"
+    );
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct UploadAttachment {
+    title: String,
+    count: u32,
+    notify: Option<bool>,
+    attachment: FileUpload,
+    thumbnail: Vec<u8>,
+    tags: Vec<String>,
+}
+
+impl ServerFn for UploadAttachment {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "upload_attachment"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Multipart
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move {
+            Ok(format!(
+                "{} x{} notify={:?}: {} ({}, {} bytes) thumbnail={:?} tags={:?}",
+                self.title,
+                self.count,
+                self.notify,
+                self.attachment.filename().unwrap_or_default(),
+                self.attachment.content_type().unwrap_or_default(),
+                self.attachment.bytes().len(),
+                self.thumbnail,
+                self.tags,
+            ))
+        })
+    }
+}
+
+// A `multipart/form-data` body like a browser sends, with a boundary it picked
+fn multipart_request(name: &str, parts: &[(&str, Option<(&str, &str)>, &[u8])]) -> Request<Body> {
+    const BOUNDARY: &str = "----WebKitFormBoundary7MA4YWxkTrZu0gW";
+    let mut body = Vec::new();
+    for (field, file, data) in parts {
+        body.extend_from_slice(format!("--{BOUNDARY}\r\n").as_bytes());
+        match file {
+            Some((filename, content_type)) => body.extend_from_slice(
+                format!(
+                    "Content-Disposition: form-data; name=\"{field}\"; filename=\"{filename}\"\r\n\
+                     Content-Type: {content_type}\r\n\r\n"
+                )
+                .as_bytes(),
+            ),
+            None => body.extend_from_slice(
+                format!("Content-Disposition: form-data; name=\"{field}\"\r\n\r\n").as_bytes(),
+            ),
+        }
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+
+    Request::builder()
+        .method("POST")
+        .uri(format!("/api/{name}"))
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn multipart_bodies_are_decoded_into_files_and_fields() {
+    _ = UploadAttachment::register();
+    let req = multipart_request(
+        UploadAttachment::url(),
+        &[
+            ("title", None, b"Q3 report"),
+            ("count", None, b"2"),
+            ("notify", None, b"on"),
+            (
+                "attachment",
+                Some(("report; final.pdf", "application/pdf")),
+                b"%PDF\r\n\x00\xff",
+            ),
+            ("thumbnail", Some(("thumb.png", "image/png")), &[1, 2, 3]),
+            ("tags", None, b"finance"),
+            ("tags", None, b"quarterly"),
+        ],
+    );
+    let (path, headers, _) = server_fn_request(UploadAttachment::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let message: String = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        message,
+        "Q3 report x2 notify=Some(true): report; final.pdf (application/pdf, 8 bytes) \
+         thumbnail=[1, 2, 3] tags=[\"finance\", \"quarterly\"]"
+    );
+}
+
+#[tokio::test]
+async fn empty_sequences_in_multipart_bodies_have_no_parts() {
+    _ = UploadAttachment::register();
+    // an empty `Vec` is sent without any parts, like unchecked checkboxes
+    let req = multipart_request(
+        UploadAttachment::url(),
+        &[
+            ("title", None, b"Q3 report"),
+            ("count", None, b"2"),
+            (
+                "attachment",
+                Some(("report.pdf", "application/pdf")),
+                b"%PDF",
+            ),
+        ],
+    );
+    let (path, headers, _) = server_fn_request(UploadAttachment::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let message: String = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        message,
+        "Q3 report x2 notify=None: report.pdf (application/pdf, 4 bytes) thumbnail=[] tags=[]"
+    );
+}
+
+#[tokio::test]
+async fn missing_files_in_multipart_bodies_are_a_bad_request() {
+    _ = UploadAttachment::register();
+    // the browser sends an empty part for a file input that was left empty
+    let req = multipart_request(
+        UploadAttachment::url(),
+        &[
+            ("title", None, b"Q3 report"),
+            ("count", None, b"2"),
+            ("attachment", Some(("", "application/octet-stream")), b""),
+            ("thumbnail", Some(("thumb.png", "image/png")), &[1]),
+        ],
+    );
+    let (path, headers, _) = server_fn_request(UploadAttachment::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+// A `multipart/form-data` request with the given body, sent with `boundary` in its `Content-Type`
+fn raw_multipart_request(name: &str, boundary: &str, body: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/api/{name}"))
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary=\"{boundary}\""),
+        )
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn the_preamble_of_multipart_bodies_is_skipped() {
+    _ = UploadAttachment::register();
+    let req = raw_multipart_request(
+        UploadAttachment::url(),
+        "simple boundary",
+        "This is the preamble. It is to be ignored.\r\n\
+         --simple boundary\r\n\
+         Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+         Q3 report\r\n\
+         --simple boundary \r\n\
+         Content-Disposition: form-data; name=\"count\"\r\n\r\n\
+         2\r\n\
+         --simple boundary\r\n\
+         Content-Disposition: form-data; name=\"attachment\"; filename=\"a.txt\"\r\n\r\n\
+         hi\r\n\
+         --simple boundary\r\n\
+         Content-Disposition: form-data; name=\"thumbnail\"; filename=\"t.png\"\r\n\r\n\
+         \x01\r\n\
+         --simple boundary\r\n\
+         Content-Disposition: form-data; name=\"tags\"\r\n\r\n\
+         finance\r\n\
+         --simple boundary--\r\n\
+         This is the epilogue.",
+    );
+    let (path, headers, _) = server_fn_request(UploadAttachment::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let message: String = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        message,
+        "Q3 report x2 notify=None: a.txt (, 2 bytes) thumbnail=[1] tags=[\"finance\"]"
+    );
+}
+
+#[tokio::test]
+async fn multipart_bodies_must_use_the_boundary_of_their_content_type() {
+    _ = GetPost::register();
+    _ = UploadAttachment::register();
+    let body = "--other\r\n\
+                Content-Disposition: form-data; name=\"id\"\r\n\r\n\
+                7\r\n\
+                --other--\r\n";
+
+    for name in [GetPost::url(), UploadAttachment::url()] {
+        let req = raw_multipart_request(name, "declared", body);
+        let (path, headers, _) = server_fn_request(name);
+
+        let res = leptos_axum::handle_server_fns(path, headers, req)
+            .await
+            .into_response();
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn multipart_text_fields_are_passed_to_url_encoded_server_fns() {
+    _ = GetPost::register();
+    let req = multipart_request(
+        GetPost::url(),
+        &[
+            ("id", None, b"7"),
+            ("cover", Some(("cover.png", "image/png")), &[0, 1]),
+        ],
+    );
+    let (path, headers, _) = server_fn_request(GetPost::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "\"post 7\"");
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Panicky {}
+
+impl ServerFn for Panicky {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "panicky"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { panic!("the database connection was poisoned") })
+    }
+}
+
+#[tokio::test]
+async fn panicking_server_fns_get_an_internal_server_error() {
+    _ = Panicky::register();
+    let (path, headers, req) = server_fn_request(Panicky::url());
+
+    let res = tokio::time::timeout(
+        Duration::from_secs(2),
+        leptos_axum::handle_server_fns(path, headers, req),
+    )
+    .await
+    .expect("the handler should respond instead of hanging")
+    .into_response();
+
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    // the panic's message stays on the server
+    assert_eq!(body, "Internal Server Error");
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct ApiStatus {}
+
+impl ServerFn for ApiStatus {
+    type Output = String;
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "api_status"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn required_headers() -> &'static [&'static str] {
+        &["X-Api-Key"]
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<String, ServerFnError>>>> {
+        Box::pin(async move { Ok("ok".to_string()) })
+    }
+}
+
+#[tokio::test]
+async fn requests_missing_a_required_header_are_a_bad_request() {
+    _ = ApiStatus::register();
+    let (path, headers, req) = server_fn_request(ApiStatus::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(
+        body,
+        r#"{"error":"missing argument X-Api-Key","kind":"MissingArg"}"#
+    );
+
+    let (path, headers, mut req) = server_fn_request(ApiStatus::url());
+    req.headers_mut()
+        .insert("x-api-key", "secret".parse().unwrap());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "\"ok\"");
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct ExportCsv {}
+
+impl ServerFn for ExportCsv {
+    type Output = ();
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "export_csv"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, cx: Scope) -> Pin<Box<dyn Future<Output = Result<(), ServerFnError>>>> {
+        Box::pin(async move {
+            let res_options = use_context::<leptos_axum::ResponseOptions>(cx).unwrap();
+            res_options.set_status(StatusCode::CREATED).await;
+            res_options
+                .set_body("text/csv", "id,title\n1,Hello\n")
+                .await
+                .unwrap();
+            Ok(())
+        })
+    }
+}
+
+#[tokio::test]
+async fn a_body_set_by_the_server_fn_replaces_its_result() {
+    _ = ExportCsv::register();
+    let (path, headers, req) = server_fn_request(ExportCsv::url());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::CREATED);
+    assert_eq!(res.headers().get(header::CONTENT_TYPE).unwrap(), "text/csv");
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "id,title\n1,Hello\n");
+}
+
+#[tokio::test]
+async fn buffered_results_are_sent_with_their_length() {
+    _ = Greet::register();
+    let (path, mut headers, req) = server_fn_request(Greet::url());
+    headers.insert(header::ACCEPT, "application/json".parse().unwrap());
+
+    let res = leptos_axum::handle_server_fns(path, headers, req)
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let length = res.headers().get(header::CONTENT_LENGTH).unwrap().clone();
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(length, body.len().to_string().as_str());
+}
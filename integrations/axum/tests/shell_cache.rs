@@ -0,0 +1,103 @@
+use axum::{
+    body::{Body, StreamBody},
+    http::Request,
+};
+use leptos::*;
+use std::{
+    future::Future,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tracing::{span, Event, Metadata, Subscriber};
+
+// Counts how many times the head of the app shell is built.
+static SHELLS_BUILT: AtomicUsize = AtomicUsize::new(0);
+
+struct ShellCounter;
+
+impl Subscriber for ShellCounter {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "leptos_axum::shell"
+    }
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+    fn event(&self, _event: &Event<'_>) {
+        SHELLS_BUILT.fetch_add(1, Ordering::SeqCst);
+    }
+    fn enter(&self, _span: &span::Id) {}
+    fn exit(&self, _span: &span::Id) {}
+}
+
+#[component]
+fn App(cx: Scope) -> impl IntoView {
+    view! { cx, <p>"Hello, world!"</p> }
+}
+
+fn request(lang: &str) -> Request<Body> {
+    Request::builder()
+        .uri("/")
+        .header(http::header::ACCEPT_LANGUAGE, lang)
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn render<F>(handler: &impl Fn(Request<Body>) -> F, lang: &str) -> String
+where
+    F: Future<Output = http::Response<StreamBody<leptos_axum::PinnedHtmlStream>>>,
+{
+    let res = handler(request(lang)).await;
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+fn built() -> usize {
+    SHELLS_BUILT.swap(0, Ordering::SeqCst)
+}
+
+// This is the only test in this file, because it sets the global tracing subscriber.
+#[tokio::test]
+async fn cached_shell_is_only_built_once_per_locale() {
+    tracing::subscriber::set_global_default(ShellCounter).unwrap();
+    let options = LeptosOptions::builder()
+        .output_name("test_app")
+        .cache_shell(true)
+        .build();
+
+    let handler = leptos_axum::render_app_to_stream(options.clone(), |cx| view! { cx, <App/> });
+    let first = render(&handler, "en").await;
+    let second = render(&handler, "en").await;
+    assert_eq!(first, second);
+    assert_eq!(built(), 1);
+
+    // every locale has its own shell
+    let french = render(&handler, "fr").await;
+    assert!(french.contains(r#"<html lang="fr">"#));
+    render(&handler, "fr").await;
+    assert_eq!(built(), 1);
+
+    // a shell with a nonce can't be reused
+    let csp_options = options.clone();
+    let handler = leptos_axum::render_app_to_stream_with_context(
+        options.clone(),
+        move |cx| {
+            leptos_axum::set_content_security_policy(
+                cx,
+                &csp_options,
+                CspNonce("abc123".to_string()),
+            )
+        },
+        |cx| view! { cx, <App/> },
+    );
+    render(&handler, "en").await;
+    render(&handler, "en").await;
+    assert_eq!(built(), 2);
+
+    // without the option, the shell is built for every page
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <App/> });
+    render(&handler, "en").await;
+    render(&handler, "en").await;
+    assert_eq!(built(), 2);
+}
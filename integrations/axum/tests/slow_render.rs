@@ -0,0 +1,91 @@
+use axum::body::Body;
+use http::Request;
+use leptos::*;
+use leptos_config::LeptosOptions;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// Collects the fields of every `leptos_axum::slow_render` event.
+#[derive(Clone, Default)]
+struct SlowRenderCollector(Arc<Mutex<Vec<HashMap<String, String>>>>);
+
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl Subscriber for SlowRenderCollector {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "leptos_axum::slow_render"
+    }
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = HashMap::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        self.0.lock().unwrap().push(fields);
+    }
+    fn enter(&self, _span: &span::Id) {}
+    fn exit(&self, _span: &span::Id) {}
+}
+
+#[component]
+fn SlowApp(cx: Scope) -> impl IntoView {
+    std::thread::sleep(Duration::from_millis(50));
+    view! { cx, <p>"Finally!"</p> }
+}
+
+async fn render(slow_render_threshold: Option<u64>) -> Vec<HashMap<String, String>> {
+    let mut options = LeptosOptions::builder().output_name("test_app").build();
+    options.slow_render_threshold = slow_render_threshold;
+    let collector = SlowRenderCollector::default();
+    let _guard = tracing::subscriber::set_default(collector.clone());
+
+    let handler = leptos_axum::render_app_to_stream(options, |cx| view! { cx, <SlowApp/> });
+    let req = Request::builder().uri("/slow").body(Body::empty()).unwrap();
+    let res = handler(req).await;
+    _ = hyper::body::to_bytes(res.into_body()).await.unwrap();
+
+    let events = collector.0.lock().unwrap().clone();
+    events
+}
+
+#[tokio::test]
+async fn renders_over_the_threshold_are_logged() {
+    let events = render(Some(10)).await;
+
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event["message"], "slow render");
+    assert_eq!(event["path"], "/slow");
+    assert_eq!(event["status"], "200");
+    assert_eq!(event["threshold_ms"], "10");
+    assert!(event["duration_ms"].parse::<u64>().unwrap() >= 50);
+}
+
+#[tokio::test]
+async fn renders_under_the_threshold_are_not_logged() {
+    assert!(render(Some(60_000)).await.is_empty());
+}
+
+#[tokio::test]
+async fn slow_renders_are_not_logged_by_default() {
+    assert!(render(None).await.is_empty());
+}
@@ -0,0 +1,204 @@
+use http::{header, Uri};
+use leptos_config::LeptosOptions;
+use std::path::{Path, PathBuf};
+
+fn site_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(name);
+    std::fs::create_dir_all(root.join(".well-known")).unwrap();
+    root
+}
+
+fn options(root: &Path) -> LeptosOptions {
+    LeptosOptions::builder()
+        .output_name("test_app")
+        .site_root(root.to_str().unwrap())
+        .build()
+}
+
+async fn content_type(options: &LeptosOptions, path: &'static str) -> Option<String> {
+    let res = leptos_axum::serve_static_file(options, &Uri::from_static(path)).await?;
+    Some(
+        res.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string(),
+    )
+}
+
+#[tokio::test]
+async fn extensionless_binary_file_uses_default_content_type() {
+    let root = site_root("leptos_axum_static_default");
+    std::fs::write(root.join(".well-known/blob"), [0u8, 159, 146, 150]).unwrap();
+
+    assert_eq!(
+        content_type(&options(&root), "/.well-known/blob")
+            .await
+            .unwrap(),
+        "application/octet-stream"
+    );
+
+    let custom = LeptosOptions::builder()
+        .output_name("test_app")
+        .site_root(root.to_str().unwrap())
+        .static_default_content_type("application/x-custom")
+        .build();
+    assert_eq!(
+        content_type(&custom, "/.well-known/blob").await.unwrap(),
+        "application/x-custom"
+    );
+}
+
+#[tokio::test]
+async fn extensionless_text_file_is_sniffed() {
+    let root = site_root("leptos_axum_static_sniffed");
+    std::fs::write(
+        root.join(".well-known/security"),
+        "Contact: mailto:security@example.com\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        content_type(&options(&root), "/.well-known/security")
+            .await
+            .unwrap(),
+        "text/plain; charset=utf-8"
+    );
+}
+
+#[tokio::test]
+async fn missing_files_and_escaping_paths_are_not_served() {
+    let root = site_root("leptos_axum_static_missing");
+    std::fs::write(root.join("style.css"), "body {}").unwrap();
+    let options = options(&root);
+
+    assert_eq!(
+        content_type(&options, "/style.css").await.unwrap(),
+        "text/css; charset=utf-8"
+    );
+    assert!(content_type(&options, "/missing.css").await.is_none());
+    assert!(
+        content_type(&options, "/../leptos_axum_static_missing/style.css")
+            .await
+            .is_none()
+    );
+}
+
+async fn ranged(
+    options: &LeptosOptions,
+    path: &'static str,
+    range: Option<&'static str>,
+) -> http::Response<axum::body::Body> {
+    let mut headers = http::HeaderMap::new();
+    if let Some(range) = range {
+        headers.insert(header::RANGE, range.parse().unwrap());
+    }
+    leptos_axum::serve_static_file_with_headers(options, &Uri::from_static(path), &headers)
+        .await
+        .unwrap()
+}
+
+async fn body(res: http::Response<axum::body::Body>) -> axum::body::Bytes {
+    hyper::body::to_bytes(res.into_body()).await.unwrap()
+}
+
+#[tokio::test]
+async fn valid_ranges_get_partial_content() {
+    let root = site_root("leptos_axum_static_range");
+    std::fs::write(root.join("video.mp4"), "0123456789").unwrap();
+    let options = options(&root);
+
+    let res = ranged(&options, "/video.mp4", Some("bytes=2-5")).await;
+    assert_eq!(res.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(res.headers()[header::CONTENT_RANGE], "bytes 2-5/10");
+    assert_eq!(res.headers()[header::ACCEPT_RANGES], "bytes");
+    assert_eq!(body(res).await, "2345");
+
+    let res = ranged(&options, "/video.mp4", Some("bytes=7-")).await;
+    assert_eq!(res.headers()[header::CONTENT_RANGE], "bytes 7-9/10");
+    assert_eq!(body(res).await, "789");
+
+    let res = ranged(&options, "/video.mp4", Some("bytes=-3")).await;
+    assert_eq!(res.headers()[header::CONTENT_RANGE], "bytes 7-9/10");
+    assert_eq!(body(res).await, "789");
+
+    // an end past the end of the file is clamped
+    let res = ranged(&options, "/video.mp4", Some("bytes=8-100")).await;
+    assert_eq!(res.headers()[header::CONTENT_RANGE], "bytes 8-9/10");
+    assert_eq!(body(res).await, "89");
+}
+
+#[tokio::test]
+async fn unsatisfiable_ranges_are_rejected() {
+    let root = site_root("leptos_axum_static_unsatisfiable");
+    std::fs::write(root.join("video.mp4"), "0123456789").unwrap();
+
+    let res = ranged(&options(&root), "/video.mp4", Some("bytes=10-20")).await;
+    assert_eq!(res.status(), http::StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(res.headers()[header::CONTENT_RANGE], "bytes */10");
+    assert!(body(res).await.is_empty());
+}
+
+#[tokio::test]
+async fn full_requests_advertise_range_support() {
+    let root = site_root("leptos_axum_static_full");
+    std::fs::write(root.join("video.mp4"), "0123456789").unwrap();
+    let options = options(&root);
+
+    for range in [None, Some("bytes=0-1,4-5"), Some("items=0-1")] {
+        let res = ranged(&options, "/video.mp4", range).await;
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(res.headers()[header::ACCEPT_RANGES], "bytes");
+        assert!(res.headers().get(header::CONTENT_RANGE).is_none());
+        assert_eq!(body(res).await, "0123456789");
+    }
+}
+
+#[tokio::test]
+async fn ranges_of_large_files_are_read_from_their_offset() {
+    let root = site_root("leptos_axum_static_large");
+    let contents: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    std::fs::write(root.join("movie.webm"), &contents).unwrap();
+    let options = options(&root);
+
+    let res = ranged(&options, "/movie.webm", Some("bytes=3000000-3000099")).await;
+    assert_eq!(res.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(res.headers()[header::CONTENT_LENGTH], "100");
+    assert_eq!(body(res).await, contents[3_000_000..3_000_100]);
+
+    let res = ranged(&options, "/movie.webm", None).await;
+    assert_eq!(
+        res.headers()[header::CONTENT_LENGTH],
+        contents.len().to_string().as_str()
+    );
+    assert_eq!(body(res).await.len(), contents.len());
+}
+
+#[tokio::test]
+async fn roots_are_tried_in_order() {
+    let framework = site_root("leptos_axum_static_roots_framework");
+    let app = site_root("leptos_axum_static_roots_app");
+    std::fs::write(framework.join("theme.css"), "framework").unwrap();
+    std::fs::write(app.join("theme.css"), "app").unwrap();
+    std::fs::write(app.join("logo.svg"), "<svg/>").unwrap();
+    _ = std::fs::remove_file(framework.join("logo.svg"));
+    let options = options(&framework);
+    let roots = [&framework, &app];
+
+    let serve = |path: &'static str| {
+        leptos_axum::serve_static_file_from_roots(
+            &options,
+            &roots,
+            &Uri::from_static(path),
+            &http::HeaderMap::new(),
+        )
+    };
+
+    // only the second root has the file
+    let res = serve("/logo.svg").await.unwrap();
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "image/svg+xml");
+    assert_eq!(body(res).await, "<svg/>");
+
+    // the first root wins when both have it
+    assert_eq!(body(serve("/theme.css").await.unwrap()).await, "framework");
+    assert!(serve("/missing.css").await.is_none());
+}
@@ -0,0 +1,97 @@
+use leptos::*;
+use leptos_axum::{IntegrationWarning, LeptosRoutes};
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+use std::{future::Future, pin::Pin};
+
+// The `#[server]` macro only generates the server half when the calling crate has an `ssr`
+// feature, so the test server functions implement `ServerFn` by hand.
+#[derive(Clone, Serialize, Deserialize)]
+struct SaveDraft {}
+
+impl ServerFn for SaveDraft {
+    type Output = ();
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "save_draft"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<(), ServerFnError>>>> {
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Publish {}
+
+impl ServerFn for Publish {
+    type Output = ();
+
+    fn prefix() -> &'static str {
+        "/api"
+    }
+
+    fn url() -> &'static str {
+        "publish"
+    }
+
+    fn encoding() -> Encoding {
+        Encoding::Url
+    }
+
+    fn call_fn(self, _cx: Scope) -> Pin<Box<dyn Future<Output = Result<(), ServerFnError>>>> {
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+#[component]
+fn Editor(cx: Scope) -> impl IntoView {
+    let _save = create_server_action::<SaveDraft>(cx);
+    let _publish = create_server_action::<Publish>(cx);
+    view! { cx, <p>"Editor"</p> }
+}
+
+#[component]
+fn EditorApp(cx: Scope) -> impl IntoView {
+    view! { cx,
+        <Router>
+            <Routes>
+                <Route path="" view=|cx| view! { cx, <p>"Home"</p> }/>
+                <Route path="posts/:id/edit" view=|cx| view! { cx, <Editor/> }/>
+                <Route path="about" view=|cx| view! { cx, <p>"About"</p> }/>
+            </Routes>
+        </Router>
+    }
+}
+
+#[tokio::test]
+async fn missing_routes_and_registrations_are_reported() {
+    _ = Publish::register();
+    let options = LeptosOptions::builder().output_name("test_app").build();
+    // `/about` is left out, as if the route list had been written by hand
+    let router = axum::Router::new().leptos_routes(
+        options,
+        vec!["/".to_string(), "/posts/:id/edit".to_string()],
+        |cx| view! { cx, <EditorApp/> },
+    );
+
+    let warnings =
+        leptos_axum::validate_integration(|cx| view! { cx, <EditorApp/> }, &router).await;
+
+    assert_eq!(
+        warnings,
+        vec![
+            IntegrationWarning::UnmountedRoute("/about".to_string()),
+            IntegrationWarning::UnregisteredServerFn("save_draft".to_string()),
+        ]
+    );
+    assert!(warnings[1].to_string().contains("register()"));
+}
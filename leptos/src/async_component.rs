@@ -0,0 +1,107 @@
+use leptos_dom::{IntoView, View};
+use leptos_reactive::{
+    create_rw_signal, provide_context, spawn_local, use_context, RwSignal, Scope, SuspenseContext,
+};
+use std::{cell::RefCell, future::Future, rc::Rc};
+
+/// Holds the views of the `async` components rendered under a single `<Suspense/>`.
+///
+/// `<Suspense/>` renders its children once to find out what they're waiting on, and again
+/// once everything has resolved, so each `async` component is called more than once. The
+/// components are matched to their slots by the order in which they're called, which means
+/// the same `async` components must be rendered in the same order on every pass.
+#[derive(Clone)]
+pub(crate) struct AsyncViews {
+    cx: Scope,
+    inner: Rc<RefCell<AsyncViewsInner>>,
+}
+
+#[derive(Default)]
+struct AsyncViewsInner {
+    slots: Vec<RwSignal<Option<View>>>,
+    cursor: usize,
+}
+
+impl AsyncViews {
+    pub(crate) fn provide(cx: Scope) {
+        provide_context(
+            cx,
+            AsyncViews {
+                cx,
+                inner: Default::default(),
+            },
+        );
+    }
+
+    /// Returns the slot for the next `async` component, and whether it was just created.
+    fn next_slot(&self) -> (RwSignal<Option<View>>, bool) {
+        let mut inner = self.inner.borrow_mut();
+
+        // once every slot has resolved, a new pass over the children has started
+        if inner.cursor >= inner.slots.len()
+            && inner
+                .slots
+                .iter()
+                .all(|slot| slot.with(|view| view.is_some()))
+        {
+            inner.cursor = 0;
+        }
+
+        let cursor = inner.cursor;
+        inner.cursor += 1;
+        match inner.slots.get(cursor) {
+            Some(slot) => (*slot, false),
+            None => {
+                let slot = create_rw_signal(self.cx, None);
+                inner.slots.push(slot);
+                (slot, true)
+            }
+        }
+    }
+}
+
+/// Renders the view returned by an `async` component. This is used by the
+/// [component](crate::component) macro for `async fn` components; you shouldn't need
+/// to call it yourself.
+///
+/// The future is only created and spawned the first time the component renders under its
+/// `<Suspense/>`, and it counts as a pending resource for that `<Suspense/>` until it resolves.
+#[doc(hidden)]
+pub fn async_component<F, Fut, V>(cx: Scope, body: F) -> impl IntoView
+where
+    F: FnOnce(Scope) -> Fut,
+    Fut: Future<Output = V> + 'static,
+    V: IntoView,
+{
+    let (slot, owner, is_new) = match use_context::<AsyncViews>(cx) {
+        Some(views) => {
+            let (slot, is_new) = views.next_slot();
+            (slot, views.cx, is_new)
+        }
+        None => {
+            leptos_dom::debug_warn!(
+                "an async component was rendered outside a <Suspense/>, so it will be \
+                 empty until its future resolves and won't be awaited during server rendering"
+            );
+            (create_rw_signal(cx, None), cx, true)
+        }
+    };
+
+    if is_new {
+        let suspense = use_context::<SuspenseContext>(cx);
+        if let Some(suspense) = suspense {
+            suspense.increment();
+        }
+
+        let fut = body(owner);
+        spawn_local(async move {
+            let view = fut.await.into_view(owner);
+            slot.set(Some(view));
+            if let Some(suspense) = suspense {
+                suspense.decrement();
+            }
+        });
+    }
+
+    move || slot.get()
+}
@@ -150,9 +150,13 @@ pub use leptos_server::*;
 
 pub use tracing;
 pub use typed_builder;
+mod async_component;
+pub use async_component::*;
 mod error_boundary;
 pub use error_boundary::*;
 mod for_loop;
+mod locale;
+pub use locale::*;
 mod show;
 pub use for_loop::*;
 pub use show::*;
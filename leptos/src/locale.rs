@@ -0,0 +1,38 @@
+use leptos_dom::{document, is_server};
+use leptos_reactive::{use_context, Scope};
+
+/// The locale (a BCP 47 language tag, like `en-US`) that the page was rendered for.
+///
+/// Server integrations negotiate it from the request's `Accept-Language` header, provide it
+/// as context while rendering, and embed it in the page as the `lang` attribute of the `<html>`
+/// element. On the client, [use_locale] reads it back from that attribute, so numbers and dates
+/// formatted with it during hydration match the server-rendered HTML, whatever the browser's
+/// default locale is.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Locale(pub String);
+
+impl Locale {
+    /// The language tag, like `en-US`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Returns the [Locale] the page was rendered for.
+///
+/// On the server, this is the locale provided as context by the integration. On the client,
+/// it's the locale provided as context, if any, and otherwise the `lang` attribute of the `<html>`
+/// element embedded by the server. Returns `None` if neither is available.
+pub fn use_locale(cx: Scope) -> Option<Locale> {
+    use_context::<Locale>(cx).or_else(|| {
+        if is_server() {
+            None
+        } else {
+            document()
+                .document_element()
+                .and_then(|html| html.get_attribute("lang"))
+                .filter(|lang| !lang.is_empty())
+                .map(Locale)
+        }
+    })
+}
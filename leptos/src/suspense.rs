@@ -1,3 +1,4 @@
+use crate::async_component::AsyncViews;
 use cfg_if::cfg_if;
 use leptos_dom::HydrationCtx;
 use leptos_dom::{DynChild, Fragment, IntoView};
@@ -69,6 +70,8 @@ where
 
     // provide this SuspenseContext to any resources below it
     provide_context(cx, context);
+    // and a place for any async components below it to keep their views between renders
+    AsyncViews::provide(cx);
 
     let orig_child = Rc::new(children);
 
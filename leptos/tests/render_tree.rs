@@ -0,0 +1,55 @@
+#![cfg(all(feature = "testing", not(any(feature = "csr", feature = "hydrate"))))]
+
+use leptos::{testing::*, *};
+
+#[component]
+fn Counter(cx: Scope, initial_value: i32) -> impl IntoView {
+    let (value, set_value) = create_signal(cx, initial_value);
+    view! {
+        cx,
+        <div class="counter">
+            <button on:click=move |_| set_value.update(|value| *value -= 1)>"-1"</button>
+            <span>"Value: " {move || value.get().to_string()} "!"</span>
+            <input type="number" disabled/>
+        </div>
+    }
+}
+
+#[test]
+fn render_to_tree_returns_the_structure_of_a_component() {
+    let tree = render_to_tree(|cx| view! { cx, <Counter initial_value=3/> });
+
+    assert_eq!(
+        tree,
+        vec![TestNode::element("div")
+            .attr("class", "counter")
+            .child(TestNode::element("button").child(TestNode::text("-1")))
+            .child(TestNode::element("span").child(TestNode::text("Value: 3!")))
+            .child(
+                TestNode::element("input")
+                    .attr("type", "number")
+                    .attr("disabled", "")
+            )]
+    );
+}
+
+#[test]
+fn render_to_tree_decodes_text_and_serializes_to_json() {
+    let tree = render_to_tree(|cx| {
+        let name = "<Ada & Grace>";
+        view! { cx, <p title="greeting">"Hi, " {name}</p><br/> }
+    });
+
+    assert_eq!(
+        serde_json::Value::from(tree.iter().map(TestNode::to_json).collect::<Vec<_>>()),
+        serde_json::json!([
+            {
+                "type": "element",
+                "name": "p",
+                "attrs": { "title": "greeting" },
+                "children": [{ "type": "text", "text": "Hi, <Ada & Grace>" }]
+            },
+            { "type": "element", "name": "br", "attrs": {}, "children": [] }
+        ])
+    );
+}
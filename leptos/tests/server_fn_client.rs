@@ -0,0 +1,17 @@
+#![cfg(not(feature = "ssr"))]
+
+use leptos::*;
+
+// The body refers to a crate that only exists on the server, so this only compiles because the
+// client build doesn't include it.
+#[server(ReadSecret, "/api")]
+pub async fn read_secret() -> Result<String, ServerFnError> {
+    Ok(server_only_secrets::read("api-key").await?)
+}
+
+#[test]
+fn server_fn_bodies_are_left_out_of_client_builds() {
+    assert_eq!(ReadSecret::prefix(), "/api");
+    assert!(ReadSecret::url().ends_with("read_secret"));
+    assert_eq!(ReadSecret::encoding(), Encoding::Url);
+}
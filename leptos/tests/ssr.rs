@@ -129,3 +129,289 @@ fn ssr_with_styles() {
         );
     });
 }
+
+#[cfg(all(
+    feature = "debug-markers",
+    debug_assertions,
+    not(any(feature = "csr", feature = "hydrate"))
+))]
+#[test]
+fn ssr_test_with_debug_markers() {
+    use leptos::*;
+
+    _ = create_scope(create_runtime(), |cx| {
+        let (value, _) = create_signal(cx, 0);
+        let rendered = view! {
+            cx,
+            <span>"Value: " {move || value.get().to_string()} "!"</span>
+        };
+
+        assert_eq!(
+            rendered.into_view(cx).render_to_string(cx),
+            "<span id=\"_0-1\">Value: <!--leptos-dyn--><!--hk=_0-2o|leptos-dyn-child-start-->0<!--hk=_0-2c|leptos-dyn-child-end--><!--/leptos-dyn-->!</span>"
+        );
+    });
+}
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[tokio::test]
+async fn ssr_test_with_async_components() {
+    use futures::StreamExt;
+    use leptos::*;
+    use std::time::Duration;
+
+    #[component]
+    async fn Greeting(cx: Scope, name: &'static str) -> impl IntoView {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        view! { cx, <p>"Hello, " {name} "!"</p> }
+    }
+
+    let html = tokio::task::LocalSet::new()
+        .run_until(async {
+            let (stream, runtime, _) = render_to_stream_with_prefix_undisposed(
+                |cx| {
+                    view! { cx,
+                        <Suspense fallback=move || view! { cx, <p>"Loading..."</p> }>
+                            <Greeting name="Ada"/>
+                            <Greeting name="Grace"/>
+                        </Suspense>
+                    }
+                    .into_view(cx)
+                },
+                |_| "".into(),
+            );
+            let html = stream.collect::<String>().await;
+            runtime.dispose();
+            html
+        })
+        .await;
+
+    assert!(html.contains("Loading..."));
+    let ada = html.find("Ada").expect("first component was not rendered");
+    let grace = html
+        .find("Grace")
+        .expect("second component was not rendered");
+    assert!(ada < grace);
+}
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[test]
+fn ssr_escapes_text_from_expressions() {
+    use leptos::*;
+
+    _ = create_scope(create_runtime(), |cx| {
+        let name = "<script>alert('hi')</script> & co";
+        let rendered = view! {
+            cx,
+            <p>{name}</p>
+        };
+
+        assert_eq!(
+            rendered.into_view(cx).render_to_string(cx),
+            "<p id=\"_0-1\">&lt;script&gt;alert('hi')&lt;/script&gt; &amp; co</p>"
+        );
+    });
+}
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[test]
+fn ssr_escapes_dynamic_text() {
+    use leptos::*;
+
+    _ = create_scope(create_runtime(), |cx| {
+        let (value, _) = create_signal(cx, "<b>1</b>".to_string());
+        let rendered = view! {
+            cx,
+            <span>{move || value.get()}</span>
+        };
+
+        assert_eq!(
+            rendered.into_view(cx).render_to_string(cx),
+            "<span id=\"_0-1\"><!--hk=_0-2o|leptos-dyn-child-start-->&lt;b&gt;1&lt;/b&gt;<!--hk=_0-2c|leptos-dyn-child-end--></span>"
+        );
+    });
+}
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[test]
+fn ssr_does_not_escape_raw_text() {
+    use leptos::*;
+
+    _ = create_scope(create_runtime(), |cx| {
+        let data = r#"{"tag":"<b>","amp":"&"}"#;
+        let rendered = view! {
+            cx,
+            <script type="application/json">{html::raw(data)}</script>
+        };
+
+        assert_eq!(
+            rendered.into_view(cx).render_to_string(cx),
+            "<script type=\"application/json\" id=\"_0-1\">{\"tag\":\"<b>\",\"amp\":\"&\"}</script>"
+        );
+    });
+}
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[test]
+fn ssr_does_not_escape_script_and_style_contents() {
+    use leptos::*;
+
+    _ = create_scope(create_runtime(), |cx| {
+        let js = "if (a && b < c) {}";
+        let css = "ul > li { color: red; }";
+        let rendered = view! {
+            cx,
+            <div>
+                <script>{js}</script>
+                <style>{css}</style>
+            </div>
+        };
+
+        assert_eq!(
+            rendered.into_view(cx).render_to_string(cx),
+            "<div id=\"_0-1\"><script id=\"_0-2\">if (a && b < c) {}</script><style id=\"_0-3\">ul > li { color: red; }</style></div>"
+        );
+
+        // elements built without the macro's template are rendered the same way
+        let script = html::script(cx).child(js.to_string());
+        assert_eq!(
+            script.into_view(cx).render_to_string(cx),
+            "<script id=\"_0-4\">if (a && b < c) {}</script>"
+        );
+    });
+}
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[test]
+fn ssr_does_not_escape_script_and_style_literals() {
+    use leptos::*;
+
+    _ = create_scope(create_runtime(), |cx| {
+        let rendered = view! {
+            cx,
+            <div>
+                <script>"if (a < b && c) { go(); }"</script>
+                <style>"ul > li { color: red; }"</style>
+            </div>
+        };
+
+        assert_eq!(
+            rendered.into_view(cx).render_to_string(cx),
+            "<div id=\"_0-1\"><script id=\"_0-2\">if (a < b && c) { go(); }</script><style id=\"_0-3\">ul > li { color: red; }</style></div>"
+        );
+    });
+}
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[test]
+fn ssr_escapes_static_text_and_attributes() {
+    use leptos::*;
+
+    _ = create_scope(create_runtime(), |cx| {
+        let rendered = view! {
+            cx,
+            <p title="say \"hi\" & <bye>">"1 < 2 & {braces} stay — ünïcode"</p>
+        };
+
+        assert_eq!(
+            rendered.into_view(cx).render_to_string(cx),
+            "<p title=\"say &quot;hi&quot; &amp; &lt;bye&gt;\" id=\"_0-1\">1 &lt; 2 &amp; {braces} stay — ünïcode</p>"
+        );
+    });
+}
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[test]
+fn ssr_escapes_dynamic_attributes() {
+    use leptos::*;
+
+    _ = create_scope(create_runtime(), |cx| {
+        let title = "\"><script>alert('&')</script>";
+        let rendered = view! {
+            cx,
+            <p title=title/>
+        };
+
+        assert_eq!(
+            rendered.into_view(cx).render_to_string(cx),
+            "<p title=\"&quot;&gt;&lt;script&gt;alert('&amp;')&lt;/script&gt;\" id=\"_0-1\"/>"
+        );
+    });
+}
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[test]
+fn ssr_spreads_attributes() {
+    use leptos::*;
+
+    _ = create_scope(create_runtime(), |cx| {
+        let attrs = vec![
+            ("title", Attribute::String("Greeting".into())),
+            ("aria-label", Attribute::String("a \"greeting\"".into())),
+            ("hidden", Attribute::Bool(true)),
+            ("draggable", Attribute::Bool(false)),
+        ];
+        let rendered = view! {
+            cx,
+            <p {..attrs} title="Hello">"Hello, world!"</p>
+        };
+
+        // the explicit title wins, and the hydration key can't clash with a spread `id`
+        assert_eq!(
+            rendered.into_view(cx).render_to_string(cx),
+            "<p aria-label=\"a &quot;greeting&quot;\" hidden title=\"Hello\" leptos-hk=\"_0-1\">Hello, world!</p>"
+        );
+    });
+}
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[test]
+fn ssr_renders_boolean_attributes_only_while_on() {
+    use leptos::*;
+
+    _ = create_scope(create_runtime(), |cx| {
+        let (busy, _) = create_signal(cx, false);
+        let rendered = view! {
+            cx,
+            <div>
+                <button disabled>"Delete"</button>
+                <button disabled=false>"Save"</button>
+                <button disabled=move || !busy.get()>"Cancel"</button>
+            </div>
+        };
+
+        assert_eq!(
+            rendered.into_view(cx).render_to_string(cx),
+            "<div id=\"_0-1\"><button disabled id=\"_0-2\">Delete</button><button id=\"_0-3\">Save</button><button disabled id=\"_0-4\">Cancel</button></div>"
+        );
+    });
+}
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[test]
+fn ssr_blocks_script_urls_only_when_asked() {
+    use leptos::*;
+
+    assert_eq!(
+        escape_attr_for("href", " JavaScript:alert(1)"),
+        " JavaScript:alert(1)"
+    );
+
+    set_unsafe_urls(UnsafeUrls::Block);
+    assert_eq!(
+        escape_attr_for("href", " JavaScript:alert(1)"),
+        "about:invalid"
+    );
+    assert_eq!(
+        escape_attr_for("SRC", "java\tscript:alert(1)"),
+        "about:invalid"
+    );
+    assert_eq!(escape_attr_for("href", "vbscript:msgbox"), "about:invalid");
+    assert_eq!(
+        escape_attr_for("href", "/search?q=a&b=\"c\""),
+        "/search?q=a&amp;b=&quot;c&quot;"
+    );
+    assert_eq!(escape_attr_for("title", "javascript:"), "javascript:");
+    assert_eq!(escape_attr_for("href", "javascript"), "javascript");
+    set_unsafe_urls(UnsafeUrls::Allow);
+}
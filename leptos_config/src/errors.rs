@@ -1,4 +1,4 @@
-use std::{net::AddrParseError, num::ParseIntError};
+use std::{net::AddrParseError, num::ParseIntError, str::ParseBoolError};
 
 use thiserror::Error;
 
@@ -14,6 +14,10 @@ pub enum LeptosConfigError {
     ConfigError(String),
     #[error("Config Error: {0}")]
     EnvVarError(String),
+    #[error("Site files not found: {0}")]
+    SiteNotFound(String),
+    #[error("Invalid config: {0}")]
+    ValidationError(String),
 }
 impl From<config::ConfigError> for LeptosConfigError {
     fn from(e: config::ConfigError) -> Self {
@@ -32,3 +36,9 @@ impl From<AddrParseError> for LeptosConfigError {
         Self::ConfigError(e.to_string())
     }
 }
+
+impl From<ParseBoolError> for LeptosConfigError {
+    fn from(e: ParseBoolError) -> Self {
+        Self::ConfigError(e.to_string())
+    }
+}
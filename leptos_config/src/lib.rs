@@ -3,13 +3,16 @@
 pub mod errors;
 
 use crate::errors::LeptosConfigError;
-use config::{Config, File, FileFormat};
+use config::builder::{ConfigBuilder, DefaultState};
+use config::{Config, File};
 use regex::Regex;
 use std::convert::TryFrom;
 use std::fs;
 use std::{env::VarError, net::SocketAddr, str::FromStr};
 use typed_builder::TypedBuilder;
 
+pub use config::FileFormat;
+
 /// A Struct to allow us to parse LeptosOptions from the file. Not really needed, most interactions should
 /// occur with LeptosOptions
 #[derive(Clone, Debug, serde::Deserialize)]
@@ -44,10 +47,240 @@ pub struct LeptosOptions {
     /// Defaults to `127.0.0.1:3000`
     #[builder(setter(into), default=SocketAddr::from(([127,0,0,1], 3000)))]
     pub site_address: SocketAddr,
+    /// Whether the site is served over `http` or `https`. Browsers only let a page served over HTTPS open a
+    /// secure WebSocket, so with `https` the live-reload script connects with `wss://` instead of `ws://`.
+    /// Can be overridden with the `LEPTOS_SITE_SCHEME` env var, which takes `http` or `https`.
+    /// Defaults to `http`
+    #[builder(default)]
+    #[serde(default)]
+    pub site_scheme: Scheme,
     /// The port the Websocket watcher listens on. Should match the `reload_port` in cargo-leptos(if using).
     /// Defaults to `3001`
     #[builder(default = 3001)]
     pub reload_port: u32,
+    /// The host the browser connects to for the live-reload WebSocket, when it isn't the IP of
+    /// [site_address](Self::site_address), like `localhost` when the server listens on `0.0.0.0`, or the host
+    /// of a Docker container's mapped port. Can be overridden with the `LEPTOS_RELOAD_WS_HOST` env var.
+    /// Defaults to `None`, which uses the IP of [site_address](Self::site_address)
+    #[builder(default, setter(strip_option, into))]
+    #[serde(default)]
+    pub reload_ws_host: Option<String>,
+    /// The port the browser connects to for the live-reload WebSocket, when it isn't
+    /// [reload_port](Self::reload_port), like when Docker maps the port to another one on the host. Can be
+    /// overridden with the `LEPTOS_RELOAD_EXTERNAL_PORT` env var.
+    /// Defaults to `None`, which uses [reload_port](Self::reload_port)
+    #[builder(default, setter(strip_option))]
+    #[serde(default)]
+    pub reload_external_port: Option<u32>,
+    /// The charset sent in the `Content-Type` header of rendered pages and in their `<meta charset>` tag.
+    /// Only change this if you need to serve a legacy encoding.
+    /// Defaults to `utf-8`
+    #[builder(setter(into), default=default_charset())]
+    #[serde(default = "default_charset")]
+    pub charset: String,
+    /// The doctype that starts every rendered page. Only change this if your pages need another one, like an
+    /// XHTML doctype. Can be overridden with the `LEPTOS_DOCTYPE` env var.
+    /// Defaults to `<!DOCTYPE html>`
+    #[builder(setter(into), default=default_doctype())]
+    #[serde(default = "default_doctype")]
+    pub doctype: String,
+    /// The language of rendered pages, which is set as the `lang` attribute of their `<html>` element and
+    /// provided to the app as its `Locale`. When [negotiate_lang](Self::negotiate_lang) is on, the Axum
+    /// integration uses the language picked from the request's `Accept-Language` header instead, so this is
+    /// only used for requests that don't name one. Can be overridden with the `LEPTOS_LANG` env var.
+    /// Defaults to `en`
+    #[builder(setter(into), default=default_lang())]
+    #[serde(default = "default_lang")]
+    pub lang: String,
+    /// Whether the Axum integration picks the language of each page from the request's `Accept-Language`
+    /// header, falling back to [lang](Self::lang). Turn it off for a site in a single language, so its pages
+    /// are always marked with [lang](Self::lang), whichever language the browser prefers.
+    /// Can be overridden with the `LEPTOS_NEGOTIATE_LANG` env var.
+    /// Defaults to `true`
+    #[builder(default = true)]
+    #[serde(default = "default_negotiate_lang")]
+    pub negotiate_lang: bool,
+    /// Whether the integrations should emit a JSON access log line for every rendered page, with its path,
+    /// status, duration, and the number of bytes sent. Lines are emitted as `tracing` events with the
+    /// `leptos_axum::access_log` target.
+    /// Defaults to `false`
+    #[builder(default = false)]
+    #[serde(default)]
+    pub access_log: bool,
+    /// The `Content-Type` used when serving a static file whose type can't be guessed from its extension,
+    /// like the files under `/.well-known/`, and that doesn't look like plain text.
+    /// Defaults to `application/octet-stream`
+    #[builder(setter(into), default=default_static_content_type())]
+    #[serde(default = "default_static_content_type")]
+    pub static_default_content_type: String,
+    /// Whether the integrations should collapse runs of whitespace in rendered pages into a single space, to
+    /// reduce their size. Whitespace is never removed entirely, so the page has the same text nodes as before,
+    /// and the contents of `<pre>`, `<textarea>`, `<script>`, and `<style>` elements and of attribute values are
+    /// left untouched.
+    /// Defaults to `false`
+    #[builder(default = false)]
+    #[serde(default)]
+    pub collapse_whitespace: bool,
+    /// Whether the integrations should compress rendered pages with Brotli or gzip, when the browser's
+    /// `Accept-Encoding` header allows it. Each chunk of the stream is compressed and flushed as it's
+    /// rendered, so the page still arrives incrementally.
+    /// Can be overridden with the `LEPTOS_COMPRESS_HTML` env var.
+    /// Defaults to `false`
+    #[builder(default = false)]
+    #[serde(default)]
+    pub compress_html: bool,
+    /// The largest request body, in bytes, that the integrations will read for a server function call or a
+    /// rendered page. Larger requests are rejected with a `413 Payload Too Large` status before they're handled.
+    /// Server function handlers only see this if the router has an `Extension(Arc<LeptosOptions>)` layer, or
+    /// through `leptos_axum::handle_server_fns_with_options`.
+    /// Can be overridden with the `LEPTOS_MAX_BODY_SIZE` env var.
+    /// Defaults to `None`, which means there is no limit
+    #[builder(default, setter(strip_option))]
+    #[serde(default)]
+    pub max_body_size: Option<usize>,
+    /// How long, in milliseconds, the integrations will wait for a server function to return or for a page's
+    /// app shell to render before giving up with a `408 Request Timeout` status. Once a page has started
+    /// streaming, the rest of it isn't subject to the timeout.
+    /// Server function handlers only see this if the router has an `Extension(Arc<LeptosOptions>)` layer, or
+    /// through `leptos_axum::handle_server_fns_with_options`.
+    /// Can be overridden with the `LEPTOS_REQUEST_TIMEOUT` env var.
+    /// Defaults to `None`, which means there is no timeout
+    #[builder(default, setter(strip_option))]
+    #[serde(default)]
+    pub request_timeout: Option<u64>,
+    /// How long, in milliseconds, rendering a page can take before the integrations log it as slow. A page that
+    /// takes longer, from receiving the request to sending the last of the page, is logged as a `tracing` warning
+    /// with the `leptos_axum::slow_render` target and the fields `path`, `route` (the matched route, if any),
+    /// `status`, `duration_ms`, and `threshold_ms`.
+    /// Can be overridden with the `LEPTOS_SLOW_RENDER_THRESHOLD` env var.
+    /// Defaults to `None`, which means renders are never logged as slow
+    #[builder(default, setter(strip_option))]
+    #[serde(default)]
+    pub slow_render_threshold: Option<u64>,
+    /// Origins that the app will connect to, like a font CDN or an API on another domain. For each one, the
+    /// head of every rendered page includes
+    /// `<link rel="preconnect" href="{origin}" crossorigin=""><link rel="dns-prefetch" href="{origin}">`, so the
+    /// browser can set up the connection before it's needed.
+    /// In a config file, this is an array of strings; the `LEPTOS_PRECONNECT_ORIGINS` env var takes a
+    /// comma-separated list.
+    /// Defaults to no origins
+    #[builder(default, setter(into))]
+    #[serde(default)]
+    pub preconnect_origins: Vec<String>,
+    /// Whether the integrations should build the static part of the app shell, from the doctype to the hydration
+    /// scripts, once and reuse it, instead of formatting it again for every page. Each handler caches one shell per
+    /// locale and per whether the live-reload script is included, since those are the only parts of it that change
+    /// between requests. Pages with a CSP nonce always get a freshly built shell, because the nonce changes with every
+    /// response.
+    /// Defaults to `false`
+    #[builder(default = false)]
+    #[serde(default)]
+    pub cache_shell: bool,
+    /// An identifier for the deployed build of the app, like its version or a commit hash, to help match reports
+    /// from users with what was deployed. When it's set, every rendered page has a `<meta name="version">` tag in
+    /// its head and an `X-App-Version` response header with this value. To use your crate's version, pass
+    /// `env!("CARGO_PKG_VERSION")` to the builder, or set the `LEPTOS_APP_VERSION` env var.
+    /// Defaults to `None`, which leaves out both
+    #[builder(default, setter(strip_option, into))]
+    #[serde(default)]
+    pub app_version: Option<String>,
+    /// The path at which the server serves the data of the app's resources for hydration, instead of including it
+    /// in the page, like `/_leptos/state`. When it's set, the page waits for all of its resources before sending
+    /// their data as a single blob, which the client fetches from `{hydration_state_endpoint}/{key}` while it
+    /// hydrates; the cost is an extra round trip before the resources are hydrated. The key is a hash of the
+    /// data, so the page's HTML can be cached as long as the data doesn't change, and the blob itself can be
+    /// cached for as long as the server keeps it; a page whose blob is gone loads its resources in the browser.
+    /// The key isn't tied to the user, so don't set this for apps whose resources load data that's only meant
+    /// for an authenticated user. The router has to serve the endpoint, like
+    /// `.route("/_leptos/state/:key", get(leptos_axum::handle_hydration_state))`. Set it with the
+    /// `LEPTOS_HYDRATION_STATE_ENDPOINT` env var.
+    /// Defaults to `None`, which streams each resource's data into the page as it resolves
+    #[builder(default, setter(strip_option, into))]
+    #[serde(default)]
+    pub hydration_state_endpoint: Option<String>,
+    /// The name of the function that the app's JS module exports to load its WASM, which the hydration script
+    /// in the shell calls before hydrating. Only change this if the app has a custom WASM entry point.
+    /// The default name is imported as the module's default export, which is how wasm-bindgen exports it; any
+    /// other name is imported as a named export. Can be overridden with the `LEPTOS_INIT_FN_NAME` env var.
+    /// Defaults to `init`
+    #[builder(setter(into), default=default_init_fn_name())]
+    #[serde(default = "default_init_fn_name")]
+    pub init_fn_name: String,
+    /// The name of the function that the app's JS module exports to hydrate the page, which the hydration script
+    /// in the shell calls once the WASM is loaded. Can be overridden with the `LEPTOS_HYDRATE_FN_NAME` env var.
+    /// Defaults to `hydrate`
+    #[builder(setter(into), default=default_hydrate_fn_name())]
+    #[serde(default = "default_hydrate_fn_name")]
+    pub hydrate_fn_name: String,
+    /// Whether the head of every rendered page includes `<link rel="modulepreload">` and `<link rel="preload">`
+    /// hints for the app's JS and WASM, so the browser starts downloading them before it reaches the hydration
+    /// script. Turn them off if they get in the way, like when a proxy rewrites or blocks the preload requests,
+    /// or when a service worker loads the WASM itself and the preloaded copy would be downloaded for nothing.
+    /// The hydration script is included either way. Can be overridden with the `LEPTOS_PRELOAD_HINTS` env var.
+    /// Defaults to `true`
+    #[builder(default = true)]
+    #[serde(default = "default_preload_hints")]
+    pub preload_hints: bool,
+    /// Whether the integrations should believe the `Forwarded`, `X-Forwarded-Proto`, and `X-Forwarded-Host`
+    /// headers when working out the URL a page was requested at, which they provide as a `RequestUrl`. Only
+    /// turn this on if the app is served behind a reverse proxy that sets these headers, since otherwise any
+    /// client can send them to claim another origin.
+    /// Can be overridden with the `LEPTOS_TRUST_FORWARDED_HEADERS` env var.
+    /// Defaults to `false`
+    #[builder(default = false)]
+    #[serde(default)]
+    pub trust_forwarded_headers: bool,
+    /// The origins, like `https://app.example.com`, that may call the app's server functions from their own pages,
+    /// for frontends or browser extensions served from somewhere else. A request from one of them gets CORS
+    /// headers allowing it, and the server function handlers answer its `OPTIONS` preflight requests. `"*"` allows
+    /// every origin. In a config file, this is an array of strings; the `LEPTOS_SERVER_FN_CORS_ORIGINS` env var
+    /// takes a comma-separated list.
+    /// Defaults to no origins, which turns CORS off, so browsers only let the app's own pages call them
+    #[builder(default, setter(into))]
+    #[serde(default)]
+    pub server_fn_cors_origins: Vec<String>,
+    /// The methods that another origin allowed by [server_fn_cors_origins](Self::server_fn_cors_origins) may call
+    /// server functions with, sent in the `Access-Control-Allow-Methods` header of preflight responses. The
+    /// `LEPTOS_SERVER_FN_CORS_METHODS` env var takes a comma-separated list.
+    /// Defaults to `POST`
+    #[builder(setter(into), default=default_server_fn_cors_methods())]
+    #[serde(default = "default_server_fn_cors_methods")]
+    pub server_fn_cors_methods: Vec<String>,
+    /// The request headers that another origin allowed by [server_fn_cors_origins](Self::server_fn_cors_origins)
+    /// may send to server functions, sent in the `Access-Control-Allow-Headers` header of preflight responses. The
+    /// `LEPTOS_SERVER_FN_CORS_HEADERS` env var takes a comma-separated list.
+    /// Defaults to `Content-Type` and `Accept`
+    #[builder(setter(into), default=default_server_fn_cors_headers())]
+    #[serde(default = "default_server_fn_cors_headers")]
+    pub server_fn_cors_headers: Vec<String>,
+    /// The request header that carries the ID of a request, which the Axum integration provides to the app as a
+    /// `RequestId` and send back in the same response header. A request without the header gets a new ID.
+    /// Use the name your load balancer or tracing setup already uses, like `X-Correlation-ID`. With
+    /// `traceparent`, the W3C Trace Context header, the ID is the whole header value, and new IDs are generated
+    /// as valid `traceparent` values, but they start a new trace rather than continuing one. Can be overridden
+    /// with the `LEPTOS_REQUEST_ID_HEADER` env var.
+    /// Defaults to `X-Request-ID`
+    #[builder(setter(into), default=default_request_id_header())]
+    #[serde(default = "default_request_id_header")]
+    pub request_id_header: String,
+    /// Whether the Axum integration generates a random `CspNonce` for every rendered page, unless the app's
+    /// additional context already provides one, so every inline script in the page gets a `nonce` attribute.
+    /// The nonce is provided as context, where the app can read it to send a `Content-Security-Policy` that
+    /// allows it. Pages with a nonce always get a freshly built shell, even if [cache_shell](Self::cache_shell)
+    /// is set. Can be overridden with the `LEPTOS_CSP_NONCE` env var.
+    /// Defaults to `false`
+    #[builder(default = false)]
+    #[serde(default)]
+    pub csp_nonce: bool,
+    /// What [get_configuration] does when [site_root](Self::site_root) or [site_pkg_dir](Self::site_pkg_dir)
+    /// doesn't exist, or doesn't contain the app's JS and WASM files. A server started like that can't send its
+    /// assets, so every page is rendered but never hydrated; see [check_site](Self::check_site). Turn the check
+    /// off if the assets are served from somewhere else, like a CDN. Can be overridden with the
+    /// `LEPTOS_SITE_CHECK` env var, which takes `warn`, `error`, or `off`.
+    /// Defaults to `warn`
+    #[builder(default)]
+    #[serde(default)]
+    pub site_check: SiteCheck,
 }
 
 impl LeptosOptions {
@@ -57,11 +290,303 @@ impl LeptosOptions {
                 .map_err(|e| LeptosConfigError::EnvVarError(format!("LEPTOS_OUTPUT_NAME: {e}")))?,
             site_root: env_w_default("LEPTOS_SITE_ROOT", "target/site")?,
             site_pkg_dir: env_w_default("LEPTOS_SITE_PKG_DIR", "pkg")?,
-            env: Env::default(),
+            // an unset LEPTOS_ENV means development, but one that's set has to be a known environment
+            env: match env_optional("LEPTOS_ENV")? {
+                Some(env) => env.parse()?,
+                None => Env::default(),
+            },
             site_address: env_w_default("LEPTOS_SITE_ADDR", "127.0.0.1:3000")?.parse()?,
+            site_scheme: env_w_default("LEPTOS_SITE_SCHEME", "http")?.parse()?,
             reload_port: env_w_default("LEPTOS_RELOAD_PORT", "3001")?.parse()?,
+            reload_ws_host: env_optional("LEPTOS_RELOAD_WS_HOST")?,
+            reload_external_port: env_optional("LEPTOS_RELOAD_EXTERNAL_PORT")?
+                .map(|port| port.parse())
+                .transpose()?,
+            charset: env_w_default("LEPTOS_CHARSET", &default_charset())?,
+            doctype: env_w_default("LEPTOS_DOCTYPE", &default_doctype())?,
+            lang: env_w_default("LEPTOS_LANG", &default_lang())?,
+            negotiate_lang: env_w_default("LEPTOS_NEGOTIATE_LANG", "true")?.parse()?,
+            access_log: env_w_default("LEPTOS_ACCESS_LOG", "false")?.parse()?,
+            static_default_content_type: env_w_default(
+                "LEPTOS_STATIC_DEFAULT_CONTENT_TYPE",
+                &default_static_content_type(),
+            )?,
+            collapse_whitespace: env_w_default("LEPTOS_COLLAPSE_WHITESPACE", "false")?.parse()?,
+            compress_html: env_w_default("LEPTOS_COMPRESS_HTML", "false")?.parse()?,
+            max_body_size: env_optional("LEPTOS_MAX_BODY_SIZE")?
+                .map(|size| size.parse())
+                .transpose()?,
+            request_timeout: env_optional("LEPTOS_REQUEST_TIMEOUT")?
+                .map(|timeout| timeout.parse())
+                .transpose()?,
+            slow_render_threshold: env_optional("LEPTOS_SLOW_RENDER_THRESHOLD")?
+                .map(|threshold| threshold.parse())
+                .transpose()?,
+            preconnect_origins: env_optional("LEPTOS_PRECONNECT_ORIGINS")?
+                .map(|origins| env_list(&origins))
+                .unwrap_or_default(),
+            cache_shell: env_w_default("LEPTOS_CACHE_SHELL", "false")?.parse()?,
+            app_version: env_optional("LEPTOS_APP_VERSION")?,
+            hydration_state_endpoint: env_optional("LEPTOS_HYDRATION_STATE_ENDPOINT")?,
+            init_fn_name: env_w_default("LEPTOS_INIT_FN_NAME", &default_init_fn_name())?,
+            hydrate_fn_name: env_w_default("LEPTOS_HYDRATE_FN_NAME", &default_hydrate_fn_name())?,
+            preload_hints: env_w_default("LEPTOS_PRELOAD_HINTS", "true")?.parse()?,
+            trust_forwarded_headers: env_w_default("LEPTOS_TRUST_FORWARDED_HEADERS", "false")?
+                .parse()?,
+            server_fn_cors_origins: env_optional("LEPTOS_SERVER_FN_CORS_ORIGINS")?
+                .map(|origins| env_list(&origins))
+                .unwrap_or_default(),
+            server_fn_cors_methods: env_optional("LEPTOS_SERVER_FN_CORS_METHODS")?
+                .map(|methods| env_list(&methods))
+                .unwrap_or_else(default_server_fn_cors_methods),
+            server_fn_cors_headers: env_optional("LEPTOS_SERVER_FN_CORS_HEADERS")?
+                .map(|headers| env_list(&headers))
+                .unwrap_or_else(default_server_fn_cors_headers),
+            request_id_header: env_w_default(
+                "LEPTOS_REQUEST_ID_HEADER",
+                &default_request_id_header(),
+            )?,
+            csp_nonce: env_w_default("LEPTOS_CSP_NONCE", "false")?.parse()?,
+            site_check: env_w_default("LEPTOS_SITE_CHECK", "warn")?.parse()?,
         })
     }
+
+    /// The scheme, host, and port that the browser connects to for the live-reload WebSocket, like
+    /// `ws://127.0.0.1:3001`: [reload_ws_host](Self::reload_ws_host) and
+    /// [reload_external_port](Self::reload_external_port) if they're set, or else the IP of
+    /// [site_address](Self::site_address) and [reload_port](Self::reload_port). The scheme is `wss` if
+    /// [site_scheme](Self::site_scheme) is `https`.
+    pub fn reload_ws_origin(&self) -> String {
+        let host = match &self.reload_ws_host {
+            Some(host) => host.clone(),
+            None => self.site_address.ip().to_string(),
+        };
+        let port = self.reload_external_port.unwrap_or(self.reload_port);
+        format!("{}://{host}:{port}", self.site_scheme.websocket())
+    }
+
+    /// Checks that the options make sense, so a mistake in the config is caught at startup rather than showing up
+    /// later as a broken asset path. These are checked:
+    /// - [output_name](Self::output_name) isn't empty
+    /// - [site_pkg_dir](Self::site_pkg_dir) isn't empty and doesn't start with a `/`, since it's relative to
+    ///   [site_root](Self::site_root)
+    /// - [reload_port](Self::reload_port) is a port from 1 to 65535
+    /// - [charset](Self::charset) is a charset name, which can be sent in a header
+    ///
+    /// Fails with [LeptosConfigError::ValidationError] listing every problem found. [get_configuration] and
+    /// [get_configuration_from_files] run this on the options they load. Call it yourself at startup if you
+    /// build the options in code.
+    pub fn validate(&self) -> Result<(), LeptosConfigError> {
+        let mut problems = Vec::new();
+        if self.output_name.is_empty() {
+            problems.push("output-name is empty".to_string());
+        }
+        if self.site_pkg_dir.is_empty() {
+            problems.push("site-pkg-dir is empty".to_string());
+        } else if self.site_pkg_dir.starts_with('/') {
+            problems.push(format!(
+                "site-pkg-dir `{}` starts with a `/`, but it's relative to site-root",
+                self.site_pkg_dir
+            ));
+        }
+        if self.reload_port == 0 || self.reload_port > u16::MAX as u32 {
+            problems.push(format!(
+                "reload-port {} isn't a port from 1 to 65535",
+                self.reload_port
+            ));
+        }
+        if !is_charset_name(&self.charset) {
+            problems.push(format!("charset `{}` isn't a charset name", self.charset));
+        }
+
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(LeptosConfigError::ValidationError(problems.join("; "))),
+        }
+    }
+
+    /// Checks that [site_root](Self::site_root) and [site_pkg_dir](Self::site_pkg_dir) exist, and that the
+    /// package directory contains the app's `{output_name}.js` and its `{output_name}.wasm` (or
+    /// `{output_name}_bg.wasm`, as wasm-pack names it). What happens when they don't depends on
+    /// [site_check](Self::site_check):
+    /// - with [SiteCheck::Warn], each missing path is logged as a warning with the `leptos_config::site_check`
+    ///   target, and this returns `Ok`
+    /// - with [SiteCheck::Error], this returns [LeptosConfigError::SiteNotFound] listing the missing paths
+    /// - with [SiteCheck::Off], nothing is checked
+    ///
+    /// [get_configuration] and [get_configuration_from_files] run this on the options they load. Call it
+    /// yourself at startup if you build the options in code.
+    pub fn check_site(&self) -> Result<(), LeptosConfigError> {
+        if self.site_check == SiteCheck::Off {
+            return Ok(());
+        }
+
+        let missing = self.missing_site_paths();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        match self.site_check {
+            SiteCheck::Error => Err(LeptosConfigError::SiteNotFound(missing.join(", "))),
+            _ => {
+                for path in missing {
+                    tracing::warn!(
+                        target: "leptos_config::site_check",
+                        "{path} doesn't exist, so the app's assets can't be served. Check site-root and \
+                         site-pkg-dir, or set site-check to `off` if the assets are served elsewhere."
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // The site directories and asset files that don't exist, stopping at the first missing directory
+    fn missing_site_paths(&self) -> Vec<String> {
+        let site_root = std::path::Path::new(&self.site_root);
+        if !site_root.is_dir() {
+            return vec![site_root.display().to_string()];
+        }
+        let pkg_dir = site_root.join(&self.site_pkg_dir);
+        if !pkg_dir.is_dir() {
+            return vec![pkg_dir.display().to_string()];
+        }
+
+        let mut missing = Vec::new();
+        let js = pkg_dir.join(format!("{}.js", self.output_name));
+        if !js.is_file() {
+            missing.push(js.display().to_string());
+        }
+        let wasm = pkg_dir.join(format!("{}.wasm", self.output_name));
+        let wasm_bg = pkg_dir.join(format!("{}_bg.wasm", self.output_name));
+        if !wasm.is_file() && !wasm_bg.is_file() {
+            missing.push(wasm.display().to_string());
+        }
+        missing
+    }
+}
+
+/// What [LeptosOptions::check_site] does when the site's directories or asset files are missing.
+/// Defaults to `Warn`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SiteCheck {
+    /// Log a warning for each missing path.
+    #[default]
+    Warn,
+    /// Fail with [LeptosConfigError::SiteNotFound].
+    Error,
+    /// Don't check the site at all.
+    Off,
+}
+
+impl FromStr for SiteCheck {
+    type Err = LeptosConfigError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "warn" => Ok(Self::Warn),
+            "error" => Ok(Self::Error),
+            "off" => Ok(Self::Off),
+            other => Err(LeptosConfigError::ConfigError(format!(
+                "{other} is not a supported site check. Use `warn`, `error`, or `off`."
+            ))),
+        }
+    }
+}
+
+/// The scheme the site is served with; see [LeptosOptions::site_scheme].
+/// Defaults to `Http`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scheme {
+    /// Plain HTTP, with `ws://` for the live-reload WebSocket.
+    #[default]
+    Http,
+    /// HTTPS, with `wss://` for the live-reload WebSocket.
+    Https,
+}
+
+impl Scheme {
+    /// The scheme of the site's URLs, `http` or `https`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::Https => "https",
+        }
+    }
+
+    /// The scheme of WebSocket URLs on the site, `ws` or `wss`.
+    pub fn websocket(self) -> &'static str {
+        match self {
+            Self::Http => "ws",
+            Self::Https => "wss",
+        }
+    }
+}
+
+impl FromStr for Scheme {
+    type Err = LeptosConfigError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "http" => Ok(Self::Http),
+            "https" => Ok(Self::Https),
+            other => Err(LeptosConfigError::ConfigError(format!(
+                "{other} is not a supported site scheme. Use `http` or `https`."
+            ))),
+        }
+    }
+}
+
+fn default_charset() -> String {
+    "utf-8".to_string()
+}
+
+// Whether a charset is a name like `utf-8` or `ISO-8859-1`, made of the characters that a charset name
+// in a `Content-Type` header can have
+fn is_charset_name(charset: &str) -> bool {
+    !charset.is_empty()
+        && charset
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'+-^_`{}~".contains(c))
+}
+
+fn default_doctype() -> String {
+    "<!DOCTYPE html>".to_string()
+}
+
+fn default_lang() -> String {
+    "en".to_string()
+}
+
+fn default_negotiate_lang() -> bool {
+    true
+}
+
+fn default_request_id_header() -> String {
+    "X-Request-ID".to_string()
+}
+
+fn default_static_content_type() -> String {
+    "application/octet-stream".to_string()
+}
+
+fn default_init_fn_name() -> String {
+    "init".to_string()
+}
+
+fn default_hydrate_fn_name() -> String {
+    "hydrate".to_string()
+}
+
+fn default_preload_hints() -> bool {
+    true
+}
+
+fn default_server_fn_cors_methods() -> Vec<String> {
+    vec!["POST".to_string()]
+}
+
+fn default_server_fn_cors_headers() -> Vec<String> {
+    vec!["Content-Type".to_string(), "Accept".to_string()]
 }
 
 fn env_w_default(key: &str, default: &str) -> Result<String, LeptosConfigError> {
@@ -72,13 +597,69 @@ fn env_w_default(key: &str, default: &str) -> Result<String, LeptosConfigError>
     }
 }
 
+// An unset or empty env var means the option is turned off
+fn env_optional(key: &str) -> Result<Option<String>, LeptosConfigError> {
+    match std::env::var(key) {
+        Ok(val) if val.is_empty() => Ok(None),
+        Ok(val) => Ok(Some(val)),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(e) => Err(LeptosConfigError::EnvVarError(format!("{key}: {e}"))),
+    }
+}
+
+// The items of a comma-separated env var, skipping empty ones
+fn env_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
 /// An enum that can be used to define the environment Leptos is running in.
 /// Setting this to the `PROD` variant will not include the WebSocket code for `cargo-leptos` watch mode.
 /// Defaults to `DEV`.
-#[derive(Debug, Clone, serde::Deserialize)]
+///
+/// It's parsed from its name in any case, like `prod` or `PROD`, or from `development` or `production`.
+/// Anything else is an error, so a typo doesn't quietly run the app in development. Its
+/// [Display](std::fmt::Display) implementation writes the name that [as_str](Self::as_str) returns, which
+/// parses back into the same variant.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(try_from = "String")]
 pub enum Env {
     PROD,
     DEV,
+    /// A deployment that mirrors production, for checking a release before it goes out. It's treated like
+    /// `PROD` by the integrations.
+    STAGING,
+    /// Automated tests. It's treated like `PROD` by the integrations.
+    TEST,
+}
+
+impl Env {
+    /// The name of the environment, like `prod`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PROD => "prod",
+            Self::DEV => "dev",
+            Self::STAGING => "staging",
+            Self::TEST => "test",
+        }
+    }
+
+    // The environment with the given name, which every conversion into an [Env] goes through
+    fn parse(input: &str) -> Result<Self, String> {
+        match input.to_lowercase().as_str() {
+            "dev" | "development" => Ok(Self::DEV),
+            "prod" | "production" => Ok(Self::PROD),
+            "staging" => Ok(Self::STAGING),
+            "test" => Ok(Self::TEST),
+            other => Err(format!(
+                "{other} is not a supported environment. Use `dev`, `prod`, `staging`, or `test`."
+            )),
+        }
+    }
 }
 
 impl Default for Env {
@@ -87,49 +668,35 @@ impl Default for Env {
     }
 }
 
+impl std::fmt::Display for Env {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl FromStr for Env {
-    type Err = ();
+    type Err = LeptosConfigError;
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let sanitized = input.to_lowercase();
-        match sanitized.as_ref() {
-            "dev" => Ok(Self::DEV),
-            "development" => Ok(Self::DEV),
-            "prod" => Ok(Self::PROD),
-            "production" => Ok(Self::PROD),
-            _ => Ok(Self::DEV),
-        }
+        Self::parse(input).map_err(LeptosConfigError::ConfigError)
     }
 }
 
 impl From<&str> for Env {
+    /// ## Panics
+    /// Panics if `str` isn't the name of an environment.
     fn from(str: &str) -> Self {
-        let sanitized = str.to_lowercase();
-        match sanitized.as_str() {
-            "dev" => Self::DEV,
-            "development" => Self::DEV,
-            "prod" => Self::PROD,
-            "production" => Self::PROD,
-            _ => {
-                panic!("Env var is not recognized. Maybe try `dev` or `prod`")
-            }
-        }
+        Self::parse(str).unwrap_or_else(|e| panic!("{e}"))
     }
 }
+
 impl From<&Result<String, VarError>> for Env {
+    /// Reads the result of `env::var("LEPTOS_ENV")`, which is `DEV` if the var isn't set.
+    ///
+    /// ## Panics
+    /// Panics if the var is set to something that isn't the name of an environment.
     fn from(input: &Result<String, VarError>) -> Self {
         match input {
-            Ok(str) => {
-                let sanitized = str.to_lowercase();
-                match sanitized.as_ref() {
-                    "dev" => Self::DEV,
-                    "development" => Self::DEV,
-                    "prod" => Self::PROD,
-                    "production" => Self::PROD,
-                    _ => {
-                        panic!("Env var is not recognized. Maybe try `dev` or `prod`")
-                    }
-                }
-            }
+            Ok(str) => Self::from(str.as_str()),
             Err(_) => Self::DEV,
         }
     }
@@ -139,54 +706,329 @@ impl TryFrom<String> for Env {
     type Error = String;
 
     fn try_from(s: String) -> Result<Self, Self::Error> {
-        match s.to_lowercase().as_str() {
-            "dev" => Ok(Self::DEV),
-            "development" => Ok(Self::DEV),
-            "prod" => Ok(Self::PROD),
-            "production" => Ok(Self::PROD),
-            other => Err(format!(
-                "{other} is not a supported environment. Use either `dev` or `production`."
-            )),
-        }
+        Self::parse(&s)
     }
 }
 
 /// Loads [LeptosOptions] from a Cargo.toml with layered overrides. If an env var is specified, like `LEPTOS_ENV`,
 /// it will override a setting in the file. It takes in an optional path to a Cargo.toml file. If None is provided,
 /// you'll need to set the options as environment variables or rely on the defaults. This is the preferred
-/// approach for cargo-leptos. If Some("./Cargo.toml") is provided, Leptos will read in the settings itself. This
-/// option currently does not allow dashes in file or foldernames, as all dashes become underscores
+/// approach for cargo-leptos. If Some("./Cargo.toml") is provided, Leptos will read in the settings itself. The
+/// dashes in the names of the settings, like `site-root`, become underscores; their values are read as-is.
+///
+/// `LEPTOS_OUTPUT_NAME` always takes precedence over the `output-name` in the file, because it's set by
+/// cargo-leptos to the name of the WASM and JS files it actually built. If both are set and they disagree,
+/// a warning is logged with the `leptos_config` target, since the name from the file would point to files
+/// that don't exist.
+///
+/// The options are checked with [LeptosOptions::validate], and fail to load if they don't make sense.
+/// The site directories the options point to are checked with [LeptosOptions::check_site], so a deploy that's
+/// missing its assets is caught at startup. By default that only logs a warning; set `site-check` to `error`
+/// to fail instead, or to `off` to skip the check.
 pub async fn get_configuration(path: Option<&str>) -> Result<ConfFile, LeptosConfigError> {
     if let Some(path) = path {
-        let text = fs::read_to_string(path).map_err(|_| LeptosConfigError::ConfigNotFound)?;
+        get_configuration_from_files(&[path]).await
+    } else {
+        let leptos_options = LeptosOptions::try_from_env()?;
+        leptos_options.validate()?;
+        leptos_options.check_site()?;
+        Ok(ConfFile { leptos_options })
+    }
+}
+
+/// Loads [LeptosOptions] from several files merged in order, for projects that split their configuration
+/// across a base file and one or more overlays. Each path can be either a Cargo.toml with a
+/// `[package.metadata.leptos]` section, or a standalone TOML file containing only the options, like
+/// ```toml
+/// output-name = "my_app"
+/// site-root = "target/site"
+/// ```
+///
+/// The precedence chain, from lowest to highest, is:
+/// 1. the files, in the order they're given, so later files override earlier ones
+/// 2. environment variables, like `LEPTOS_ENV` or `LEPTOS_OUTPUT_NAME`
+///
+/// Every option can be overridden by an env var, which is `LEPTOS_` followed by its name in
+/// upper case, like `LEPTOS_SITE_PKG_DIR`, except for [site_address](LeptosOptions::site_address), which is
+/// `LEPTOS_SITE_ADDR`.
+///
+/// All of the notes on [get_configuration] apply to every file.
+pub async fn get_configuration_from_files(paths: &[&str]) -> Result<ConfFile, LeptosConfigError> {
+    let mut builder = Config::builder();
+    for path in paths {
+        // Read each file in order, so that later files override earlier ones
+        builder = builder.add_source(File::from_str(
+            &config_file_to_toml(path)?,
+            FileFormat::Toml,
+        ));
+    }
+    load_configuration(builder)
+}
+
+/// Loads your own config struct from the same Cargo.toml section as [get_configuration], so the app's settings,
+/// like a database URL or feature flags, can live next to its Leptos options and be overridden by env vars the
+/// same way:
+/// ```toml
+/// [package.metadata.leptos]
+/// output-name = "my_app"
+/// database-url = "postgres://localhost/my_app"
+/// beta-features = false
+/// ```
+/// ```ignore
+/// #[derive(serde::Deserialize)]
+/// struct AppConfig {
+///     database_url: String,
+///     #[serde(default)]
+///     beta_features: bool,
+/// }
+///
+/// let app_config: AppConfig = get_configuration_as(Some("Cargo.toml")).await?;
+/// ```
+/// The whole section is deserialized into `T`, with the dashes in the names of its keys replaced by
+/// underscores, and keys that `T` doesn't have are ignored, so it can pick out only the settings it needs. To
+/// read the [LeptosOptions] along with your own, add them to `T` with `#[serde(flatten)]`.
+///
+/// Any env var starting with `LEPTOS_` overrides the key named by the rest of it in lower case, so
+/// `LEPTOS_DATABASE_URL` overrides `database-url`. Env vars are read as single values, so a key that's an
+/// array can only be set in the file. Without a path, the settings are read from env vars alone.
+pub async fn get_configuration_as<T>(path: Option<&str>) -> Result<T, LeptosConfigError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let mut builder = Config::builder();
+    if let Some(path) = path {
+        builder = builder.add_source(File::from_str(
+            &config_file_to_toml(path)?,
+            FileFormat::Toml,
+        ));
+    }
+    for (var, value) in std::env::vars() {
+        if let Some(key) = var.strip_prefix("LEPTOS_") {
+            builder =
+                builder.set_override(format!("leptos_options.{}", key.to_lowercase()), value)?;
+        }
+    }
+    builder
+        .build()?
+        .get::<T>("leptos_options")
+        .map_err(|e| LeptosConfigError::ConfigError(e.to_string()))
+}
+
+/// Loads [LeptosOptions] from a config file of its own, for deployments that don't ship the Cargo.toml, like
+/// a container image. Unlike the files read by [get_configuration_from_files], the options are in a
+/// `[leptos_options]` table, named as they are in [LeptosOptions]:
+/// ```toml
+/// [leptos_options]
+/// output_name = "my_app"
+/// site_root = "site"
+/// ```
+/// A file whose name ends in `.json` is read as JSON, with the options in a `"leptos_options"` object, and
+/// anything else as TOML. Environment variables override the file, as with [get_configuration].
+pub async fn get_configuration_from_file(path: &str) -> Result<ConfFile, LeptosConfigError> {
+    let text = fs::read_to_string(path).map_err(|_| LeptosConfigError::ConfigNotFound)?;
+    let format = match path.ends_with(".json") {
+        true => FileFormat::Json,
+        false => FileFormat::Toml,
+    };
+    get_configuration_from_str(&text, format).await
+}
+
+/// Loads [LeptosOptions] from the contents of a config file in the given format, laid out like the files
+/// read by [get_configuration_from_file]. Environment variables override it, as with [get_configuration].
+/// ```ignore
+/// let conf = get_configuration_from_str(
+///     r#"{ "leptos_options": { "output_name": "my_app" } }"#,
+///     FileFormat::Json,
+/// )
+/// .await?;
+/// ```
+pub async fn get_configuration_from_str(
+    text: &str,
+    format: FileFormat,
+) -> Result<ConfFile, LeptosConfigError> {
+    load_configuration(Config::builder().add_source(File::from_str(text, format)))
+}
+
+// Layers the env vars over the files in `builder` and loads the options, checking them as
+// [get_configuration] describes
+fn load_configuration(builder: ConfigBuilder<DefaultState>) -> Result<ConfFile, LeptosConfigError> {
+    // Layer on the environment-specific values.
+    // E.g. `LEPTOS_RELOAD_PORT=5001 would set `LeptosOptions.reload_port`
+    let settings = env_overrides(builder)?.build()?;
+
+    let mut conf: ConfFile = settings
+        .try_deserialize()
+        .map_err(|e| LeptosConfigError::ConfigError(e.to_string()))?;
+    // wasm-bindgen names the files it generates with underscores instead of dashes
+    conf.leptos_options.output_name = conf.leptos_options.output_name.replace('-', "_");
 
-        let re: Regex = Regex::new(r#"(?m)^\[package.metadata.leptos\]"#).unwrap();
-        let start = match re.find(&text) {
-            Some(found) => found.start(),
-            None => return Err(LeptosConfigError::ConfigSectionNotFound),
+    if let Ok(env_output_name) = std::env::var("LEPTOS_OUTPUT_NAME") {
+        if env_output_name != conf.leptos_options.output_name {
+            tracing::warn!(
+                "LEPTOS_OUTPUT_NAME is set to `{env_output_name}`, but the config file sets output-name to \
+                 `{}`. Using `{env_output_name}` from the environment.",
+                conf.leptos_options.output_name
+            );
+            conf.leptos_options.output_name = env_output_name;
+        }
+    }
+
+    conf.leptos_options.validate()?;
+    conf.leptos_options.check_site()?;
+    Ok(conf)
+}
+
+// How the value of an option's env var is read
+#[derive(Clone, Copy)]
+enum EnvOption {
+    Value,
+    // an empty value turns the option off
+    Optional,
+    // a comma-separated list
+    List,
+}
+
+// The options that env vars override in a config file, and their env vars. They're listed rather than
+// split out of the names of the env vars, because the names of the options contain underscores too.
+// `output_name` isn't here, since it overrides the file with a warning.
+const ENV_OPTIONS: &[(&str, &str, EnvOption)] = &[
+    ("site_root", "LEPTOS_SITE_ROOT", EnvOption::Value),
+    ("site_pkg_dir", "LEPTOS_SITE_PKG_DIR", EnvOption::Value),
+    ("env", "LEPTOS_ENV", EnvOption::Value),
+    ("site_address", "LEPTOS_SITE_ADDR", EnvOption::Value),
+    ("site_scheme", "LEPTOS_SITE_SCHEME", EnvOption::Value),
+    ("reload_port", "LEPTOS_RELOAD_PORT", EnvOption::Value),
+    (
+        "reload_ws_host",
+        "LEPTOS_RELOAD_WS_HOST",
+        EnvOption::Optional,
+    ),
+    (
+        "reload_external_port",
+        "LEPTOS_RELOAD_EXTERNAL_PORT",
+        EnvOption::Optional,
+    ),
+    ("charset", "LEPTOS_CHARSET", EnvOption::Value),
+    ("doctype", "LEPTOS_DOCTYPE", EnvOption::Value),
+    ("lang", "LEPTOS_LANG", EnvOption::Value),
+    ("negotiate_lang", "LEPTOS_NEGOTIATE_LANG", EnvOption::Value),
+    ("access_log", "LEPTOS_ACCESS_LOG", EnvOption::Value),
+    (
+        "static_default_content_type",
+        "LEPTOS_STATIC_DEFAULT_CONTENT_TYPE",
+        EnvOption::Value,
+    ),
+    (
+        "collapse_whitespace",
+        "LEPTOS_COLLAPSE_WHITESPACE",
+        EnvOption::Value,
+    ),
+    ("compress_html", "LEPTOS_COMPRESS_HTML", EnvOption::Value),
+    ("max_body_size", "LEPTOS_MAX_BODY_SIZE", EnvOption::Optional),
+    (
+        "request_timeout",
+        "LEPTOS_REQUEST_TIMEOUT",
+        EnvOption::Optional,
+    ),
+    (
+        "slow_render_threshold",
+        "LEPTOS_SLOW_RENDER_THRESHOLD",
+        EnvOption::Optional,
+    ),
+    (
+        "preconnect_origins",
+        "LEPTOS_PRECONNECT_ORIGINS",
+        EnvOption::List,
+    ),
+    ("cache_shell", "LEPTOS_CACHE_SHELL", EnvOption::Value),
+    ("app_version", "LEPTOS_APP_VERSION", EnvOption::Optional),
+    (
+        "hydration_state_endpoint",
+        "LEPTOS_HYDRATION_STATE_ENDPOINT",
+        EnvOption::Optional,
+    ),
+    ("init_fn_name", "LEPTOS_INIT_FN_NAME", EnvOption::Value),
+    (
+        "hydrate_fn_name",
+        "LEPTOS_HYDRATE_FN_NAME",
+        EnvOption::Value,
+    ),
+    ("preload_hints", "LEPTOS_PRELOAD_HINTS", EnvOption::Value),
+    (
+        "trust_forwarded_headers",
+        "LEPTOS_TRUST_FORWARDED_HEADERS",
+        EnvOption::Value,
+    ),
+    (
+        "server_fn_cors_origins",
+        "LEPTOS_SERVER_FN_CORS_ORIGINS",
+        EnvOption::List,
+    ),
+    (
+        "server_fn_cors_methods",
+        "LEPTOS_SERVER_FN_CORS_METHODS",
+        EnvOption::List,
+    ),
+    (
+        "server_fn_cors_headers",
+        "LEPTOS_SERVER_FN_CORS_HEADERS",
+        EnvOption::List,
+    ),
+    (
+        "request_id_header",
+        "LEPTOS_REQUEST_ID_HEADER",
+        EnvOption::Value,
+    ),
+    ("csp_nonce", "LEPTOS_CSP_NONCE", EnvOption::Value),
+    ("site_check", "LEPTOS_SITE_CHECK", EnvOption::Value),
+];
+
+// Overrides the options in `builder` with the env vars in [ENV_OPTIONS] that are set
+fn env_overrides(
+    mut builder: ConfigBuilder<DefaultState>,
+) -> Result<ConfigBuilder<DefaultState>, LeptosConfigError> {
+    for &(option, var, kind) in ENV_OPTIONS {
+        let value = match std::env::var(var) {
+            Ok(value) => value,
+            Err(VarError::NotPresent) => continue,
+            Err(e) => return Err(LeptosConfigError::EnvVarError(format!("{var}: {e}"))),
         };
+        let key = format!("leptos_options.{option}");
+        builder = match kind {
+            EnvOption::Value => builder.set_override(key, value)?,
+            EnvOption::Optional if value.is_empty() => {
+                builder.set_override(key, config::Value::new(None, config::ValueKind::Nil))?
+            }
+            EnvOption::Optional => builder.set_override(key, value)?,
+            EnvOption::List => builder.set_override(key, env_list(&value))?,
+        };
+    }
+    Ok(builder)
+}
 
-        // so that serde error messages have right line number
-        let newlines = text[..start].matches('\n').count();
-        let input = "\n".repeat(newlines) + &text[start..];
-        let toml = input
-            .replace("[package.metadata.leptos]", "[leptos_options]")
-            .replace('-', "_");
-        let settings = Config::builder()
-            // Read the "default" configuration file
-            .add_source(File::from_str(&toml, FileFormat::Toml))
-            // Layer on the environment-specific values.
-            // Add in settings from environment variables (with a prefix of LEPTOS and '_' as separator)
-            // E.g. `LEPTOS_RELOAD_PORT=5001 would set `LeptosOptions.reload_port`
-            .add_source(config::Environment::with_prefix("LEPTOS").separator("_"))
-            .build()?;
-
-        settings
-            .try_deserialize()
-            .map_err(|e| LeptosConfigError::ConfigError(e.to_string()))
-    } else {
-        Ok(ConfFile {
-            leptos_options: LeptosOptions::try_from_env()?,
+/// Reads a Cargo.toml or standalone config file and rewrites it so its options are under `[leptos_options]`.
+fn config_file_to_toml(path: &str) -> Result<String, LeptosConfigError> {
+    let text = fs::read_to_string(path).map_err(|_| LeptosConfigError::ConfigNotFound)?;
+
+    let re: Regex = Regex::new(r#"(?m)^\[package.metadata.leptos\]"#).unwrap();
+    let input = match re.find(&text) {
+        Some(found) => {
+            // so that serde error messages have right line number
+            let start = found.start();
+            let newlines = text[..start].matches('\n').count();
+            "\n".repeat(newlines) + &text[start..]
+        }
+        None if path.ends_with("Cargo.toml") => {
+            return Err(LeptosConfigError::ConfigSectionNotFound)
+        }
+        None => format!("[package.metadata.leptos]\n{text}"),
+    };
+    let input = input.replace("[package.metadata.leptos]", "[leptos_options]");
+    // only the keys use dashes instead of underscores, values like origins can contain dashes too
+    let key: Regex = Regex::new(r#"(?m)^(\s*)([A-Za-z0-9_-]+)(\s*=)"#).unwrap();
+    Ok(key
+        .replace_all(&input, |caps: &regex::Captures| {
+            format!("{}{}{}", &caps[1], caps[2].replace('-', "_"), &caps[3])
         })
-    }
+        .into_owned())
 }
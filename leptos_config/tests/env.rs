@@ -0,0 +1,32 @@
+use leptos_config::get_configuration;
+
+// This is the only test in this file, because it sets env vars for the whole process.
+#[tokio::test]
+async fn options_are_read_from_env_vars() {
+    std::env::set_var("LEPTOS_OUTPUT_NAME", "app");
+    std::env::set_var("LEPTOS_MAX_BODY_SIZE", "4096");
+    std::env::set_var("LEPTOS_REQUEST_TIMEOUT", "");
+    std::env::set_var(
+        "LEPTOS_PRECONNECT_ORIGINS",
+        "https://fonts.gstatic.com, https://api.example.com,",
+    );
+    std::env::set_var("LEPTOS_REQUEST_ID_HEADER", "X-Correlation-ID");
+    let conf = get_configuration(None).await.unwrap();
+    assert_eq!(conf.leptos_options.max_body_size, Some(4096));
+    // an empty env var turns the limit off
+    assert_eq!(conf.leptos_options.request_timeout, None);
+    assert_eq!(
+        conf.leptos_options.preconnect_origins,
+        vec!["https://fonts.gstatic.com", "https://api.example.com"]
+    );
+    assert_eq!(conf.leptos_options.request_id_header, "X-Correlation-ID");
+
+    std::env::set_var("LEPTOS_REQUEST_TIMEOUT", "thirty seconds");
+    assert!(get_configuration(None).await.is_err());
+
+    std::env::remove_var("LEPTOS_OUTPUT_NAME");
+    std::env::remove_var("LEPTOS_MAX_BODY_SIZE");
+    std::env::remove_var("LEPTOS_REQUEST_TIMEOUT");
+    std::env::remove_var("LEPTOS_PRECONNECT_ORIGINS");
+    std::env::remove_var("LEPTOS_REQUEST_ID_HEADER");
+}
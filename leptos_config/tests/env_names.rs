@@ -0,0 +1,58 @@
+use leptos_config::{errors::LeptosConfigError, get_configuration_from_str, Env, FileFormat};
+use std::env::VarError;
+
+const ALL: [Env; 4] = [Env::PROD, Env::DEV, Env::STAGING, Env::TEST];
+
+#[test]
+fn environments_round_trip() {
+    for env in ALL {
+        assert_eq!(env.to_string().parse::<Env>().unwrap(), env);
+        assert_eq!(env.as_str(), env.to_string());
+        assert_eq!(Env::try_from(env.to_string()), Ok(env.clone()));
+        assert_eq!(Env::from(env.as_str()), env);
+    }
+    assert_eq!("PROD".parse::<Env>().unwrap(), Env::PROD);
+    assert_eq!("production".parse::<Env>().unwrap(), Env::PROD);
+    assert_eq!("Development".parse::<Env>().unwrap(), Env::DEV);
+}
+
+#[test]
+fn typos_are_errors() {
+    assert!(matches!(
+        "prd".parse::<Env>(),
+        Err(LeptosConfigError::ConfigError(message)) if message.contains("prd")
+    ));
+    assert!(Env::try_from("prd".to_string()).is_err());
+    assert!(std::panic::catch_unwind(|| Env::from("prd")).is_err());
+    assert!(std::panic::catch_unwind(|| Env::from(&Ok::<_, VarError>("prd".to_string()))).is_err());
+}
+
+#[test]
+fn unset_env_var_is_dev() {
+    assert_eq!(Env::from(&Err(VarError::NotPresent)), Env::DEV);
+}
+
+#[tokio::test]
+async fn config_files_with_a_typo_fail_to_load() {
+    let config = |env: &str| {
+        format!(
+            r#"[leptos_options]
+output_name = "app"
+site_root = "site"
+site_pkg_dir = "pkg"
+env = "{env}"
+site_address = "127.0.0.1:3000"
+reload_port = 3001
+"#
+        )
+    };
+
+    let conf = get_configuration_from_str(&config("staging"), FileFormat::Toml)
+        .await
+        .unwrap();
+    assert_eq!(conf.leptos_options.env, Env::STAGING);
+
+    assert!(get_configuration_from_str(&config("prd"), FileFormat::Toml)
+        .await
+        .is_err());
+}
@@ -0,0 +1,55 @@
+use leptos_config::get_configuration;
+
+// This is the only test in this file, because it sets env vars for the whole process.
+#[tokio::test]
+async fn env_vars_override_multi_word_options_in_the_config_file() {
+    let path = std::env::temp_dir().join("leptos_config_env_overrides_Cargo.toml");
+    std::fs::write(
+        &path,
+        r#"[package]
+name = "app"
+
+[package.metadata.leptos]
+output-name = "from_file"
+site-root = "target/site"
+site-pkg-dir = "pkg"
+env = "DEV"
+site-address = "127.0.0.1:3000"
+reload-port = 3001
+max-body-size = 1024
+"#,
+    )
+    .unwrap();
+    std::env::set_var("LEPTOS_OUTPUT_NAME", "from_env");
+    std::env::set_var("LEPTOS_SITE_PKG_DIR", "assets");
+    std::env::set_var("LEPTOS_RELOAD_PORT", "5001");
+    std::env::set_var("LEPTOS_ENV", "production");
+    std::env::set_var("LEPTOS_MAX_BODY_SIZE", "");
+    std::env::set_var(
+        "LEPTOS_SERVER_FN_CORS_ORIGINS",
+        "https://a.example, https://b.example",
+    );
+
+    let conf = get_configuration(Some(path.to_str().unwrap())).await;
+
+    std::env::remove_var("LEPTOS_OUTPUT_NAME");
+    std::env::remove_var("LEPTOS_SITE_PKG_DIR");
+    std::env::remove_var("LEPTOS_RELOAD_PORT");
+    std::env::remove_var("LEPTOS_ENV");
+    std::env::remove_var("LEPTOS_MAX_BODY_SIZE");
+    std::env::remove_var("LEPTOS_SERVER_FN_CORS_ORIGINS");
+
+    let options = conf.unwrap().leptos_options;
+    assert_eq!(options.output_name, "from_env");
+    assert_eq!(options.site_pkg_dir, "assets");
+    assert_eq!(options.reload_port, 5001);
+    assert!(matches!(options.env, leptos_config::Env::PROD));
+    // an empty env var turns the limit off
+    assert_eq!(options.max_body_size, None);
+    assert_eq!(
+        options.server_fn_cors_origins,
+        vec!["https://a.example", "https://b.example"]
+    );
+    // options without an env var keep their value from the file
+    assert_eq!(options.site_root, "target/site");
+}
@@ -0,0 +1,47 @@
+use leptos_config::{get_configuration_as, LeptosOptions};
+
+#[derive(Debug, serde::Deserialize)]
+struct AppConfig {
+    database_url: String,
+    stripe_key: String,
+    #[serde(default)]
+    beta_features: bool,
+    #[serde(flatten)]
+    leptos_options: LeptosOptions,
+}
+
+// This is the only test in this file, because it sets an env var for the whole process.
+#[tokio::test]
+async fn own_settings_are_read_from_the_leptos_section() {
+    let path = std::env::temp_dir().join("leptos_config_extra_keys_Cargo.toml");
+    std::fs::write(
+        &path,
+        r#"[package]
+name = "app"
+
+[package.metadata.leptos]
+output-name = "app"
+site-root = "target/site"
+site-pkg-dir = "pkg"
+env = "DEV"
+site-address = "127.0.0.1:3000"
+reload-port = 3001
+database-url = "postgres://localhost/app"
+stripe-key = "sk_test_from_file"
+beta-features = true
+"#,
+    )
+    .unwrap();
+    std::env::set_var("LEPTOS_STRIPE_KEY", "sk_test_from_env");
+
+    let config = get_configuration_as::<AppConfig>(Some(path.to_str().unwrap())).await;
+
+    std::env::remove_var("LEPTOS_STRIPE_KEY");
+
+    let config = config.unwrap();
+    assert_eq!(config.database_url, "postgres://localhost/app");
+    assert_eq!(config.stripe_key, "sk_test_from_env");
+    assert!(config.beta_features);
+    assert_eq!(config.leptos_options.output_name, "app");
+    assert_eq!(config.leptos_options.reload_port, 3001);
+}
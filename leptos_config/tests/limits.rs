@@ -0,0 +1,56 @@
+use leptos_config::{get_configuration_from_files, LeptosOptions};
+use std::path::PathBuf;
+
+fn write_config(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[tokio::test]
+async fn limits_are_read_from_the_config_file() {
+    let path = write_config(
+        "leptos_config_limits.toml",
+        r#"output-name = "app"
+site-root = "target/site"
+site-pkg-dir = "pkg"
+site-address = "127.0.0.1:3000"
+reload-port = 3001
+env = "DEV"
+max-body-size = 1048576
+request-timeout = 2500
+"#,
+    );
+
+    let conf = get_configuration_from_files(&[path.to_str().unwrap()])
+        .await
+        .unwrap();
+
+    assert_eq!(conf.leptos_options.max_body_size, Some(1024 * 1024));
+    assert_eq!(conf.leptos_options.request_timeout, Some(2500));
+}
+
+#[tokio::test]
+async fn limits_default_to_none() {
+    let path = write_config(
+        "leptos_config_no_limits.toml",
+        r#"output-name = "app"
+site-root = "target/site"
+site-pkg-dir = "pkg"
+site-address = "127.0.0.1:3000"
+reload-port = 3001
+env = "DEV"
+"#,
+    );
+
+    let conf = get_configuration_from_files(&[path.to_str().unwrap()])
+        .await
+        .unwrap();
+
+    assert_eq!(conf.leptos_options.max_body_size, None);
+    assert_eq!(conf.leptos_options.request_timeout, None);
+
+    let options = LeptosOptions::builder().output_name("app").build();
+    assert_eq!(options.max_body_size, None);
+    assert_eq!(options.request_timeout, None);
+}
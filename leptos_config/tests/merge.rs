@@ -0,0 +1,42 @@
+use leptos_config::get_configuration_from_files;
+use std::path::PathBuf;
+
+fn write_config(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[tokio::test]
+async fn overlay_overrides_base_config() {
+    let base = write_config(
+        "leptos_config_merge_Cargo.toml",
+        r#"[package]
+name = "app"
+
+[package.metadata.leptos]
+output-name = "app"
+site-root = "target/site"
+site-pkg-dir = "pkg"
+env = "DEV"
+site-address = "127.0.0.1:3000"
+reload-port = 3001
+"#,
+    );
+    let overlay = write_config(
+        "leptos_config_merge_overlay.toml",
+        r#"site-address = "0.0.0.0:8080"
+env = "PROD"
+"#,
+    );
+
+    let conf = get_configuration_from_files(&[base.to_str().unwrap(), overlay.to_str().unwrap()])
+        .await
+        .unwrap();
+
+    let options = conf.leptos_options;
+    assert_eq!(options.site_address.to_string(), "0.0.0.0:8080");
+    assert!(matches!(options.env, leptos_config::Env::PROD));
+    assert_eq!(options.output_name, "app");
+    assert_eq!(options.reload_port, 3001);
+}
@@ -0,0 +1,74 @@
+use leptos_config::get_configuration;
+use std::sync::{Arc, Mutex};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Metadata, Subscriber,
+};
+
+/// Collects the messages of every warning logged by `leptos_config`.
+#[derive(Clone, Default)]
+struct WarningCollector(Arc<Mutex<Vec<String>>>);
+
+struct MessageVisitor<'a>(&'a mut String);
+
+impl Visit for MessageVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            *self.0 = format!("{value:?}");
+        }
+    }
+}
+
+impl Subscriber for WarningCollector {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "leptos_config" && *metadata.level() == Level::WARN
+    }
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+    fn event(&self, event: &Event<'_>) {
+        let mut message = String::new();
+        event.record(&mut MessageVisitor(&mut message));
+        self.0.lock().unwrap().push(message);
+    }
+    fn enter(&self, _span: &span::Id) {}
+    fn exit(&self, _span: &span::Id) {}
+}
+
+// This is the only test in this file, because it sets an env var for the whole process.
+#[tokio::test]
+async fn env_output_name_overrides_config_file_with_a_warning() {
+    let path = std::env::temp_dir().join("leptos_config_output_name_Cargo.toml");
+    std::fs::write(
+        &path,
+        r#"[package]
+name = "app"
+
+[package.metadata.leptos]
+output-name = "from_file"
+site-root = "target/site"
+site-pkg-dir = "pkg"
+env = "DEV"
+site-address = "127.0.0.1:3000"
+reload-port = 3001
+"#,
+    )
+    .unwrap();
+    std::env::set_var("LEPTOS_OUTPUT_NAME", "from_env");
+
+    let collector = WarningCollector::default();
+    let conf = {
+        let _guard = tracing::subscriber::set_default(collector.clone());
+        get_configuration(Some(path.to_str().unwrap()))
+            .await
+            .unwrap()
+    };
+    std::env::remove_var("LEPTOS_OUTPUT_NAME");
+
+    assert_eq!(conf.leptos_options.output_name, "from_env");
+    let warnings = collector.0.lock().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("from_env") && warnings[0].contains("from_file"));
+}
@@ -0,0 +1,32 @@
+use leptos_config::get_configuration_from_files;
+
+#[tokio::test]
+async fn preconnect_origins_are_read_from_a_toml_array() {
+    let path = std::env::temp_dir().join("leptos_config_preconnect.toml");
+    std::fs::write(
+        &path,
+        r#"output-name = "my-app"
+site-root = "target/site"
+site-pkg-dir = "pkg"
+site-address = "127.0.0.1:3000"
+reload-port = 3001
+env = "DEV"
+preconnect-origins = [
+    "https://fonts.gstatic.com",
+    "https://api.my-app.dev",
+]
+"#,
+    )
+    .unwrap();
+
+    let conf = get_configuration_from_files(&[path.to_str().unwrap()])
+        .await
+        .unwrap();
+
+    // dashes in values are kept, except in the output name
+    assert_eq!(
+        conf.leptos_options.preconnect_origins,
+        vec!["https://fonts.gstatic.com", "https://api.my-app.dev"]
+    );
+    assert_eq!(conf.leptos_options.output_name, "my_app");
+}
@@ -0,0 +1,25 @@
+use leptos_config::{LeptosOptions, Scheme};
+
+#[test]
+fn reload_origin_defaults_to_the_site_address() {
+    let options = LeptosOptions::builder().output_name("app").build();
+    assert_eq!(options.reload_ws_origin(), "ws://127.0.0.1:3001");
+}
+
+#[test]
+fn reload_host_and_port_can_be_overridden() {
+    let options = LeptosOptions::builder()
+        .output_name("app")
+        .site_address(([0, 0, 0, 0], 3000))
+        .reload_ws_host("localhost")
+        .reload_external_port(8001)
+        .site_scheme(Scheme::Https)
+        .build();
+    assert_eq!(options.reload_ws_origin(), "wss://localhost:8001");
+
+    let options = LeptosOptions::builder()
+        .output_name("app")
+        .reload_external_port(8001)
+        .build();
+    assert_eq!(options.reload_ws_origin(), "ws://127.0.0.1:8001");
+}
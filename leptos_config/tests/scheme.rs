@@ -0,0 +1,45 @@
+use leptos_config::{get_configuration_from_files, LeptosOptions, Scheme};
+
+#[test]
+fn schemes_are_parsed() {
+    assert_eq!("http".parse::<Scheme>().unwrap(), Scheme::Http);
+    assert_eq!("https".parse::<Scheme>().unwrap(), Scheme::Https);
+    assert_eq!("HTTPS".parse::<Scheme>().unwrap(), Scheme::Https);
+    assert!("ftp".parse::<Scheme>().is_err());
+    assert!("".parse::<Scheme>().is_err());
+}
+
+#[test]
+fn websocket_scheme_follows_the_site_scheme() {
+    assert_eq!(Scheme::Http.websocket(), "ws");
+    assert_eq!(Scheme::Https.websocket(), "wss");
+
+    let options = LeptosOptions::builder().output_name("app").build();
+    assert_eq!(options.site_scheme, Scheme::Http);
+}
+
+#[tokio::test]
+async fn scheme_is_read_from_the_config_file() {
+    let path = std::env::temp_dir().join("leptos_config_scheme_Cargo.toml");
+    std::fs::write(
+        &path,
+        r#"[package]
+name = "app"
+
+[package.metadata.leptos]
+output-name = "app"
+site-root = "target/site"
+site-pkg-dir = "pkg"
+env = "DEV"
+site-address = "127.0.0.1:3000"
+site-scheme = "https"
+reload-port = 3001
+"#,
+    )
+    .unwrap();
+
+    let conf = get_configuration_from_files(&[path.to_str().unwrap()])
+        .await
+        .unwrap();
+    assert_eq!(conf.leptos_options.site_scheme, Scheme::Https);
+}
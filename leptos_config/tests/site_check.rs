@@ -0,0 +1,50 @@
+use leptos_config::{errors::LeptosConfigError, LeptosOptions, SiteCheck};
+use std::path::Path;
+
+fn options(site_root: &Path, site_check: SiteCheck) -> LeptosOptions {
+    LeptosOptions::builder()
+        .output_name("app")
+        .site_root(site_root.to_str().unwrap())
+        .site_check(site_check)
+        .build()
+}
+
+#[test]
+fn missing_site_root_is_an_error_when_configured() {
+    let site_root = std::env::temp_dir().join("leptos_config_site_check_missing");
+    _ = std::fs::remove_dir_all(&site_root);
+
+    let err = options(&site_root, SiteCheck::Error)
+        .check_site()
+        .unwrap_err();
+    match err {
+        LeptosConfigError::SiteNotFound(missing) => {
+            assert_eq!(missing, site_root.display().to_string())
+        }
+        other => panic!("expected SiteNotFound, got {other:?}"),
+    }
+
+    // warnings and a disabled check don't stop the server from starting
+    assert!(options(&site_root, SiteCheck::Warn).check_site().is_ok());
+    assert!(options(&site_root, SiteCheck::Off).check_site().is_ok());
+}
+
+#[test]
+fn missing_assets_are_listed() {
+    let site_root = std::env::temp_dir().join("leptos_config_site_check_assets");
+    let pkg = site_root.join("pkg");
+    std::fs::create_dir_all(&pkg).unwrap();
+    std::fs::write(pkg.join("app.js"), "").unwrap();
+    _ = std::fs::remove_file(pkg.join("app.wasm"));
+    _ = std::fs::remove_file(pkg.join("app_bg.wasm"));
+
+    let err = options(&site_root, SiteCheck::Error)
+        .check_site()
+        .unwrap_err();
+    assert!(matches!(err, LeptosConfigError::SiteNotFound(missing)
+        if missing == pkg.join("app.wasm").display().to_string()));
+
+    // wasm-pack's name for the WASM file counts too
+    std::fs::write(pkg.join("app_bg.wasm"), "").unwrap();
+    assert!(options(&site_root, SiteCheck::Error).check_site().is_ok());
+}
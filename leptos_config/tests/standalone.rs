@@ -0,0 +1,79 @@
+use leptos_config::{get_configuration_from_file, get_configuration_from_str, FileFormat};
+
+#[tokio::test]
+async fn options_are_loaded_from_a_toml_string() {
+    let conf = get_configuration_from_str(
+        r#"[leptos_options]
+output_name = "app"
+site_root = "site"
+site_pkg_dir = "pkg"
+env = "PROD"
+site_address = "0.0.0.0:8080"
+reload_port = 3001
+"#,
+        FileFormat::Toml,
+    )
+    .await
+    .unwrap();
+
+    let options = conf.leptos_options;
+    assert_eq!(options.output_name, "app");
+    assert_eq!(options.site_root, "site");
+    assert_eq!(options.site_address.to_string(), "0.0.0.0:8080");
+    assert!(matches!(options.env, leptos_config::Env::PROD));
+}
+
+#[tokio::test]
+async fn options_are_loaded_from_a_json_string() {
+    let conf = get_configuration_from_str(
+        r#"{
+            "leptos_options": {
+                "output_name": "app",
+                "site_root": "site",
+                "site_pkg_dir": "pkg",
+                "env": "DEV",
+                "site_address": "127.0.0.1:3000",
+                "reload_port": 3001,
+                "preconnect_origins": ["https://fonts.gstatic.com"]
+            }
+        }"#,
+        FileFormat::Json,
+    )
+    .await
+    .unwrap();
+
+    let options = conf.leptos_options;
+    assert_eq!(options.output_name, "app");
+    assert_eq!(options.reload_port, 3001);
+    assert_eq!(
+        options.preconnect_origins,
+        vec!["https://fonts.gstatic.com"]
+    );
+}
+
+#[tokio::test]
+async fn json_files_are_read_as_json() {
+    let path = std::env::temp_dir().join("leptos_config_standalone.json");
+    std::fs::write(
+        &path,
+        r#"{ "leptos_options": { "output_name": "app", "site_root": "site", "site_pkg_dir": "pkg",
+             "env": "DEV", "site_address": "127.0.0.1:3000", "reload_port": 3001 } }"#,
+    )
+    .unwrap();
+
+    let conf = get_configuration_from_file(path.to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(conf.leptos_options.site_root, "site");
+}
+
+#[tokio::test]
+async fn missing_files_are_not_found() {
+    let err = get_configuration_from_file("/nonexistent/leptos.toml")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        leptos_config::errors::LeptosConfigError::ConfigNotFound
+    ));
+}
@@ -0,0 +1,99 @@
+use leptos_config::{errors::LeptosConfigError, get_configuration_from_files, LeptosOptions};
+
+fn options() -> LeptosOptions {
+    LeptosOptions::builder().output_name("app").build()
+}
+
+fn problems(options: &LeptosOptions) -> String {
+    match options.validate() {
+        Err(LeptosConfigError::ValidationError(problems)) => problems,
+        other => panic!("expected ValidationError, got {other:?}"),
+    }
+}
+
+#[test]
+fn default_options_are_valid() {
+    assert!(options().validate().is_ok());
+}
+
+#[test]
+fn empty_output_name_is_invalid() {
+    let mut options = options();
+    options.output_name = String::new();
+    assert_eq!(problems(&options), "output-name is empty");
+}
+
+#[test]
+fn site_pkg_dir_must_be_relative() {
+    let mut options = options();
+    options.site_pkg_dir = "/pkg".to_string();
+    assert!(problems(&options).contains("site-pkg-dir `/pkg` starts with a `/`"));
+
+    options.site_pkg_dir = String::new();
+    assert_eq!(problems(&options), "site-pkg-dir is empty");
+}
+
+#[test]
+fn reload_port_must_be_a_port() {
+    let mut options = options();
+    options.reload_port = 0;
+    assert_eq!(
+        problems(&options),
+        "reload-port 0 isn't a port from 1 to 65535"
+    );
+
+    options.reload_port = 70000;
+    assert_eq!(
+        problems(&options),
+        "reload-port 70000 isn't a port from 1 to 65535"
+    );
+}
+
+#[test]
+fn charset_must_be_a_charset_name() {
+    let mut options = options();
+    options.charset = "ISO-8859-1".to_string();
+    assert!(options.validate().is_ok());
+
+    options.charset = "utf-8\r\nSet-Cookie: a=b".to_string();
+    assert!(problems(&options).starts_with("charset `utf-8"));
+
+    options.charset = String::new();
+    assert_eq!(problems(&options), "charset `` isn't a charset name");
+}
+
+#[test]
+fn every_problem_is_listed() {
+    let mut options = options();
+    options.output_name = String::new();
+    options.reload_port = 0;
+    assert_eq!(
+        problems(&options),
+        "output-name is empty; reload-port 0 isn't a port from 1 to 65535"
+    );
+}
+
+#[tokio::test]
+async fn invalid_config_files_fail_to_load() {
+    let path = std::env::temp_dir().join("leptos_config_validate_Cargo.toml");
+    std::fs::write(
+        &path,
+        r#"[package]
+name = "app"
+
+[package.metadata.leptos]
+output-name = "app"
+site-root = "target/site"
+site-pkg-dir = "/pkg"
+env = "DEV"
+site-address = "127.0.0.1:3000"
+reload-port = 3001
+"#,
+    )
+    .unwrap();
+
+    let err = get_configuration_from_files(&[path.to_str().unwrap()])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, LeptosConfigError::ValidationError(_)));
+}
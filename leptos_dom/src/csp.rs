@@ -0,0 +1,20 @@
+/// A nonce for the page's `Content-Security-Policy`, which allows the inline `<script>`s that
+/// are rendered on the server to run under a policy like `script-src 'nonce-...'`.
+///
+/// When this is provided as context in the root scope before the app renders (for example, in
+/// the `additional_context` of a server integration), every inline script emitted while
+/// rendering to a stream is given a `nonce` attribute with this value: the script that stores
+/// [Resource](leptos_reactive::Resource) data, the scripts that stream resolved resources and
+/// `<Suspense/>` fragments, and the integrations' hydration scripts.
+///
+/// The nonce should be generated randomly for each response, and only use the characters of
+/// base64 (`A-Z`, `a-z`, `0-9`, `+`, `/`, `-`, `_` and a trailing `=`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CspNonce(pub String);
+
+impl CspNonce {
+  /// Returns the ` nonce="..."` attribute to add to an inline `<script>` tag.
+  pub fn to_attribute(&self) -> String {
+    format!(r#" nonce="{}""#, self.0)
+  }
+}
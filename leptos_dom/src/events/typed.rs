@@ -1,7 +1,7 @@
 //! Collection of typed events.
 
 use std::{borrow::Cow, marker::PhantomData};
-use wasm_bindgen::convert::FromWasmAbi;
+use wasm_bindgen::{convert::FromWasmAbi, JsCast};
 
 /// A trait for converting types into [web_sys events](web_sys).
 pub trait EventDescriptor: Clone {
@@ -78,6 +78,33 @@ impl<E: FromWasmAbi> Custom<E> {
   }
 }
 
+/// Wraps an event handler so that it calls `preventDefault()` and/or `stopPropagation()`
+/// on the event before it runs, for modifiers like `on:submit:prevent` in the
+/// [`view`](https://docs.rs/leptos_macro/latest/leptos_macro/macro.view.html) macro.
+///
+/// The event is only used to give the handler's argument its type.
+#[doc(hidden)]
+pub fn with_modifiers<E: EventDescriptor>(
+  _event: &E,
+  prevent: bool,
+  stop: bool,
+  mut handler: impl FnMut(E::EventType) + 'static,
+) -> impl FnMut(E::EventType) + 'static
+where
+  E::EventType: JsCast,
+{
+  move |ev: E::EventType| {
+    let event = ev.unchecked_ref::<web_sys::Event>();
+    if prevent {
+      event.prevent_default();
+    }
+    if stop {
+      event.stop_propagation();
+    }
+    handler(ev)
+  }
+}
+
 macro_rules! generate_event_types {
   {$(
     $( #[$does_not_bubble:ident] )?
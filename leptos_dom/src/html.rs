@@ -540,6 +540,20 @@ impl<El: ElementDescriptor + 'static> HtmlElement<El> {
     }
   }
 
+  /// Adds each of the `(name, value)` pairs as an attribute, as with
+  /// [`attr`](Self::attr). Values that are functions or signals update their
+  /// attributes reactively.
+  #[track_caller]
+  pub fn attrs<N, A>(self, attrs: impl IntoIterator<Item = (N, A)>) -> Self
+  where
+    N: Into<Cow<'static, str>>,
+    A: IntoAttribute,
+  {
+    attrs
+      .into_iter()
+      .fold(self, |this, (name, value)| this.attr(name, value))
+  }
+
   /// Adds a class to an element.
   #[track_caller]
   pub fn class(
@@ -814,6 +828,36 @@ pub fn text(text: impl Into<Cow<'static, str>>) -> Text {
   Text::new(text.into())
 }
 
+/// Creates a text node whose contents are not escaped when it's rendered to
+/// HTML on the server.
+///
+/// Strings in a view are always escaped when they're server-rendered, so
+/// `{"<b>bold</b>"}` shows up as the literal text `<b>bold</b>`. That's
+/// usually what you want, but it gets in the way of text that the browser
+/// doesn't parse as HTML, like the contents of a `<script>` or `<style>`:
+/// ```
+/// # cfg_if::cfg_if! { if #[cfg(not(any(feature = "csr", feature = "hydrate")))] {
+/// # use leptos::*;
+/// let data = r#"{"a":1,"b":"<c>"}"#;
+/// let html = render_to_string(move |cx| view! { cx,
+///   <script type="application/json">{html::raw(data)}</script>
+/// });
+/// assert!(html.contains(r#"{"a":1,"b":"<c>"}"#));
+/// # }}
+/// ```
+///
+/// Only that one node is affected. In the browser, the contents are set as
+/// the text of a text node, so they are never parsed as HTML there.
+///
+/// **You're responsible for making sure the contents are safe.** Anything
+/// in them is sent to the browser as-is, so passing untrusted input to `raw`,
+/// like a user's name or a query parameter, opens your app up to cross-site
+/// scripting (XSS) attacks. This includes a `</script>` in the contents of a
+/// `<script>`, which ends the script early.
+pub fn raw(text: impl Into<Cow<'static, str>>) -> Text {
+  Text::new_raw(text.into())
+}
+
 macro_rules! generate_html_tags {
   ($(
     #[$meta:meta]
@@ -0,0 +1,23 @@
+use std::rc::Rc;
+
+/// Sends the data of the app's [Resource](leptos_reactive::Resource)s in a separate response,
+/// instead of streaming it into the page.
+///
+/// When this is provided as context in the root scope before the app renders (like
+/// [CspNonce](crate::CspNonce)), rendering to a stream waits for every resource to resolve,
+/// and then calls this function with all of their data, as a JSON array of `[id, json]` pairs
+/// in which `json` is the resource's serialized value. The function stores the data and
+/// returns the URL it will be served at, and the page gets a single script that fetches it and
+/// passes each resource's value to the client, in place of a script for each resource. If the
+/// script can't fetch the data, the resources load it in the browser instead. If the function
+/// couldn't store the data, it returns `None`, and the data is streamed into the page instead.
+///
+/// The integrations provide this when `LeptosOptions::hydration_state_endpoint` is set.
+#[derive(Clone)]
+pub struct HydrationStateEndpoint(pub Rc<dyn Fn(String) -> Option<String>>);
+
+impl std::fmt::Debug for HydrationStateEndpoint {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_tuple("HydrationStateEndpoint").finish()
+  }
+}
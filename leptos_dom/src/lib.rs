@@ -9,25 +9,34 @@
 pub extern crate tracing;
 
 mod components;
+mod csp;
 mod events;
 mod helpers;
 #[doc(hidden)]
 pub mod html;
 mod hydration;
+mod hydration_state;
 mod logging;
 mod macro_helpers;
 mod node_ref;
 mod ssr;
+#[cfg(all(
+  feature = "testing",
+  not(all(target_arch = "wasm32", feature = "web"))
+))]
+pub mod testing;
 mod transparent;
 
 use cfg_if::cfg_if;
 pub use components::*;
+pub use csp::*;
 pub use events::typed as ev;
 #[cfg(all(target_arch = "wasm32", feature = "web"))]
 use events::{add_event_listener, add_event_listener_undelegated};
 pub use helpers::*;
 pub use html::*;
 pub use hydration::{HydrationCtx, HydrationKey};
+pub use hydration_state::*;
 pub use js_sys;
 use leptos_reactive::Scope;
 pub use logging::*;
@@ -333,6 +342,10 @@ pub struct Text {
   node: web_sys::Node,
   /// The current contents of the text node.
   pub content: Cow<'static, str>,
+  /// Whether the contents are rendered into HTML as they are, rather than
+  /// escaped. See [`raw`](crate::html::raw).
+  #[cfg(not(all(target_arch = "wasm32", feature = "web")))]
+  pub(crate) raw: bool,
 }
 
 impl fmt::Debug for Text {
@@ -357,6 +370,18 @@ impl Text {
         .create_text_node(&content)
         .unchecked_into::<web_sys::Node>(),
       content,
+      #[cfg(not(all(target_arch = "wasm32", feature = "web")))]
+      raw: false,
+    }
+  }
+
+  /// Creates a new [`Text`] whose contents are not escaped when it's rendered
+  /// to HTML. See [`raw`](crate::html::raw).
+  pub fn new_raw(content: Cow<'static, str>) -> Self {
+    Self {
+      #[cfg(not(all(target_arch = "wasm32", feature = "web")))]
+      raw: true,
+      ..Self::new(content)
     }
   }
 }
@@ -99,6 +99,12 @@ pub trait IntoAttribute {
   fn into_attribute(self, cx: Scope) -> Attribute;
 }
 
+impl IntoAttribute for Attribute {
+  fn into_attribute(self, _: Scope) -> Attribute {
+    self
+  }
+}
+
 impl IntoAttribute for String {
   fn into_attribute(self, _: Scope) -> Attribute {
     Attribute::String(self)
@@ -134,6 +140,23 @@ impl<T: IntoAttribute> IntoAttribute for (Scope, T) {
   }
 }
 
+/// Filters a spread of attributes, like `<div {..attrs}/>` in the
+/// [`view`](https://docs.rs/leptos_macro/latest/leptos_macro/macro.view.html) macro,
+/// down to the ones the element doesn't set itself. An attribute written on the
+/// element wins over one of the same name in the spread.
+#[doc(hidden)]
+pub fn spread_attrs<N, A>(
+  attrs: impl IntoIterator<Item = (N, A)>,
+  explicit: &'static [&'static str],
+) -> impl Iterator<Item = (N, A)>
+where
+  N: AsRef<str>,
+{
+  attrs
+    .into_iter()
+    .filter(move |(name, _)| !explicit.contains(&name.as_ref()))
+}
+
 macro_rules! attr_type {
   ($attr_type:ty) => {
     impl IntoAttribute for $attr_type {
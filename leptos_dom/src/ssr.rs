@@ -1,11 +1,19 @@
 #![cfg(not(all(target_arch = "wasm32", feature = "web")))]
 
-use crate::{CoreComponent, HydrationCtx, IntoView, View};
+use crate::{
+  CoreComponent, CspNonce, HydrationCtx, HydrationStateEndpoint, IntoView,
+  Text, View,
+};
 use cfg_if::cfg_if;
-use futures::{stream::FuturesUnordered, Stream, StreamExt};
+use futures::{
+  stream::{FuturesUnordered, LocalBoxStream},
+  Future, Stream, StreamExt,
+};
 use itertools::Itertools;
 use leptos_reactive::*;
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap, pin::Pin};
+
+type PinnedFuture<T> = Pin<Box<dyn Future<Output = T>>>;
 
 /// Renders the given function to a static HTML string.
 ///
@@ -119,6 +127,41 @@ pub fn render_to_stream_with_prefix_undisposed_with_context(
   view: impl FnOnce(Scope) -> View + 'static,
   prefix: impl FnOnce(Scope) -> Cow<'static, str> + 'static,
   additional_context: impl FnOnce(Scope) + 'static,
+) -> (impl Stream<Item = String>, RuntimeId, ScopeId) {
+  render_to_stream_inner(view, prefix, additional_context, false)
+}
+
+/// Renders a function to a stream of HTML strings in document order, and returns the [Scope] and
+/// [RuntimeId] that were created, so they can be disposed when appropriate. After the `view` runs,
+/// the `prefix` will run with the same scope.
+///
+/// Unlike [render_to_stream_with_prefix_undisposed_with_context], which sends the whole shell at once
+/// with a fallback for each `<Suspense/>` and then swaps in each one's HTML with a script as it
+/// resolves, this sends the page in order, up to each `<Suspense/>` in turn, and waits for it to
+/// resolve before sending its HTML in place of the fallback. The fallbacks are never shown, and the
+/// page doesn't need JavaScript to be complete, but everything after a `<Suspense/>` waits for it, even
+/// if it resolves after a `<Suspense/>` further down. All of their resources still load at the same time.
+///
+/// This renders:
+/// 1) the prefix
+/// 2) JavaScript necessary to receive streaming [Resource](leptos_reactive::Resource) data.
+/// 3) the application shell, up to the first `<Suspense/>` that isn't already resolved
+/// 4) the HTML of that `<Suspense/>`, once the resources read under it have resolved, and the
+///    shell up to the next one, until the end of the shell.
+/// 5) streaming [Resource](leptos_reactive::Resource) data.
+pub fn render_to_stream_in_order_with_prefix_undisposed_with_context(
+  view: impl FnOnce(Scope) -> View + 'static,
+  prefix: impl FnOnce(Scope) -> Cow<'static, str> + 'static,
+  additional_context: impl FnOnce(Scope) + 'static,
+) -> (impl Stream<Item = String>, RuntimeId, ScopeId) {
+  render_to_stream_inner(view, prefix, additional_context, true)
+}
+
+fn render_to_stream_inner(
+  view: impl FnOnce(Scope) -> View + 'static,
+  prefix: impl FnOnce(Scope) -> Cow<'static, str> + 'static,
+  additional_context: impl FnOnce(Scope) + 'static,
+  in_order: bool,
 ) -> (impl Stream<Item = String>, RuntimeId, ScopeId) {
   HydrationCtx::reset_id();
 
@@ -126,13 +169,25 @@ pub fn render_to_stream_with_prefix_undisposed_with_context(
   let runtime = create_runtime();
 
   let (
-    (shell, prefix, pending_resources, pending_fragments, serializers),
+    (
+      shell,
+      prefix,
+      pending_resources,
+      pending_fragments,
+      serializers,
+      nonce,
+      state_endpoint,
+    ),
     scope,
     _,
   ) = run_scope_undisposed(runtime, {
     move |cx| {
       // Add additional context items
       additional_context(cx);
+      // every inline script gets the CSP nonce, if there is one
+      let nonce = use_context::<CspNonce>(cx)
+        .map(|nonce| nonce.to_attribute())
+        .unwrap_or_default();
       // the actual app body/template code
       // this does NOT contain any of the data being loaded asynchronously in resources
       let shell = view(cx).render_to_string(cx);
@@ -147,10 +202,32 @@ pub fn render_to_stream_with_prefix_undisposed_with_context(
         pending_resources,
         cx.pending_fragments(),
         cx.serialization_resolvers(),
+        nonce,
+        use_context::<HydrationStateEndpoint>(cx),
       )
     }
   });
 
+  // the script that resources are sent to, which is needed before any of them has resolved
+  let resource_script = format!(
+    r#"<script{nonce}>
+                  __LEPTOS_PENDING_RESOURCES = {pending_resources};
+                  __LEPTOS_RESOLVED_RESOURCES = new Map();
+                  __LEPTOS_RESOURCE_RESOLVERS = new Map();
+                  __LEPTOS_RESOURCE_LOADERS = {{}};
+              </script>"#
+  );
+
+  if in_order {
+    let html =
+      futures::stream::once(
+        async move { format!("{prefix}{resource_script}") },
+      )
+      .chain(in_order_chunks(shell, pending_fragments));
+    let resources = resource_stream(serializers, state_endpoint, nonce);
+    return (html.chain(resources).boxed_local(), runtime, scope);
+  }
+
   let fragments = FuturesUnordered::new();
   for (fragment_id, (key_before, fut)) in pending_fragments {
     fragments.push(async move { (fragment_id, key_before, fut.await) })
@@ -159,11 +236,13 @@ pub fn render_to_stream_with_prefix_undisposed_with_context(
   // resources and fragments
   // stream HTML for each <Suspense/> as it resolves
   // TODO can remove id_before_suspense entirely now
-  let fragments = fragments.map(|(fragment_id, _, html)| {
-    format!(
-      r#"
+  let fragments = fragments.map({
+    let nonce = nonce.clone();
+    move |(fragment_id, _, html)| {
+      format!(
+        r#"
               <template id="{fragment_id}f">{html}</template>
-              <script>
+              <script{nonce}>
                   var id = "{fragment_id}";
                   var open;
                   var close;
@@ -183,21 +262,10 @@ pub fn render_to_stream_with_prefix_undisposed_with_context(
                   close.parentNode.insertBefore(tpl.content.cloneNode(true), close);
               </script>
               "#
-    )
-  });
-  // stream data for each Resource as it resolves
-  let resources = serializers.map(|(id, json)| {
-    let id = serde_json::to_string(&id).unwrap();
-    format!(
-      r#"<script>
-                  if(__LEPTOS_RESOURCE_RESOLVERS.get({id})) {{
-                      __LEPTOS_RESOURCE_RESOLVERS.get({id})({json:?})
-                  }} else {{
-                      __LEPTOS_RESOLVED_RESOURCES.set({id}, {json:?});
-                  }}
-              </script>"#,
-    )
+      )
+    }
   });
+  let resources = resource_stream(serializers, state_endpoint, nonce);
 
   // HTML for the view function and script to store resources
   let stream = futures::stream::once(async move {
@@ -205,11 +273,7 @@ pub fn render_to_stream_with_prefix_undisposed_with_context(
       r#"
               {prefix}
               {shell}
-              <script>
-                  __LEPTOS_PENDING_RESOURCES = {pending_resources};
-                  __LEPTOS_RESOLVED_RESOURCES = new Map();
-                  __LEPTOS_RESOURCE_RESOLVERS = new Map();
-              </script>
+              {resource_script}
           "#
     )
   })
@@ -218,7 +282,135 @@ pub fn render_to_stream_with_prefix_undisposed_with_context(
   .chain(fragments)
   .chain(resources);
 
-  (stream, runtime, scope)
+  (stream.boxed_local(), runtime, scope)
+}
+
+// A piece of a page that's streamed in order: HTML that's ready, or a `<Suspense/>` that's still loading
+enum InOrderChunk {
+  Html(String),
+  Suspense(String, PinnedFuture<String>),
+}
+
+// Splits the shell at each pending `<Suspense/>`, whose fallback is replaced by its HTML once it resolves
+fn in_order_chunks(
+  shell: Cow<'static, str>,
+  mut pending_fragments: HashMap<String, (String, PinnedFuture<String>)>,
+) -> impl Stream<Item = String> {
+  let mut chunks = Vec::new();
+  let mut rest = shell.as_ref();
+  // the pending <Suspense/> that comes first in the rest of the shell
+  while let Some((start, id)) = pending_fragments
+    .keys()
+    .filter_map(|id| {
+      rest
+        .find(&format!("<!--suspense-open-{id}-->"))
+        .map(|start| (start, id.clone()))
+    })
+    .min()
+  {
+    let close = format!("<!--suspense-close-{id}-->");
+    let end = match rest[start..].find(&close) {
+      Some(len) => start + len + close.len(),
+      None => break,
+    };
+    let (_, fut) = pending_fragments.remove(&id).unwrap();
+    chunks.push(InOrderChunk::Html(rest[..start].to_string()));
+    chunks.push(InOrderChunk::Suspense(id, fut));
+    rest = &rest[end..];
+  }
+  chunks.push(InOrderChunk::Html(rest.to_string()));
+
+  futures::stream::iter(chunks).then(|chunk| async move {
+    match chunk {
+      InOrderChunk::Html(html) => html,
+      InOrderChunk::Suspense(id, fut) => {
+        let html = fut.await;
+        format!("<!--suspense-open-{id}-->{html}<!--suspense-close-{id}-->")
+      }
+    }
+  })
+}
+
+// Streams the data for each Resource as it resolves, or sends all of it to the endpoint
+fn resource_stream(
+  serializers: FuturesUnordered<PinnedFuture<(ResourceId, String)>>,
+  state_endpoint: Option<HydrationStateEndpoint>,
+  nonce: String,
+) -> LocalBoxStream<'static, String> {
+  match state_endpoint {
+    None => serializers
+      .map(move |(id, json)| resource_script(&nonce, id, json))
+      .boxed_local(),
+    Some(HydrationStateEndpoint(endpoint)) => {
+      futures::stream::once(async move {
+        let state = serializers.collect::<Vec<_>>().await;
+        if state.is_empty() {
+          return String::new();
+        }
+        let url = match endpoint(serde_json::to_string(&state).unwrap()) {
+          Some(url) => serde_json::to_string(&url).unwrap(),
+          // the endpoint couldn't keep the data, so it's sent with the page after all
+          None => {
+            return state
+              .into_iter()
+              .map(|(id, json)| resource_script(&nonce, id, json))
+              .collect();
+          }
+        };
+        // if the data can't be fetched, like when the page was cached for longer than the server
+        // keeps it, every pending resource loads its data itself, including those that are only
+        // created later on
+        format!(
+          r#"<script{nonce}>
+                  fetch({url}).then((res) => {{
+                      if(!res.ok) {{
+                          throw new Error(res.statusText);
+                      }}
+                      return res.json();
+                  }}).then((state) => {{
+                      for (const [id, json] of state) {{
+                          if(__LEPTOS_RESOURCE_RESOLVERS.get(id)) {{
+                              __LEPTOS_RESOURCE_RESOLVERS.get(id)(json)
+                          }} else {{
+                              __LEPTOS_RESOLVED_RESOURCES.set(id, json);
+                          }}
+                      }}
+                  }}).catch(() => {{
+                      const pending = __LEPTOS_PENDING_RESOURCES;
+                      __LEPTOS_PENDING_RESOURCES = [];
+                      for (const id of pending) {{
+                          const load = __LEPTOS_RESOURCE_LOADERS[JSON.stringify(id)];
+                          if(load) {{
+                              load();
+                          }}
+                      }}
+                      __LEPTOS_RESOURCE_LOADERS = new Proxy({{}}, {{
+                          set: (_, id, load) => {{
+                              load();
+                              return true;
+                          }}
+                      }});
+                  }});
+              </script>"#,
+        )
+      })
+      .boxed_local()
+    }
+  }
+}
+
+// The script that passes the data of one Resource to the client
+fn resource_script(nonce: &str, id: ResourceId, json: String) -> String {
+  let id = serde_json::to_string(&id).unwrap();
+  format!(
+    r#"<script{nonce}>
+                  if(__LEPTOS_RESOURCE_RESOLVERS.get({id})) {{
+                      __LEPTOS_RESOURCE_RESOLVERS.get({id})({json:?})
+                  }} else {{
+                      __LEPTOS_RESOLVED_RESOURCES.set({id}, {json:?});
+                  }}
+              </script>"#,
+  )
 }
 
 impl View {
@@ -229,7 +421,7 @@ impl View {
 
   pub(crate) fn render_to_string_helper(self) -> Cow<'static, str> {
     match self {
-      View::Text(node) => node.content,
+      View::Text(node) => text_to_html(node),
       View::Component(node) => {
         let content = || {
           node
@@ -298,9 +490,9 @@ impl View {
                   // browser create the dynamic text as it's own text node
                   if let View::Text(t) = child {
                     if !cfg!(debug_assertions) {
-                      format!("<!>{}", t.content).into()
+                      format!("<!>{}", text_to_html(t)).into()
                     } else {
-                      t.content
+                      text_to_html(t)
                     }
                   } else {
                     child.render_to_string_helper()
@@ -393,11 +585,8 @@ impl View {
                 None
               } else {
                 Some(
-                  format!(
-                    " {name}=\"{}\"",
-                    html_escape::encode_double_quoted_attribute(&value)
-                  )
-                  .into(),
+                  format!(" {name}=\"{}\"", escape_attr_for(&name, &value))
+                    .into(),
                 )
               }
             })
@@ -408,10 +597,17 @@ impl View {
           } else if let Some(inner_html) = inner_html {
             format!("<{tag_name}{attrs}>{inner_html}</{tag_name}>").into()
           } else {
+            let raw_text = is_raw_text_element(&tag_name);
             let children = el
               .children
               .into_iter()
-              .map(|node| node.render_to_string_helper())
+              .map(|node| {
+                if raw_text {
+                  into_raw_text(node).render_to_string_helper()
+                } else {
+                  node.render_to_string_helper()
+                }
+              })
               .join("");
 
             format!("<{tag_name}{attrs}>{children}</{tag_name}>").into()
@@ -459,6 +655,40 @@ fn to_kebab_case(name: &str) -> String {
   new_name
 }
 
+// The contents of `<script>` and `<style>` are raw text, which the browser doesn't
+// unescape, so text inside them is rendered as it is
+fn is_raw_text_element(tag_name: &str) -> bool {
+  tag_name.eq_ignore_ascii_case("script")
+    || tag_name.eq_ignore_ascii_case("style")
+}
+
+fn into_raw_text(view: View) -> View {
+  match view {
+    View::Text(mut text) => {
+      text.raw = true;
+      View::Text(text)
+    }
+    view => view,
+  }
+}
+
+// Renders a child of a `<script>` or `<style>` in the `view` macro's server-side template
+#[doc(hidden)]
+pub fn raw_text_to_string(cx: Scope, view: View) -> Cow<'static, str> {
+  into_raw_text(view).render_to_string(cx)
+}
+
+// Text is escaped, unless it was created with `html::raw` or is inside a `<script>` or `<style>`
+fn text_to_html(text: Text) -> Cow<'static, str> {
+  if text.raw {
+    return text.content;
+  }
+  match html_escape::encode_text(&text.content) {
+    Cow::Borrowed(_) => text.content,
+    Cow::Owned(escaped) => escaped.into(),
+  }
+}
+
 #[doc(hidden)]
 pub fn escape_attr<T>(value: &T) -> Cow<'_, str>
 where
@@ -466,3 +696,117 @@ where
 {
   html_escape::encode_double_quoted_attribute(value)
 }
+
+// Renders a spread of attributes, like `<div {..attrs}/>` in the `view` macro,
+// for the server-side template, each with a leading space
+#[doc(hidden)]
+pub fn spread_attrs_ssr<N, A>(
+  cx: Scope,
+  attrs: impl IntoIterator<Item = (N, A)>,
+  explicit: &'static [&'static str],
+) -> String
+where
+  N: AsRef<str>,
+  A: crate::IntoAttribute,
+{
+  let mut html = String::new();
+  for (name, value) in crate::spread_attrs(attrs, explicit) {
+    let name = name.as_ref();
+    let mut value = value.into_attribute(cx);
+    while let crate::Attribute::Fn(_, f) = value {
+      value = f();
+    }
+    match value {
+      crate::Attribute::String(value)
+      | crate::Attribute::Option(_, Some(value)) => {
+        html.push(' ');
+        html.push_str(name);
+        html.push_str("=\"");
+        html.push_str(&escape_attr_for(name, &value));
+        html.push('"');
+      }
+      crate::Attribute::Bool(true) => {
+        html.push(' ');
+        html.push_str(name);
+      }
+      _ => {}
+    }
+  }
+  html
+}
+
+/// Whether attributes that hold a URL, like `href` or `src`, can be rendered on the server
+/// with a URL that runs a script when it's followed, like `javascript:alert(1)`.
+///
+/// Set it once, before rendering, with [set_unsafe_urls].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnsafeUrls {
+  /// URLs are rendered as they are, only escaped like any other attribute value.
+  #[default]
+  Allow,
+  /// `javascript:` and `vbscript:` URLs are rendered as `about:invalid`, which does nothing.
+  Block,
+}
+
+static BLOCK_UNSAFE_URLS: std::sync::atomic::AtomicBool =
+  std::sync::atomic::AtomicBool::new(false);
+
+/// Sets whether script URLs can be rendered in URL attributes on the server, for every
+/// render after it's called. See [UnsafeUrls].
+pub fn set_unsafe_urls(policy: UnsafeUrls) {
+  BLOCK_UNSAFE_URLS.store(
+    policy == UnsafeUrls::Block,
+    std::sync::atomic::Ordering::Relaxed,
+  );
+}
+
+// Attributes whose value is a URL the browser may navigate to or load
+const URL_ATTRIBUTES: &[&str] = &[
+  "action",
+  "background",
+  "cite",
+  "data",
+  "formaction",
+  "href",
+  "manifest",
+  "poster",
+  "src",
+  "xlink:href",
+];
+
+/// Escapes the value of the attribute `name` for server-rendered HTML, where attribute values
+/// are always double-quoted.
+///
+/// HTML is escaped differently depending on where a value appears:
+/// - text is escaped so `&`, `<` and `>` can't start an entity or a tag;
+/// - attribute values also have `"` escaped, so they can't close the attribute;
+/// - attributes that hold a URL, like `href` and `src`, are escaped like other attributes and,
+///   if [UnsafeUrls::Block] has been set with [set_unsafe_urls], a `javascript:` or
+///   `vbscript:` URL is replaced by `about:invalid`.
+///
+/// Literal text and attribute values written in `view!` are escaped the same way when the
+/// macro expands, but aren't checked for script URLs. Text that's meant to be HTML, like
+/// `inner_html` or `html::raw`, is never escaped.
+pub fn escape_attr_for<'a>(name: &str, value: &'a str) -> Cow<'a, str> {
+  if BLOCK_UNSAFE_URLS.load(std::sync::atomic::Ordering::Relaxed)
+    && URL_ATTRIBUTES
+      .iter()
+      .any(|attr| attr.eq_ignore_ascii_case(name))
+    && is_script_url(value)
+  {
+    return Cow::Borrowed("about:invalid");
+  }
+  html_escape::encode_double_quoted_attribute(value)
+}
+
+// Browsers ignore whitespace and control characters in a URL's scheme, and its case,
+// so `java\tscript:` and ` JavaScript:` run scripts too
+fn is_script_url(url: &str) -> bool {
+  let scheme = url
+    .chars()
+    .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+    .take_while(|c| *c != ':')
+    .collect::<String>()
+    .to_ascii_lowercase();
+  url.contains(':') && (scheme == "javascript" || scheme == "vbscript")
+}
@@ -0,0 +1,280 @@
+//! Renders views into a tree of nodes, so tests can check their structure
+//! without matching on HTML strings.
+
+use crate::{render_to_string, IntoView, View};
+use leptos_reactive::Scope;
+use std::collections::BTreeMap;
+
+/// A node of a rendered view, as returned by [`render_to_tree`].
+///
+/// It serializes into JSON with a `type` field saying which kind of node it
+/// is, so
+/// ```html
+/// <p class="greeting">"Hello, " <b>"world"</b></p>
+/// ```
+/// becomes
+/// ```json
+/// {
+///   "type": "element",
+///   "name": "p",
+///   "attrs": { "class": "greeting" },
+///   "children": [
+///     { "type": "text", "text": "Hello, " },
+///     {
+///       "type": "element",
+///       "name": "b",
+///       "attrs": {},
+///       "children": [{ "type": "text", "text": "world" }]
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TestNode {
+  /// An HTML element.
+  Element {
+    /// The element's tag name, like `div`.
+    name: String,
+    /// The element's attributes, sorted by name. Attributes without a value,
+    /// like `disabled`, have an empty one.
+    attrs: BTreeMap<String, String>,
+    /// The element's children. Void elements, like `<br>`, have none.
+    children: Vec<TestNode>,
+  },
+  /// A text node, with its entities decoded.
+  Text {
+    /// The text.
+    text: String,
+  },
+}
+
+impl TestNode {
+  /// Creates an element node with no attributes or children.
+  pub fn element(name: impl Into<String>) -> Self {
+    Self::Element {
+      name: name.into(),
+      attrs: BTreeMap::new(),
+      children: Vec::new(),
+    }
+  }
+
+  /// Creates a text node.
+  pub fn text(text: impl Into<String>) -> Self {
+    Self::Text { text: text.into() }
+  }
+
+  /// Adds an attribute to an element node. Does nothing to a text node.
+  pub fn attr(
+    mut self,
+    name: impl Into<String>,
+    value: impl Into<String>,
+  ) -> Self {
+    if let Self::Element { attrs, .. } = &mut self {
+      attrs.insert(name.into(), value.into());
+    }
+    self
+  }
+
+  /// Adds a child to an element node. Does nothing to a text node.
+  pub fn child(mut self, child: TestNode) -> Self {
+    if let Self::Element { children, .. } = &mut self {
+      children.push(child);
+    }
+    self
+  }
+
+  /// Returns the node as a JSON value, in the format described on [`TestNode`].
+  pub fn to_json(&self) -> serde_json::Value {
+    serde_json::to_value(self).expect("a TestNode is always valid JSON")
+  }
+}
+
+/// Renders the given function into a tree of [`TestNode`]s, one for each
+/// node at the top level of the view.
+///
+/// The tree is what a browser would build from the server-rendered HTML,
+/// minus the parts that only matter for hydration: the comments that mark
+/// components and dynamic children are left out, as are the `id` attributes
+/// that hold hydration keys (like `id="_0-1"`), and text that was split up by
+/// those comments is joined back together.
+/// ```
+/// # cfg_if::cfg_if! { if #[cfg(all(feature = "testing", not(any(feature = "csr", feature = "hydrate"))))] {
+/// # use leptos::*;
+/// # use leptos_dom::testing::*;
+/// let tree = render_to_tree(|cx| view! { cx,
+///   <ul class="list"><li>"One"</li></ul>
+/// });
+/// assert_eq!(
+///   tree,
+///   vec![TestNode::element("ul")
+///     .attr("class", "list")
+///     .child(TestNode::element("li").child(TestNode::text("One")))]
+/// );
+/// # }}
+/// ```
+///
+/// Like [`render_to_string`], this doesn't wait for resources, so any
+/// `<Suspense/>` is rendered with its fallback.
+pub fn render_to_tree<F, N>(f: F) -> Vec<TestNode>
+where
+  F: FnOnce(Scope) -> N + 'static,
+  N: IntoView,
+{
+  parse_html(&render_to_string(f))
+}
+
+impl View {
+  /// Consumes the node and renders it into a tree of [`TestNode`]s. See
+  /// [`render_to_tree`].
+  pub fn render_to_tree(self, cx: Scope) -> Vec<TestNode> {
+    parse_html(&self.render_to_string(cx))
+  }
+}
+
+const VOID_ELEMENTS: &[&str] = &[
+  "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta",
+  "param", "source", "track", "wbr",
+];
+
+// Parses the HTML that the renderer produces. This isn't a general HTML parser:
+// it expects the well-formed output of `render_to_string`.
+fn parse_html(html: &str) -> Vec<TestNode> {
+  let mut parser = Parser { rest: html };
+  parser.nodes(None)
+}
+
+struct Parser<'a> {
+  rest: &'a str,
+}
+
+impl Parser<'_> {
+  // Parses nodes until the end of the input or the closing tag of `parent`
+  fn nodes(&mut self, parent: Option<&str>) -> Vec<TestNode> {
+    let mut nodes = Vec::new();
+    while !self.rest.is_empty() {
+      if let Some(rest) = self.rest.strip_prefix("<!--") {
+        // hydration markers
+        self.rest = rest.split_once("-->").map(|(_, rest)| rest).unwrap_or("");
+      } else if let Some(rest) = self.rest.strip_prefix("<!") {
+        // `<!>` markers and doctypes
+        self.rest = rest.split_once('>').map(|(_, rest)| rest).unwrap_or("");
+      } else if let Some(rest) = self.rest.strip_prefix("</") {
+        let (name, rest) = rest.split_once('>').unwrap_or((rest, ""));
+        self.rest = rest;
+        if Some(name.trim()) == parent {
+          break;
+        }
+      } else if self.rest.starts_with('<')
+        && self.rest[1..].starts_with(|c: char| c.is_ascii_alphabetic())
+      {
+        nodes.push(self.element());
+      } else {
+        // a `<` that doesn't start a tag is part of the text
+        let first = self.rest.chars().next().map_or(0, char::len_utf8);
+        let end = self.rest[first..].find('<').map(|end| end + first);
+        let (text, rest) = self.rest.split_at(end.unwrap_or(self.rest.len()));
+        self.rest = rest;
+        push_text(&mut nodes, &html_escape::decode_html_entities(text));
+      }
+    }
+    nodes
+  }
+
+  fn element(&mut self) -> TestNode {
+    let end = self.rest[1..]
+      .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+      .map(|end| end + 1)
+      .unwrap_or(self.rest.len());
+    let name = self.rest[1..end].to_ascii_lowercase();
+    self.rest = &self.rest[end..];
+
+    let mut attrs = BTreeMap::new();
+    let mut self_closing = false;
+    loop {
+      self.rest = self.rest.trim_start();
+      if let Some(rest) = self.rest.strip_prefix("/>") {
+        self.rest = rest;
+        self_closing = true;
+        break;
+      } else if let Some(rest) = self.rest.strip_prefix('>') {
+        self.rest = rest;
+        break;
+      } else if self.rest.is_empty() {
+        break;
+      } else if let Some(rest) = self.rest.strip_prefix('/') {
+        self.rest = rest;
+        continue;
+      }
+
+      let end = self
+        .rest
+        .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+        .unwrap_or(self.rest.len());
+      let attr = self.rest[..end].to_string();
+      self.rest = &self.rest[end..];
+      let value = match self.rest.strip_prefix('=') {
+        Some(rest) => {
+          let (value, rest) = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+              rest[1..].split_once(quote).unwrap_or((&rest[1..], ""))
+            }
+            _ => rest.split_at(
+              rest
+                .find(|c: char| c.is_whitespace() || c == '>')
+                .unwrap_or(rest.len()),
+            ),
+          };
+          self.rest = rest;
+          html_escape::decode_html_entities(value).into_owned()
+        }
+        None => String::new(),
+      };
+      if !(attr == "id" && is_hydration_key(&value)) {
+        attrs.insert(attr, value);
+      }
+    }
+
+    let children = if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+      Vec::new()
+    } else if matches!(name.as_str(), "script" | "style") {
+      // their contents are never escaped
+      let close = format!("</{name}>");
+      let (text, rest) =
+        self.rest.split_once(&close).unwrap_or((self.rest, ""));
+      self.rest = rest;
+      let mut children = Vec::new();
+      push_text(&mut children, text);
+      children
+    } else {
+      self.nodes(Some(&name))
+    };
+
+    TestNode::Element {
+      name,
+      attrs,
+      children,
+    }
+  }
+}
+
+// Adds text to the nodes, joining it to the last node if that's text too
+fn push_text(nodes: &mut Vec<TestNode>, text: &str) {
+  if text.is_empty() {
+    return;
+  }
+  match nodes.last_mut() {
+    Some(TestNode::Text { text: last }) => last.push_str(text),
+    _ => nodes.push(TestNode::text(text)),
+  }
+}
+
+// Hydration keys look like `_0-1-2`
+fn is_hydration_key(value: &str) -> bool {
+  match value.strip_prefix('_') {
+    Some(key) => {
+      !key.is_empty() && key.chars().all(|c| c.is_ascii_digit() || c == '-')
+    }
+    None => false,
+  }
+}
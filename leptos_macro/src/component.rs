@@ -165,10 +165,21 @@ impl ToTokens for Model {
             (quote! {}, quote! {}, quote! {})
         };
 
-        let component = if *is_transparent {
+        // `async fn` components are awaited by the nearest `<Suspense/>`
+        let body_call = if body.sig.asyncness.is_some() {
+            quote! {
+                ::leptos::async_component(cx, move |cx| #body_name(cx, #prop_names))
+            }
+        } else {
             quote! {
                 #body_name(cx, #prop_names)
             }
+        };
+
+        let component = if *is_transparent {
+            quote! {
+                #body_call
+            }
         } else {
             quote! {
                 ::leptos::Component::new(
@@ -176,7 +187,7 @@ impl ToTokens for Model {
                     move |cx| {
                         #tracing_guard_expr
 
-                        #body_name(cx, #prop_names)
+                        #body_call
                     }
                 )
             }
@@ -227,6 +238,10 @@ impl Model {
 
         self
     }
+
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
 }
 
 struct Prop {
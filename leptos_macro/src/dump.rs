@@ -0,0 +1,52 @@
+use proc_macro2::TokenStream;
+use std::{env, fs::OpenOptions, io::Write};
+
+// With `LEPTOS_DUMP_EXPANSION=1`, writes the code generated by a macro to stderr, or appends
+// it to the file named by `LEPTOS_DUMP_EXPANSION_FILE`. This only happens while the macro is
+// expanded, so the generated code is the same either way. See the docs on `view!`.
+pub(crate) fn dump_expansion(label: &str, tokens: &TokenStream) {
+    if env::var("LEPTOS_DUMP_EXPANSION").as_deref() != Ok("1") {
+        return;
+    }
+
+    let dump = format!("// ===== {label} =====\n{}\n", pretty(tokens));
+    match env::var_os("LEPTOS_DUMP_EXPANSION_FILE") {
+        Some(path) => {
+            let written = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(dump.as_bytes()));
+            if let Err(e) = written {
+                eprintln!(
+                    "[leptos_macro] couldn't write the expansion of {label} to {}: {e}",
+                    path.to_string_lossy()
+                );
+            }
+        }
+        None => eprint!("{dump}"),
+    }
+}
+
+// Items (like a component) are formatted as they are, and an expression (like a view) as the
+// body of a `fn view()`. Anything else is printed unformatted.
+fn pretty(tokens: &TokenStream) -> String {
+    if let Ok(file) = syn::parse2::<syn::File>(tokens.clone()) {
+        return prettyplease::unparse(&file);
+    }
+    match syn::parse2::<syn::Expr>(tokens.clone()) {
+        Ok(expr) => {
+            let item: syn::Item = syn::parse_quote! {
+                fn view() {
+                    #expr
+                }
+            };
+            prettyplease::unparse(&syn::File {
+                shebang: None,
+                attrs: vec![],
+                items: vec![item],
+            })
+        }
+        Err(_) => tokens.to_string(),
+    }
+}
@@ -0,0 +1,105 @@
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned};
+use syn::{Expr, Stmt};
+use syn_rsx::{Node, NodeBlock};
+
+use crate::is_component_node;
+
+// The tokens for a `{ }` block in the view. With the `require-keys` feature, a block that maps
+// an iterator into elements also uses a deprecated constant, so the compiler warns about it at the
+// `.map()` call. Because it's an ordinary `deprecated` warning, `#[allow(deprecated)]` on the
+// surrounding statement or function silences it for that site.
+pub(crate) fn block_to_tokens(block: &NodeBlock) -> TokenStream {
+    let value = block.value.as_ref();
+    if !cfg!(feature = "require-keys") {
+        return quote! { #value };
+    }
+    match find_unkeyed_map(value) {
+        Some(span) => {
+            let warning = quote_spanned! {span=>
+                #[deprecated(
+                    note = "this list's elements have no keys, so the whole list is re-created \
+                            whenever it changes; use the <For/> component, which keys each element"
+                )]
+                #[allow(non_upper_case_globals)]
+                const unkeyed_list: () = ();
+                _ = unkeyed_list;
+            };
+            // `{ }` blocks are spliced in, so the compiler doesn't warn about unused braces
+            let stmts = match value {
+                Expr::Block(block) if block.attrs.is_empty() && block.label.is_none() => {
+                    let stmts = &block.block.stmts;
+                    quote! { #(#stmts)* }
+                }
+                _ => quote! { #value },
+            };
+            quote! {
+                {
+                    #warning
+                    #stmts
+                }
+            }
+        }
+        None => quote! { #value },
+    }
+}
+
+// Finds a `.map()` whose closure returns a `view!` of a single element
+fn find_unkeyed_map(expr: &Expr) -> Option<Span> {
+    match expr {
+        Expr::MethodCall(call) => {
+            if call.method == "map" {
+                if let Some(Expr::Closure(closure)) = call.args.first() {
+                    if returns_element(&closure.body) {
+                        return Some(call.method.span());
+                    }
+                }
+            }
+            find_unkeyed_map(&call.receiver).or_else(|| call.args.iter().find_map(find_unkeyed_map))
+        }
+        Expr::Call(call) => call.args.iter().find_map(find_unkeyed_map),
+        Expr::Closure(closure) => find_unkeyed_map(&closure.body),
+        Expr::Paren(paren) => find_unkeyed_map(&paren.expr),
+        Expr::Reference(reference) => find_unkeyed_map(&reference.expr),
+        Expr::Block(block) => block.block.stmts.iter().find_map(|stmt| match stmt {
+            Stmt::Expr(expr) | Stmt::Semi(expr, _) => find_unkeyed_map(expr),
+            Stmt::Local(local) => local
+                .init
+                .as_ref()
+                .and_then(|(_, init)| find_unkeyed_map(init)),
+            Stmt::Item(_) => None,
+        }),
+        _ => None,
+    }
+}
+
+fn returns_element(body: &Expr) -> bool {
+    match body {
+        Expr::Block(block) => match block.block.stmts.last() {
+            Some(Stmt::Expr(expr)) => returns_element(expr),
+            _ => false,
+        },
+        Expr::Paren(paren) => returns_element(&paren.expr),
+        Expr::MethodCall(call) if call.method == "into_view" || call.method == "into_any" => {
+            returns_element(&call.receiver)
+        }
+        Expr::Macro(mac)
+            if mac.mac.path.segments.last().map(|s| s.ident == "view") == Some(true) =>
+        {
+            match view_nodes(mac.mac.tokens.clone()).as_deref() {
+                Some([Node::Element(element)]) => !is_component_node(element),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+// Parses the nodes of a nested `view!`, skipping the `cx,` and `class = ...,` arguments
+fn view_nodes(tokens: TokenStream) -> Option<Vec<Node>> {
+    let mut tokens = tokens.into_iter().skip(2).peekable();
+    if matches!(tokens.peek(), Some(TokenTree::Ident(ident)) if ident == "class") {
+        tokens.nth(3);
+    }
+    syn_rsx::parse2(tokens.collect()).ok()
+}
@@ -27,8 +27,11 @@ impl Default for Mode {
     }
 }
 
+mod dump;
+mod keys;
 mod params;
 mod view;
+use dump::dump_expansion;
 use view::render_view;
 mod component;
 mod props;
@@ -129,6 +132,25 @@ mod server;
 /// # });
 /// ```
 ///
+///    The modifiers `:prevent` and `:stop` call `prevent_default()` and `stop_propagation()` on the event
+///    before the handler runs, and can be combined, as in `on:click:prevent:stop`.
+/// ```rust
+/// # use leptos::*;
+/// # run_scope(create_runtime(), |cx| {
+/// # if !cfg!(any(feature = "csr", feature = "hydrate")) {
+/// view! {
+///   cx,
+///   <form on:submit:prevent=|ev| {
+///     log::debug!("submitted without navigating: {ev:#?}");
+///   }>
+///     <button type="submit">"Submit"</button>
+///   </form>
+/// }
+/// # ;
+/// # }
+/// # });
+/// ```
+///
 /// 6. DOM properties can be set with `prop:` attributes, which take any primitive type or `JsValue` (or a signal
 ///    that returns a primitive or JsValue). They can also take an `Option`, in which case `Some` sets the property
 ///    and `None` deletes the property.
@@ -230,6 +252,36 @@ mod server;
 /// # });
 /// ```
 ///
+/// 10. With the `require-keys` feature, interpolating a list of elements made with `.map()` causes a
+///     `deprecated` warning that suggests using the `<For/>` component, which keys each element so that
+///     only the elements that changed are updated. Add `#[allow(deprecated)]` to the surrounding statement
+///     or function to silence it for lists that don't need keys.
+/// ```rust,ignore
+/// view! { cx,
+///   <ul>
+///     // ⚠️ warns: the whole list is re-created whenever it changes
+///     {items.get().into_iter().map(|item| view! { cx, <li>{item}</li> }).collect::<Vec<_>>()}
+///     // ✅ doesn't warn
+///     <For each=move || items.get() key=|item| *item view=move |item| view! { cx, <li>{item}</li> }/>
+///   </ul>
+/// }
+/// ```
+///
+/// 11. A list of attributes can be spread onto an element with `{..attrs}`, where `attrs` is anything
+///     that can be iterated over as `(name, value)` pairs, like a `Vec` or a `HashMap`. The values can be
+///     anything an attribute can be set to, including signals and closures, which update the attribute
+///     reactively. An attribute written on the element wins over one of the same name in the spread.
+/// ```rust
+/// # use leptos::*;
+/// # run_scope(create_runtime(), |cx| {
+/// # if !cfg!(any(feature = "csr", feature = "hydrate")) {
+/// let attrs = vec![("title", "Greeting"), ("aria-label", "greeting")];
+/// view! { cx, <p {..attrs} title="Hello">"Hello, world!"</p> } // has title="Hello"
+/// # ;
+/// # }
+/// # });
+/// ```
+///
 /// Here’s a simple example that shows off several of these features, put together
 /// ```rust
 /// # use leptos::*;
@@ -259,6 +311,18 @@ mod server;
 /// # ;
 /// # }
 /// ```
+///
+/// ## Debugging the Generated Code
+/// To see the code that `view!` and `#[component]` generate, set `LEPTOS_DUMP_EXPANSION=1` while
+/// compiling. Each expansion is formatted as Rust code, under a `// ===== view! =====` or
+/// `// ===== #[component] Name =====` header, and written to stderr. Set `LEPTOS_DUMP_EXPANSION_FILE`
+/// to a path to append them to that file instead. A view is shown as the body of a `fn view()`.
+/// ```sh
+/// LEPTOS_DUMP_EXPANSION=1 LEPTOS_DUMP_EXPANSION_FILE=expanded.rs cargo build
+/// ```
+/// This happens while the macros are expanded, and doesn't change the code they generate. Cargo
+/// doesn't rebuild a crate when these variables change, so touch the file you're interested in
+/// (or run `cargo clean -p your_crate`) to see code that's already been compiled.
 #[proc_macro_error::proc_macro_error]
 #[proc_macro]
 pub fn view(tokens: TokenStream) -> TokenStream {
@@ -295,7 +359,7 @@ pub fn view(tokens: TokenStream) -> TokenStream {
                     .collect()
             };
 
-            match parse(tokens.into()) {
+            let expansion = match parse(tokens.into()) {
                 Ok(nodes) => render_view(
                     &proc_macro2::Ident::new(&cx.to_string(), cx.span()),
                     &nodes,
@@ -303,8 +367,9 @@ pub fn view(tokens: TokenStream) -> TokenStream {
                     global_class.as_ref(),
                 ),
                 Err(error) => error.to_compile_error(),
-            }
-            .into()
+            };
+            dump_expansion("view!", &expansion);
+            expansion.into()
         }
         _ => {
             panic!("view! macro needs a context and RSX: e.g., view! {{ cx, <div>...</div> }}")
@@ -514,6 +579,38 @@ pub fn view(tokens: TokenStream) -> TokenStream {
 ///   }
 /// }
 /// ```
+///
+/// ## Async Components
+/// A component can also be an `async fn`, if it needs to wait for some data before it can render.
+/// Async components must be used inside a [Suspense](https://docs.rs/leptos/latest/leptos/fn.Suspense.html)
+/// (or `Transition`), which shows its fallback until every async component below it has resolved.
+/// * On the server, the `<Suspense/>` waits for the component's future before streaming its HTML,
+///   just as it does for resources.
+/// * On the client, the future runs when the component is first rendered, and the fallback is shown
+///   while it's pending.
+///
+/// Because `<Suspense/>` renders its children more than once, the future is only started the first
+/// time; the same async components must be rendered in the same order every time. Note that the data
+/// isn't serialized for hydration, so the future runs again on the client.
+/// ```rust
+/// # use leptos::*;
+/// # async fn load_user_name(id: u32) -> String { String::new() }
+///
+/// #[component]
+/// async fn UserName(cx: Scope, id: u32) -> impl IntoView {
+///   let name = load_user_name(id).await;
+///   view! { cx, <span>{name}</span> }
+/// }
+///
+/// #[component]
+/// fn Profile(cx: Scope) -> impl IntoView {
+///   view! { cx,
+///     <Suspense fallback=move || view! { cx, <p>"Loading..."</p> }>
+///       <UserName id=1/>
+///     </Suspense>
+///   }
+/// }
+/// ```
 #[proc_macro_error::proc_macro_error]
 #[proc_macro_attribute]
 pub fn component(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
@@ -535,10 +632,10 @@ pub fn component(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
         false
     };
 
-    parse_macro_input!(s as component::Model)
-        .is_transparent(is_transparent)
-        .into_token_stream()
-        .into()
+    let model = parse_macro_input!(s as component::Model).is_transparent(is_transparent);
+    let expansion = model.to_token_stream();
+    dump_expansion(&format!("#[component] {}", model.name()), &expansion);
+    expansion.into()
 }
 
 /// Declares that a function is a [server function](leptos_server). This means that
@@ -547,7 +644,7 @@ pub fn component(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 /// If you call a server function from the client (i.e., when the `csr` or `hydrate` features
 /// are enabled), it will instead make a network request to the server.
 ///
-/// You can specify one, two, or three arguments to the server function:
+/// You can specify up to five kinds of arguments to the server function:
 /// 1. **Required**: A type name that will be used to identify and register the server function
 ///   (e.g., `MyServerFn`).
 /// 2. *Optional*: A URL prefix at which the function will be mounted when it’s registered
@@ -557,6 +654,30 @@ pub fn component(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 ///   Defaults to `"Url"`. If you want to use this server function to power a `<form>` that will
 ///   work without WebAssembly, the encoding must be `"Url"`.
 ///
+///    `"Ndjson"` sends URL-encoded arguments and streams the result back as newline-delimited JSON;
+///    the function must return an [NdjsonStream](leptos_server::NdjsonStream), and the client
+///    receives each item as soon as it arrives.
+///
+///    `"File"` sends URL-encoded arguments and streams a file back as the raw body of the response,
+///    for downloads; the function must return a [FileDownload](leptos_server::FileDownload), which
+///    the client receives once all of it has arrived.
+///
+///    `"Multipart"` receives the arguments as `multipart/form-data`, so it can power a
+///    `<form enctype="multipart/form-data">` with file inputs; a file argument can be a
+///    [FileUpload](leptos_server::FileUpload) or a `Vec<u8>`. The result is sent back as JSON.
+///
+///    Any other encoding is a compile error.
+/// 4. *Optional*: `headers(...)`, a list of headers to add to every successful response of the
+///   function, like `headers(cache_control = "no-store", "X-Robots-Tag" = "noindex")`. Names can
+///   be identifiers, whose underscores become dashes, or strings. The prefix and encoding can be
+///   left out before it. A header that the function sets itself with the integration's
+///   `ResponseOptions` takes the place of the declared one, so they can still be set dynamically.
+/// 5. *Optional*: any number of `require_header = "..."` arguments, naming headers that every request
+///   to the function must have, like `require_header = "X-Api-Key"`. The integrations reject a request
+///   without one of them with `400 Bad Request` before the function runs, with the body
+///   `missing argument X-Api-Key` (or a JSON error with the kind `MissingArg`, for clients that accept
+///   JSON). Only the header's presence is checked, so the function still checks its value.
+///
 /// The server function itself can take any number of arguments, each of which should be serializable
 /// and deserializable with `serde`. Optionally, its first argument can be a Leptos [Scope](leptos_reactive::Scope),
 /// which will be injected *on the server side.* This can be used to inject the raw HTTP request or other
@@ -590,6 +711,27 @@ pub fn component(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 /// - **The [Scope](leptos_reactive::Scope) comes from the server.** Optionally, the first argument of a server function
 ///   can be a Leptos [Scope](leptos_reactive::Scope). This scope can be used to inject dependencies like the HTTP request
 ///   or response or other server-only dependencies, but it does *not* have access to reactive state that exists in the client.
+///
+/// ## What the client build contains
+/// The body of a server function is only compiled when the crate's own `ssr` feature is enabled. In a `csr` or
+/// `hydrate` build, the function is replaced by one that sends its arguments to the server, so the body, and
+/// everything it calls, never reaches the client binary and doesn't even have to compile there. What's left on
+/// the client is the function's signature, its arguments struct, and the URL it's mounted at.
+///
+/// The macro can only strip the body, though. Anything outside of it is compiled for the client as usual, so
+/// gate server-only imports, helpers, and dependencies behind the `ssr` feature yourself:
+/// ```ignore
+/// #[cfg(feature = "ssr")]
+/// use sqlx::PgPool;
+///
+/// #[server(CountPosts, "/api")]
+/// pub async fn count_posts(cx: Scope) -> Result<i64, ServerFnError> {
+///     let pool = use_context::<PgPool>(cx).unwrap();
+///     // ...
+/// }
+/// ```
+/// with `sqlx` as an optional dependency that's enabled by `ssr = ["dep:sqlx", ...]` in your `Cargo.toml`. The
+/// types of the arguments and of the return value are used on both sides, so they can't be server-only.
 #[proc_macro_attribute]
 pub fn server(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
     match server_macro_impl(args, s.into()) {
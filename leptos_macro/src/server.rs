@@ -28,12 +28,20 @@ pub fn server_macro_impl(args: proc_macro::TokenStream, s: TokenStream2) -> Resu
         struct_name,
         prefix,
         encoding,
+        headers,
+        required_headers,
         ..
     } = syn::parse::<ServerFnName>(args)?;
     let prefix = prefix.unwrap_or_else(|| Literal::string(""));
+    let is_ndjson = encoding == Encoding::Ndjson;
+    let is_file = encoding == Encoding::File;
+    let is_multipart = encoding == Encoding::Multipart;
     let encoding = match encoding {
         Encoding::Cbor => quote! { ::leptos::Encoding::Cbor },
         Encoding::Url => quote! { ::leptos::Encoding::Url },
+        Encoding::Ndjson => quote! { ::leptos::Encoding::Ndjson },
+        Encoding::File => quote! { ::leptos::Encoding::File },
+        Encoding::Multipart => quote! { ::leptos::Encoding::Multipart },
     };
 
     let body = syn::parse::<ServerFnBody>(s.into())?;
@@ -136,6 +144,62 @@ pub fn server_macro_impl(args: proc_macro::TokenStream, s: TokenStream2) -> Resu
         panic!("server functions should return Result<T, ServerFnError>");
     };
 
+    // NDJSON results are streamed rather than serialized as a single value
+    let into_ndjson = if is_ndjson {
+        quote! {
+            #[cfg(any(feature = "ssr", doc))]
+            fn into_ndjson(output: Self::Output) -> Result<::leptos::NdjsonLines, ::leptos::ServerFnError> {
+                Ok(output.into_lines())
+            }
+        }
+    } else {
+        quote! {}
+    };
+    // files are sent as the raw body of the response
+    let into_file = if is_file {
+        quote! {
+            #[cfg(any(feature = "ssr", doc))]
+            fn into_file(output: Self::Output) -> Result<::leptos::FileDownload, ::leptos::ServerFnError> {
+                Ok(output)
+            }
+        }
+    } else {
+        quote! {}
+    };
+    // headers declared with `headers(...)`, added to every successful response
+    let headers = if headers.is_empty() {
+        quote! {}
+    } else {
+        let names = headers.iter().map(|(name, _)| name);
+        let values = headers.iter().map(|(_, value)| value);
+        quote! {
+            #[cfg(any(feature = "ssr", doc))]
+            fn headers() -> &'static [(&'static str, &'static str)] {
+                &[#((#names, #values)),*]
+            }
+        }
+    };
+    // headers declared with `require_header = "..."`, which every request must have
+    let required_headers = if required_headers.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            #[cfg(any(feature = "ssr", doc))]
+            fn required_headers() -> &'static [&'static str] {
+                &[#(#required_headers),*]
+            }
+        }
+    };
+    let call_server_fn = if is_ndjson {
+        quote! { ::leptos::call_server_fn_ndjson(&url, #struct_name { #(#field_names_5),* }).await }
+    } else if is_file {
+        quote! { ::leptos::call_server_fn_file(&url, #struct_name { #(#field_names_5),* }).await }
+    } else if is_multipart {
+        quote! { ::leptos::call_server_fn_multipart(&url, #struct_name { #(#field_names_5),* }).await }
+    } else {
+        quote! { ::leptos::call_server_fn(&url, #struct_name { #(#field_names_5),* }, #encoding).await }
+    };
+
     Ok(quote::quote! {
         #[derive(Clone, ::serde::Serialize, ::serde::Deserialize)]
         pub struct #struct_name {
@@ -157,6 +221,14 @@ pub fn server_macro_impl(args: proc_macro::TokenStream, s: TokenStream2) -> Resu
                 #encoding
             }
 
+            #into_ndjson
+
+            #into_file
+
+            #headers
+
+            #required_headers
+
             #[cfg(any(feature = "ssr", doc))]
             fn call_fn(self, cx: ::leptos::Scope) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self::Output, ::leptos::ServerFnError>>>> {
                 let #struct_name { #(#field_names),* } = self;
@@ -179,7 +251,7 @@ pub fn server_macro_impl(args: proc_macro::TokenStream, s: TokenStream2) -> Resu
         #vis async fn #fn_name(#(#fn_args_2),*) #output_arrow #return_ty {
             let prefix = #struct_name::prefix().to_string();
             let url = prefix + "/" + #struct_name::url();
-            ::leptos::call_server_fn(&url, #struct_name { #(#field_names_5),* }, #encoding).await
+            #call_server_fn
         }
     })
 }
@@ -190,15 +262,39 @@ pub struct ServerFnName {
     prefix: Option<Literal>,
     _comma2: Option<Token![,]>,
     encoding: Encoding,
+    _comma3: Option<Token![,]>,
+    headers: Vec<(String, String)>,
+    required_headers: Vec<String>,
 }
 
 impl Parse for ServerFnName {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let struct_name = input.parse()?;
         let _comma = input.parse()?;
-        let prefix = input.parse()?;
+        let prefix = if input.peek(LitStr) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
         let _comma2 = input.parse()?;
-        let encoding = input.parse().unwrap_or(Encoding::Url);
+        let encoding = if input.peek(LitStr) {
+            input.parse()?
+        } else {
+            Encoding::Url
+        };
+        let _comma3 = input.parse()?;
+        let mut headers = Vec::new();
+        let mut required_headers = Vec::new();
+        while !input.is_empty() {
+            if input.peek(Ident) && input.peek2(Token![=]) {
+                required_headers.push(parse_required_header(input)?);
+            } else {
+                headers.extend(parse_headers(input)?);
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
 
         Ok(Self {
             struct_name,
@@ -206,10 +302,90 @@ impl Parse for ServerFnName {
             prefix,
             _comma2,
             encoding,
+            _comma3,
+            headers,
+            required_headers,
         })
     }
 }
 
+// Parses `require_header = "X-Api-Key"`
+fn parse_required_header(input: ParseStream) -> syn::Result<String> {
+    let keyword: Ident = input.parse()?;
+    if keyword != "require_header" {
+        return Err(syn::Error::new(
+            keyword.span(),
+            "expected `headers(...)` or `require_header = \"...\"` after the server function's name, \
+             prefix, and encoding",
+        ));
+    }
+    input.parse::<Token![=]>()?;
+    let name: LitStr = input.parse()?;
+    if !is_header_name(&name.value()) {
+        return Err(syn::Error::new(
+            name.span(),
+            format!("invalid header name {:?}", name.value()),
+        ));
+    }
+    Ok(name.value())
+}
+
+fn is_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+// Parses `headers(cache_control = "no-store", "X-Robots-Tag" = "noindex")`. Names can be given as
+// identifiers, whose underscores become dashes, or as strings.
+fn parse_headers(input: ParseStream) -> syn::Result<Vec<(String, String)>> {
+    let keyword: Ident = input.parse()?;
+    if keyword != "headers" {
+        return Err(syn::Error::new(
+            keyword.span(),
+            "expected `headers(...)` or `require_header = \"...\"` after the server function's name, \
+             prefix, and encoding",
+        ));
+    }
+    let content;
+    syn::parenthesized!(content in input);
+
+    let mut headers = Vec::new();
+    while !content.is_empty() {
+        let (name, span) = if content.peek(LitStr) {
+            let name: LitStr = content.parse()?;
+            (name.value(), name.span())
+        } else {
+            let name: Ident = content.parse()?;
+            (name.to_string().replace('_', "-"), name.span())
+        };
+        if !is_header_name(&name) {
+            return Err(syn::Error::new(
+                span,
+                format!("invalid header name {name:?}"),
+            ));
+        }
+        content.parse::<Token![=]>()?;
+        let value: LitStr = content.parse()?;
+        if !value
+            .value()
+            .bytes()
+            .all(|b| b == b'\t' || (b' '..=b'~').contains(&b))
+        {
+            return Err(syn::Error::new(
+                value.span(),
+                format!("invalid value for the {name} header"),
+            ));
+        }
+        headers.push((name, value.value()));
+        if !content.is_empty() {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    Ok(headers)
+}
+
 pub struct ServerFnBody {
     pub attrs: Vec<Attribute>,
     pub vis: syn::Visibility,
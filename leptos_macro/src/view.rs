@@ -3,7 +3,7 @@ use quote::{format_ident, quote, quote_spanned};
 use syn::{spanned::Spanned, Expr, ExprLit, ExprPath, Lit};
 use syn_rsx::{Node, NodeAttribute, NodeElement, NodeName};
 
-use crate::{is_component_node, Mode};
+use crate::{is_component_node, keys::block_to_tokens, Mode};
 
 #[derive(Clone, Copy)]
 enum TagType {
@@ -142,6 +142,12 @@ const TYPED_EVENTS: [&str; 126] = [
     "visibilitychange",
 ];
 
+// HTML attributes that are switched on by being present, like `<input disabled>`, and
+// off by being left out, rather than by their value
+const BOOLEAN_ATTRIBUTES: [&str; 7] = [
+    "disabled", "checked", "selected", "readonly", "required", "hidden", "multiple",
+];
+
 pub(crate) fn render_view(
     cx: &Ident,
     nodes: &[Node],
@@ -195,7 +201,7 @@ fn root_node_to_tokens_ssr(
             }
         }
         Node::Block(node) => {
-            let value = node.value.as_ref();
+            let value = block_to_tokens(node);
             quote! {
                 #[allow(unused_braces)]
                 #value
@@ -291,9 +297,15 @@ fn element_to_tokens_ssr(
         template.push('<');
         template.push_str(&node.name.to_string());
 
+        let explicit = explicit_attr_names(node);
         for attr in &node.attributes {
             if let Node::Attribute(attr) = attr {
                 attribute_to_tokens_ssr(cx, attr, template, holes, exprs_for_compiler);
+            } else if let Some(attrs) = spread_expr(attr) {
+                template.push_str("{}");
+                holes.push(quote! {
+                  leptos::spread_attrs_ssr(#cx, #attrs, &[#(#explicit),*]),
+                })
             }
         }
 
@@ -303,11 +315,11 @@ fn element_to_tokens_ssr(
         } else {
             quote! { leptos::HydrationCtx::id(), }
         };
-        match node
-            .attributes
-            .iter()
-            .find(|node| matches!(node, Node::Attribute(attr) if attr.key.to_string() == "id"))
-        {
+        // a spread may set its own `id`
+        match node.attributes.iter().find(|node| {
+            matches!(node, Node::Attribute(attr) if attr.key.to_string() == "id")
+                || spread_expr(node).is_some()
+        }) {
             Some(_) => {
                 template.push_str(" leptos-hk=\"_{}\"");
             }
@@ -323,6 +335,8 @@ fn element_to_tokens_ssr(
             template.push_str("/>");
         } else {
             template.push('>');
+            // the contents of `<script>` and `<style>` are raw text, which isn't escaped
+            let raw_text = matches!(node.name.to_string().as_str(), "script" | "style");
             for child in &node.children {
                 match child {
                     Node::Element(child) => element_to_tokens_ssr(
@@ -336,24 +350,44 @@ fn element_to_tokens_ssr(
                     ),
                     Node::Text(text) => {
                         if let Some(value) = value_to_string(&text.value) {
-                            template.push_str(&value);
+                            if raw_text {
+                                template.push_str(&escape_braces(&value));
+                            } else {
+                                template.push_str(&escape_static(&value, false));
+                            }
                         } else {
                             template.push_str("{}");
                             let value = text.value.as_ref();
 
-                            holes.push(quote! {
-                              #value.into_view(#cx).render_to_string(#cx),
+                            holes.push(if raw_text {
+                                quote! {
+                                  leptos::raw_text_to_string(#cx, #value.into_view(#cx)),
+                                }
+                            } else {
+                                quote! {
+                                  #value.into_view(#cx).render_to_string(#cx),
+                                }
                             })
                         }
                     }
                     Node::Block(block) => {
                         if let Some(value) = value_to_string(&block.value) {
-                            template.push_str(&value);
+                            if raw_text {
+                                template.push_str(&escape_braces(&value));
+                            } else {
+                                template.push_str(&escape_static(&value, false));
+                            }
                         } else {
                             template.push_str("{}");
-                            let value = block.value.as_ref();
-                            holes.push(quote! {
-                              #value.into_view(#cx).render_to_string(#cx),
+                            let value = block_to_tokens(block);
+                            holes.push(if raw_text {
+                                quote! {
+                                  leptos::raw_text_to_string(#cx, #value.into_view(#cx)),
+                                }
+                            } else {
+                                dynamic_hole_ssr(quote! {
+                                  #value.into_view(#cx).render_to_string(#cx)
+                                })
                             })
                         }
                     }
@@ -369,6 +403,55 @@ fn element_to_tokens_ssr(
     }
 }
 
+// With the `debug-markers` feature, wraps the HTML rendered for a dynamic block in
+// `<!--leptos-dyn-->` comments in debug builds, so the reactive regions are easy to
+// spot in the SSR output. Hydration finds its own markers by their hydration keys,
+// so it isn't affected by the extra comments.
+fn dynamic_hole_ssr(rendered: TokenStream) -> TokenStream {
+    if cfg!(feature = "debug-markers") {
+        quote! {
+          {
+            let html = #rendered;
+            if cfg!(debug_assertions) {
+              format!("<!--leptos-dyn-->{html}<!--/leptos-dyn-->").into()
+            } else {
+              html
+            }
+          },
+        }
+    } else {
+        quote! {
+          #rendered,
+        }
+    }
+}
+
+// Escapes a literal from the view for the SSR template at compile time, the same way the
+// renderer escapes dynamic text and attribute values. The template is a `format!` string,
+// so braces are doubled too.
+//
+// Literals inside a `<script>` or `<style>` are raw text, which the browser doesn't unescape,
+// so they only have their braces doubled.
+fn escape_static(value: &str, is_attribute: bool) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if is_attribute => escaped.push_str("&quot;"),
+            '{' => escaped.push_str("{{"),
+            '}' => escaped.push_str("}}"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn escape_braces(value: &str) -> String {
+    value.replace('{', "{{").replace('}', "}}")
+}
+
 fn value_to_string(value: &syn_rsx::NodeValueExpr) -> Option<String> {
     match &value.as_ref() {
         syn::Expr::Lit(lit) => match &lit.lit {
@@ -399,8 +482,7 @@ fn attribute_to_tokens_ssr(
             .expect("event listener attributes need a value")
             .as_ref();
 
-        #[allow(unused_variables)]
-        let (name, is_force_undelegated) = parse_event(name);
+        let (name, modifiers) = parse_event(name);
 
         let event_type = TYPED_EVENTS
             .iter()
@@ -411,11 +493,12 @@ fn attribute_to_tokens_ssr(
             .parse::<TokenStream>()
             .expect("couldn't parse event name");
 
-        let event_type = if is_force_undelegated {
+        let event_type = if modifiers.undelegated {
             quote! { ::leptos::ev::undelegated(::leptos::ev::#event_type) }
         } else {
             quote! { ::leptos::ev::#event_type }
         };
+        let handler = modifiers.wrap_handler(&event_type, handler);
         exprs_for_compiler.push(quote! {
             leptos::ssr_event_listener(#event_type, #handler);
         })
@@ -425,20 +508,34 @@ fn attribute_to_tokens_ssr(
     } else {
         let name = name.replacen("attr:", "", 1);
 
-        if name != "class" {
+        let is_dynamic_boolean = BOOLEAN_ATTRIBUTES.contains(&name.as_str())
+            && node
+                .value
+                .as_ref()
+                .map(|value| value_to_string(value).is_none())
+                .unwrap_or(false);
+
+        if is_dynamic_boolean {
+            // rendered only while it's on, so `disabled=false` leaves it out
+            template.push_str("{}");
+            let value = node.value.as_ref().unwrap().as_ref();
+            holes.push(quote! {
+              leptos::spread_attrs_ssr(#cx, [(#name, {#value})], &[]),
+            })
+        } else if name != "class" {
             template.push(' ');
             template.push_str(&name);
 
             if let Some(value) = node.value.as_ref() {
                 if let Some(value) = value_to_string(value) {
                     template.push_str("=\"");
-                    template.push_str(&value);
+                    template.push_str(&escape_static(&value, true));
                     template.push('"');
                 } else {
                     template.push_str("=\"{}\"");
                     let value = value.as_ref();
                     holes.push(quote! {
-                      leptos::escape_attr(&{#value}.into_attribute(#cx).as_nameless_value_string()),
+                      leptos::escape_attr_for(#name, &{#value}.into_attribute(#cx).as_nameless_value_string()),
                     })
                 }
             }
@@ -548,7 +645,7 @@ fn set_class_attribute_ssr(
     {
         template.push_str(" class=\"");
 
-        template.push_str(&static_class_attr);
+        template.push_str(&escape_static(&static_class_attr, true));
 
         for (_span, value) in dyn_class_attr {
             if let Some(value) = value {
@@ -632,10 +729,7 @@ fn node_to_tokens(
                 leptos::text(#value)
             }
         }
-        Node::Block(node) => {
-            let value = node.value.as_ref();
-            quote! { #value }
-        }
+        Node::Block(node) => block_to_tokens(node),
         Node::Attribute(node) => attribute_to_tokens(cx, node),
         Node::Element(node) => element_to_tokens(cx, node, parent_type, global_class),
     }
@@ -682,11 +776,16 @@ fn element_to_tokens(
             parent_type = TagType::Html;
             quote! { leptos::leptos_dom::#name(#cx) }
         };
+        let explicit = explicit_attr_names(node);
         let attrs = node.attributes.iter().filter_map(|node| {
             if let Node::Attribute(node) = node {
                 Some(attribute_to_tokens(cx, node))
             } else {
-                None
+                spread_expr(node).map(|attrs| {
+                    quote! {
+                        .attrs(leptos::spread_attrs(#attrs, &[#(#explicit),*]))
+                    }
+                })
             }
         });
         let global_class_expr = match global_class {
@@ -714,7 +813,7 @@ fn element_to_tokens(
                     }
                 }
                 Node::Block(node) => {
-                    let value = node.value.as_ref();
+                    let value = block_to_tokens(node);
                     quote! {
                         #[allow(unused_braces)] #value
                     }
@@ -756,7 +855,7 @@ fn attribute_to_tokens(cx: &Ident, node: &NodeAttribute) -> TokenStream {
             .expect("event listener attributes need a value")
             .as_ref();
 
-        let (name, is_force_undelegated) = parse_event(name);
+        let (name, modifiers) = parse_event(name);
 
         let event_type = TYPED_EVENTS
             .iter()
@@ -815,7 +914,7 @@ fn attribute_to_tokens(cx: &Ident, node: &NodeAttribute) -> TokenStream {
             event_type
         };
 
-        let event_type = if is_force_undelegated {
+        let event_type = if modifiers.undelegated {
             let undelegated = if let Some(undelegated) = undelegated_ident {
                 let span = undelegated.span();
                 quote_spanned! {
@@ -829,6 +928,8 @@ fn attribute_to_tokens(cx: &Ident, node: &NodeAttribute) -> TokenStream {
             quote! { ::leptos::ev::#event_type }
         };
 
+        let handler = modifiers.wrap_handler(&event_type, handler);
+
         quote! {
             #on(#event_type, #handler)
         }
@@ -884,6 +985,7 @@ fn attribute_to_tokens(cx: &Ident, node: &NodeAttribute) -> TokenStream {
 
                 quote! { #value }
             }
+            None if BOOLEAN_ATTRIBUTES.contains(&name.as_str()) => quote_spanned! { span => true },
             None => quote_spanned! { span => "" },
         };
         let attr = match &node.key {
@@ -906,6 +1008,45 @@ fn attribute_to_tokens(cx: &Ident, node: &NodeAttribute) -> TokenStream {
     }
 }
 
+// The expression in an attribute spread like `<div {..attrs}/>`, which is parsed
+// as a block holding the range `..attrs`
+fn spread_expr(node: &Node) -> Option<&Expr> {
+    let block = match node {
+        Node::Block(block) => block,
+        _ => return None,
+    };
+    let stmts = match block.value.as_ref() {
+        Expr::Block(block) => &block.block.stmts,
+        _ => return None,
+    };
+    match stmts.as_slice() {
+        [syn::Stmt::Expr(Expr::Range(range))]
+            if range.from.is_none() && matches!(range.limits, syn::RangeLimits::HalfOpen(_)) =>
+        {
+            range.to.as_deref()
+        }
+        _ => None,
+    }
+}
+
+// The attributes written on an element, which win over any of the same name in a spread
+fn explicit_attr_names(node: &NodeElement) -> Vec<String> {
+    node.attributes
+        .iter()
+        .filter_map(|attr| match attr {
+            Node::Attribute(attr) => Some(attr.key.to_string()),
+            _ => None,
+        })
+        .filter(|name| {
+            !matches!(name.as_str(), "ref" | "_ref" | "node_ref")
+                && !name.starts_with("on:")
+                && !name.starts_with("prop:")
+                && !name.starts_with("class:")
+        })
+        .map(|name| name.replacen("attr:", "", 1))
+        .collect()
+}
+
 fn component_to_tokens(
     cx: &Ident,
     node: &NodeElement,
@@ -1175,11 +1316,44 @@ fn is_ambiguous_element(tag: &str) -> bool {
     tag == "a" || tag == "script"
 }
 
-fn parse_event(event_name: &str) -> (&str, bool) {
-    if let Some(event_name) = event_name.strip_suffix(":undelegated") {
-        (event_name, true)
-    } else {
-        (event_name, false)
+// The modifiers after an event's name, like `on:click:undelegated:prevent:stop`
+#[derive(Default)]
+struct EventModifiers {
+    undelegated: bool,
+    prevent: bool,
+    stop: bool,
+}
+
+impl EventModifiers {
+    // Calls `prevent_default()` and `stop_propagation()` before the handler, if they're asked for
+    fn wrap_handler(&self, event_type: &TokenStream, handler: &Expr) -> TokenStream {
+        if self.prevent || self.stop {
+            let (prevent, stop) = (self.prevent, self.stop);
+            quote! {
+                ::leptos::ev::with_modifiers(&#event_type, #prevent, #stop, #handler)
+            }
+        } else {
+            quote! { #handler }
+        }
+    }
+}
+
+fn parse_event(event_name: &str) -> (&str, EventModifiers) {
+    let mut event_name = event_name;
+    let mut modifiers = EventModifiers::default();
+    loop {
+        if let Some(name) = event_name.strip_suffix(":undelegated") {
+            modifiers.undelegated = true;
+            event_name = name;
+        } else if let Some(name) = event_name.strip_suffix(":prevent") {
+            modifiers.prevent = true;
+            event_name = name;
+        } else if let Some(name) = event_name.strip_suffix(":stop") {
+            modifiers.stop = true;
+            event_name = name;
+        } else {
+            return (event_name, modifiers);
+        }
     }
 }
 
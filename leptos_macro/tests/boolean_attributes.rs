@@ -0,0 +1,5 @@
+#[test]
+fn bare_boolean_attributes_compile() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/boolean_attributes.rs");
+}
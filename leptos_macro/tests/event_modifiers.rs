@@ -0,0 +1,5 @@
+#[test]
+fn event_modifiers_compile() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/event_modifiers.rs");
+}
@@ -0,0 +1,10 @@
+// The lint only exists with the feature: `cargo test -p leptos_macro --features require-keys`
+#![cfg(feature = "require-keys")]
+
+#[test]
+fn unkeyed_lists_warn() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/unkeyed_list.rs");
+    t.pass("tests/ui/for_list.rs");
+    t.pass("tests/ui/unkeyed_list_allowed.rs");
+}
@@ -0,0 +1,5 @@
+#[test]
+fn unknown_encodings_are_rejected() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/server_unknown_encoding.rs");
+}
@@ -0,0 +1,21 @@
+use leptos::*;
+
+fn form(cx: Scope) -> impl IntoView {
+    let (busy, _) = create_signal(cx, false);
+    view! { cx,
+        <form>
+            <input type="checkbox" checked required readonly/>
+            <select multiple>
+                <option selected>"One"</option>
+                <option selected=false>"Two"</option>
+            </select>
+            <p hidden>"Hidden"</p>
+            <button disabled=move || busy.get()>"Save"</button>
+            <button disabled>"Delete"</button>
+        </form>
+    }
+}
+
+fn main() {
+    _ = form;
+}
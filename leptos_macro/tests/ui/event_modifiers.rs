@@ -0,0 +1,18 @@
+use leptos::*;
+
+fn form(cx: Scope) -> impl IntoView {
+    let (count, set_count) = create_signal(cx, 0);
+    view! { cx,
+        // the handlers still get the event type of their event
+        <form on:submit:prevent=move |ev: web_sys::SubmitEvent| { _ = ev.submitter(); }>
+            <button on:click:stop=move |ev| { _ = ev.client_x(); }>"Stop"</button>
+            <button on:click:prevent:stop=move |_| { set_count.update(|n| *n += 1); }>"Both"</button>
+            <input on:keydown:undelegated:prevent=move |ev| { _ = ev.key(); }/>
+            <span>{count}</span>
+        </form>
+    }
+}
+
+fn main() {
+    _ = form;
+}
@@ -0,0 +1,15 @@
+#![deny(deprecated)]
+
+use leptos::*;
+
+fn list(cx: Scope, items: ReadSignal<Vec<u32>>) -> impl IntoView {
+    view! { cx,
+        <ul>
+            <For each=move || items.get() key=|item| *item view=move |item| view! { cx, <li>{item}</li> }/>
+        </ul>
+    }
+}
+
+fn main() {
+    _ = list;
+}
@@ -0,0 +1,9 @@
+use leptos::*;
+
+#[server(AddTodo, "/api", "Json")]
+pub async fn add_todo(title: String) -> Result<(), ServerFnError> {
+    _ = title;
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+// the warning is denied so that trybuild can check its message
+#![deny(deprecated)]
+
+use leptos::*;
+
+fn list(cx: Scope, items: Vec<u32>) -> impl IntoView {
+    view! { cx,
+        <ul>
+            {items.into_iter().map(|item| view! { cx, <li>{item}</li> }).collect::<Vec<_>>()}
+        </ul>
+    }
+}
+
+fn main() {
+    _ = list;
+}
@@ -0,0 +1,17 @@
+#![deny(deprecated)]
+
+use leptos::*;
+
+// the list is small and never changes, so it doesn't need keys
+#[allow(deprecated)]
+fn list(cx: Scope) -> impl IntoView {
+    view! { cx,
+        <ul>
+            {[1, 2, 3].into_iter().map(|item| view! { cx, <li>{item}</li> }).collect::<Vec<_>>()}
+        </ul>
+    }
+}
+
+fn main() {
+    _ = list;
+}
@@ -274,12 +274,36 @@ where
 }
 
 #[cfg(not(feature = "hydrate"))]
-fn load_resource<S, T>(_cx: Scope, _id: ResourceId, r: Rc<ResourceState<S, T>>)
+fn load_resource<S, T>(cx: Scope, id: ResourceId, r: Rc<ResourceState<S, T>>)
 where
     S: PartialEq + Debug + Clone + 'static,
-    T: 'static,
+    T: Serializable + 'static,
 {
-    r.load(false)
+    // A value seeded with Scope::seed_resources is used the first time the
+    // resource loads, instead of running the fetcher
+    let seeded = with_runtime(cx.runtime, |runtime| {
+        runtime
+            .shared_context
+            .borrow_mut()
+            .resolved_resources
+            .remove(&id)
+    })
+    .ok()
+    .flatten()
+    .and_then(|data| T::from_json(&data).ok());
+
+    match seeded {
+        Some(res) => {
+            r.resolved.set(true);
+
+            r.set_value.update(|n| *n = Some(res));
+            r.set_loading.update(|n| *n = false);
+
+            // for reactivity
+            r.source.subscribe();
+        }
+        None => r.load(false),
+    }
 }
 
 #[cfg(feature = "hydrate")]
@@ -337,6 +361,23 @@ where
                 resolve.as_ref().unchecked_ref(),
             );
 
+            // if the server's data can't be fetched, the page loads the resource itself instead
+            let load = {
+                let r = Rc::clone(&r);
+                move || r.load(false)
+            };
+            let load = wasm_bindgen::closure::Closure::wrap(Box::new(load) as Box<dyn Fn()>);
+            let resource_loaders = js_sys::Reflect::get(
+                &web_sys::window().unwrap(),
+                &wasm_bindgen::JsValue::from_str("__LEPTOS_RESOURCE_LOADERS"),
+            )
+            .expect_throw("no __LEPTOS_RESOURCE_LOADERS found in the JS global scope");
+            _ = js_sys::Reflect::set(
+                &resource_loaders,
+                &wasm_bindgen::JsValue::from_str(&id),
+                &load.into_js_value(),
+            );
+
             // for reactivity
             r.source.subscribe()
         } else {
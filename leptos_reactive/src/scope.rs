@@ -314,6 +314,33 @@ impl Scope {
         with_runtime(self.runtime, |runtime| runtime.serialization_resolvers()).unwrap_or_default()
     }
 
+    /// Provides values for [Resource](crate::Resource)s that haven't been created yet, so that they
+    /// start out resolved instead of running their fetchers. This lets a server render pages that
+    /// share common data without loading it again for every request: call it before rendering the
+    /// app, for example in the `additional_context` of an integration's render function.
+    ///
+    /// Each value is the resource's output serialized to JSON, keyed by its [ResourceId]. Resources
+    /// get their IDs in the order they're created in a runtime, so a value is matched with the
+    /// resource created at the same point in another render of the same page. These are the pairs
+    /// that [Scope::serialization_resolvers] yield, which are also sent to the client to hydrate
+    /// the page, so they can be saved from one render and used to seed the next.
+    ///
+    /// A seeded resource uses its value once, when it first loads; it runs its fetcher as usual
+    /// when its source changes or it's refetched. A value that can't be deserialized into the
+    /// resource's output type is ignored, and the resource loads as if it hadn't been seeded.
+    ///
+    /// The values are used as they are, so deciding when they are stale, and not seeding them
+    /// anymore, is up to the caller.
+    pub fn seed_resources(&self, resources: impl IntoIterator<Item = (ResourceId, String)>) {
+        _ = with_runtime(self.runtime, |runtime| {
+            runtime
+                .shared_context
+                .borrow_mut()
+                .resolved_resources
+                .extend(resources)
+        });
+    }
+
     /// Registers the given [SuspenseContext](crate::SuspenseContext) with the current scope,
     /// calling the `resolver` when its resources are all resolved.
     pub fn register_suspense(
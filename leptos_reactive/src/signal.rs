@@ -244,7 +244,6 @@ where
         self.id.with_no_subscription(self.runtime, f)
     }
 
-    #[cfg(feature = "hydrate")]
     pub(crate) fn subscribe(&self) {
         _ = with_runtime(self.runtime, |runtime| self.id.subscribe(runtime))
     }
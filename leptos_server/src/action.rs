@@ -110,6 +110,8 @@ where
     /// Associates the URL of the given server function with this action.
     /// This enables integration with the `ActionForm` component in `leptos_router`.
     pub fn using_server_fn<T: ServerFn>(self) -> Self {
+        #[cfg(feature = "ssr")]
+        crate::reference_server_fn(T::url());
         let prefix = T::prefix();
         self.0.update(|state| {
             state.url = if prefix.is_empty() {
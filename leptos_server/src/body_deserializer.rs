@@ -0,0 +1,177 @@
+use crate::{
+    multipart::{multipart_boundary, parse_multipart, skip_preamble},
+    Encoding, ServerFnError, ServerFnRegistry,
+};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// Parses a request body into a JSON value holding a server function's arguments, with one
+/// field per argument. See [register_body_deserializer].
+pub type BodyDeserializer = dyn Fn(&[u8]) -> Result<serde_json::Value, ServerFnError> + Send + Sync;
+
+lazy_static::lazy_static! {
+    static ref BODY_DESERIALIZERS: RwLock<HashMap<String, Arc<BodyDeserializer>>> = Default::default();
+}
+
+/// Registers a deserializer for request bodies with the given `Content-Type`, so server functions
+/// can be called with arguments in a format that Leptos doesn't support out of the box.
+///
+/// The deserializer turns the body into a JSON object with one field per argument of the server
+/// function, which is then converted into the server function's own [Encoding]. This lets a library
+/// add support for an encoding by registering it once, in `main`, next to the server functions:
+/// ```rust,ignore
+/// // accepts bodies like `name=Alice;age=42`
+/// leptos_server::register_body_deserializer("text/x-semicolon-pairs", |body| {
+///     let body = std::str::from_utf8(body).map_err(|e| ServerFnError::Args(e.to_string()))?;
+///     let fields = body
+///         .split(';')
+///         .filter_map(|pair| pair.split_once('='))
+///         .map(|(key, value)| (key.to_string(), serde_json::Value::from(value)))
+///         .collect();
+///     Ok(serde_json::Value::Object(fields))
+/// })?;
+/// _ = AddUser::register();
+/// ```
+///
+/// The content type is matched without its parameters (like `; charset=utf-8`) and regardless of
+/// case. Registering a content type again replaces its deserializer, and registering one of the
+/// built-in content types (`application/x-www-form-urlencoded`, `application/cbor`, or
+/// `multipart/form-data`) overrides the built-in handling of it. Bodies with any other content type
+/// are passed to the server function unchanged.
+pub fn register_body_deserializer(
+    content_type: &str,
+    deserializer: impl Fn(&[u8]) -> Result<serde_json::Value, ServerFnError> + Send + Sync + 'static,
+) -> Result<(), ServerFnError> {
+    let mut write = BODY_DESERIALIZERS
+        .write()
+        .map_err(|e| ServerFnError::Registration(e.to_string()))?;
+    write.insert(media_type(content_type), Arc::new(deserializer));
+    Ok(())
+}
+
+/// Returns the deserializer registered for the given `Content-Type`, if there is one.
+pub fn body_deserializer(content_type: &str) -> Option<Arc<BodyDeserializer>> {
+    BODY_DESERIALIZERS
+        .read()
+        .ok()
+        .and_then(|deserializers| deserializers.get(&media_type(content_type)).cloned())
+}
+
+/// Prepares the body of a request to the server function at `path` to be passed to the function
+/// returned by [server_fn_by_path](crate::server_fn_by_path). If a deserializer has been registered
+/// for the request's `Content-Type`, the body is parsed with it and converted into the server
+/// function's [Encoding]. A `multipart/form-data` body, like a `<form>` with a file input would send,
+/// for a server function with a URL-encoded encoding has its text fields converted into URL-encoded
+/// arguments, and one for a server function with the `"Multipart"` encoding has the preamble before its
+/// first boundary dropped. Both are split with the `boundary` of the `Content-Type`. Otherwise, the
+/// body is returned unchanged.
+///
+/// Server function handlers should call this on every request body, so that the deserializers
+/// registered with [register_body_deserializer] are used. Handlers that use a [ServerFnRegistry]
+/// other than the global one should call [ServerFnRegistry::decode_body] instead.
+pub fn decode_server_fn_body<'a>(
+    path: &str,
+    content_type: Option<&str>,
+    body: &'a [u8],
+) -> Result<Cow<'a, [u8]>, ServerFnError> {
+    ServerFnRegistry::global().decode_body(path, content_type, body)
+}
+
+pub(crate) fn decode_body<'a>(
+    encoding: Option<Encoding>,
+    content_type: Option<&str>,
+    body: &'a [u8],
+) -> Result<Cow<'a, [u8]>, ServerFnError> {
+    let multipart =
+        content_type.filter(|content_type| media_type(content_type) == "multipart/form-data");
+    let deserializer = match (content_type.and_then(body_deserializer), multipart) {
+        (Some(deserializer), _) => deserializer,
+        (None, Some(content_type))
+            if matches!(
+                encoding,
+                Some(Encoding::Url | Encoding::Ndjson | Encoding::File)
+            ) =>
+        {
+            let boundary = multipart_boundary(content_type)?;
+            return multipart_to_url(body, &boundary).map(|args| Cow::Owned(args.into_bytes()));
+        }
+        // the server fn reads the boundary from the delimiter line the body now starts with
+        (None, Some(content_type)) if encoding == Some(Encoding::Multipart) => {
+            let boundary = multipart_boundary(content_type)?;
+            return skip_preamble(body, &boundary).map(Cow::Borrowed);
+        }
+        (None, _) => return Ok(Cow::Borrowed(body)),
+    };
+    let encoding = match encoding {
+        Some(encoding) => encoding,
+        // there's no server function to call, which the handler will report
+        None => return Ok(Cow::Borrowed(body)),
+    };
+
+    let args = deserializer(body)?;
+    match encoding {
+        Encoding::Url | Encoding::Ndjson | Encoding::File => serde_urlencoded::to_string(&args)
+            .map(|args| Cow::Owned(args.into_bytes()))
+            .map_err(|e| ServerFnError::Args(e.to_string())),
+        Encoding::Cbor => {
+            let mut buffer = Vec::new();
+            ciborium::ser::into_writer(&args, &mut buffer)
+                .map_err(|e| ServerFnError::Args(e.to_string()))?;
+            Ok(Cow::Owned(buffer))
+        }
+        Encoding::Multipart => json_to_multipart(&args).map(Cow::Owned),
+    }
+}
+
+// The text fields of a `multipart/form-data` body as URL-encoded arguments. Files can't be
+// URL-encoded, so they're left out.
+fn multipart_to_url(body: &[u8], boundary: &str) -> Result<String, ServerFnError> {
+    let mut args = form_urlencoded::Serializer::new(String::new());
+    for part in parse_multipart(body, boundary)? {
+        if part.filename.is_none() {
+            args.append_pair(&part.name, &String::from_utf8_lossy(&part.data));
+        }
+    }
+    Ok(args.finish())
+}
+
+// The arguments parsed by a deserializer as a `multipart/form-data` body, with one text field for
+// each argument, or for each item of an array
+fn json_to_multipart(args: &serde_json::Value) -> Result<Vec<u8>, ServerFnError> {
+    const BOUNDARY: &str = "leptos-server-fn-args";
+
+    let fields = args
+        .as_object()
+        .ok_or_else(|| ServerFnError::Args("arguments should be an object".to_string()))?;
+    let mut body = String::new();
+    for (name, value) in fields {
+        let values = match value {
+            serde_json::Value::Array(items) => items.iter().collect(),
+            value => vec![value],
+        };
+        for value in values {
+            let text = match value {
+                serde_json::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            body.push_str(&format!(
+                "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{text}\r\n"
+            ));
+        }
+    }
+    body.push_str(&format!("--{BOUNDARY}--\r\n"));
+    Ok(body.into_bytes())
+}
+
+// The content type without its parameters, in lowercase
+fn media_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
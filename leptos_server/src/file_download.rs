@@ -0,0 +1,194 @@
+use serde::Serialize;
+use std::{fmt, path::PathBuf};
+
+/// A file sent from a server function as the raw body of its response, like a download, instead of
+/// being serialized. The server streams the file from disk, so it's never held in memory as a whole.
+///
+/// Return this from a server function that uses the `"File"` encoding:
+/// ```rust,ignore
+/// #[server(DownloadReport, "/api", "File")]
+/// pub async fn download_report(id: u32) -> Result<FileDownload, ServerFnError> {
+///     let path = render_report_to_disk(id).await?;
+///     Ok(FileDownload::new(path).with_filename(format!("report-{id}.pdf")))
+/// }
+/// ```
+/// The response is sent with a `Content-Disposition: attachment` header, so a link or `<form>` that
+/// points at the server function makes the browser download the file. Its `Content-Type` is the one
+/// set with [with_content_type](FileDownload::with_content_type), or else guessed by the integration
+/// from the file's extension.
+///
+/// Calling the function from the client fetches the whole file, which is then available from
+/// [bytes](FileDownload::bytes).
+pub struct FileDownload {
+    source: FileSource,
+    content_type: Option<String>,
+    filename: Option<String>,
+}
+
+enum FileSource {
+    Path(PathBuf),
+    // only a file that the client has fetched
+    #[cfg_attr(feature = "ssr", allow(dead_code))]
+    Bytes(Vec<u8>),
+}
+
+impl FileDownload {
+    /// Sends the file at `path`. It's opened once the server function has returned, so a file that
+    /// doesn't exist by then gets a `404 Not Found` response.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            source: FileSource::Path(path.into()),
+            content_type: None,
+            filename: None,
+        }
+    }
+
+    /// Sets the `Content-Type` of the response, instead of guessing it from the file's extension.
+    pub fn with_content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// Sets the name the browser saves the file as, which defaults to the name of the file on disk.
+    pub fn with_filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// The path of the file to send, on the server.
+    pub fn path(&self) -> Option<&std::path::Path> {
+        match &self.source {
+            FileSource::Path(path) => Some(path),
+            FileSource::Bytes(_) => None,
+        }
+    }
+
+    /// The contents of the file, once it's been received by the client.
+    pub fn bytes(&self) -> Option<&[u8]> {
+        match &self.source {
+            FileSource::Path(_) => None,
+            FileSource::Bytes(bytes) => Some(bytes),
+        }
+    }
+
+    /// The `Content-Type` of the file, if it was set with [with_content_type](FileDownload::with_content_type)
+    /// or, on the client, sent by the server.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// The name the file is saved as: the one set with [with_filename](FileDownload::with_filename), or
+    /// else the name of the file on disk.
+    pub fn filename(&self) -> Option<String> {
+        self.filename.clone().or_else(|| {
+            self.path()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+    }
+
+    /// The value of the `Content-Disposition` header the file is sent with. Names that aren't plain
+    /// ASCII are also given as a percent-encoded `filename*`, as described in RFC 6266.
+    pub fn content_disposition(&self) -> String {
+        let filename = match self.filename() {
+            Some(filename) => filename,
+            None => return "attachment".to_string(),
+        };
+        let fallback: String = filename
+            .chars()
+            .map(|c| match c {
+                '"' | '\\' => '_',
+                c if c.is_ascii() && !c.is_ascii_control() => c,
+                _ => '_',
+            })
+            .collect();
+        if fallback == filename {
+            format!(r#"attachment; filename="{filename}""#)
+        } else {
+            let encoded: String = filename
+                .bytes()
+                .map(|b| match b {
+                    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                        (b as char).to_string()
+                    }
+                    _ => format!("%{b:02X}"),
+                })
+                .collect();
+            format!(r#"attachment; filename="{fallback}"; filename*=UTF-8''{encoded}"#)
+        }
+    }
+}
+
+impl fmt::Debug for FileDownload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("FileDownload");
+        match &self.source {
+            FileSource::Path(path) => debug.field("path", path),
+            FileSource::Bytes(bytes) => debug.field("len", &bytes.len()),
+        };
+        debug
+            .field("content_type", &self.content_type)
+            .field("filename", &self.filename)
+            .finish()
+    }
+}
+
+/// A [FileDownload] can only be sent with the `"File"` encoding, which sends the file as the body of
+/// the response; serializing it as a single value always fails.
+impl Serialize for FileDownload {
+    fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom(
+            "a FileDownload can only be returned by a server function with the \"File\" encoding",
+        ))
+    }
+}
+
+/// Executes the HTTP call to a server function that uses the `"File"` encoding from the client,
+/// returning the file once all of it has been received.
+#[cfg(not(feature = "ssr"))]
+pub async fn call_server_fn_file(
+    url: &str,
+    args: impl crate::ServerFn,
+) -> Result<FileDownload, crate::ServerFnError> {
+    use crate::ServerFnError;
+
+    let args = serde_urlencoded::to_string(&args)
+        .map_err(|e| ServerFnError::Serialization(e.to_string()))?;
+    let resp = gloo_net::http::Request::post(url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(args)
+        .send()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    // check for error status
+    let status = resp.status();
+    if (400..=599).contains(&status) {
+        return Err(ServerFnError::ServerError(resp.status_text()));
+    }
+
+    let content_type = resp.headers().get("Content-Type");
+    let filename = resp
+        .headers()
+        .get("Content-Disposition")
+        .and_then(|disposition| disposition_filename(&disposition));
+    let bytes = resp
+        .binary()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+    Ok(FileDownload {
+        source: FileSource::Bytes(bytes),
+        content_type,
+        filename,
+    })
+}
+
+// The plain `filename` parameter of a `Content-Disposition` header
+#[cfg(not(feature = "ssr"))]
+fn disposition_filename(disposition: &str) -> Option<String> {
+    disposition
+        .split(';')
+        .filter_map(|param| param.trim().strip_prefix("filename="))
+        .map(|filename| filename.trim_matches('"').to_string())
+        .next()
+}
@@ -92,28 +92,33 @@ use syn::{
 use thiserror::Error;
 
 mod action;
+#[cfg(any(feature = "ssr", doc))]
+mod body_deserializer;
+mod file_download;
 mod multi_action;
+mod multipart;
+mod ndjson;
+#[cfg(any(feature = "ssr", doc))]
+mod registry;
 pub use action::*;
+#[cfg(any(feature = "ssr", doc))]
+pub use body_deserializer::*;
+pub use file_download::*;
 pub use multi_action::*;
+pub use multipart::*;
+pub use ndjson::*;
+#[cfg(any(feature = "ssr", doc))]
+pub use registry::*;
 
 #[cfg(any(feature = "ssr", doc))]
-use std::{
-    collections::HashMap,
-    sync::{Arc, RwLock},
-};
+use std::sync::Arc;
 
 #[cfg(any(feature = "ssr", doc))]
 type ServerFnTraitObj = dyn Fn(Scope, &[u8]) -> Pin<Box<dyn Future<Output = Result<Payload, ServerFnError>>>>
     + Send
     + Sync;
 
-#[cfg(any(feature = "ssr", doc))]
-lazy_static::lazy_static! {
-    static ref REGISTERED_SERVER_FUNCTIONS: Arc<RwLock<HashMap<&'static str, Arc<ServerFnTraitObj>>>> = Default::default();
-}
-
 /// A dual type to hold the possible Response datatypes
-#[derive(Debug)]
 pub enum Payload {
     ///Encodes Data using CBOR
     Binary(Vec<u8>),
@@ -121,6 +126,22 @@ pub enum Payload {
     Url(String),
     ///Encodes Data using Json
     Json(String),
+    ///Streams Data as newline-delimited JSON, one line at a time
+    Ndjson(NdjsonLines),
+    ///Streams a file from disk as the raw body of the response
+    File(FileDownload),
+}
+
+impl std::fmt::Debug for Payload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Binary(data) => f.debug_tuple("Binary").field(data).finish(),
+            Self::Url(data) => f.debug_tuple("Url").field(data).finish(),
+            Self::Json(data) => f.debug_tuple("Json").field(data).finish(),
+            Self::Ndjson(_) => f.debug_tuple("Ndjson").finish_non_exhaustive(),
+            Self::File(file) => f.debug_tuple("File").field(file).finish(),
+        }
+    }
 }
 
 /// Attempts to find a server function registered at the given path.
@@ -168,29 +189,38 @@ pub enum Payload {
 /// ```
 #[cfg(any(feature = "ssr", doc))]
 pub fn server_fn_by_path(path: &str) -> Option<Arc<ServerFnTraitObj>> {
-    REGISTERED_SERVER_FUNCTIONS
-        .read()
-        .ok()
-        .and_then(|fns| fns.get(path).cloned())
+    ServerFnRegistry::global().get(path)
 }
 
 /// Returns the set of currently-registered server function paths, for debugging purposes.
 #[cfg(any(feature = "ssr", doc))]
 pub fn server_fns_by_path() -> Vec<&'static str> {
-    REGISTERED_SERVER_FUNCTIONS
-        .read()
-        .map(|vals| vals.keys().copied().collect())
-        .unwrap_or_default()
+    ServerFnRegistry::global().paths()
+}
+
+/// Returns the [Encoding] of the server function registered at the given path.
+#[cfg(any(feature = "ssr", doc))]
+pub fn server_fn_encoding_by_path(path: &str) -> Option<Encoding> {
+    ServerFnRegistry::global().encoding(path)
 }
 
 /// Holds the current options for encoding types.
 /// More could be added, but they need to be serde
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Encoding {
     /// A Binary Encoding Scheme Called Cbor
     Cbor,
     /// The Default URL-encoded encoding method
     Url,
+    /// URL-encoded arguments, with the result streamed back as newline-delimited JSON.
+    /// The server function must return an [NdjsonStream].
+    Ndjson,
+    /// URL-encoded arguments, with the result sent as the raw contents of a file.
+    /// The server function must return a [FileDownload].
+    File,
+    /// Arguments sent as `multipart/form-data`, which can include files, with a JSON result.
+    /// See [FileUpload].
+    Multipart,
 }
 
 impl FromStr for Encoding {
@@ -200,6 +230,9 @@ impl FromStr for Encoding {
         match input {
             "URL" => Ok(Encoding::Url),
             "Cbor" => Ok(Encoding::Cbor),
+            "Ndjson" => Ok(Encoding::Ndjson),
+            "File" => Ok(Encoding::File),
+            "Multipart" => Ok(Encoding::Multipart),
             _ => Err(()),
         }
     }
@@ -210,6 +243,9 @@ impl quote::ToTokens for Encoding {
         let option: syn::Ident = match *self {
             Encoding::Cbor => parse_quote!(Cbor),
             Encoding::Url => parse_quote!(Url),
+            Encoding::Ndjson => parse_quote!(Ndjson),
+            Encoding::File => parse_quote!(File),
+            Encoding::Multipart => parse_quote!(Multipart),
         };
         let expansion: syn::Ident = syn::parse_quote! {
           Encoding::#option
@@ -220,13 +256,23 @@ impl quote::ToTokens for Encoding {
 
 impl Parse for Encoding {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let variant_name: String = input.parse::<Literal>()?.to_string();
+        let literal = input.parse::<Literal>()?;
+        let variant_name = literal.to_string();
 
         // Need doubled quotes because variant_name doubles it
         match variant_name.as_ref() {
             "\"Url\"" => Ok(Self::Url),
             "\"Cbor\"" => Ok(Self::Cbor),
-            _ => panic!("Encoding Not Found"),
+            "\"Ndjson\"" => Ok(Self::Ndjson),
+            "\"File\"" => Ok(Self::File),
+            "\"Multipart\"" => Ok(Self::Multipart),
+            _ => Err(syn::Error::new(
+                literal.span(),
+                format!(
+                    "unknown server function encoding {variant_name}; \
+                     expected one of \"Url\", \"Cbor\", \"Ndjson\", \"File\", or \"Multipart\""
+                ),
+            )),
         }
     }
 }
@@ -273,19 +319,73 @@ where
         cx: Scope,
     ) -> Pin<Box<dyn Future<Output = Result<Self::Output, ServerFnError>>>>;
 
-    /// Registers the server function, allowing the server to query it by URL.
+    /// Turns the function's output into the lines of a newline-delimited JSON response. The
+    /// `server` macro implements this for server functions with the `"Ndjson"` encoding, which
+    /// must return an [NdjsonStream].
+    #[cfg(any(feature = "ssr", doc))]
+    #[doc(hidden)]
+    fn into_ndjson(output: Self::Output) -> Result<NdjsonLines, ServerFnError> {
+        _ = output;
+        Err(ServerFnError::Serialization(
+            "only server functions that return an NdjsonStream can use the Ndjson encoding"
+                .to_string(),
+        ))
+    }
+
+    /// Turns the function's output into the file sent as the response. The `server` macro
+    /// implements this for server functions with the `"File"` encoding, which must return a
+    /// [FileDownload].
+    #[cfg(any(feature = "ssr", doc))]
+    #[doc(hidden)]
+    fn into_file(output: Self::Output) -> Result<FileDownload, ServerFnError> {
+        _ = output;
+        Err(ServerFnError::Serialization(
+            "only server functions that return a FileDownload can use the File encoding"
+                .to_string(),
+        ))
+    }
+
+    /// Headers that are added to every successful response of the function, unless it sets the
+    /// same header with the integration's `ResponseOptions`. The `server` macro implements this for
+    /// server functions with a `headers(...)` argument.
+    #[cfg(any(feature = "ssr", doc))]
+    fn headers() -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// Headers that every request to the function must have. The integrations reject a request
+    /// without one of them with `400 Bad Request` and a [ServerFnError::MissingArg] naming the header,
+    /// before the function runs. The `server` macro implements this for server functions with
+    /// `require_header = "..."` arguments.
+    #[cfg(any(feature = "ssr", doc))]
+    fn required_headers() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Registers the server function in the global [ServerFnRegistry], allowing the server to
+    /// query it by URL.
     #[cfg(any(feature = "ssr", doc))]
     fn register() -> Result<(), ServerFnError> {
+        Self::register_in(&ServerFnRegistry::global())
+    }
+
+    /// Registers the server function in the given [ServerFnRegistry], so it can only be called
+    /// by a handler that uses that registry.
+    #[cfg(any(feature = "ssr", doc))]
+    fn register_in(registry: &ServerFnRegistry) -> Result<(), ServerFnError> {
         // create the handler for this server function
         // takes a String -> returns its async value
 
         let run_server_fn = Arc::new(|cx: Scope, data: &[u8]| {
             // decode the args
             let value = match Self::encoding() {
-                Encoding::Url => serde_urlencoded::from_bytes(data)
-                    .map_err(|e| ServerFnError::Deserialization(e.to_string())),
+                Encoding::Url | Encoding::Ndjson | Encoding::File => {
+                    serde_urlencoded::from_bytes(data)
+                        .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+                }
                 Encoding::Cbor => ciborium::de::from_reader(data)
                     .map_err(|e| ServerFnError::Deserialization(e.to_string())),
+                Encoding::Multipart => multipart::from_multipart(data),
             };
             Box::pin(async move {
                 let value: Self = match value {
@@ -307,6 +407,12 @@ where
                         Ok(r) => Payload::Url(r),
                         Err(e) => return Err(e),
                     },
+                    Encoding::Multipart => match serde_json::to_string(&result)
+                        .map_err(|e| ServerFnError::Serialization(e.to_string()))
+                    {
+                        Ok(r) => Payload::Json(r),
+                        Err(e) => return Err(e),
+                    },
                     Encoding::Cbor => {
                         let mut buffer: Vec<u8> = Vec::new();
                         match ciborium::ser::into_writer(&result, &mut buffer)
@@ -316,29 +422,28 @@ where
                             Err(e) => return Err(e),
                         }
                     }
+                    Encoding::Ndjson => match Self::into_ndjson(result) {
+                        Ok(lines) => Payload::Ndjson(lines),
+                        Err(e) => return Err(e),
+                    },
+                    Encoding::File => match Self::into_file(result) {
+                        Ok(file) => Payload::File(file),
+                        Err(e) => return Err(e),
+                    },
                 };
 
                 Ok(result)
             }) as Pin<Box<dyn Future<Output = Result<Payload, ServerFnError>>>>
         });
 
-        // store it in the hashmap
-        let mut write = REGISTERED_SERVER_FUNCTIONS
-            .write()
-            .map_err(|e| ServerFnError::Registration(e.to_string()))?;
-        let prev = write.insert(Self::url(), run_server_fn);
-
-        // if there was already a server function with this key,
-        // return Err
-        match prev {
-            Some(_) => Err(ServerFnError::Registration(format!(
-                "There was already a server function registered at {:?}. \
-                This can happen if you use the same server function name in two different modules
-                on `stable` or in `release` mode.",
-                Self::url()
-            ))),
-            None => Ok(()),
-        }
+        // store it in the registry
+        registry.insert(
+            Self::url(),
+            Self::encoding(),
+            Self::headers(),
+            Self::required_headers(),
+            run_server_fn,
+        )
     }
 }
 
@@ -388,7 +493,7 @@ where
         Url(String),
     }
     let args_encoded = match &enc {
-        Encoding::Url => Payload::Url(
+        Encoding::Url | Encoding::Ndjson | Encoding::File | Encoding::Multipart => Payload::Url(
             serde_urlencoded::to_string(&args)
                 .map_err(|e| ServerFnError::Serialization(e.to_string()))?,
         ),
@@ -401,12 +506,16 @@ where
     };
 
     let content_type_header = match &enc {
-        Encoding::Url => "application/x-www-form-urlencoded",
+        Encoding::Url | Encoding::Ndjson | Encoding::File | Encoding::Multipart => {
+            "application/x-www-form-urlencoded"
+        }
         Encoding::Cbor => "application/cbor",
     };
 
     let accept_header = match &enc {
-        Encoding::Url => "application/x-www-form-urlencoded",
+        Encoding::Url | Encoding::Ndjson | Encoding::File | Encoding::Multipart => {
+            "application/x-www-form-urlencoded"
+        }
         Encoding::Cbor => "application/cbor",
     };
 
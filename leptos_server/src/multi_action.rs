@@ -115,6 +115,8 @@ where
     /// Associates the URL of the given server function with this action.
     /// This enables integration with the `MultiActionForm` component in `leptos_router`.
     pub fn using_server_fn<T: ServerFn>(self) -> Self {
+        #[cfg(feature = "ssr")]
+        crate::reference_server_fn(T::url());
         let prefix = T::prefix();
         self.0.update(|a| {
             a.url = if prefix.is_empty() {
@@ -0,0 +1,781 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A file uploaded to a server function that uses the `"Multipart"` encoding, which receives its
+/// arguments as a `multipart/form-data` body, like the one a browser sends for a
+/// `<form enctype="multipart/form-data">` with an `<input type="file">`:
+/// ```rust,ignore
+/// #[server(UploadAvatar, "/api", "Multipart")]
+/// pub async fn upload_avatar(user_id: u32, avatar: FileUpload) -> Result<(), ServerFnError> {
+///     let path = format!("avatars/{user_id}.png");
+///     std::fs::write(path, avatar.bytes()).map_err(|e| ServerFnError::ServerError(e.to_string()))
+/// }
+/// ```
+/// Each argument is read from the parts with the same name:
+/// - text fields can be read as a `String`, a number, a `bool` (a checked checkbox sends `on`), or a
+///   unit-only enum, just like URL-encoded arguments
+/// - a file can be read as a [FileUpload], which also has its name and content type, or as a `Vec<u8>`
+///   with only its contents
+/// - several files, or text fields, with the same name can be read as a `Vec` of any of these
+/// - an `Option` is `None` when there's no part with its name; a file input that's left empty is skipped,
+///   so it's `None` too
+/// - a `Vec` is empty when there's no part with its name, which is how an empty `Vec` is sent
+///
+/// The whole body is read into memory before the server function is called, so uploads are limited by
+/// the integration's maximum body size, if it has one. Streaming an upload to disk as it arrives isn't
+/// supported; a server function that takes uploads bigger than it's willing to buffer should be a
+/// plain route of the server framework instead.
+///
+/// Calling the function from the client sends its arguments as `multipart/form-data` too, with a
+/// [FileUpload] or `Vec<u8>` as a file. Arguments of any other type should be text-like.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct FileUpload {
+    filename: Option<String>,
+    content_type: Option<String>,
+    bytes: Vec<u8>,
+}
+
+impl FileUpload {
+    /// A file with the given contents, to send to a server function from the client.
+    pub fn new(bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            filename: None,
+            content_type: None,
+            bytes: bytes.into(),
+        }
+    }
+
+    /// Sets the name the file is sent with.
+    pub fn with_filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Sets the `Content-Type` the file is sent with.
+    pub fn with_content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// The name of the file on the client's device, as the browser sent it.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// The `Content-Type` of the file, as the browser sent it. Browsers guess it from the file's
+    /// extension, so don't rely on it to tell what the file really contains.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// The contents of the file.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Takes the contents of the file.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl fmt::Debug for FileUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileUpload")
+            .field("filename", &self.filename)
+            .field("content_type", &self.content_type)
+            .field("len", &self.bytes.len())
+            .finish()
+    }
+}
+
+/// Executes the HTTP call to a server function that uses the `"Multipart"` encoding from the client,
+/// sending its arguments as `multipart/form-data`.
+#[cfg(not(feature = "ssr"))]
+pub async fn call_server_fn_multipart<T>(
+    url: &str,
+    args: impl crate::ServerFn,
+) -> Result<T, crate::ServerFnError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    use crate::ServerFnError;
+    use serde_json::Value;
+
+    let fields = match serde_json::to_value(&args) {
+        Ok(Value::Object(fields)) => fields,
+        Ok(_) => {
+            return Err(ServerFnError::Serialization(
+                "server function arguments should be a struct".to_string(),
+            ))
+        }
+        Err(e) => return Err(ServerFnError::Serialization(e.to_string())),
+    };
+    let form =
+        web_sys::FormData::new().map_err(|e| ServerFnError::Serialization(format!("{e:?}")))?;
+    for (name, value) in fields {
+        let values = match value {
+            Value::Array(items) if !is_bytes(&items) => items,
+            value => vec![value],
+        };
+        for value in values {
+            append_form_value(&form, &name, value)
+                .map_err(|e| ServerFnError::Serialization(format!("{e:?}")))?;
+        }
+    }
+
+    let resp = gloo_net::http::Request::post(url)
+        .header("Accept", "application/json")
+        .body(form)
+        .send()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    // check for error status
+    let status = resp.status();
+    if (400..=599).contains(&status) {
+        return Err(ServerFnError::ServerError(resp.status_text()));
+    }
+
+    let text = resp
+        .text()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+    serde_json::from_str(&text).map_err(|e| ServerFnError::Deserialization(e.to_string()))
+}
+
+// Whether a serialized array is a `Vec<u8>`, which is sent as a file
+#[cfg(not(feature = "ssr"))]
+fn is_bytes(items: &[serde_json::Value]) -> bool {
+    !items.is_empty()
+        && items
+            .iter()
+            .all(|item| item.as_u64().map(|byte| byte <= 255).unwrap_or(false))
+}
+
+// Adds one value to the form: a serialized [FileUpload] or `Vec<u8>` as a file, and anything else as text
+#[cfg(not(feature = "ssr"))]
+fn append_form_value(
+    form: &web_sys::FormData,
+    name: &str,
+    value: serde_json::Value,
+) -> Result<(), wasm_bindgen::JsValue> {
+    use serde_json::Value;
+
+    let blob = |bytes: &[Value], content_type: Option<&str>| {
+        let bytes: Vec<u8> = bytes
+            .iter()
+            .filter_map(|byte| byte.as_u64())
+            .map(|byte| byte as u8)
+            .collect();
+        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes.as_slice()));
+        let options = web_sys::BlobPropertyBag::new();
+        if let Some(content_type) = content_type {
+            options.set_type(content_type);
+        }
+        web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+    };
+
+    match value {
+        Value::Object(file) if file.get("bytes").map(Value::is_array).unwrap_or(false) => {
+            let bytes = file["bytes"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let blob = blob(bytes, file.get("content_type").and_then(Value::as_str))?;
+            match file.get("filename").and_then(Value::as_str) {
+                Some(filename) => form.append_with_blob_and_filename(name, &blob, filename),
+                None => form.append_with_blob(name, &blob),
+            }
+        }
+        Value::Array(bytes) => form.append_with_blob(name, &blob(&bytes, None)?),
+        Value::String(text) => form.append_with_str(name, &text),
+        // an `Option` that's `None` is left out, like an empty file input
+        Value::Null => Ok(()),
+        other => form.append_with_str(name, &other.to_string()),
+    }
+}
+
+#[cfg(any(feature = "ssr", doc))]
+pub(crate) use parse::*;
+
+#[cfg(any(feature = "ssr", doc))]
+mod parse {
+    use crate::ServerFnError;
+    use serde::de::{self, value::MapDeserializer, value::SeqDeserializer, IntoDeserializer};
+
+    type Error = de::value::Error;
+
+    /// One part of a `multipart/form-data` body.
+    pub(crate) struct Part {
+        pub(crate) name: String,
+        pub(crate) filename: Option<String>,
+        pub(crate) content_type: Option<String>,
+        pub(crate) data: Vec<u8>,
+    }
+
+    impl Part {
+        fn is_file(&self) -> bool {
+            self.filename.is_some()
+        }
+
+        fn text(self) -> Result<String, Error> {
+            String::from_utf8(self.data).map_err(de::Error::custom)
+        }
+    }
+
+    /// Deserializes a server function's arguments from a `multipart/form-data` body that has been
+    /// through [decode_body](crate::ServerFnRegistry::decode_body), which checks it against the
+    /// boundary in the request's `Content-Type` and drops its preamble, so it starts with the
+    /// delimiter line.
+    pub(crate) fn from_multipart<T: de::DeserializeOwned>(body: &[u8]) -> Result<T, ServerFnError> {
+        let boundary = find(body, b"\r\n")
+            .and_then(|end| body[..end].strip_prefix(b"--"))
+            .map(String::from_utf8_lossy)
+            .ok_or_else(|| malformed("no boundary"))?;
+        let parts = parse_multipart(body, &boundary)?;
+        // the parts for each argument, in the order they were sent
+        let mut fields: Vec<(String, Vec<Part>)> = Vec::new();
+        for part in parts {
+            match fields.iter_mut().find(|(name, _)| *name == part.name) {
+                Some((_, parts)) => parts.push(part),
+                None => fields.push((part.name.clone(), vec![part])),
+            }
+        }
+        T::deserialize(FormDeserializer(fields))
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+
+    /// The `boundary` parameter of a `multipart/form-data` content type, which may be quoted.
+    pub(crate) fn multipart_boundary(content_type: &str) -> Result<String, ServerFnError> {
+        content_type
+            .split(';')
+            .skip(1)
+            .filter_map(|param| param.split_once('='))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("boundary"))
+            .map(|(_, value)| value.trim().trim_matches('"').to_string())
+            .filter(|boundary| !boundary.is_empty())
+            .ok_or_else(|| malformed("no boundary in the Content-Type"))
+    }
+
+    /// The body from its first delimiter line on, without the preamble that may come before it.
+    pub(crate) fn skip_preamble<'a>(
+        body: &'a [u8],
+        boundary: &str,
+    ) -> Result<&'a [u8], ServerFnError> {
+        let dash_boundary = format!("--{boundary}");
+        if body.starts_with(dash_boundary.as_bytes()) {
+            return Ok(body);
+        }
+        let delimiter = format!("\r\n{dash_boundary}");
+        find(body, delimiter.as_bytes())
+            .map(|start| &body[start + 2..])
+            .ok_or_else(|| malformed("the boundary in the Content-Type isn't in the body"))
+    }
+
+    /// Splits a `multipart/form-data` body into its parts, with the boundary from the request's
+    /// `Content-Type`. Any preamble before the first delimiter is skipped. File inputs that were left
+    /// empty are skipped too.
+    pub(crate) fn parse_multipart(body: &[u8], boundary: &str) -> Result<Vec<Part>, ServerFnError> {
+        let body = skip_preamble(body, boundary)?;
+        let delimiter = format!("\r\n--{boundary}").into_bytes();
+
+        let mut parts = Vec::new();
+        let mut rest = &body[delimiter.len() - 2..];
+        loop {
+            rest = match rest.strip_prefix(b"--") {
+                Some(_) => return Ok(parts),
+                None => delimiter_line_end(rest)?,
+            };
+            let end =
+                find(rest, &delimiter).ok_or_else(|| malformed("missing the closing boundary"))?;
+            let part =
+                parse_part(&rest[..end]).ok_or_else(|| malformed("a part without a name"))?;
+            // a file input that was left empty
+            let empty_file = part.filename.as_deref() == Some("") && part.data.is_empty();
+            if !empty_file {
+                parts.push(part);
+            }
+
+            rest = &rest[end + delimiter.len()..];
+        }
+    }
+
+    // The rest of a delimiter line, which may end with whitespace before its line break
+    fn delimiter_line_end(rest: &[u8]) -> Result<&[u8], ServerFnError> {
+        let padding = rest
+            .iter()
+            .take_while(|c| **c == b' ' || **c == b'\t')
+            .count();
+        rest[padding..]
+            .strip_prefix(b"\r\n")
+            .ok_or_else(|| malformed("no line break after a boundary"))
+    }
+
+    fn malformed(reason: &str) -> ServerFnError {
+        ServerFnError::Deserialization(format!("malformed multipart body: {reason}"))
+    }
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    // A part's headers and its data, which are separated by an empty line
+    fn parse_part(part: &[u8]) -> Option<Part> {
+        let (headers, data) = match part.strip_prefix(b"\r\n") {
+            // a part without any headers
+            Some(data) => (&[][..], data),
+            None => {
+                let end = find(part, b"\r\n\r\n")?;
+                (&part[..end], &part[end + 4..])
+            }
+        };
+
+        let headers = String::from_utf8_lossy(headers);
+        let mut name = None;
+        let mut filename = None;
+        let mut content_type = None;
+        for line in headers.split("\r\n") {
+            let (header, value) = match line.split_once(':') {
+                Some(header) => header,
+                None => continue,
+            };
+            if header.trim().eq_ignore_ascii_case("content-disposition") {
+                for (key, value) in disposition_params(value) {
+                    match key.to_ascii_lowercase().as_str() {
+                        "name" => name = Some(value),
+                        "filename" => filename = Some(value),
+                        _ => {}
+                    }
+                }
+            } else if header.trim().eq_ignore_ascii_case("content-type") {
+                content_type = Some(value.trim().to_string());
+            }
+        }
+
+        Some(Part {
+            name: name?,
+            filename,
+            content_type,
+            data: data.to_vec(),
+        })
+    }
+
+    // The `key=value` parameters of a `Content-Disposition` header, whose values may be quoted
+    fn disposition_params(value: &str) -> Vec<(String, String)> {
+        let mut params = Vec::new();
+        let mut current = String::new();
+        let mut in_quotes = false;
+        let mut escaped = false;
+        for c in value.chars().chain(std::iter::once(';')) {
+            match c {
+                _ if escaped => {
+                    current.push(c);
+                    escaped = false;
+                }
+                '\\' if in_quotes => escaped = true,
+                '"' => in_quotes = !in_quotes,
+                ';' if !in_quotes => {
+                    if let Some((key, value)) = current.split_once('=') {
+                        params.push((key.trim().to_string(), value.trim().to_string()));
+                    }
+                    current.clear();
+                }
+                c => current.push(c),
+            }
+        }
+        params
+    }
+
+    // Forwards the methods of a [Deserializer](de::Deserializer) to the [PartDeserializer] returned
+    // by `self.into_part()`
+    macro_rules! delegate_to_part {
+        ($($method:ident($($arg:ident: $ty:ty),*)),* $(,)?) => {
+            $(
+                fn $method<V: de::Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Error> {
+                    self.into_part()?.$method($($arg,)* visitor)
+                }
+            )*
+        };
+    }
+
+    // The arguments of the server function, as a map from each name to its parts
+    struct FormDeserializer(Vec<(String, Vec<Part>)>);
+
+    impl<'de> de::Deserializer<'de> for FormDeserializer {
+        type Error = Error;
+
+        fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            let fields = self
+                .0
+                .into_iter()
+                .map(|(name, parts)| (name.clone(), FieldDeserializer(name, parts)));
+            visitor.visit_map(MapDeserializer::new(fields))
+        }
+
+        // Every argument gets a field, with no parts if none were sent: that's how an empty `Vec` is
+        // sent, and how a form sends checkboxes with the same name that are all unchecked
+        fn deserialize_struct<V: de::Visitor<'de>>(
+            mut self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            for field in fields {
+                if !self.0.iter().any(|(name, _)| name == field) {
+                    self.0.push((field.to_string(), Vec::new()));
+                }
+            }
+            self.deserialize_any(visitor)
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf option unit
+            unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    // All the parts with the name of one argument. Without any, it's an empty sequence or `None`.
+    // Anything but a sequence is read from the last of them.
+    struct FieldDeserializer(String, Vec<Part>);
+
+    impl FieldDeserializer {
+        fn into_part(mut self) -> Result<PartDeserializer, Error> {
+            let name = self.0;
+            self.1
+                .pop()
+                .map(PartDeserializer)
+                .ok_or_else(|| de::Error::custom(format!("missing field `{name}`")))
+        }
+    }
+
+    impl<'de> IntoDeserializer<'de, Error> for FieldDeserializer {
+        type Deserializer = Self;
+
+        fn into_deserializer(self) -> Self {
+            self
+        }
+    }
+
+    impl<'de> de::Deserializer<'de> for FieldDeserializer {
+        type Error = Error;
+
+        // Several parts are a sequence of values. A single one is a sequence too, but of what? A file
+        // could be a `Vec<u8>` or a `Vec<FileUpload>` with one file, so that's decided by the first
+        // element the sequence is asked for.
+        fn deserialize_seq<V: de::Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Error> {
+            if self.1.len() > 1 {
+                return visitor.visit_seq(SeqDeserializer::new(
+                    self.1.into_iter().map(PartDeserializer),
+                ));
+            }
+            match self.1.pop() {
+                Some(part) if !part.data.is_empty() => visitor.visit_seq(SinglePartSeq {
+                    part: Some(part),
+                    bytes: None,
+                }),
+                _ => {
+                    visitor.visit_seq(SeqDeserializer::new(std::iter::empty::<PartDeserializer>()))
+                }
+            }
+        }
+
+        fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            if self.1.is_empty() {
+                return visitor.visit_none();
+            }
+            self.into_part()?.deserialize_option(visitor)
+        }
+
+        delegate_to_part! {
+            deserialize_any(), deserialize_bool(), deserialize_i8(), deserialize_i16(), deserialize_i32(),
+            deserialize_i64(), deserialize_u8(), deserialize_u16(), deserialize_u32(), deserialize_u64(),
+            deserialize_f32(), deserialize_f64(), deserialize_char(), deserialize_str(), deserialize_string(),
+            deserialize_bytes(), deserialize_byte_buf(), deserialize_unit(),
+            deserialize_unit_struct(name: &'static str), deserialize_newtype_struct(name: &'static str),
+            deserialize_tuple(len: usize), deserialize_tuple_struct(name: &'static str, len: usize),
+            deserialize_map(), deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+            deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+            deserialize_identifier(), deserialize_ignored_any(),
+        }
+    }
+
+    // The sequence for a single part: its bytes, if the first element is a `u8`, or else the part itself
+    struct SinglePartSeq {
+        part: Option<Part>,
+        bytes: Option<std::vec::IntoIter<u8>>,
+    }
+
+    impl<'de> de::SeqAccess<'de> for SinglePartSeq {
+        type Error = Error;
+
+        fn next_element_seed<T: de::DeserializeSeed<'de>>(
+            &mut self,
+            seed: T,
+        ) -> Result<Option<T::Value>, Error> {
+            if let Some(bytes) = &mut self.bytes {
+                return match bytes.next() {
+                    Some(byte) => seed
+                        .deserialize(IntoDeserializer::<Error>::into_deserializer(byte))
+                        .map(Some),
+                    None => Ok(None),
+                };
+            }
+            if self.part.is_none() {
+                return Ok(None);
+            }
+            seed.deserialize(FirstElement(self)).map(Some)
+        }
+    }
+
+    // The first element of a [SinglePartSeq], which decides what the rest of it is
+    struct FirstElement<'a>(&'a mut SinglePartSeq);
+
+    impl FirstElement<'_> {
+        fn into_part(self) -> Result<PartDeserializer, Error> {
+            self.0
+                .part
+                .take()
+                .map(PartDeserializer)
+                .ok_or_else(|| de::Error::custom("no value"))
+        }
+    }
+
+    impl<'de> de::Deserializer<'de> for FirstElement<'_> {
+        type Error = Error;
+
+        fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            let no_value = || <Error as de::Error>::custom("no value");
+            let part = self.0.part.take().ok_or_else(no_value)?;
+            let mut bytes = part.data.into_iter();
+            let first = bytes.next().ok_or_else(no_value)?;
+            self.0.bytes = Some(bytes);
+            visitor.visit_u8(first)
+        }
+
+        delegate_to_part! {
+            deserialize_any(), deserialize_bool(), deserialize_i8(), deserialize_i16(), deserialize_i32(),
+            deserialize_i64(), deserialize_u16(), deserialize_u32(), deserialize_u64(), deserialize_f32(),
+            deserialize_f64(), deserialize_char(), deserialize_str(), deserialize_string(),
+            deserialize_bytes(), deserialize_byte_buf(), deserialize_option(), deserialize_unit(),
+            deserialize_unit_struct(name: &'static str), deserialize_newtype_struct(name: &'static str),
+            deserialize_seq(), deserialize_tuple(len: usize),
+            deserialize_tuple_struct(name: &'static str, len: usize), deserialize_map(),
+            deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+            deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+            deserialize_identifier(), deserialize_ignored_any(),
+        }
+    }
+
+    // A single part: a text field, whose text is parsed into the type that's asked for, or a file
+    struct PartDeserializer(Part);
+
+    impl<'de> IntoDeserializer<'de, Error> for PartDeserializer {
+        type Deserializer = Self;
+
+        fn into_deserializer(self) -> Self {
+            self
+        }
+    }
+
+    // Parses the text of a part into a primitive
+    macro_rules! parse_text {
+        ($($method:ident => $visit:ident),* $(,)?) => {
+            $(
+                fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                    let text = self.0.text()?;
+                    visitor.$visit(text.trim().parse().map_err(<Error as de::Error>::custom)?)
+                }
+            )*
+        };
+    }
+
+    impl<'de> de::Deserializer<'de> for PartDeserializer {
+        type Error = Error;
+
+        fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            if self.0.is_file() {
+                return self.deserialize_map(visitor);
+            }
+            match String::from_utf8(self.0.data) {
+                Ok(text) => visitor.visit_string(text),
+                Err(e) => visitor.visit_byte_buf(e.into_bytes()),
+            }
+        }
+
+        // a checked checkbox is sent as `on`
+        fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.0.text()?.trim() {
+                "on" | "true" => visitor.visit_bool(true),
+                "off" | "false" => visitor.visit_bool(false),
+                other => Err(de::Error::custom(format!("{other} is not a boolean"))),
+            }
+        }
+
+        parse_text! {
+            deserialize_i8 => visit_i8, deserialize_i16 => visit_i16, deserialize_i32 => visit_i32,
+            deserialize_i64 => visit_i64, deserialize_u8 => visit_u8, deserialize_u16 => visit_u16,
+            deserialize_u32 => visit_u32, deserialize_u64 => visit_u64, deserialize_f32 => visit_f32,
+            deserialize_f64 => visit_f64, deserialize_char => visit_char,
+        }
+
+        fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_string(self.0.text()?)
+        }
+
+        fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_string(self.0.text()?)
+        }
+
+        fn deserialize_identifier<V: de::Visitor<'de>>(
+            self,
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            visitor.visit_string(self.0.text()?)
+        }
+
+        fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_byte_buf(self.0.data)
+        }
+
+        fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_byte_buf(self.0.data)
+        }
+
+        fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_some(self)
+        }
+
+        fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_unit()
+        }
+
+        fn deserialize_unit_struct<V: de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            visitor.visit_unit()
+        }
+
+        fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            visitor.visit_newtype_struct(self)
+        }
+
+        fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_seq(SeqDeserializer::new(self.0.data.into_iter()))
+        }
+
+        fn deserialize_tuple<V: de::Visitor<'de>>(
+            self,
+            _len: usize,
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            self.deserialize_seq(visitor)
+        }
+
+        fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            _len: usize,
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            self.deserialize_seq(visitor)
+        }
+
+        // a file, as a [FileUpload](crate::FileUpload)
+        fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            let Part {
+                filename,
+                content_type,
+                data,
+                ..
+            } = self.0;
+            let fields = vec![
+                ("filename", FileField::Text(filename)),
+                ("content_type", FileField::Text(content_type)),
+                ("bytes", FileField::Bytes(data)),
+            ];
+            visitor.visit_map(MapDeserializer::new(fields.into_iter()))
+        }
+
+        fn deserialize_struct<V: de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            _fields: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            self.deserialize_map(visitor)
+        }
+
+        // a unit variant, named by the text
+        fn deserialize_enum<V: de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            _variants: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(self.0.text()?))
+        }
+
+        fn deserialize_ignored_any<V: de::Visitor<'de>>(
+            self,
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            visitor.visit_unit()
+        }
+    }
+
+    // One of the fields of a file
+    enum FileField {
+        Text(Option<String>),
+        Bytes(Vec<u8>),
+    }
+
+    impl<'de> IntoDeserializer<'de, Error> for FileField {
+        type Deserializer = Self;
+
+        fn into_deserializer(self) -> Self {
+            self
+        }
+    }
+
+    impl<'de> de::Deserializer<'de> for FileField {
+        type Error = Error;
+
+        fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self {
+                FileField::Text(Some(text)) => visitor.visit_string(text),
+                FileField::Text(None) => visitor.visit_none(),
+                FileField::Bytes(bytes) => visitor.visit_byte_buf(bytes),
+            }
+        }
+
+        fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self {
+                FileField::Text(None) => visitor.visit_none(),
+                field => visitor.visit_some(field),
+            }
+        }
+
+        fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self {
+                FileField::Bytes(bytes) => {
+                    visitor.visit_seq(SeqDeserializer::new(bytes.into_iter()))
+                }
+                field => field.deserialize_any(visitor),
+            }
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf unit unit_struct
+            newtype_struct tuple tuple_struct map struct enum identifier ignored_any
+        }
+    }
+}
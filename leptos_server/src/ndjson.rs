@@ -0,0 +1,236 @@
+use crate::ServerFnError;
+use futures::{Stream, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::VecDeque,
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The lines of a newline-delimited JSON response, each of which ends with `\n`.
+pub type NdjsonLines = Pin<Box<dyn Stream<Item = String>>>;
+
+/// A stream of items sent from a server function as newline-delimited JSON (NDJSON), so the
+/// client can handle each item as soon as it arrives instead of waiting for the whole collection.
+///
+/// Return this from a server function that uses the `"Ndjson"` encoding:
+/// ```rust,ignore
+/// #[server(ListPosts, "/api", "Ndjson")]
+/// pub async fn list_posts() -> Result<NdjsonStream<Post>, ServerFnError> {
+///     Ok(NdjsonStream::new(load_posts_one_by_one()))
+/// }
+///
+/// // on the client, items are yielded as they're received
+/// let mut posts = list_posts().await?;
+/// while let Some(post) = posts.next().await {
+///     log!("{:?}", post?);
+/// }
+/// ```
+///
+/// ## Wire Format
+/// The response has the `application/x-ndjson` content type. Each line is one item, serialized
+/// as a JSON `Result<T, ServerFnError>`: `{"Ok":<item>}`.
+///
+/// Because the response status has already been sent by the time the stream is running, an error
+/// partway through the stream is sent as a final `{"Err":<ServerFnError>}` line instead, and the
+/// stream ends after it. The client yields it as an `Err` item.
+pub struct NdjsonStream<T> {
+    inner: Pin<Box<dyn Stream<Item = Result<T, ServerFnError>>>>,
+}
+
+impl<T> NdjsonStream<T> {
+    /// Creates a stream that sends each item of `stream` to the client as it's produced.
+    pub fn new(stream: impl Stream<Item = Result<T, ServerFnError>> + 'static) -> Self {
+        Self {
+            inner: Box::pin(stream),
+        }
+    }
+
+    /// Serializes each item as one line of NDJSON. The lines end after the first error.
+    pub fn into_lines(self) -> NdjsonLines
+    where
+        T: Serialize + 'static,
+    {
+        Box::pin(futures::stream::unfold(
+            (self.inner, false),
+            |(mut inner, done)| async move {
+                if done {
+                    return None;
+                }
+                let (line, done) = match inner.next().await? {
+                    Ok(item) => match serde_json::to_string(&Ok::<&T, ServerFnError>(&item)) {
+                        Ok(line) => (line, false),
+                        Err(e) => (
+                            error_line(ServerFnError::Serialization(e.to_string())),
+                            true,
+                        ),
+                    },
+                    Err(e) => (error_line(e), true),
+                };
+                Some((line + "\n", (inner, done)))
+            },
+        ))
+    }
+
+    /// Parses NDJSON from a stream of byte chunks, yielding each item as soon as the line it's on
+    /// has been received. Lines can be split across chunks in any way.
+    pub fn decode(chunks: impl Stream<Item = Result<Vec<u8>, ServerFnError>> + 'static) -> Self
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let state = DecodeState {
+            chunks: Box::pin(chunks),
+            buffer: Vec::new(),
+            pending: VecDeque::new(),
+            done: false,
+        };
+        Self::new(futures::stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(item) = state.pending.pop_front() {
+                    if item.is_err() {
+                        // nothing after an error is meaningful
+                        state.pending.clear();
+                        state.done = true;
+                    }
+                    return Some((item, state));
+                }
+                if state.done {
+                    return None;
+                }
+                match state.chunks.next().await {
+                    Some(Ok(chunk)) => {
+                        state.buffer.extend_from_slice(&chunk);
+                        while let Some(end) = state.buffer.iter().position(|b| *b == b'\n') {
+                            let line: Vec<u8> = state.buffer.drain(..=end).collect();
+                            state.parse_line(&line);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        state.pending.push_back(Err(e));
+                    }
+                    None => {
+                        // the last line doesn't have to end with a newline
+                        let line = std::mem::take(&mut state.buffer);
+                        state.parse_line(&line);
+                        state.done = true;
+                    }
+                }
+            }
+        }))
+    }
+}
+
+fn error_line(error: ServerFnError) -> String {
+    serde_json::to_string(&Err::<(), ServerFnError>(error))
+        .expect("ServerFnError should always serialize")
+}
+
+struct DecodeState<T> {
+    chunks: Pin<Box<dyn Stream<Item = Result<Vec<u8>, ServerFnError>>>>,
+    buffer: Vec<u8>,
+    pending: VecDeque<Result<T, ServerFnError>>,
+    done: bool,
+}
+
+impl<T: DeserializeOwned> DecodeState<T> {
+    fn parse_line(&mut self, line: &[u8]) {
+        if line.iter().all(|b| b.is_ascii_whitespace()) {
+            return;
+        }
+        let item = serde_json::from_slice::<Result<T, ServerFnError>>(line)
+            .unwrap_or_else(|e| Err(ServerFnError::Deserialization(e.to_string())));
+        self.pending.push_back(item);
+    }
+}
+
+impl<T> Stream for NdjsonStream<T> {
+    type Item = Result<T, ServerFnError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl<T: 'static> FromIterator<T> for NdjsonStream<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+        let items = items.into_iter().map(Ok).collect::<Vec<_>>();
+        Self::new(futures::stream::iter(items))
+    }
+}
+
+impl<T> fmt::Debug for NdjsonStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NdjsonStream").finish_non_exhaustive()
+    }
+}
+
+/// An [NdjsonStream] can only be sent with the `"Ndjson"` encoding, which sends its items one by
+/// one; serializing it as a single value always fails.
+impl<T> Serialize for NdjsonStream<T> {
+    fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom(
+            "an NdjsonStream can only be returned by a server function with the \"Ndjson\" encoding",
+        ))
+    }
+}
+
+/// Executes the HTTP call to a server function that uses the `"Ndjson"` encoding from the client,
+/// returning a stream that yields each item as soon as it has been received.
+#[cfg(not(feature = "ssr"))]
+pub async fn call_server_fn_ndjson<T>(
+    url: &str,
+    args: impl crate::ServerFn,
+) -> Result<NdjsonStream<T>, ServerFnError>
+where
+    T: DeserializeOwned + 'static,
+{
+    use js_sys::{Reflect, Uint8Array};
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::ReadableStreamDefaultReader;
+
+    let args = serde_urlencoded::to_string(&args)
+        .map_err(|e| ServerFnError::Serialization(e.to_string()))?;
+    let resp = gloo_net::http::Request::post(url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept", "application/x-ndjson")
+        .body(args)
+        .send()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    // check for error status
+    let status = resp.status();
+    if (500..=599).contains(&status) {
+        return Err(ServerFnError::ServerError(resp.status_text()));
+    }
+
+    let body = resp
+        .body()
+        .ok_or_else(|| ServerFnError::Deserialization("the response has no body".to_string()))?;
+    let reader = ReadableStreamDefaultReader::new(&body)
+        .map_err(|e| ServerFnError::Deserialization(format!("{e:?}")))?;
+
+    let chunks = futures::stream::unfold(Some(reader), |reader| async move {
+        let reader = reader?;
+        let chunk = async {
+            let result = JsFuture::from(reader.read()).await?;
+            let done = Reflect::get(&result, &JsValue::from_str("done"))?
+                .as_bool()
+                .unwrap_or(true);
+            if done {
+                return Ok(None);
+            }
+            let value = Reflect::get(&result, &JsValue::from_str("value"))?;
+            Ok::<_, JsValue>(Some(value.unchecked_into::<Uint8Array>().to_vec()))
+        };
+        match chunk.await {
+            Ok(Some(chunk)) => Some((Ok(chunk), Some(reader))),
+            Ok(None) => None,
+            Err(e) => Some((Err(ServerFnError::Request(format!("{e:?}"))), None)),
+        }
+    });
+
+    Ok(NdjsonStream::decode(chunks))
+}
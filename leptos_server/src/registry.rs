@@ -0,0 +1,167 @@
+use crate::{decode_body, Encoding, ServerFnError, ServerFnTraitObj};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
+
+lazy_static::lazy_static! {
+    static ref GLOBAL_REGISTRY: ServerFnRegistry = ServerFnRegistry::new();
+    static ref REFERENCED_SERVER_FNS: RwLock<HashSet<&'static str>> = Default::default();
+}
+
+// The headers that a server function declares for its responses, as `(name, value)` pairs
+type ResponseHeaders = &'static [(&'static str, &'static str)];
+
+/// A set of server functions that a server can call by URL.
+///
+/// By default, every server function is registered in a single, process-wide registry: this is
+/// the one that [ServerFn::register](crate::ServerFn::register) adds to, that
+/// [server_fn_by_path](crate::server_fn_by_path) searches, and that the server function
+/// handlers in the integrations use unless they're given another one.
+///
+/// When several apps are served from the same process, each one can have its own registry
+/// instead, so they can register server functions with the same name without colliding. Register
+/// the app's functions with [ServerFn::register_in](crate::ServerFn::register_in), and pass the
+/// registry to the handler (in `leptos_axum`, add it to the router with `.layer(Extension(registry))`;
+/// in `leptos_actix`, with `.app_data(registry)`):
+/// ```rust,ignore
+/// let registry = ServerFnRegistry::new();
+/// _ = GetPosts::register_in(&registry);
+///
+/// let app = Router::new()
+///     .route("/api/*fn_name", post(leptos_axum::handle_server_fns))
+///     .layer(Extension(registry));
+/// ```
+///
+/// Cloning a registry is cheap, and the clones share the same server functions.
+#[derive(Clone, Default)]
+pub struct ServerFnRegistry {
+    fns: Arc<RwLock<HashMap<&'static str, Arc<ServerFnTraitObj>>>>,
+    encodings: Arc<RwLock<HashMap<&'static str, Encoding>>>,
+    headers: Arc<RwLock<HashMap<&'static str, ResponseHeaders>>>,
+    required_headers: Arc<RwLock<HashMap<&'static str, &'static [&'static str]>>>,
+}
+
+impl ServerFnRegistry {
+    /// Creates an empty registry, separate from the global one.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide registry that server functions are registered in by default.
+    pub fn global() -> Self {
+        GLOBAL_REGISTRY.clone()
+    }
+
+    /// Returns the server function registered at the given path, if there is one.
+    pub fn get(&self, path: &str) -> Option<Arc<ServerFnTraitObj>> {
+        self.fns.read().ok().and_then(|fns| fns.get(path).cloned())
+    }
+
+    /// Returns the [Encoding] of the server function registered at the given path.
+    pub fn encoding(&self, path: &str) -> Option<Encoding> {
+        self.encodings
+            .read()
+            .ok()
+            .and_then(|encodings| encodings.get(path).copied())
+    }
+
+    /// Returns the headers declared by the server function registered at the given path, which
+    /// are added to its successful responses. See [ServerFn::headers](crate::ServerFn::headers).
+    pub fn headers(&self, path: &str) -> ResponseHeaders {
+        self.headers
+            .read()
+            .ok()
+            .and_then(|headers| headers.get(path).copied())
+            .unwrap_or_default()
+    }
+
+    /// Returns the headers that requests to the server function registered at the given path must
+    /// have. See [ServerFn::required_headers](crate::ServerFn::required_headers).
+    pub fn required_headers(&self, path: &str) -> &'static [&'static str] {
+        self.required_headers
+            .read()
+            .ok()
+            .and_then(|headers| headers.get(path).copied())
+            .unwrap_or_default()
+    }
+
+    /// Returns the paths of the server functions in this registry, for debugging purposes.
+    pub fn paths(&self) -> Vec<&'static str> {
+        self.fns
+            .read()
+            .map(|fns| fns.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Prepares the body of a request to the server function at `path` in this registry, like
+    /// [decode_server_fn_body](crate::decode_server_fn_body) does for the global registry.
+    pub fn decode_body<'a>(
+        &self,
+        path: &str,
+        content_type: Option<&str>,
+        body: &'a [u8],
+    ) -> Result<Cow<'a, [u8]>, ServerFnError> {
+        decode_body(self.encoding(path), content_type, body)
+    }
+
+    // Adds a server function, returning `Err` if there was already one at the same path
+    pub(crate) fn insert(
+        &self,
+        path: &'static str,
+        encoding: Encoding,
+        headers: ResponseHeaders,
+        required_headers: &'static [&'static str],
+        server_fn: Arc<ServerFnTraitObj>,
+    ) -> Result<(), ServerFnError> {
+        let prev = self
+            .fns
+            .write()
+            .map_err(|e| ServerFnError::Registration(e.to_string()))?
+            .insert(path, server_fn);
+        self.encodings
+            .write()
+            .map_err(|e| ServerFnError::Registration(e.to_string()))?
+            .insert(path, encoding);
+        self.headers
+            .write()
+            .map_err(|e| ServerFnError::Registration(e.to_string()))?
+            .insert(path, headers);
+        self.required_headers
+            .write()
+            .map_err(|e| ServerFnError::Registration(e.to_string()))?
+            .insert(path, required_headers);
+
+        // if there was already a server function with this key,
+        // return Err
+        match prev {
+            Some(_) => Err(ServerFnError::Registration(format!(
+                "There was already a server function registered at {:?}. \
+                This can happen if you use the same server function name in two different modules
+                on `stable` or in `release` mode.",
+                path
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the paths of the server functions that actions have been created for on the server,
+/// with [create_server_action](crate::create_server_action) or
+/// [create_server_multi_action](crate::create_server_multi_action), while rendering the app.
+///
+/// These are the server functions that the app can call from the client, which integrations
+/// can use to check that they've all been registered.
+pub fn referenced_server_fns() -> Vec<&'static str> {
+    REFERENCED_SERVER_FNS
+        .read()
+        .map(|paths| paths.iter().copied().collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn reference_server_fn(path: &'static str) {
+    if let Ok(mut paths) = REFERENCED_SERVER_FNS.write() {
+        paths.insert(path);
+    }
+}
@@ -0,0 +1,49 @@
+use futures::{executor::block_on, StreamExt};
+use leptos_server::{NdjsonStream, ServerFnError};
+
+#[test]
+fn items_are_sent_one_per_line() {
+    let lines: Vec<String> = block_on(NdjsonStream::from_iter([1, 2, 3]).into_lines().collect());
+    assert_eq!(lines, vec!["{\"Ok\":1}\n", "{\"Ok\":2}\n", "{\"Ok\":3}\n"]);
+}
+
+#[test]
+fn errors_end_the_stream() {
+    let stream = NdjsonStream::new(futures::stream::iter([
+        Ok(1),
+        Err(ServerFnError::ServerError("boom".to_string())),
+        Ok(3),
+    ]));
+    let lines: Vec<String> = block_on(stream.into_lines().collect());
+    assert_eq!(
+        lines,
+        vec!["{\"Ok\":1}\n", "{\"Err\":{\"ServerError\":\"boom\"}}\n"]
+    );
+
+    let chunks = futures::stream::iter(lines.into_iter().map(|line| Ok(line.into_bytes())));
+    let items: Vec<Result<u32, ServerFnError>> = block_on(NdjsonStream::decode(chunks).collect());
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].as_ref().unwrap(), &1);
+    assert!(matches!(&items[1], Err(ServerFnError::ServerError(e)) if e == "boom"));
+}
+
+#[test]
+fn lines_can_be_split_across_chunks() {
+    let chunks = ["{\"Ok\":\"a\"}\n{\"O", "k\":\"b\"", "}\n", "{\"Ok\":\"c\"}"]
+        .into_iter()
+        .map(|chunk| Ok(chunk.as_bytes().to_vec()));
+    let items: Vec<String> = block_on(
+        NdjsonStream::decode(futures::stream::iter(chunks))
+            .map(Result::unwrap)
+            .collect(),
+    );
+    assert_eq!(items, vec!["a", "b", "c"]);
+}
+
+#[test]
+fn invalid_lines_are_reported() {
+    let chunks = futures::stream::iter([Ok(b"{\"Ok\":1}\nnot json\n{\"Ok\":2}\n".to_vec())]);
+    let items: Vec<Result<u32, ServerFnError>> = block_on(NdjsonStream::decode(chunks).collect());
+    assert_eq!(items.len(), 2);
+    assert!(matches!(items[1], Err(ServerFnError::Deserialization(_))));
+}
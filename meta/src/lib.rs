@@ -48,7 +48,7 @@
 use cfg_if::cfg_if;
 use std::{
     cell::{Cell, RefCell},
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::Debug,
     rc::Rc,
 };
@@ -76,6 +76,41 @@ pub use title::*;
 pub struct MetaContext {
     pub(crate) title: TitleContext,
     pub(crate) tags: MetaTagsContext,
+    pub(crate) defaults: MetaDefaults,
+}
+
+/// The metadata that a server sets for a page before rendering it, which the page's own
+/// [Title] and [Meta] components override.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct MetaDefaults {
+    title: Option<String>,
+    meta: Vec<(String, String)>,
+    // the names of the <Meta/> tags the app has set
+    overridden: Rc<RefCell<HashSet<String>>>,
+}
+
+impl MetaDefaults {
+    pub(crate) fn override_meta(&self, name: String) {
+        self.overridden.borrow_mut().insert(name);
+    }
+
+    #[cfg(feature = "ssr")]
+    fn as_string(&self) -> String {
+        use leptos::leptos_dom::escape_attr;
+
+        let overridden = self.overridden.borrow();
+        self.meta
+            .iter()
+            .filter(|(name, _)| !overridden.contains(name))
+            .map(|(name, content)| {
+                format!(
+                    r#"<meta name="{}" content="{}"/>"#,
+                    escape_attr(name),
+                    escape_attr(content)
+                )
+            })
+            .collect()
+    }
 }
 
 /// Manages all of the element created by components.
@@ -197,6 +232,42 @@ impl MetaContext {
         Default::default()
     }
 
+    /// Sets the page's title, unless the app sets one with a [Title].
+    ///
+    /// This lets a server set metadata that it knows about before rendering the app, like the
+    /// name of a tenant's site resolved from the request's host, by providing the context before
+    /// the app runs (with the `additional_context` of an integration's render function). The app's
+    /// own [Title] and [Meta] components always take precedence over these defaults.
+    /// ```
+    /// use leptos::*;
+    /// use leptos_meta::*;
+    ///
+    /// let additional_context = |cx| {
+    ///     provide_context(
+    ///         cx,
+    ///         MetaContext::new()
+    ///             .with_default_title("Acme Widgets")
+    ///             .with_default_meta("description", "Widgets for every occasion."),
+    ///     )
+    /// };
+    /// # _ = additional_context;
+    /// ```
+    pub fn with_default_title(mut self, title: impl Into<String>) -> Self {
+        self.defaults.title = Some(title.into());
+        self
+    }
+
+    /// Adds a `<meta name="{name}" content="{content}">` tag to the page, unless the app sets a
+    /// [Meta] with the same `name`. See [MetaContext::with_default_title].
+    pub fn with_default_meta(
+        mut self,
+        name: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        self.defaults.meta.push((name.into(), content.into()));
+        self
+    }
+
     #[cfg(feature = "ssr")]
     /// Converts the existing metadata tags into HTML that can be injected into the document head.
     ///
@@ -238,7 +309,12 @@ impl MetaContext {
             tags.push_str("<title>");
             tags.push_str(&title);
             tags.push_str("</title>");
+        } else if let Some(title) = &self.defaults.title {
+            tags.push_str("<title>");
+            tags.push_str(&leptos::leptos_dom::escape_attr(title));
+            tags.push_str("</title>");
         }
+        tags.push_str(&self.defaults.as_string());
         tags.push_str(&self.tags.as_string());
 
         HydrationCtx::continue_from(prev_key);
@@ -39,6 +39,9 @@ pub fn Meta(
     content: Option<TextProp>,
 ) -> impl IntoView {
     let meta = use_head(cx);
+    if let Some(name) = &name {
+        meta.defaults.override_meta(name.get());
+    }
     let next_id = meta.tags.get_next_id();
     let id = format!("leptos-link-{}", next_id.0);
 
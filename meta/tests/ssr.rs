@@ -0,0 +1,41 @@
+#![cfg(feature = "ssr")]
+
+use leptos::*;
+use leptos_meta::*;
+
+#[test]
+fn style_contents_are_not_escaped() {
+    _ = create_scope(create_runtime(), |cx| {
+        provide_meta_context(cx);
+
+        let app = view! { cx,
+            <main>
+                <Style>"ul > li { color: red; }"</Style>
+            </main>
+        };
+        _ = app.into_view(cx).render_to_string(cx);
+
+        let head = use_head(cx).dehydrate();
+        assert!(head.contains(">ul > li { color: red; }</style>"), "{head}");
+    });
+}
+
+#[test]
+fn script_contents_are_not_escaped() {
+    _ = create_scope(create_runtime(), |cx| {
+        provide_meta_context(cx);
+
+        let app = view! { cx,
+            <main>
+                <Script>"if (a && b) { start(); }"</Script>
+            </main>
+        };
+        _ = app.into_view(cx).render_to_string(cx);
+
+        let head = use_head(cx).dehydrate();
+        assert!(
+            head.contains(">if (a && b) { start(); }</script>"),
+            "{head}"
+        );
+    });
+}
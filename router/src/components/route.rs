@@ -7,7 +7,7 @@ use leptos::*;
 
 use crate::{
     matching::{resolve_path, PathMatch, RouteDefinition, RouteMatch},
-    ParamsMap, RouterContext,
+    Method, ParamsMap, RouterContext,
 };
 
 thread_local! {
@@ -30,6 +30,18 @@ pub fn Route<E, F, P>(
     /// `children` may be empty or include nested routes.
     #[prop(optional)]
     children: Option<Box<dyn FnOnce(Scope) -> Fragment>>,
+    /// Static response headers to send when this route is rendered on the server, like
+    /// `headers=vec![("X-Route", "about")]`. Nested routes inherit their parents' headers,
+    /// and can override them. These are read when the route list is generated, so the server
+    /// integration can set them without running any code per request.
+    #[prop(optional)]
+    headers: Vec<(&'static str, &'static str)>,
+    /// The HTTP methods this route responds to on the server, like
+    /// `methods=vec![Method::Get, Method::Post]` for a page with an `<ActionForm/>` that posts
+    /// back to it. Nested routes inherit their parents' methods unless they declare their own.
+    /// Routes that don't declare any, and don't inherit any, respond to `GET` only.
+    #[prop(optional)]
+    methods: Vec<Method>,
 ) -> impl IntoView
 where
     E: IntoView,
@@ -59,6 +71,8 @@ where
         id,
         path: path.to_string(),
         children,
+        headers,
+        methods,
         view: Rc::new(move |cx| view(cx).into_view(cx)),
     }
 }
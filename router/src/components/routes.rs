@@ -60,6 +60,13 @@ pub fn Routes(
         move |_| get_route_matches(branches.clone(), router.pathname().get())
     });
 
+    #[cfg(feature = "ssr")]
+    if let Some(not_found) = use_context::<crate::RouteNotFound>(cx) {
+        if matches.get().is_empty() {
+            not_found.set();
+        }
+    }
+
     // Rebuild the list of nested routes conservatively, and show the root route here
     let disposers = RefCell::new(Vec::<ScopeDisposer>::new());
 
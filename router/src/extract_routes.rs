@@ -1,5 +1,12 @@
 use leptos::*;
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 use crate::{Branch, RouterIntegrationContext, ServerIntegration};
 
@@ -7,10 +14,72 @@ use crate::{Branch, RouterIntegrationContext, ServerIntegration};
 #[derive(Clone, Default, Debug)]
 pub struct PossibleBranchContext(pub(crate) Rc<RefCell<Vec<Branch>>>);
 
+/// Context that a server integration provides to find out whether the router matched a route for the
+/// request, so it can respond with a `404 Not Found`. [Routes](crate::Routes) sets it when rendered on the
+/// server with a path that none of its routes match.
+#[derive(Clone, Default, Debug)]
+pub struct RouteNotFound(pub Arc<AtomicBool>);
+
+impl RouteNotFound {
+    /// Whether a [Routes](crate::Routes) was rendered without a route that matches the path.
+    pub fn get(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// An HTTP method that a route responds to on the server, declared with the `methods` prop of
+/// its [Route](crate::Route).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Method {
+    /// `GET`, which every route responds to unless it declares other methods.
+    Get,
+    /// `POST`, like the submission of an `<ActionForm/>` or a plain `<form method="post">`.
+    Post,
+    /// `PUT`
+    Put,
+    /// `PATCH`
+    Patch,
+    /// `DELETE`
+    Delete,
+}
+
+/// A route this application could possibly serve, along with the static response headers
+/// declared for it with the `headers` prop of its [Route](crate::Route) and its parents, and
+/// the methods it responds to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RouteListing {
+    /// The path of the route, in the leptos_router format.
+    pub path: String,
+    /// The headers to send with the rendered route, with any header declared by a nested
+    /// route replacing the one declared by its parent.
+    pub headers: Vec<(&'static str, &'static str)>,
+    /// The HTTP methods the route responds to: the ones declared by the route itself or its
+    /// nearest parent that declares any, or just [Method::Get].
+    pub methods: Vec<Method>,
+}
+
 /// Generates a list of all routes this application could possibly serve. This returns the raw routes in the leptos_router
 /// format. Odds are you want `generate_route_list()` from either the actix or axum integrations if you want
 /// to work with their router
 pub fn generate_route_list_inner<IV>(app_fn: impl FnOnce(Scope) -> IV + 'static) -> Vec<String>
+where
+    IV: IntoView + 'static,
+{
+    generate_route_listing_inner(app_fn)
+        .into_iter()
+        .map(|listing| listing.path)
+        .collect()
+}
+
+/// Generates a list of all routes this application could possibly serve, like [generate_route_list_inner],
+/// along with the response headers declared for each of them.
+pub fn generate_route_listing_inner<IV>(
+    app_fn: impl FnOnce(Scope) -> IV + 'static,
+) -> Vec<RouteListing>
 where
     IV: IntoView + 'static,
 {
@@ -29,7 +98,27 @@ where
         let branches = branches.0.borrow();
         branches
             .iter()
-            .flat_map(|branch| branch.routes.last().map(|route| route.pattern.clone()))
+            .flat_map(|branch| {
+                let path = branch.routes.last()?.pattern.clone();
+                let mut headers: Vec<(&'static str, &'static str)> = Vec::new();
+                for &(name, value) in branch.routes.iter().flat_map(|route| &route.key.headers) {
+                    headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+                    headers.push((name, value));
+                }
+                let methods = branch
+                    .routes
+                    .iter()
+                    .rev()
+                    .map(|route| &route.key.methods)
+                    .find(|methods| !methods.is_empty())
+                    .cloned()
+                    .unwrap_or_else(|| vec![Method::Get]);
+                Some(RouteListing {
+                    path,
+                    headers,
+                    methods,
+                })
+            })
             .collect()
     })
 }
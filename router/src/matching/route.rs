@@ -3,11 +3,15 @@ use std::rc::Rc;
 use leptos::leptos_dom::View;
 use leptos::*;
 
+use crate::Method;
+
 #[derive(Clone)]
 pub struct RouteDefinition {
     pub id: usize,
     pub path: String,
     pub children: Vec<RouteDefinition>,
+    pub headers: Vec<(&'static str, &'static str)>,
+    pub methods: Vec<Method>,
     pub view: Rc<dyn Fn(Scope) -> View>,
 }
 
@@ -16,12 +20,17 @@ impl std::fmt::Debug for RouteDefinition {
         f.debug_struct("RouteDefinition")
             .field("path", &self.path)
             .field("children", &self.children)
+            .field("headers", &self.headers)
+            .field("methods", &self.methods)
             .finish()
     }
 }
 
 impl PartialEq for RouteDefinition {
     fn eq(&self, other: &Self) -> bool {
-        self.path == other.path && self.children == other.children
+        self.path == other.path
+            && self.children == other.children
+            && self.headers == other.headers
+            && self.methods == other.methods
     }
 }